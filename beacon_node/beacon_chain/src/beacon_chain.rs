@@ -1,3 +1,4 @@
+use crate::block_production_report::BlockProductionReport;
 use crate::block_verification::{
     check_block_relevancy, get_block_root, signature_verify_chain_segment, BlockError,
     FullyVerifiedBlock, GossipVerifiedBlock, IntoFullyVerifiedBlock,
@@ -14,8 +15,10 @@ use crate::persisted_beacon_chain::PersistedBeaconChain;
 use crate::shuffling_cache::ShufflingCache;
 use crate::snapshot_cache::SnapshotCache;
 use crate::timeout_rw_lock::TimeoutRwLock;
+use crate::validator_monitor::ValidatorMonitor;
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
 use crate::BeaconSnapshot;
+use bls::verify_signature_sets;
 use operation_pool::{OperationPool, PersistedOperationPool};
 use slog::{crit, debug, error, info, trace, warn, Logger};
 use slot_clock::SlotClock;
@@ -67,7 +70,7 @@ pub const OP_POOL_DB_KEY: [u8; 32] = [0; 32];
 pub const ETH1_CACHE_DB_KEY: [u8; 32] = [0; 32];
 pub const FORK_CHOICE_DB_KEY: [u8; 32] = [0; 32];
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum AttestationType {
     /// An attestation with a single-signature that has been published in accordance with the naive
     /// aggregation strategy.
@@ -190,6 +193,8 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     /// This pool accepts `Attestation` objects that only have one aggregation bit set and provides
     /// a method to get an aggregated `Attestation` for some `AttestationData`.
     pub naive_aggregation_pool: NaiveAggregationPool<T::EthSpec>,
+    /// Tracks the on-chain performance of a user-defined set of validators.
+    pub validator_monitor: ValidatorMonitor<T::EthSpec>,
     /// Provides information from the Ethereum 1 (PoW) chain.
     pub eth1_chain: Option<Eth1Chain<T::Eth1Chain, T::EthSpec, T::Store>>,
     /// Stores a "snapshot" of the chain at the time the head-of-the-chain block was received.
@@ -218,6 +223,7 @@ pub struct BeaconChain<T: BeaconChainTypes> {
 }
 
 type BeaconBlockAndState<T> = (BeaconBlock<T>, BeaconState<T>);
+type BeaconBlockStateAndReport<T> = (BeaconBlock<T>, BeaconState<T>, BlockProductionReport);
 
 impl<T: BeaconChainTypes> BeaconChain<T> {
     /// Persists the core `BeaconChain` components (including the head block) and the fork choice.
@@ -607,6 +613,22 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             .ok_or_else(|| Error::CanonicalHeadLockTimeout)
     }
 
+    /// Returns the number of validators which are active in the current epoch of the head state.
+    pub fn head_active_validator_count(&self) -> Result<usize, Error> {
+        self.canonical_head
+            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .map(|head| {
+                let state = &head.beacon_state;
+                let epoch = state.current_epoch();
+                state
+                    .validators
+                    .iter()
+                    .filter(|validator| validator.is_active_at(epoch))
+                    .count()
+            })
+            .ok_or_else(|| Error::CanonicalHeadLockTimeout)
+    }
+
     /// Returns the validator index (if any) for the given public key.
     ///
     /// ## Notes
@@ -873,8 +895,20 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         let timer = metrics::start_timer(&metrics::ATTESTATION_PROCESSING_TIMES);
 
         let outcome = self.process_attestation_internal(attestation.clone(), attestation_type);
+        self.register_attestation_outcome(attestation, &outcome);
 
-        match &outcome {
+        metrics::stop_timer(timer);
+        outcome
+    }
+
+    /// Updates the metrics and notifies the event handler of the `outcome` of processing
+    /// `attestation`.
+    fn register_attestation_outcome(
+        &self,
+        attestation: Attestation<T::EthSpec>,
+        outcome: &Result<AttestationProcessingOutcome, Error>,
+    ) {
+        match outcome {
             Ok(outcome) => match outcome {
                 AttestationProcessingOutcome::Processed => {
                     metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_SUCCESSES);
@@ -918,9 +952,6 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     });
             }
         }
-
-        metrics::stop_timer(timer);
-        outcome
     }
 
     pub fn process_attestation_internal(
@@ -928,13 +959,229 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         attestation: Attestation<T::EthSpec>,
         attestation_type: AttestationType,
     ) -> Result<AttestationProcessingOutcome, Error> {
+        let indexed_attestation = match self.index_attestation_for_processing(&attestation)? {
+            Ok(indexed_attestation) => indexed_attestation,
+            Err(outcome) => return Ok(outcome),
+        };
+
+        let signature_setup_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_SIGNATURE_SETUP_TIMES);
+
+        let pubkey_cache = self
+            .validator_pubkey_cache
+            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+            .ok_or_else(|| Error::ValidatorPubkeyCacheLockTimeout)?;
+
+        let (fork, genesis_validators_root) = self.head_fork_and_genesis_validators_root()?;
+
+        let signature_set = indexed_attestation_signature_set_from_pubkeys(
+            |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
+            &attestation.signature,
+            &indexed_attestation,
+            &fork,
+            genesis_validators_root,
+            &self.spec,
+        )
+        .map_err(Error::SignatureSetError)?;
+
+        metrics::stop_timer(signature_setup_timer);
+
+        let signature_verification_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_SIGNATURE_TIMES);
+
+        let signature_is_valid = signature_set.is_valid();
+
+        metrics::stop_timer(signature_verification_timer);
+
+        drop(pubkey_cache);
+
+        if signature_is_valid {
+            self.import_verified_attestation(
+                attestation,
+                &indexed_attestation,
+                attestation_type,
+                &fork,
+                genesis_validators_root,
+            )
+        } else {
+            Ok(AttestationProcessingOutcome::InvalidSignature)
+        }
+    }
+
+    /// Accept a batch of new attestations from the network, verifying their signatures together.
+    ///
+    /// The signatures of all attestations which pass the initial checks are verified with a single
+    /// multi-set verification, which is substantially cheaper than verifying them individually.
+    /// If the batch fails verification, each signature is verified individually so that only the
+    /// attestations with invalid signatures are rejected.
+    ///
+    /// Returns an outcome for each attestation, in the order they were provided. An `Err` is
+    /// returned if the batch could not be processed at all.
+    pub fn process_attestation_batch(
+        &self,
+        attestations: Vec<(Attestation<T::EthSpec>, AttestationType)>,
+    ) -> Result<Vec<Result<AttestationProcessingOutcome, Error>>, Error> {
+        metrics::inc_counter_by(
+            &metrics::ATTESTATION_PROCESSING_REQUESTS,
+            attestations.len() as i64,
+        );
+        let timer = metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_TIMES);
+
+        let originals = attestations
+            .iter()
+            .map(|(attestation, _)| attestation.clone())
+            .collect::<Vec<_>>();
+        let outcomes = self.process_attestation_batch_internal(attestations)?;
+
+        for (attestation, outcome) in originals.into_iter().zip(outcomes.iter()) {
+            self.register_attestation_outcome(attestation, outcome);
+        }
+
+        metrics::stop_timer(timer);
+        Ok(outcomes)
+    }
+
+    fn process_attestation_batch_internal(
+        &self,
+        attestations: Vec<(Attestation<T::EthSpec>, AttestationType)>,
+    ) -> Result<Vec<Result<AttestationProcessingOutcome, Error>>, Error> {
+        let mut outcomes = Vec::with_capacity(attestations.len());
+        outcomes.resize_with(attestations.len(), || None);
+
+        let mut indexed_attestations = Vec::with_capacity(attestations.len());
+        for (i, (attestation, attestation_type)) in attestations.into_iter().enumerate() {
+            match self.index_attestation_for_processing(&attestation) {
+                Ok(Ok(indexed_attestation)) => indexed_attestations.push((
+                    i,
+                    attestation,
+                    attestation_type,
+                    indexed_attestation,
+                )),
+                Ok(Err(outcome)) => outcomes[i] = Some(Ok(outcome)),
+                Err(e) => outcomes[i] = Some(Err(e)),
+            }
+        }
+
+        let signature_setup_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_SIGNATURE_SETUP_TIMES);
+
+        let pubkey_cache = self
+            .validator_pubkey_cache
+            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+            .ok_or_else(|| Error::ValidatorPubkeyCacheLockTimeout)?;
+
+        let (fork, genesis_validators_root) = self.head_fork_and_genesis_validators_root()?;
+
+        let mut signature_sets = Vec::with_capacity(indexed_attestations.len());
+        let mut verifiable_attestations = Vec::with_capacity(indexed_attestations.len());
+        for (i, attestation, attestation_type, indexed_attestation) in indexed_attestations {
+            match indexed_attestation_signature_set_from_pubkeys(
+                |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
+                &attestation.signature,
+                &indexed_attestation,
+                &fork,
+                genesis_validators_root,
+                &self.spec,
+            ) {
+                Ok(signature_set) => {
+                    signature_sets.push(signature_set);
+                    verifiable_attestations.push((
+                        i,
+                        attestation,
+                        attestation_type,
+                        indexed_attestation,
+                    ));
+                }
+                Err(e) => outcomes[i] = Some(Err(Error::SignatureSetError(e))),
+            }
+        }
+
+        metrics::stop_timer(signature_setup_timer);
+
+        let signature_verification_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_SIGNATURE_TIMES);
+
+        // A single signature gains nothing from batch verification.
+        let batch_is_valid =
+            signature_sets.len() > 1 && verify_signature_sets(signature_sets.clone());
+
+        let signatures_are_valid = if batch_is_valid {
+            vec![true; signature_sets.len()]
+        } else {
+            if signature_sets.len() > 1 {
+                metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES);
+                debug!(
+                    self.log,
+                    "Attestation batch signature verification failed";
+                    "msg" => "falling back to individual verification",
+                    "batch_size" => signature_sets.len(),
+                );
+            }
+            signature_sets
+                .iter()
+                .map(|signature_set| signature_set.is_valid())
+                .collect()
+        };
+
+        metrics::stop_timer(signature_verification_timer);
+
+        drop(pubkey_cache);
+
+        for ((i, attestation, attestation_type, indexed_attestation), signature_is_valid) in
+            verifiable_attestations
+                .into_iter()
+                .zip(signatures_are_valid.into_iter())
+        {
+            outcomes[i] = Some(if signature_is_valid {
+                self.import_verified_attestation(
+                    attestation,
+                    &indexed_attestation,
+                    attestation_type,
+                    &fork,
+                    genesis_validators_root,
+                )
+            } else {
+                Ok(AttestationProcessingOutcome::InvalidSignature)
+            });
+        }
+
+        // Each attestation is given an outcome either during the initial checks, during signature
+        // set creation or after signature verification.
+        Ok(outcomes
+            .into_iter()
+            .map(|outcome| outcome.expect("every attestation has an outcome"))
+            .collect())
+    }
+
+    /// Returns the `Fork` and `genesis_validators_root` of the canonical head, used to verify
+    /// attestation signatures.
+    fn head_fork_and_genesis_validators_root(&self) -> Result<(Fork, Hash256), Error> {
+        self.canonical_head
+            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .ok_or_else(|| Error::CanonicalHeadLockTimeout)
+            .map(|head| {
+                (
+                    head.beacon_state.fork.clone(),
+                    head.beacon_state.genesis_validators_root,
+                )
+            })
+    }
+
+    /// Performs the checks on `attestation` which precede signature verification and returns
+    /// its `IndexedAttestation`.
+    ///
+    /// Returns `Ok(Err(outcome))` if the attestation should not be processed any further.
+    fn index_attestation_for_processing(
+        &self,
+        attestation: &Attestation<T::EthSpec>,
+    ) -> Result<Result<IndexedAttestation<T::EthSpec>, AttestationProcessingOutcome>, Error> {
         let initial_validation_timer =
             metrics::start_timer(&metrics::ATTESTATION_PROCESSING_INITIAL_VALIDATION_TIMES);
 
         // There is no point in processing an attestation with an empty bitfield. Reject
         // it immediately.
         if attestation.aggregation_bits.num_set_bits() == 0 {
-            return Ok(AttestationProcessingOutcome::EmptyAggregationBitfield);
+            return Ok(Err(AttestationProcessingOutcome::EmptyAggregationBitfield));
         }
 
         let attestation_epoch = attestation.data.slot.epoch(T::EthSpec::slots_per_epoch());
@@ -943,19 +1190,19 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         // Attestation must be from the current or previous epoch.
         if attestation_epoch > epoch_now {
-            return Ok(AttestationProcessingOutcome::FutureEpoch {
+            return Ok(Err(AttestationProcessingOutcome::FutureEpoch {
                 attestation_epoch,
                 current_epoch: epoch_now,
-            });
+            }));
         } else if attestation_epoch + 1 < epoch_now {
-            return Ok(AttestationProcessingOutcome::PastEpoch {
+            return Ok(Err(AttestationProcessingOutcome::PastEpoch {
                 attestation_epoch,
                 current_epoch: epoch_now,
-            });
+            }));
         }
 
         if target.epoch != attestation.data.slot.epoch(T::EthSpec::slots_per_epoch()) {
-            return Ok(AttestationProcessingOutcome::BadTargetEpoch);
+            return Ok(Err(AttestationProcessingOutcome::BadTargetEpoch));
         }
 
         // Attestation target must be for a known block.
@@ -970,7 +1217,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         {
             (slot, state_root)
         } else {
-            return Ok(AttestationProcessingOutcome::UnknownTargetRoot(target.root));
+            return Ok(Err(AttestationProcessingOutcome::UnknownTargetRoot(
+                target.root,
+            )));
         };
 
         // Load the slot and state root for `attestation.data.beacon_block_root`.
@@ -987,9 +1236,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         {
             slot
         } else {
-            return Ok(AttestationProcessingOutcome::UnknownHeadBlock {
+            return Ok(Err(AttestationProcessingOutcome::UnknownHeadBlock {
                 beacon_block_root: attestation.data.beacon_block_root,
-            });
+            }));
         };
 
         // TODO: currently we do not check the FFG source/target. This is what the spec dictates
@@ -1004,10 +1253,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         // Attestations must not be for blocks in the future. If this is the case, the attestation
         // should not be considered.
         if block_slot > attestation.data.slot {
-            return Ok(AttestationProcessingOutcome::AttestsToFutureBlock {
+            return Ok(Err(AttestationProcessingOutcome::AttestsToFutureBlock {
                 block: block_slot,
                 attestation: attestation.data.slot,
-            });
+            }));
         }
 
         metrics::stop_timer(initial_validation_timer);
@@ -1028,7 +1277,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     .get_beacon_committee(attestation.data.slot, attestation.data.index)
                 {
                     let indexed_attestation =
-                        get_indexed_attestation(committee.committee, &attestation)?;
+                        get_indexed_attestation(committee.committee, attestation)?;
 
                     // Drop the shuffling cache to avoid holding the lock for any longer than
                     // required.
@@ -1036,10 +1285,12 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
                     indexed_attestation
                 } else {
-                    return Ok(AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
-                        slot: attestation.data.slot,
-                        index: attestation.data.index,
-                    });
+                    return Ok(Err(
+                        AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
+                            slot: attestation.data.slot,
+                            index: attestation.data.index,
+                        },
+                    ));
                 }
             } else {
                 // Drop the shuffling cache to avoid holding the lock for any longer than
@@ -1095,144 +1346,132 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 if let Some(committee) = committee_cache
                     .get_beacon_committee(attestation.data.slot, attestation.data.index)
                 {
-                    get_indexed_attestation(committee.committee, &attestation)?
+                    get_indexed_attestation(committee.committee, attestation)?
                 } else {
-                    return Ok(AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
-                        slot: attestation.data.slot,
-                        index: attestation.data.index,
-                    });
+                    return Ok(Err(
+                        AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
+                            slot: attestation.data.slot,
+                            index: attestation.data.index,
+                        },
+                    ));
                 }
             };
 
-        let signature_setup_timer =
-            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_SIGNATURE_SETUP_TIMES);
-
-        let pubkey_cache = self
-            .validator_pubkey_cache
-            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
-            .ok_or_else(|| Error::ValidatorPubkeyCacheLockTimeout)?;
-
-        let (fork, genesis_validators_root) = self
-            .canonical_head
-            .try_read_for(HEAD_LOCK_TIMEOUT)
-            .ok_or_else(|| Error::CanonicalHeadLockTimeout)
-            .map(|head| {
-                (
-                    head.beacon_state.fork.clone(),
-                    head.beacon_state.genesis_validators_root,
-                )
-            })?;
-
-        let signature_set = indexed_attestation_signature_set_from_pubkeys(
-            |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
-            &attestation.signature,
-            &indexed_attestation,
-            &fork,
-            genesis_validators_root,
-            &self.spec,
-        )
-        .map_err(Error::SignatureSetError)?;
-
-        metrics::stop_timer(signature_setup_timer);
-
-        let signature_verification_timer =
-            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_SIGNATURE_TIMES);
-
-        let signature_is_valid = signature_set.is_valid();
-
-        metrics::stop_timer(signature_verification_timer);
+        Ok(Ok(indexed_attestation))
+    }
 
-        drop(pubkey_cache);
+    /// Imports an `attestation` with a verified signature into fork choice and, if required, the
+    /// operation pool.
+    fn import_verified_attestation(
+        &self,
+        attestation: Attestation<T::EthSpec>,
+        indexed_attestation: &IndexedAttestation<T::EthSpec>,
+        attestation_type: AttestationType,
+        fork: &Fork,
+        genesis_validators_root: Hash256,
+    ) -> Result<AttestationProcessingOutcome, Error> {
+        // Provide the attestation to fork choice, updating the validator latest messages but
+        // _without_ finding and updating the head.
+        if let Err(e) = self
+            .fork_choice
+            .process_indexed_attestation(indexed_attestation)
+        {
+            error!(
+                self.log,
+                "Add attestation to fork choice failed";
+                "beacon_block_root" =>  format!("{}", attestation.data.beacon_block_root),
+                "error" => format!("{:?}", e)
+            );
+            return Err(e.into());
+        }
 
-        if signature_is_valid {
-            // Provide the attestation to fork choice, updating the validator latest messages but
-            // _without_ finding and updating the head.
-            if let Err(e) = self
-                .fork_choice
-                .process_indexed_attestation(&indexed_attestation)
-            {
-                error!(
-                    self.log,
-                    "Add attestation to fork choice failed";
-                    "beacon_block_root" =>  format!("{}", attestation.data.beacon_block_root),
-                    "error" => format!("{:?}", e)
-                );
-                return Err(e.into());
-            }
+        // Provide the valid attestation to op pool, which may choose to retain the
+        // attestation for inclusion in a future block. If we receive an attestation from a
+        // subnet without a validator responsible for aggregating it, we don't store it in the
+        // op pool.
+        if self.eth1_chain.is_some() {
+            match attestation_type {
+                AttestationType::Unaggregated { should_store } if should_store => {
+                    // The op pool keeps unaggregated attestations so they can be used to
+                    // extend the aggregates it receives from other aggregators.
+                    if let Err(e) = self.op_pool.insert_attestation(
+                        attestation.clone(),
+                        fork,
+                        genesis_validators_root,
+                        &self.spec,
+                    ) {
+                        error!(
+                            self.log,
+                            "Failed to add unaggregated attestation to op pool";
+                            "error" => format!("{:?}", e),
+                            "index" => attestation.data.index,
+                            "slot" => attestation.data.slot.as_u64(),
+                        );
+                    }
 
-            // Provide the valid attestation to op pool, which may choose to retain the
-            // attestation for inclusion in a future block. If we receive an attestation from a
-            // subnet without a validator responsible for aggregating it, we don't store it in the
-            // op pool.
-            if self.eth1_chain.is_some() {
-                match attestation_type {
-                    AttestationType::Unaggregated { should_store } if should_store => {
-                        match self.naive_aggregation_pool.insert(&attestation) {
-                            Ok(outcome) => trace!(
+                    match self.naive_aggregation_pool.insert(&attestation) {
+                        Ok(outcome) => trace!(
+                            self.log,
+                            "Stored unaggregated attestation";
+                            "outcome" => format!("{:?}", outcome),
+                            "index" => attestation.data.index,
+                            "slot" => attestation.data.slot.as_u64(),
+                        ),
+                        Err(NaiveAggregationError::SlotTooLow {
+                            slot,
+                            lowest_permissible_slot,
+                        }) => {
+                            trace!(
                                 self.log,
-                                "Stored unaggregated attestation";
-                                "outcome" => format!("{:?}", outcome),
+                                "Refused to store unaggregated attestation";
+                                "lowest_permissible_slot" => lowest_permissible_slot.as_u64(),
+                                "slot" => slot.as_u64(),
+                            );
+                        }
+                        Err(e) => error!(
+                                self.log,
+                                "Failed to store unaggregated attestation";
+                                "error" => format!("{:?}", e),
                                 "index" => attestation.data.index,
                                 "slot" => attestation.data.slot.as_u64(),
-                            ),
-                            Err(NaiveAggregationError::SlotTooLow {
-                                slot,
-                                lowest_permissible_slot,
-                            }) => {
-                                trace!(
-                                    self.log,
-                                    "Refused to store unaggregated attestation";
-                                    "lowest_permissible_slot" => lowest_permissible_slot.as_u64(),
-                                    "slot" => slot.as_u64(),
-                                );
-                            }
-                            Err(e) => error!(
-                                    self.log,
-                                    "Failed to store unaggregated attestation";
-                                    "error" => format!("{:?}", e),
-                                    "index" => attestation.data.index,
-                                    "slot" => attestation.data.slot.as_u64(),
-                            ),
-                        }
+                        ),
                     }
-                    AttestationType::Unaggregated { .. } => trace!(
-                        self.log,
-                        "Did not store unaggregated attestation";
-                        "index" => attestation.data.index,
-                        "slot" => attestation.data.slot.as_u64(),
-                    ),
-                    AttestationType::Aggregated => {
-                        let index = attestation.data.index;
-                        let slot = attestation.data.slot;
-
-                        match self.op_pool.insert_attestation(
-                            attestation,
-                            &fork,
-                            genesis_validators_root,
-                            &self.spec,
-                        ) {
-                            Ok(_) => {}
-                            Err(e) => {
-                                error!(
-                                    self.log,
-                                    "Failed to add attestation to op pool";
-                                    "error" => format!("{:?}", e),
-                                    "index" => index,
-                                    "slot" => slot.as_u64(),
-                                );
-                            }
+                }
+                AttestationType::Unaggregated { .. } => trace!(
+                    self.log,
+                    "Did not store unaggregated attestation";
+                    "index" => attestation.data.index,
+                    "slot" => attestation.data.slot.as_u64(),
+                ),
+                AttestationType::Aggregated => {
+                    let index = attestation.data.index;
+                    let slot = attestation.data.slot;
+
+                    match self.op_pool.insert_attestation(
+                        attestation,
+                        fork,
+                        genesis_validators_root,
+                        &self.spec,
+                    ) {
+                        Ok(_) => {}
+                        Err(e) => {
+                            error!(
+                                self.log,
+                                "Failed to add attestation to op pool";
+                                "error" => format!("{:?}", e),
+                                "index" => index,
+                                "slot" => slot.as_u64(),
+                            );
                         }
                     }
                 }
             }
+        }
 
-            // Update the metrics.
-            metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_SUCCESSES);
+        // Update the metrics.
+        metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_SUCCESSES);
 
-            Ok(AttestationProcessingOutcome::Processed)
-        } else {
-            Ok(AttestationProcessingOutcome::InvalidSignature)
-        }
+        Ok(AttestationProcessingOutcome::Processed)
     }
 
     /// Check that the shuffling at `block_root` is equal to one of the shufflings of `state`.
@@ -1725,6 +1964,8 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         metrics::stop_timer(fork_choice_register_timer);
 
         self.head_tracker.register_block(block_root, &block);
+        self.validator_monitor
+            .register_imported_block(block, block_root);
         metrics::observe(
             &metrics::OPERATIONS_PER_BLOCK_ATTESTATION,
             block.body.attestations.len() as f64,
@@ -1787,6 +2028,28 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         self.produce_block_on_state(state, slot, randao_reveal)
     }
 
+    /// As per `produce_block`, but also return a report describing the operations that were
+    /// included in the block and the reward the proposer will earn for it.
+    pub fn produce_block_with_report(
+        &self,
+        randao_reveal: Signature,
+        slot: Slot,
+    ) -> Result<BeaconBlockStateAndReport<T::EthSpec>, BlockProductionError> {
+        let start_time = Instant::now();
+
+        let state = self
+            .state_at_slot(slot - 1, StateSkipConfig::WithStateRoots)
+            .map_err(|_| BlockProductionError::UnableToProduceAtSlot(slot))?;
+
+        let (block, state, mut report) =
+            self.produce_block_on_state_with_report(state, slot, randao_reveal)?;
+
+        // Include the time taken to load the parent state.
+        report.total_time_us = start_time.elapsed().as_micros() as u64;
+
+        Ok((block, state, report))
+    }
+
     /// Produce a block for some `slot` upon the given `state`.
     ///
     /// Typically the `self.produce_block()` function should be used, instead of calling this
@@ -1797,12 +2060,39 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// produced at that slot height.
     pub fn produce_block_on_state(
         &self,
-        mut state: BeaconState<T::EthSpec>,
+        state: BeaconState<T::EthSpec>,
         produce_at_slot: Slot,
         randao_reveal: Signature,
     ) -> Result<BeaconBlockAndState<T::EthSpec>, BlockProductionError> {
+        self.produce_block_on_state_internal(state, produce_at_slot, randao_reveal, false)
+            .map(|(block, state, _report)| (block, state))
+    }
+
+    /// As per `produce_block_on_state`, but also return a report describing the operations that
+    /// were included in the block and the reward the proposer will earn for it.
+    ///
+    /// The reward is omitted from the report if it could not be computed.
+    pub fn produce_block_on_state_with_report(
+        &self,
+        state: BeaconState<T::EthSpec>,
+        produce_at_slot: Slot,
+        randao_reveal: Signature,
+    ) -> Result<BeaconBlockStateAndReport<T::EthSpec>, BlockProductionError> {
+        self.produce_block_on_state_internal(state, produce_at_slot, randao_reveal, true)
+    }
+
+    /// Produce a block for some `slot` upon the given `state`, computing the proposer reward for
+    /// the report only if `compute_reward` is `true`.
+    fn produce_block_on_state_internal(
+        &self,
+        mut state: BeaconState<T::EthSpec>,
+        produce_at_slot: Slot,
+        randao_reveal: Signature,
+        compute_reward: bool,
+    ) -> Result<BeaconBlockStateAndReport<T::EthSpec>, BlockProductionError> {
         metrics::inc_counter(&metrics::BLOCK_PRODUCTION_REQUESTS);
         let timer = metrics::start_timer(&metrics::BLOCK_PRODUCTION_TIMES);
+        let start_time = Instant::now();
 
         let eth1_chain = self
             .eth1_chain
@@ -1853,6 +2143,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 })
         };
 
+        let packing_start_time = Instant::now();
+        let (attestations, packing_report) = self
+            .op_pool
+            .get_attestations_with_report(&state, attestation_filter, &self.spec)
+            .map_err(BlockProductionError::OpPoolError)?;
+        let packing_time = packing_start_time.elapsed();
+
         let mut block = SignedBeaconBlock {
             message: BeaconBlock {
                 slot: state.slot,
@@ -1865,11 +2162,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     graffiti,
                     proposer_slashings: proposer_slashings.into(),
                     attester_slashings: attester_slashings.into(),
-                    attestations: self
-                        .op_pool
-                        .get_attestations(&state, attestation_filter, &self.spec)
-                        .map_err(BlockProductionError::OpPoolError)?
-                        .into(),
+                    attestations: attestations.into(),
                     deposits,
                     voluntary_exits: self.op_pool.get_voluntary_exits(&state, &self.spec).into(),
                 },
@@ -1878,6 +2171,25 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             signature: Signature::empty_signature(),
         };
 
+        // The block root is not known until the state root has been computed, it is set below.
+        //
+        // The reward is only for reporting, so a failure to compute it must not prevent the block
+        // from being produced.
+        let reward = if compute_reward {
+            self.compute_block_reward(&block.message, Hash256::zero(), &mut state)
+                .map_err(|e| {
+                    warn!(
+                        self.log,
+                        "Unable to compute block reward";
+                        "slot" => block.message.slot,
+                        "error" => format!("{:?}", e),
+                    )
+                })
+                .ok()
+        } else {
+            None
+        };
+
         per_block_processing(
             &mut state,
             &block,
@@ -1890,6 +2202,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         block.message.state_root = state_root;
 
+        let mut report = BlockProductionReport::new(&block.message, packing_report, reward);
+        if let Some(reward) = report.reward.as_mut() {
+            reward.block_root = block.message.canonical_root();
+        }
+        report.attestation_packing_time_us = packing_time.as_micros() as u64;
+        report.total_time_us = start_time.elapsed().as_micros() as u64;
+
         metrics::inc_counter(&metrics::BLOCK_PRODUCTION_SUCCESSES);
         metrics::stop_timer(timer);
 
@@ -1901,7 +2220,19 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             "slot" => block.message.slot
         );
 
-        Ok((block.message, state))
+        debug!(
+            self.log,
+            "Block production report";
+            "slot" => block.message.slot,
+            "reward" => report.reward.as_ref().map(|reward| reward.total),
+            "attestation_reward" => report.attestations.total_reward(),
+            "attestations_included" => report.attestations.included.len(),
+            "attestations_dropped" => report.attestations.dropped.len(),
+            "packing_time_us" => report.attestation_packing_time_us,
+            "total_time_us" => report.total_time_us,
+        );
+
+        Ok((block.message, state, report))
     }
 
     /// Execute the fork choice algorithm and enthrone the result as the canonical head.
@@ -0,0 +1,78 @@
+use crate::BlockReward;
+use operation_pool::AttestationPackingReport;
+use serde_derive::Serialize;
+use types::{BeaconBlock, EthSpec, Slot};
+
+/// Describes the contents of a block produced by `BeaconChain::produce_block_with_report`, the
+/// reward it will earn its proposer and the time taken to produce it.
+///
+/// Intended for comparing the quality of blocks produced by different nodes with different
+/// operation pools.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockProductionReport {
+    pub slot: Slot,
+    pub proposer_index: u64,
+    /// The attestations that were included, and those that were dropped.
+    pub attestations: AttestationPackingReport,
+    /// The indices of the validators slashed by the included proposer slashings.
+    pub proposer_slashings: Vec<u64>,
+    /// For each included attester slashing, the indices of the validators that attested to both
+    /// attestations.
+    pub attester_slashings: Vec<Vec<u64>>,
+    /// The indices of the validators that are exited by the included voluntary exits.
+    pub voluntary_exits: Vec<u64>,
+    /// The number of included deposits.
+    pub deposits: usize,
+    /// The reward the proposer will earn for the block, if it could be computed.
+    pub reward: Option<BlockReward>,
+    /// The time spent choosing attestations from the operation pool, in microseconds.
+    pub attestation_packing_time_us: u64,
+    /// The total time taken to produce the block, in microseconds.
+    pub total_time_us: u64,
+}
+
+impl BlockProductionReport {
+    /// Summarise the operations included in `block`, leaving the reward and timing information to
+    /// be provided by the caller.
+    pub(crate) fn new<E: EthSpec>(
+        block: &BeaconBlock<E>,
+        attestations: AttestationPackingReport,
+        reward: Option<BlockReward>,
+    ) -> Self {
+        let body = &block.body;
+
+        Self {
+            slot: block.slot,
+            proposer_index: block.proposer_index,
+            attestations,
+            proposer_slashings: body
+                .proposer_slashings
+                .iter()
+                .map(|slashing| slashing.signed_header_1.message.proposer_index)
+                .collect(),
+            attester_slashings: body
+                .attester_slashings
+                .iter()
+                .map(|slashing| {
+                    let indices_2 = &slashing.attestation_2.attesting_indices;
+                    slashing
+                        .attestation_1
+                        .attesting_indices
+                        .iter()
+                        .filter(|index| indices_2.iter().any(|other| other == *index))
+                        .copied()
+                        .collect()
+                })
+                .collect(),
+            voluntary_exits: body
+                .voluntary_exits
+                .iter()
+                .map(|exit| exit.message.validator_index)
+                .collect(),
+            deposits: body.deposits.len(),
+            reward,
+            attestation_packing_time_us: 0,
+            total_time_us: 0,
+        }
+    }
+}
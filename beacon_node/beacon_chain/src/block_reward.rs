@@ -0,0 +1,185 @@
+use crate::{BeaconChain, BeaconChainError, BeaconChainTypes};
+use serde_derive::Serialize;
+use state_processing::common::{get_attesting_indices, get_base_reward};
+use state_processing::per_slot_processing;
+use std::collections::HashSet;
+use types::{
+    AttestationData, BeaconBlock, BeaconState, BeaconStateError, BitList, EthSpec, Hash256,
+    RelativeEpoch, Slot,
+};
+
+type Error = BeaconChainError;
+
+/// The rewards earned by the proposer of a block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockReward {
+    pub block_root: Hash256,
+    pub slot: Slot,
+    pub proposer_index: u64,
+    /// The reward for including attestations from validators whose attestations had not yet been
+    /// included in the chain.
+    ///
+    /// This reward is not paid until the end of the epoch after the attestation's target epoch.
+    /// It is estimated using the effective balances at the time the block was included.
+    pub attestation_rewards: u64,
+    /// The number of previously-unseen attesters included in the block.
+    pub new_attesters: u64,
+    /// The whistleblower reward for the included proposer slashings.
+    pub proposer_slashing_rewards: u64,
+    /// The whistleblower reward for the included attester slashings.
+    pub attester_slashing_rewards: u64,
+    /// The sum of all of the above rewards.
+    pub total: u64,
+}
+
+impl<T: BeaconChainTypes> BeaconChain<T> {
+    /// Compute the rewards earned by the proposer of the block with the given `block_root`.
+    ///
+    /// The state prior to the block is obtained by loading the parent state from the database and
+    /// advancing it to the slot of the block.
+    pub fn compute_block_reward_at_root(&self, block_root: Hash256) -> Result<BlockReward, Error> {
+        let block = self
+            .get_block(&block_root)?
+            .ok_or_else(|| Error::MissingBeaconBlock(block_root))?
+            .message;
+        let parent_block = self
+            .get_block(&block.parent_root)?
+            .ok_or_else(|| Error::MissingBeaconBlock(block.parent_root))?;
+        let mut state = self
+            .get_state(&parent_block.state_root(), Some(parent_block.slot()))?
+            .ok_or_else(|| Error::MissingBeaconState(parent_block.state_root()))?;
+
+        while state.slot < block.slot {
+            per_slot_processing(&mut state, None, &self.spec)?;
+        }
+
+        self.compute_block_reward(&block, block_root, &mut state)
+    }
+
+    /// Compute the rewards earned by the proposer of `block`, where `state` is the state at the
+    /// slot of `block`, prior to the block being applied.
+    pub fn compute_block_reward(
+        &self,
+        block: &BeaconBlock<T::EthSpec>,
+        block_root: Hash256,
+        state: &mut BeaconState<T::EthSpec>,
+    ) -> Result<BlockReward, Error> {
+        if block.slot != state.slot {
+            return Err(Error::InvariantViolated(format!(
+                "block slot {} does not match state slot {}",
+                block.slot, state.slot
+            )));
+        }
+
+        state.build_committee_cache(RelativeEpoch::Previous, &self.spec)?;
+        state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+
+        let spec = &self.spec;
+        let total_active_balance = state.get_total_balance(
+            state.get_cached_active_validator_indices(RelativeEpoch::Current)?,
+            spec,
+        )?;
+
+        // Collect the validators whose attestations are already included in the state. Each
+        // validator may only have a single attestation included for each epoch.
+        let mut previous_epoch_attesters = HashSet::new();
+        for a in state.previous_epoch_attestations.iter() {
+            previous_epoch_attesters.extend(attesting_indices(
+                state,
+                &a.data,
+                &a.aggregation_bits,
+            )?);
+        }
+        let mut current_epoch_attesters = HashSet::new();
+        for a in state.current_epoch_attestations.iter() {
+            current_epoch_attesters.extend(attesting_indices(state, &a.data, &a.aggregation_bits)?);
+        }
+
+        let mut attestation_rewards = 0;
+        let mut new_attesters = 0;
+        for a in block.body.attestations.iter() {
+            let attesters = if a.data.target.epoch == state.current_epoch() {
+                &mut current_epoch_attesters
+            } else {
+                &mut previous_epoch_attesters
+            };
+
+            for index in attesting_indices(state, &a.data, &a.aggregation_bits)? {
+                let is_slashed = state
+                    .validators
+                    .get(index)
+                    .map_or(true, |validator| validator.slashed);
+
+                if attesters.insert(index) && !is_slashed {
+                    let base_reward = get_base_reward(state, index, total_active_balance, spec)?;
+                    attestation_rewards += base_reward / spec.proposer_reward_quotient;
+                    new_attesters += 1;
+                }
+            }
+        }
+
+        // The proposer receives the full whistleblower reward for each slashed validator, since
+        // there is no separate whistleblower.
+        let whistleblower_reward = |index: usize| {
+            state
+                .validators
+                .get(index)
+                .map(|validator| validator.effective_balance / spec.whistleblower_reward_quotient)
+                .ok_or_else(|| BeaconStateError::UnknownValidator(index as u64))
+        };
+
+        let mut slashed = HashSet::new();
+
+        let mut proposer_slashing_rewards = 0;
+        for proposer_slashing in block.body.proposer_slashings.iter() {
+            let index = proposer_slashing.signed_header_1.message.proposer_index as usize;
+            if slashed.insert(index) {
+                proposer_slashing_rewards += whistleblower_reward(index)?;
+            }
+        }
+
+        let mut attester_slashing_rewards = 0;
+        let epoch = state.current_epoch();
+        for attester_slashing in block.body.attester_slashings.iter() {
+            let indices_1 = attester_slashing
+                .attestation_1
+                .attesting_indices
+                .iter()
+                .collect::<HashSet<_>>();
+
+            for &index in attester_slashing.attestation_2.attesting_indices.iter() {
+                let index = index as usize;
+                let is_slashable = state
+                    .validators
+                    .get(index)
+                    .map_or(false, |validator| validator.is_slashable_at(epoch));
+
+                if indices_1.contains(&(index as u64)) && is_slashable && slashed.insert(index) {
+                    attester_slashing_rewards += whistleblower_reward(index)?;
+                }
+            }
+        }
+
+        Ok(BlockReward {
+            block_root,
+            slot: block.slot,
+            proposer_index: block.proposer_index,
+            attestation_rewards,
+            new_attesters,
+            proposer_slashing_rewards,
+            attester_slashing_rewards,
+            total: attestation_rewards + proposer_slashing_rewards + attester_slashing_rewards,
+        })
+    }
+}
+
+/// Returns the indices of the validators that signed an attestation with the given `data` and
+/// `aggregation_bits`.
+fn attesting_indices<E: EthSpec>(
+    state: &BeaconState<E>,
+    data: &AttestationData,
+    aggregation_bits: &BitList<E::MaxValidatorsPerCommittee>,
+) -> Result<Vec<usize>, BeaconStateError> {
+    let committee = state.get_beacon_committee(data.slot, data.index)?;
+    get_attesting_indices::<E>(committee.committee, aggregation_bits)
+}
@@ -509,7 +509,19 @@ impl<T: BeaconChainTypes> FullyVerifiedBlock<T> {
                 state_root
             };
 
+            // If this slot is the last of an epoch, collect the pre-transition information for
+            // any monitored validators.
+            let monitor_pending = chain
+                .validator_monitor
+                .process_epoch_transition_start(&mut state, &chain.spec);
+
             per_slot_processing(&mut state, Some(state_root), &chain.spec)?;
+
+            if let Some(pending) = monitor_pending {
+                chain
+                    .validator_monitor
+                    .process_epoch_transition_end(pending, &state);
+            }
         }
 
         metrics::stop_timer(catchup_timer);
@@ -10,6 +10,7 @@ use crate::persisted_beacon_chain::PersistedBeaconChain;
 use crate::shuffling_cache::ShufflingCache;
 use crate::snapshot_cache::{SnapshotCache, DEFAULT_SNAPSHOT_CACHE_SIZE};
 use crate::timeout_rw_lock::TimeoutRwLock;
+use crate::validator_monitor::ValidatorMonitor;
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
 use crate::{
     BeaconChain, BeaconChainTypes, BeaconSnapshot, Eth1Chain, Eth1ChainBackend, EventHandler,
@@ -26,7 +27,8 @@ use std::sync::Arc;
 use std::time::Duration;
 use store::Store;
 use types::{
-    BeaconBlock, BeaconState, ChainSpec, EthSpec, Hash256, Signature, SignedBeaconBlock, Slot,
+    BeaconBlock, BeaconState, ChainSpec, EthSpec, Hash256, PublicKeyBytes, Signature,
+    SignedBeaconBlock, Slot,
 };
 
 pub const PUBKEY_CACHE_FILENAME: &str = "pubkey_cache.ssz";
@@ -89,6 +91,8 @@ pub struct BeaconChainBuilder<T: BeaconChainTypes> {
     validator_pubkey_cache: Option<ValidatorPubkeyCache>,
     spec: ChainSpec,
     disabled_forks: Vec<String>,
+    monitored_validator_pubkeys: Vec<PublicKeyBytes>,
+    monitored_validator_indices: Vec<u64>,
     log: Option<Logger>,
 }
 
@@ -124,6 +128,8 @@ where
             pubkey_cache_path: None,
             data_dir: None,
             disabled_forks: Vec::new(),
+            monitored_validator_pubkeys: Vec::new(),
+            monitored_validator_indices: Vec::new(),
             validator_pubkey_cache: None,
             spec: TEthSpec::default_spec(),
             log: None,
@@ -176,6 +182,14 @@ where
         self
     }
 
+    /// Sets the validators that will be tracked by the validator monitor, identified by either
+    /// public key or validator index.
+    pub fn monitor_validators(mut self, pubkeys: Vec<PublicKeyBytes>, indices: Vec<u64>) -> Self {
+        self.monitored_validator_pubkeys = pubkeys;
+        self.monitored_validator_indices = indices;
+        self
+    }
+
     /// Attempt to load an existing eth1 cache from the builder's `Store`.
     pub fn get_persisted_eth1_backend(&self) -> Result<Option<SszEth1>, String> {
         let store = self
@@ -431,6 +445,11 @@ where
                 .ok_or_else(|| "Cannot build without op pool".to_string())?,
             // TODO: allow for persisting and loading the pool from disk.
             naive_aggregation_pool: <_>::default(),
+            validator_monitor: ValidatorMonitor::new(
+                self.monitored_validator_pubkeys,
+                self.monitored_validator_indices,
+                log.clone(),
+            ),
             eth1_chain: self.eth1_chain,
             genesis_validators_root: canonical_head.beacon_state.genesis_validators_root,
             canonical_head: TimeoutRwLock::new(canonical_head.clone()),
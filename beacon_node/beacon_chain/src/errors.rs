@@ -85,6 +85,7 @@ pub enum BlockProductionError {
     Eth1ChainError(Eth1ChainError),
     BeaconStateError(BeaconStateError),
     OpPoolError(OpPoolError),
+    BeaconChainError(BeaconChainError),
     /// The `BeaconChain` was explicitly configured _without_ a connection to eth1, therefore it
     /// cannot produce blocks.
     NoEth1ChainConnection,
@@ -94,3 +95,4 @@ easy_from_to!(BlockProcessingError, BlockProductionError);
 easy_from_to!(BeaconStateError, BlockProductionError);
 easy_from_to!(SlotProcessingError, BlockProductionError);
 easy_from_to!(Eth1ChainError, BlockProductionError);
+easy_from_to!(BeaconChainError, BlockProductionError);
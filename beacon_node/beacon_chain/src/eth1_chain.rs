@@ -1,7 +1,11 @@
 use crate::metrics;
-use eth1::{Config as Eth1Config, Eth1Block, Service as HttpService};
+use eth1::{
+    Config as Eth1Config, DepositTreeSnapshot, Eth1Block, Service as HttpService,
+    Status as Eth1Status,
+};
 use eth2_hashing::hash;
 use futures::Future;
+use serde_derive::Serialize;
 use slog::{debug, error, trace, Logger};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
@@ -54,6 +58,58 @@ impl From<safe_arith::ArithError> for Error {
     }
 }
 
+/// The reason for the `Eth1Data` vote that a block producer would cast.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum Eth1VoteReason {
+    /// The vote is for the candidate block with the most votes in the state (ties are broken by
+    /// the highest block number).
+    WinningVote { vote_count: u64 },
+    /// None of the votes in the state are for a candidate block, so the vote is for the latest
+    /// candidate block.
+    LatestCandidateBlock,
+    /// There are no candidate blocks in the `BlockCache`, so the vote is for `state.eth1_data`.
+    NoCandidateBlocks,
+}
+
+/// The number of times some `Eth1Data` has been voted for in the current voting period.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Eth1VoteCount {
+    pub eth1_data: Eth1Data,
+    /// The number of the candidate block for the vote, or `None` if the vote is not for one of our
+    /// candidate blocks (i.e., we consider it invalid).
+    pub block_number: Option<u64>,
+    pub vote_count: u64,
+}
+
+/// The progress of the `DepositCache` towards the head of the eth1 chain.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Eth1DepositSync {
+    /// The highest eth1 block that has been searched for deposit logs.
+    pub last_processed_block: Option<u64>,
+    /// The number of deposits in the `DepositCache`.
+    pub deposit_count: u64,
+    /// The head of the eth1 chain, as seen during the latest update of the caches.
+    pub remote_head_block: Option<u64>,
+    /// The highest eth1 block that the caches will follow (i.e., the head minus the follow
+    /// distance).
+    pub remote_follow_block: Option<u64>,
+}
+
+/// Describes the eth1 data voting in the voting period of some `BeaconState`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Eth1VotingInfo {
+    pub voting_period_start_slot: Slot,
+    pub voting_period_start_seconds: u64,
+    /// The blocks from the `BlockCache` which may be voted for, in ascending order.
+    pub candidate_blocks: Vec<Eth1Block>,
+    /// The distinct votes in `state.eth1_data_votes`, in descending order of vote count.
+    pub votes: Vec<Eth1VoteCount>,
+    /// The vote that a block producer would cast.
+    pub vote: Eth1Data,
+    pub vote_reason: Eth1VoteReason,
+    pub deposit_sync: Eth1DepositSync,
+}
+
 #[derive(Encode, Decode, Clone)]
 pub struct SszEth1 {
     use_dummy_backend: bool,
@@ -117,6 +173,33 @@ where
         }
     }
 
+    /// Returns a description of the eth1 data voting in the voting period of `state`, including
+    /// the vote that `eth1_data_for_block_production` would return.
+    ///
+    /// Returns `None` if the dummy backend is in use.
+    pub fn eth1_voting_info(
+        &self,
+        state: &BeaconState<E>,
+        spec: &ChainSpec,
+    ) -> Option<Eth1VotingInfo> {
+        if self.use_dummy_backend {
+            None
+        } else {
+            self.backend.eth1_voting_info(state, spec)
+        }
+    }
+
+    /// Returns a snapshot of the deposit tree containing at most `max_deposit_count` deposits.
+    ///
+    /// Returns `None` if the dummy backend is in use.
+    pub fn deposit_snapshot(&self, max_deposit_count: u64) -> Option<DepositTreeSnapshot> {
+        if self.use_dummy_backend {
+            None
+        } else {
+            self.backend.deposit_snapshot(max_deposit_count)
+        }
+    }
+
     /// Returns a list of `Deposits` that may be included in a block.
     ///
     /// Including all of the returned `Deposits` in a block should _not_ cause it to become
@@ -139,6 +222,16 @@ where
         }
     }
 
+    /// Returns the health of the eth1 endpoints and the deposit contract, if the backend connects
+    /// to an eth1 node.
+    pub fn eth1_status(&self) -> Option<Eth1Status> {
+        if self.use_dummy_backend {
+            None
+        } else {
+            self.backend.eth1_status()
+        }
+    }
+
     /// Instantiate `Eth1Chain` from a persisted `SszEth1`.
     ///
     /// The `Eth1Chain` will have the same caches as the persisted `SszEth1`.
@@ -191,6 +284,22 @@ pub trait Eth1ChainBackend<T: EthSpec, S: Store<T>>: Sized + Send + Sync {
         spec: &ChainSpec,
     ) -> Result<Vec<Deposit>, Error>;
 
+    /// Returns the health of the eth1 endpoints and the deposit contract, if the backend connects
+    /// to an eth1 node.
+    fn eth1_status(&self) -> Option<Eth1Status>;
+
+    /// Returns a description of the eth1 data voting in the voting period of `beacon_state`, if
+    /// the backend votes using a cache of eth1 blocks.
+    fn eth1_voting_info(
+        &self,
+        beacon_state: &BeaconState<T>,
+        spec: &ChainSpec,
+    ) -> Option<Eth1VotingInfo>;
+
+    /// Returns a snapshot of the deposit tree containing at most `max_deposit_count` deposits, if
+    /// the backend has a cache of deposits.
+    fn deposit_snapshot(&self, max_deposit_count: u64) -> Option<DepositTreeSnapshot>;
+
     /// Encode the `Eth1ChainBackend` instance to bytes.
     fn as_bytes(&self) -> Vec<u8>;
 
@@ -237,6 +346,21 @@ impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for DummyEth1ChainBackend<T
         Ok(vec![])
     }
 
+    /// The dummy back-end does not connect to an eth1 node.
+    fn eth1_status(&self) -> Option<Eth1Status> {
+        None
+    }
+
+    /// The dummy back-end does not use eth1 blocks to vote.
+    fn eth1_voting_info(&self, _: &BeaconState<T>, _: &ChainSpec) -> Option<Eth1VotingInfo> {
+        None
+    }
+
+    /// The dummy back-end does not have a deposit cache.
+    fn deposit_snapshot(&self, _: u64) -> Option<DepositTreeSnapshot> {
+        None
+    }
+
     /// Return empty Vec<u8> for dummy backend.
     fn as_bytes(&self) -> Vec<u8> {
         Vec::new()
@@ -306,13 +430,7 @@ impl<T: EthSpec, S: Store<T>> CachingEth1Backend<T, S> {
 
 impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for CachingEth1Backend<T, S> {
     fn eth1_data(&self, state: &BeaconState<T>, spec: &ChainSpec) -> Result<Eth1Data, Error> {
-        let period = T::SlotsPerEth1VotingPeriod::to_u64();
-        let voting_period_start_slot = (state.slot / period) * period;
-        let voting_period_start_seconds = slot_start_seconds::<T>(
-            state.genesis_time,
-            spec.milliseconds_per_slot,
-            voting_period_start_slot,
-        );
+        let voting_period_start_seconds = voting_period_start_seconds(state, spec);
 
         let blocks = self.core.blocks().read();
 
@@ -324,42 +442,32 @@ impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for CachingEth1Backend<T, S
             "Found eth1 data votes_to_consider";
             "votes_to_consider" => votes_to_consider.len(),
         );
-        let valid_votes = collect_valid_votes(state, &votes_to_consider);
 
-        let eth1_data = if let Some(eth1_data) = find_winning_vote(valid_votes) {
-            eth1_data
-        } else {
+        let (eth1_data, reason) = choose_vote(state, &votes_to_consider);
+
+        match reason {
+            Eth1VoteReason::WinningVote { .. } => {}
             // In this case, there are no valid votes available.
             //
             // Here we choose the eth1_data corresponding to the latest block in our voting window.
+            Eth1VoteReason::LatestCandidateBlock => debug!(
+                self.log,
+                "No valid eth1_data votes";
+                "outcome" => "Casting vote corresponding to last candidate eth1 block",
+            ),
             // If no votes exist, choose `state.eth1_data` as default vote.
-            let default_vote = votes_to_consider
-                .iter()
-                .max_by(|(_, x), (_, y)| x.cmp(y))
-                .map(|vote| {
-                    let vote = vote.0.clone();
-                    debug!(
-                        self.log,
-                        "No valid eth1_data votes";
-                        "outcome" => "Casting vote corresponding to last candidate eth1 block",
-                    );
-                    vote
-                })
-                .unwrap_or_else(|| {
-                    let vote = state.eth1_data.clone();
-                    error!(
-                        self.log,
-                        "No valid eth1_data votes, `votes_to_consider` empty";
-                        "lowest_block_number" => self.core.lowest_block_number(),
-                        "earliest_block_timestamp" => self.core.earliest_block_timestamp(),
-                        "genesis_time" => state.genesis_time,
-                        "outcome" => "casting `state.eth1_data` as eth1 vote"
-                    );
-                    metrics::inc_counter(&metrics::DEFAULT_ETH1_VOTES);
-                    vote
-                });
-            default_vote
-        };
+            Eth1VoteReason::NoCandidateBlocks => {
+                error!(
+                    self.log,
+                    "No valid eth1_data votes, `votes_to_consider` empty";
+                    "lowest_block_number" => self.core.lowest_block_number(),
+                    "earliest_block_timestamp" => self.core.earliest_block_timestamp(),
+                    "genesis_time" => state.genesis_time,
+                    "outcome" => "casting `state.eth1_data` as eth1 vote"
+                );
+                metrics::inc_counter(&metrics::DEFAULT_ETH1_VOTES);
+            }
+        }
 
         debug!(
             self.log,
@@ -403,6 +511,72 @@ impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for CachingEth1Backend<T, S
         }
     }
 
+    fn eth1_status(&self) -> Option<Eth1Status> {
+        Some(self.core.status())
+    }
+
+    fn eth1_voting_info(&self, state: &BeaconState<T>, spec: &ChainSpec) -> Option<Eth1VotingInfo> {
+        let period = T::SlotsPerEth1VotingPeriod::to_u64();
+        let voting_period_start_seconds = voting_period_start_seconds(state, spec);
+
+        let (candidate_blocks, votes_to_consider) = {
+            let blocks = self.core.blocks().read();
+            let mut candidate_blocks = blocks
+                .iter()
+                .filter(|block| is_candidate_block(block, voting_period_start_seconds, spec))
+                .cloned()
+                .collect::<Vec<_>>();
+            candidate_blocks.sort_by_key(|block| block.number);
+            (
+                candidate_blocks,
+                get_votes_to_consider(blocks.iter(), voting_period_start_seconds, spec),
+            )
+        };
+
+        let mut votes: Vec<Eth1VoteCount> = vec![];
+        for eth1_data in state.eth1_data_votes.iter() {
+            match votes.iter_mut().find(|vote| vote.eth1_data == *eth1_data) {
+                Some(vote) => vote.vote_count += 1,
+                None => votes.push(Eth1VoteCount {
+                    eth1_data: eth1_data.clone(),
+                    block_number: votes_to_consider.get(eth1_data).copied(),
+                    vote_count: 1,
+                }),
+            }
+        }
+        // A stable sort preserves the order in which votes were first cast.
+        votes.sort_by(|a, b| b.vote_count.cmp(&a.vote_count));
+
+        let (vote, vote_reason) = choose_vote(state, &votes_to_consider);
+
+        let (last_processed_block, deposit_count) = {
+            let deposits = self.core.deposits().read();
+            (deposits.last_processed_block, deposits.cache.len() as u64)
+        };
+        let remote_head_block = self.core.remote_head_block();
+        let follow_distance = self.core.config().follow_distance;
+
+        Some(Eth1VotingInfo {
+            voting_period_start_slot: (state.slot / period) * period,
+            voting_period_start_seconds,
+            candidate_blocks,
+            votes,
+            vote,
+            vote_reason,
+            deposit_sync: Eth1DepositSync {
+                last_processed_block,
+                deposit_count,
+                remote_head_block,
+                remote_follow_block: remote_head_block
+                    .map(|head| head.saturating_sub(follow_distance)),
+            },
+        })
+    }
+
+    fn deposit_snapshot(&self, max_deposit_count: u64) -> Option<DepositTreeSnapshot> {
+        self.core.deposit_snapshot(max_deposit_count)
+    }
+
     /// Return encoded byte representation of the block and deposit caches.
     fn as_bytes(&self) -> Vec<u8> {
         self.core.as_bytes()
@@ -476,6 +650,33 @@ fn collect_valid_votes<T: EthSpec>(
     valid_votes
 }
 
+/// Chooses the `Eth1Data` to vote for, given the candidate `votes_to_consider`.
+///
+/// Votes for the candidate with the most votes in `state`. If no votes in `state` are for a
+/// candidate, votes for the latest candidate. If there are no candidates, votes for
+/// `state.eth1_data`.
+fn choose_vote<T: EthSpec>(
+    state: &BeaconState<T>,
+    votes_to_consider: &HashMap<Eth1Data, BlockNumber>,
+) -> (Eth1Data, Eth1VoteReason) {
+    let valid_votes = collect_valid_votes(state, votes_to_consider);
+
+    if let Some(eth1_data) = find_winning_vote(valid_votes.clone()) {
+        let vote_count = valid_votes
+            .iter()
+            .filter(|((vote, _), _)| *vote == eth1_data)
+            .map(|(_, vote_count)| *vote_count)
+            .sum();
+
+        (eth1_data, Eth1VoteReason::WinningVote { vote_count })
+    } else if let Some((eth1_data, _)) = votes_to_consider.iter().max_by(|(_, x), (_, y)| x.cmp(y))
+    {
+        (eth1_data.clone(), Eth1VoteReason::LatestCandidateBlock)
+    } else {
+        (state.eth1_data.clone(), Eth1VoteReason::NoCandidateBlocks)
+    }
+}
+
 /// Selects the winning vote from `valid_votes`.
 fn find_winning_vote(valid_votes: Eth1DataVoteCount) -> Option<Eth1Data> {
     valid_votes
@@ -491,6 +692,17 @@ fn int_to_bytes32(int: u64) -> Vec<u8> {
     vec
 }
 
+/// Returns the unix-epoch seconds at the start of the eth1 voting period of `state`.
+fn voting_period_start_seconds<T: EthSpec>(state: &BeaconState<T>, spec: &ChainSpec) -> u64 {
+    let period = T::SlotsPerEth1VotingPeriod::to_u64();
+    let voting_period_start_slot = (state.slot / period) * period;
+    slot_start_seconds::<T>(
+        state.genesis_time,
+        spec.milliseconds_per_slot,
+        voting_period_start_slot,
+    )
+}
+
 /// Returns the unix-epoch seconds at the start of the given `slot`.
 fn slot_start_seconds<T: EthSpec>(
     genesis_unix_seconds: u64,
@@ -530,16 +742,6 @@ mod test {
         }
     }
 
-    fn get_voting_period_start_seconds(state: &BeaconState<E>, spec: &ChainSpec) -> u64 {
-        let period = <E as EthSpec>::SlotsPerEth1VotingPeriod::to_u64();
-        let voting_period_start_slot = (state.slot / period) * period;
-        slot_start_seconds::<E>(
-            state.genesis_time,
-            spec.milliseconds_per_slot,
-            voting_period_start_slot,
-        )
-    }
-
     #[test]
     fn slot_start_time() {
         let zero_sec = 0;
@@ -756,7 +958,7 @@ mod test {
 
             state.slot = Slot::from(slots_per_eth1_voting_period * 10);
             let follow_distance_seconds = eth1_follow_distance * spec.seconds_per_eth1_block;
-            let voting_period_start = get_voting_period_start_seconds(&state, &spec);
+            let voting_period_start = voting_period_start_seconds(&state, &spec);
             let start_eth1_block = voting_period_start - follow_distance_seconds * 2;
             let end_eth1_block = voting_period_start - follow_distance_seconds;
 
@@ -805,7 +1007,7 @@ mod test {
             assert_eq!(
                 get_votes_to_consider(
                     blocks.iter(),
-                    get_voting_period_start_seconds(&state, spec),
+                    voting_period_start_seconds(&state, spec),
                     &spec,
                 ),
                 HashMap::new()
@@ -824,7 +1026,7 @@ mod test {
             state.slot = Slot::from(slots_per_eth1_voting_period * 10);
 
             let follow_distance_seconds = eth1_follow_distance * spec.seconds_per_eth1_block;
-            let voting_period_start = get_voting_period_start_seconds(&state, &spec);
+            let voting_period_start = voting_period_start_seconds(&state, &spec);
             let start_eth1_block = voting_period_start - follow_distance_seconds * 2;
             let end_eth1_block = voting_period_start - follow_distance_seconds;
             let blocks = (start_eth1_block..end_eth1_block)
@@ -852,6 +1054,58 @@ mod test {
         }
     }
 
+    mod choose_vote {
+        use super::*;
+
+        fn votes_to_consider(n: u64) -> HashMap<Eth1Data, BlockNumber> {
+            (0..n).map(|i| (get_eth1_data(i), i)).collect()
+        }
+
+        #[test]
+        fn no_candidates() {
+            let spec = &E::default_spec();
+            let state: BeaconState<E> = BeaconState::new(0, get_eth1_data(42), spec);
+
+            assert_eq!(
+                choose_vote(&state, &HashMap::new()),
+                (get_eth1_data(42), Eth1VoteReason::NoCandidateBlocks)
+            );
+        }
+
+        #[test]
+        fn no_valid_votes() {
+            let spec = &E::default_spec();
+            let mut state: BeaconState<E> = BeaconState::new(0, get_eth1_data(42), spec);
+            state.eth1_data_votes = vec![get_eth1_data(100)].into();
+
+            assert_eq!(
+                choose_vote(&state, &votes_to_consider(4)),
+                (get_eth1_data(3), Eth1VoteReason::LatestCandidateBlock)
+            );
+        }
+
+        #[test]
+        fn winning_vote() {
+            let spec = &E::default_spec();
+            let mut state: BeaconState<E> = BeaconState::new(0, get_eth1_data(42), spec);
+            state.eth1_data_votes = vec![
+                get_eth1_data(1),
+                get_eth1_data(2),
+                get_eth1_data(1),
+                get_eth1_data(100),
+            ]
+            .into();
+
+            assert_eq!(
+                choose_vote(&state, &votes_to_consider(4)),
+                (
+                    get_eth1_data(1),
+                    Eth1VoteReason::WinningVote { vote_count: 2 }
+                )
+            );
+        }
+    }
+
     mod collect_valid_votes {
         use super::*;
 
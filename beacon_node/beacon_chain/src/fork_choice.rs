@@ -9,7 +9,10 @@ use ssz_derive::{Decode, Encode};
 use state_processing::common::get_indexed_attestation;
 use std::marker::PhantomData;
 use store::{DBColumn, Error as StoreError, SimpleStoreItem};
-use types::{BeaconBlock, BeaconState, BeaconStateError, Epoch, Hash256, IndexedAttestation, Slot};
+use types::{
+    BeaconBlock, BeaconState, BeaconStateError, Checkpoint, Epoch, Hash256, IndexedAttestation,
+    Slot,
+};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -102,6 +105,27 @@ impl<T: BeaconChainTypes> ForkChoice<T> {
         result
     }
 
+    /// Returns the justified checkpoint that is used for finding the head.
+    pub fn justified_checkpoint(&self) -> Checkpoint {
+        self.checkpoint_manager
+            .read()
+            .current
+            .justified
+            .clone()
+            .into()
+    }
+
+    /// Returns the finalized checkpoint that is used for finding the head.
+    pub fn finalized_checkpoint(&self) -> Checkpoint {
+        self.checkpoint_manager.read().current.finalized.clone()
+    }
+
+    /// Returns the best-known justified checkpoint, which may not yet be used for finding the
+    /// head.
+    pub fn best_justified_checkpoint(&self) -> Checkpoint {
+        self.checkpoint_manager.read().best_justified()
+    }
+
     /// Returns true if the given block is known to fork choice.
     pub fn contains_block(&self, block_root: &Hash256) -> bool {
         self.backend.contains_block(block_root)
@@ -4,6 +4,8 @@ extern crate lazy_static;
 
 mod beacon_chain;
 mod beacon_snapshot;
+mod block_production_report;
+mod block_reward;
 mod block_verification;
 pub mod builder;
 mod errors;
@@ -19,20 +21,26 @@ mod shuffling_cache;
 mod snapshot_cache;
 pub mod test_utils;
 mod timeout_rw_lock;
+mod validator_monitor;
 mod validator_pubkey_cache;
 
 pub use self::beacon_chain::{
     AttestationProcessingOutcome, AttestationType, BeaconChain, BeaconChainTypes,
-    ChainSegmentResult, StateSkipConfig,
+    ChainSegmentResult, StateSkipConfig, BEACON_CHAIN_DB_KEY,
 };
 pub use self::beacon_snapshot::BeaconSnapshot;
 pub use self::errors::{BeaconChainError, BlockProductionError};
+pub use block_production_report::BlockProductionReport;
+pub use block_reward::BlockReward;
 pub use block_verification::{BlockError, BlockProcessingOutcome, GossipVerifiedBlock};
-pub use eth1_chain::{Eth1Chain, Eth1ChainBackend};
+pub use eth1_chain::{
+    Eth1Chain, Eth1ChainBackend, Eth1DepositSync, Eth1VoteCount, Eth1VoteReason, Eth1VotingInfo,
+};
 pub use events::EventHandler;
 pub use fork_choice::ForkChoice;
 pub use metrics::scrape_for_metrics;
 pub use parking_lot;
+pub use persisted_beacon_chain::PersistedBeaconChain;
 pub use slot_clock;
 pub use state_processing::per_block_processing::errors::{
     AttestationValidationError, AttesterSlashingValidationError, DepositValidationError,
@@ -40,3 +48,4 @@ pub use state_processing::per_block_processing::errors::{
 };
 pub use store;
 pub use types;
+pub use validator_monitor::{MonitoredValidator, ValidatorEpochSummary, ValidatorMonitor};
@@ -118,6 +118,18 @@ lazy_static! {
         "beacon_attestation_processing_signature_seconds",
         "Time spent on the signature verification of attestation processing"
     );
+    pub static ref ATTESTATION_PROCESSING_BATCH_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_seconds",
+        "Full runtime of processing a batch of attestations"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_SIGNATURE_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_signature_seconds",
+        "Time spent on the signature verification of a batch of attestations"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES: Result<IntCounter> = try_create_int_counter(
+        "beacon_attestation_processing_batch_signature_failures_total",
+        "Count of attestation batches which failed signature verification and were verified individually"
+    );
 
     /*
      * Shuffling cache
@@ -247,6 +259,63 @@ lazy_static! {
         try_create_int_gauge("beacon_op_pool_proposer_slashings_total", "Count of proposer slashings in the op pool");
     pub static ref OP_POOL_NUM_VOLUNTARY_EXITS: Result<IntGauge> =
         try_create_int_gauge("beacon_op_pool_voluntary_exits_total", "Count of voluntary exits in the op pool");
+    pub static ref OP_POOL_NUM_ATTESTATION_DATA: Result<IntGauge> =
+        try_create_int_gauge("beacon_op_pool_attestation_data_total", "Count of distinct attestation data in the op pool");
+    pub static ref OP_POOL_NUM_AGGREGATE_ATTESTATIONS: Result<IntGauge> =
+        try_create_int_gauge("beacon_op_pool_aggregate_attestations_total", "Count of aggregate attestations in the op pool");
+    pub static ref OP_POOL_NUM_UNAGGREGATED_ATTESTATIONS: Result<IntGauge> =
+        try_create_int_gauge("beacon_op_pool_unaggregated_attestations_total", "Count of unaggregated attestations in the op pool");
+    pub static ref OP_POOL_NUM_ATTESTERS: Result<IntGauge> =
+        try_create_int_gauge("beacon_op_pool_attesters_total", "Count of distinct attesters for each attestation data in the op pool, summed across all data");
+    pub static ref OP_POOL_NUM_ATTESTERS_IN_BEST_AGGREGATES: Result<IntGauge> =
+        try_create_int_gauge("beacon_op_pool_attesters_in_best_aggregates_total", "Count of attesters in the largest aggregate for each attestation data in the op pool, summed across all data");
+}
+
+// Third lazy-static block is used to account for macro recursion limit.
+lazy_static! {
+    /*
+     * Validator Monitor
+     */
+    pub static ref VALIDATOR_MONITOR_PREV_EPOCH_ATTESTER: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "validator_monitor_prev_epoch_attester",
+        "Set to 1 if the validator had an attestation included for the previous epoch",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_MONITOR_PREV_EPOCH_TARGET_ATTESTER: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "validator_monitor_prev_epoch_target_attester",
+        "Set to 1 if the validator attested to the correct target in the previous epoch",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_MONITOR_PREV_EPOCH_HEAD_ATTESTER: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "validator_monitor_prev_epoch_head_attester",
+        "Set to 1 if the validator attested to the correct head in the previous epoch",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_MONITOR_PREV_EPOCH_INCLUSION_DISTANCE: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "validator_monitor_prev_epoch_inclusion_distance",
+        "The inclusion distance of the validator's attestation for the previous epoch",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_MONITOR_BALANCE_GWEI: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "validator_monitor_balance_gwei",
+        "The balance of the validator after the most recent epoch transition",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_MONITOR_BALANCE_DELTA_GWEI: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "validator_monitor_balance_delta_gwei",
+        "The change in the validator's balance during the most recent epoch transition",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_MONITOR_BLOCKS_PROPOSED_TOTAL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "validator_monitor_blocks_proposed_total",
+        "Count of blocks proposed by the validator that were included in the chain",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_MONITOR_BLOCKS_MISSED_TOTAL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "validator_monitor_blocks_missed_total",
+        "Count of slots where the validator was the proposer but no block was included",
+        &["validator"]
+    );
 }
 
 /// Scrape the `beacon_chain` for metrics that are not constantly updated (e.g., the present slot,
@@ -272,6 +341,25 @@ pub fn scrape_for_metrics<T: BeaconChainTypes>(beacon_chain: &BeaconChain<T>) {
         &OP_POOL_NUM_VOLUNTARY_EXITS,
         beacon_chain.op_pool.num_voluntary_exits(),
     );
+
+    let attestation_stats = beacon_chain.op_pool.attestation_stats();
+    set_gauge_by_usize(
+        &OP_POOL_NUM_ATTESTATION_DATA,
+        attestation_stats.num_attestation_data,
+    );
+    set_gauge_by_usize(
+        &OP_POOL_NUM_AGGREGATE_ATTESTATIONS,
+        attestation_stats.num_aggregates,
+    );
+    set_gauge_by_usize(
+        &OP_POOL_NUM_UNAGGREGATED_ATTESTATIONS,
+        attestation_stats.num_unaggregated,
+    );
+    set_gauge_by_usize(&OP_POOL_NUM_ATTESTERS, attestation_stats.num_attesters);
+    set_gauge_by_usize(
+        &OP_POOL_NUM_ATTESTERS_IN_BEST_AGGREGATES,
+        attestation_stats.num_attesters_in_best_aggregates,
+    );
 }
 
 /// Scrape the given `state` assuming it's the head state, updating the `DEFAULT_REGISTRY`.
@@ -85,6 +85,22 @@ pub struct BeaconChainHarness<T: BeaconChainTypes> {
 impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn new(eth_spec_instance: E, keypairs: Vec<Keypair>) -> Self {
+        let genesis_state =
+            interop_genesis_state::<E>(&keypairs, HARNESS_GENESIS_TIME, &E::default_spec())
+                .expect("should generate interop state");
+
+        Self::from_genesis_state(eth_spec_instance, keypairs, genesis_state)
+    }
+
+    /// Instantiate a new harness which starts from the given `genesis_state`.
+    ///
+    /// The `keypairs` are only used for producing blocks and attestations, they may be empty if
+    /// the harness is only used to import them.
+    pub fn from_genesis_state(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        genesis_state: BeaconState<E>,
+    ) -> Self {
         let data_dir = tempdir().expect("should create temporary data_dir");
         let spec = E::default_spec();
 
@@ -96,10 +112,7 @@ impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
             .store(Arc::new(MemoryStore::open()))
             .store_migrator(NullMigrator)
             .data_dir(data_dir.path().to_path_buf())
-            .genesis_state(
-                interop_genesis_state::<E>(&keypairs, HARNESS_GENESIS_TIME, &spec)
-                    .expect("should generate interop state"),
-            )
+            .genesis_state(genesis_state)
             .expect("should build state using recent genesis")
             .dummy_eth1_backend()
             .expect("should build dummy backend")
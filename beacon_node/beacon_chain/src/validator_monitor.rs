@@ -0,0 +1,490 @@
+//! Provides detailed, per-epoch information about a user-defined set of validators.
+//!
+//! The `ValidatorMonitor` is fed with information as the `BeaconChain` transitions states across
+//! epoch boundaries during block processing. At each epoch transition it determines, for each
+//! monitored validator:
+//!
+//! - Whether or not an attestation was included for the previous epoch, its inclusion distance and
+//!   the correctness of its source, target and head votes.
+//! - Which blocks the validator proposed (or failed to propose) in the current epoch.
+//! - The change in the validator's balance caused by the epoch transition.
+//!
+//! This information is exported via Prometheus metrics, logs and the HTTP API.
+
+use crate::metrics;
+use parking_lot::RwLock;
+use serde_derive::Serialize;
+use slog::{error, info, warn, Logger};
+use state_processing::per_epoch_processing::{ValidatorStatus, ValidatorStatuses};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::marker::PhantomData;
+use types::{
+    BeaconBlock, BeaconState, BeaconStateError, ChainSpec, Epoch, EthSpec, Hash256, PublicKeyBytes,
+    RelativeEpoch, Slot,
+};
+
+/// The number of epochs of summaries that are retained for each monitored validator.
+pub const HISTORIC_EPOCHS: usize = 64;
+
+/// A summary of the performance of a single validator during a single epoch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidatorEpochSummary {
+    /// The epoch that this summary describes.
+    pub epoch: Epoch,
+    /// True if the validator was active during `self.epoch`.
+    pub is_active: bool,
+    /// True if an attestation from the validator for `self.epoch` was included in the chain. An
+    /// included attestation always has a correct source vote.
+    pub is_attester: bool,
+    /// True if the included attestation had the correct target.
+    pub is_target_attester: bool,
+    /// True if the included attestation had the correct head.
+    pub is_head_attester: bool,
+    /// The distance between the attestation slot and the slot of the block that included it.
+    pub inclusion_distance: Option<u64>,
+    /// Slots in `self.epoch` where the validator proposed a block that was included in the chain.
+    pub proposed_slots: Vec<Slot>,
+    /// Slots in `self.epoch` where the validator was the proposer but no block was included.
+    pub missed_slots: Vec<Slot>,
+    /// The balance of the validator after the attestation rewards for `self.epoch` were applied.
+    pub balance: Option<u64>,
+    /// The change in balance caused by the epoch transition that applied the attestation rewards
+    /// for `self.epoch`.
+    pub balance_delta: Option<i64>,
+}
+
+/// The information held about some monitored validator, as returned by the HTTP API.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonitoredValidator {
+    /// The index of the validator, if known.
+    pub index: Option<u64>,
+    /// The public key of the validator, if known.
+    pub pubkey: Option<PublicKeyBytes>,
+    /// Summaries for the most recent epochs, in ascending epoch order.
+    pub epoch_summaries: Vec<ValidatorEpochSummary>,
+}
+
+/// Information collected from a state immediately prior to an epoch transition. It is completed
+/// by `ValidatorMonitor::process_epoch_transition_end` once the transition has occurred.
+pub struct PendingEpochTransition {
+    /// The epoch that is ending.
+    epoch: Epoch,
+    /// The epoch for which attestation rewards will be applied during the transition.
+    attestation_epoch: Option<Epoch>,
+    validators: Vec<PendingValidator>,
+}
+
+struct PendingValidator {
+    index: usize,
+    status: ValidatorStatus,
+    balance_before: u64,
+    proposed_slots: Vec<Slot>,
+    missed_slots: Vec<Slot>,
+}
+
+#[derive(Default)]
+struct ValidatorData {
+    pubkey: Option<PublicKeyBytes>,
+    summaries: BTreeMap<Epoch, ValidatorEpochSummary>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Validators that have been identified by public key but whose index is not yet known.
+    unresolved_pubkeys: HashSet<PublicKeyBytes>,
+    /// Validators with a known index.
+    validators: HashMap<usize, ValidatorData>,
+    /// The most recent epoch for which an epoch transition was processed.
+    latest_epoch: Option<Epoch>,
+}
+
+/// Monitors the on-chain performance of a user-defined set of validators.
+///
+/// Only the first epoch transition observed for each epoch is processed. This means that if
+/// there are competing forks across an epoch boundary, the summary reflects whichever fork was
+/// processed first.
+pub struct ValidatorMonitor<T: EthSpec> {
+    inner: RwLock<Inner>,
+    log: Logger,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: EthSpec> ValidatorMonitor<T> {
+    /// Create a new monitor for the validators identified by either `pubkeys` or `indices`.
+    pub fn new(pubkeys: Vec<PublicKeyBytes>, indices: Vec<u64>, log: Logger) -> Self {
+        let mut inner = Inner::default();
+
+        inner.unresolved_pubkeys.extend(pubkeys);
+        for index in indices {
+            inner.validators.entry(index as usize).or_default();
+        }
+
+        Self {
+            inner: RwLock::new(inner),
+            log,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns `true` if there are no validators being monitored.
+    pub fn is_empty(&self) -> bool {
+        let inner = self.inner.read();
+        inner.unresolved_pubkeys.is_empty() && inner.validators.is_empty()
+    }
+
+    /// Start monitoring the validator with the given `pubkey`.
+    pub fn add_validator_pubkey(&self, pubkey: PublicKeyBytes) {
+        let mut inner = self.inner.write();
+        let known = inner
+            .validators
+            .values()
+            .any(|data| data.pubkey.as_ref() == Some(&pubkey));
+        if !known {
+            inner.unresolved_pubkeys.insert(pubkey);
+        }
+    }
+
+    /// Start monitoring the validator with the given `index`.
+    pub fn add_validator_index(&self, index: u64) {
+        self.inner
+            .write()
+            .validators
+            .entry(index as usize)
+            .or_default();
+    }
+
+    /// Returns the information held about all monitored validators.
+    pub fn get_monitored_validators(&self) -> Vec<MonitoredValidator> {
+        let inner = self.inner.read();
+
+        let mut validators = inner
+            .validators
+            .iter()
+            .map(|(index, data)| MonitoredValidator {
+                index: Some(*index as u64),
+                pubkey: data.pubkey.clone(),
+                epoch_summaries: data.summaries.values().cloned().collect(),
+            })
+            .collect::<Vec<_>>();
+        validators.sort_by_key(|validator| validator.index);
+
+        validators.extend(
+            inner
+                .unresolved_pubkeys
+                .iter()
+                .map(|pubkey| MonitoredValidator {
+                    index: None,
+                    pubkey: Some(pubkey.clone()),
+                    epoch_summaries: vec![],
+                }),
+        );
+
+        validators
+    }
+
+    /// Returns the summary for the validator with the given `index` at the given `epoch`, if any.
+    pub fn get_epoch_summary(&self, index: u64, epoch: Epoch) -> Option<ValidatorEpochSummary> {
+        self.inner
+            .read()
+            .validators
+            .get(&(index as usize))
+            .and_then(|data| data.summaries.get(&epoch).cloned())
+    }
+
+    /// Logs the import of a block if it was proposed by a monitored validator.
+    pub fn register_imported_block(&self, block: &BeaconBlock<T>, block_root: Hash256) {
+        if self
+            .inner
+            .read()
+            .validators
+            .contains_key(&(block.proposer_index as usize))
+        {
+            info!(
+                self.log,
+                "Block from monitored validator";
+                "validator" => block.proposer_index,
+                "slot" => block.slot,
+                "root" => format!("{:?}", block_root),
+            );
+        }
+    }
+
+    /// Collects information from `state` if the next call to `per_slot_processing` will perform
+    /// an epoch transition. The returned value should be passed to
+    /// `Self::process_epoch_transition_end` once the transition has been performed.
+    ///
+    /// Returns `None` if there are no monitored validators, the state is not at the last slot of
+    /// an epoch or the epoch has already been processed.
+    pub fn process_epoch_transition_start(
+        &self,
+        state: &mut BeaconState<T>,
+        spec: &ChainSpec,
+    ) -> Option<PendingEpochTransition> {
+        if (state.slot + 1) % T::slots_per_epoch() != 0 || self.is_empty() {
+            return None;
+        }
+
+        let epoch = state.current_epoch();
+        if self
+            .inner
+            .read()
+            .latest_epoch
+            .map_or(false, |latest| latest >= epoch)
+        {
+            return None;
+        }
+
+        self.collect_pending_transition(state, spec)
+            .map_err(|e| {
+                error!(
+                    self.log,
+                    "Validator monitor failed";
+                    "error" => format!("{:?}", e),
+                    "epoch" => epoch,
+                )
+            })
+            .ok()
+    }
+
+    /// Completes the `pending` summaries using the `state` that results from the epoch transition,
+    /// updating the metrics and logging the results.
+    pub fn process_epoch_transition_end(
+        &self,
+        pending: PendingEpochTransition,
+        state: &BeaconState<T>,
+    ) {
+        let PendingEpochTransition {
+            epoch,
+            attestation_epoch,
+            validators,
+        } = pending;
+
+        let mut inner = self.inner.write();
+
+        if inner.latest_epoch.map_or(false, |latest| latest >= epoch) {
+            return;
+        }
+        inner.latest_epoch = Some(epoch);
+
+        for validator in validators {
+            let index = validator.index;
+            let label = index.to_string();
+            let label = &[label.as_str()];
+
+            let data = inner.validators.entry(index).or_default();
+
+            if let Some(attestation_epoch) = attestation_epoch {
+                let status = &validator.status;
+                let balance = state.balances.get(index).copied();
+                let balance_delta =
+                    balance.map(|balance| balance as i64 - validator.balance_before as i64);
+
+                let summary = data.summaries.entry(attestation_epoch).or_insert_with(|| {
+                    ValidatorEpochSummary {
+                        epoch: attestation_epoch,
+                        ..<_>::default()
+                    }
+                });
+                summary.is_active = status.is_active_in_previous_epoch;
+                summary.is_attester = status.is_previous_epoch_attester;
+                summary.is_target_attester = status.is_previous_epoch_target_attester;
+                summary.is_head_attester = status.is_previous_epoch_head_attester;
+                summary.inclusion_distance = status.inclusion_info.map(|info| info.delay);
+                summary.balance = balance;
+                summary.balance_delta = balance_delta;
+
+                metrics::set_gauge_vec(
+                    &metrics::VALIDATOR_MONITOR_PREV_EPOCH_ATTESTER,
+                    label,
+                    summary.is_attester as i64,
+                );
+                metrics::set_gauge_vec(
+                    &metrics::VALIDATOR_MONITOR_PREV_EPOCH_TARGET_ATTESTER,
+                    label,
+                    summary.is_target_attester as i64,
+                );
+                metrics::set_gauge_vec(
+                    &metrics::VALIDATOR_MONITOR_PREV_EPOCH_HEAD_ATTESTER,
+                    label,
+                    summary.is_head_attester as i64,
+                );
+                if let Some(distance) = summary.inclusion_distance {
+                    metrics::set_gauge_vec(
+                        &metrics::VALIDATOR_MONITOR_PREV_EPOCH_INCLUSION_DISTANCE,
+                        label,
+                        distance as i64,
+                    );
+                }
+                if let (Some(balance), Some(delta)) = (balance, balance_delta) {
+                    metrics::set_gauge_vec(
+                        &metrics::VALIDATOR_MONITOR_BALANCE_GWEI,
+                        label,
+                        balance as i64,
+                    );
+                    metrics::set_gauge_vec(
+                        &metrics::VALIDATOR_MONITOR_BALANCE_DELTA_GWEI,
+                        label,
+                        delta,
+                    );
+                }
+
+                if !summary.is_active {
+                    // Inactive validators are not expected to attest.
+                } else if summary.is_attester {
+                    info!(
+                        self.log,
+                        "Attestation included";
+                        "validator" => index,
+                        "epoch" => attestation_epoch,
+                        "inclusion_distance" => summary.inclusion_distance,
+                        "target_correct" => summary.is_target_attester,
+                        "head_correct" => summary.is_head_attester,
+                        "balance_delta" => summary.balance_delta,
+                    );
+                } else {
+                    warn!(
+                        self.log,
+                        "Attestation missing";
+                        "validator" => index,
+                        "epoch" => attestation_epoch,
+                        "balance_delta" => summary.balance_delta,
+                    );
+                }
+            }
+
+            for slot in &validator.proposed_slots {
+                metrics::inc_counter_vec(&metrics::VALIDATOR_MONITOR_BLOCKS_PROPOSED_TOTAL, label);
+                info!(
+                    self.log,
+                    "Block proposal included";
+                    "validator" => index,
+                    "slot" => slot,
+                );
+            }
+            for slot in &validator.missed_slots {
+                metrics::inc_counter_vec(&metrics::VALIDATOR_MONITOR_BLOCKS_MISSED_TOTAL, label);
+                warn!(
+                    self.log,
+                    "Block proposal missed";
+                    "validator" => index,
+                    "slot" => slot,
+                );
+            }
+
+            let summary = data
+                .summaries
+                .entry(epoch)
+                .or_insert_with(|| ValidatorEpochSummary {
+                    epoch,
+                    ..<_>::default()
+                });
+            summary.proposed_slots = validator.proposed_slots;
+            summary.missed_slots = validator.missed_slots;
+
+            while data.summaries.len() > HISTORIC_EPOCHS {
+                let oldest = *data
+                    .summaries
+                    .keys()
+                    .next()
+                    .expect("summaries cannot be empty");
+                data.summaries.remove(&oldest);
+            }
+        }
+    }
+
+    /// Resolves the indices of any monitored public keys, then reads the attestation, proposal
+    /// and balance information for each monitored validator from `state`.
+    fn collect_pending_transition(
+        &self,
+        state: &mut BeaconState<T>,
+        spec: &ChainSpec,
+    ) -> Result<PendingEpochTransition, BeaconStateError> {
+        let indices = {
+            let mut inner = self.inner.write();
+            let inner = &mut *inner;
+
+            if !inner.unresolved_pubkeys.is_empty() {
+                for (index, validator) in state.validators.iter().enumerate() {
+                    if inner.unresolved_pubkeys.remove(&validator.pubkey) {
+                        inner.validators.entry(index).or_default();
+                    }
+                }
+            }
+
+            inner
+                .validators
+                .iter_mut()
+                .filter_map(|(index, data)| {
+                    let validator = state.validators.get(*index)?;
+                    data.pubkey = Some(validator.pubkey.clone());
+                    Some(*index)
+                })
+                .collect::<HashSet<_>>()
+        };
+
+        state.build_committee_cache(RelativeEpoch::Previous, spec)?;
+        state.build_committee_cache(RelativeEpoch::Current, spec)?;
+
+        let mut validator_statuses = ValidatorStatuses::new(state, spec)?;
+        validator_statuses.process_attestations(state, spec)?;
+
+        let epoch = state.current_epoch();
+        let attestation_epoch = if epoch > T::genesis_epoch() {
+            Some(state.previous_epoch())
+        } else {
+            None
+        };
+
+        let mut validators = indices
+            .iter()
+            .map(|&index| {
+                Ok((
+                    index,
+                    PendingValidator {
+                        index,
+                        status: validator_statuses
+                            .statuses
+                            .get(index)
+                            .cloned()
+                            .ok_or_else(|| BeaconStateError::UnknownValidator(index as u64))?,
+                        balance_before: *state
+                            .balances
+                            .get(index)
+                            .ok_or_else(|| BeaconStateError::UnknownValidator(index as u64))?,
+                        proposed_slots: vec![],
+                        missed_slots: vec![],
+                    },
+                ))
+            })
+            .collect::<Result<HashMap<_, _>, BeaconStateError>>()?;
+
+        let genesis_slot = T::genesis_epoch().start_slot(T::slots_per_epoch());
+        for slot in epoch.slot_iter(T::slots_per_epoch()) {
+            // There is no proposer for the genesis block.
+            if slot == genesis_slot {
+                continue;
+            }
+
+            let proposer = state.get_beacon_proposer_index(slot, spec)?;
+            if let Some(validator) = validators.get_mut(&proposer) {
+                let proposed = if slot == state.slot {
+                    state.latest_block_header.slot == slot
+                } else {
+                    state.get_block_root(slot)? != state.get_block_root(slot - 1)?
+                };
+
+                if proposed {
+                    validator.proposed_slots.push(slot);
+                } else {
+                    validator.missed_slots.push(slot);
+                }
+            }
+        }
+
+        Ok(PendingEpochTransition {
+            epoch,
+            attestation_epoch,
+            validators: validators.into_iter().map(|(_, v)| v).collect(),
+        })
+    }
+}
@@ -266,3 +266,79 @@ fn attestation_that_skips_epochs() {
         "should process attestation that skips slots"
     );
 }
+
+#[test]
+fn attestation_batch_validity() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    // Extend the chain out a few epochs so we have some chain depth to play with.
+    harness.extend_chain(
+        MainnetEthSpec::slots_per_epoch() as usize * 3 + 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = chain.head().expect("should get head");
+
+    let valid_attestations = harness
+        .get_free_attestations(
+            &AttestationStrategy::AllValidators,
+            &head.beacon_state,
+            head.beacon_block_root,
+            head.beacon_block.slot(),
+        )
+        .into_iter()
+        .take(4)
+        .collect::<Vec<_>>();
+    assert_eq!(valid_attestations.len(), 4, "should get four attestations");
+
+    /*
+     * Should accept a batch of valid attestations.
+     */
+
+    let batch = valid_attestations
+        .iter()
+        .cloned()
+        .map(|attestation| (attestation, AttestationType::Aggregated))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        chain.process_attestation_batch(batch),
+        Ok(vec![
+            Ok(AttestationProcessingOutcome::Processed),
+            Ok(AttestationProcessingOutcome::Processed),
+            Ok(AttestationProcessingOutcome::Processed),
+            Ok(AttestationProcessingOutcome::Processed),
+        ]),
+        "should accept a valid batch"
+    );
+
+    /*
+     * Should only reject the attestations with a bad signature or that fail the initial checks.
+     */
+
+    let mut batch = valid_attestations
+        .iter()
+        .cloned()
+        .map(|attestation| (attestation, AttestationType::Aggregated))
+        .collect::<Vec<_>>();
+
+    let kp = generate_deterministic_keypair(0);
+    let mut agg_sig = AggregateSignature::new();
+    agg_sig.add(&Signature::new(&[42, 42], &kp.sk));
+    batch[1].0.signature = agg_sig;
+
+    batch[2].0.aggregation_bits = BitList::with_capacity(1).expect("should build bitfield");
+
+    assert_eq!(
+        chain.process_attestation_batch(batch),
+        Ok(vec![
+            Ok(AttestationProcessingOutcome::Processed),
+            Ok(AttestationProcessingOutcome::InvalidSignature),
+            Ok(AttestationProcessingOutcome::EmptyAggregationBitfield),
+            Ok(AttestationProcessingOutcome::Processed),
+        ]),
+        "should fall back to individual verification for an invalid batch"
+    );
+}
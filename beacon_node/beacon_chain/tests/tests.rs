@@ -9,10 +9,15 @@ use beacon_chain::test_utils::{
 use beacon_chain::{AttestationProcessingOutcome, AttestationType};
 use operation_pool::PersistedOperationPool;
 use state_processing::{
-    per_slot_processing, per_slot_processing::Error as SlotProcessingError, EpochProcessingError,
+    per_epoch_processing::{get_attestation_deltas, get_proposer_deltas, ValidatorStatuses},
+    per_slot_processing,
+    per_slot_processing::Error as SlotProcessingError,
+    EpochProcessingError,
 };
 use store::Store;
-use types::{BeaconStateError, EthSpec, Hash256, Keypair, MinimalEthSpec, RelativeEpoch, Slot};
+use types::{
+    BeaconStateError, EthSpec, Hash256, Keypair, MinimalEthSpec, RelativeEpoch, Signature, Slot,
+};
 
 // Should ideally be divisible by 3.
 pub const VALIDATOR_COUNT: usize = 24;
@@ -592,3 +597,142 @@ fn produces_and_processes_with_genesis_skip_slots() {
         run_skip_slot_test(i)
     }
 }
+
+#[test]
+fn attestation_deltas_match_balance_changes() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let spec = &harness.chain.spec;
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+
+    // Finish at the last slot of an epoch.
+    harness.extend_chain(
+        slots_per_epoch as usize * 3 - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let mut state = harness.chain.head().expect("should get head").beacon_state;
+    assert_eq!(
+        (state.slot + 1) % slots_per_epoch,
+        0,
+        "should be end of epoch"
+    );
+
+    state
+        .build_all_committee_caches(spec)
+        .expect("should build caches");
+    let mut validator_statuses = ValidatorStatuses::new(&state, spec).expect("should get statuses");
+    validator_statuses
+        .process_attestations(&state, spec)
+        .expect("should process attestations");
+
+    let attestation_deltas = get_attestation_deltas(&state, &validator_statuses, spec)
+        .expect("should get attestation deltas");
+    let proposer_deltas =
+        get_proposer_deltas(&state, &validator_statuses, spec).expect("should get proposer deltas");
+
+    let balances_before = state.balances.clone();
+    per_slot_processing(&mut state, None, spec).expect("should process epoch");
+
+    for i in 0..VALIDATOR_COUNT {
+        let attestation_delta = attestation_deltas[i]
+            .clone()
+            .flatten()
+            .expect("should flatten");
+        let proposer_delta = &proposer_deltas[i];
+
+        assert_eq!(
+            attestation_delta.penalties, 0,
+            "all validators should have attested correctly"
+        );
+        assert_eq!(
+            state.balances[i],
+            balances_before[i] + attestation_delta.rewards + proposer_delta.rewards
+                - proposer_delta.penalties,
+            "balance change should match the deltas"
+        );
+    }
+}
+
+#[test]
+fn block_reward_includes_new_attesters() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+
+    let head_block_root = harness.extend_chain(
+        slots_per_epoch as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let block_reward = harness
+        .chain
+        .compute_block_reward_at_root(head_block_root)
+        .expect("should compute block reward");
+
+    assert_eq!(
+        block_reward.new_attesters,
+        VALIDATOR_COUNT as u64 / slots_per_epoch,
+        "should include the attestations from the previous slot"
+    );
+    assert!(block_reward.attestation_rewards > 0);
+    assert_eq!(block_reward.proposer_slashing_rewards, 0);
+    assert_eq!(block_reward.attester_slashing_rewards, 0);
+    assert_eq!(block_reward.total, block_reward.attestation_rewards);
+}
+
+#[test]
+fn block_production_report_matches_block() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+
+    harness.extend_chain(
+        slots_per_epoch as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let slot = harness.chain.slot().expect("should have a slot");
+    let (block, _state, report) = harness
+        .chain
+        .produce_block_with_report(Signature::empty_signature(), slot)
+        .expect("should produce block");
+    let reward = report.reward.as_ref().expect("should compute the reward");
+
+    assert_eq!(report.slot, block.slot);
+    assert_eq!(report.proposer_index, block.proposer_index);
+    assert_eq!(reward.block_root, block.canonical_root());
+    assert_eq!(
+        report.attestations.included.len(),
+        block.body.attestations.len()
+    );
+    assert!(
+        !report.attestations.included.is_empty(),
+        "should include the attestations from the previous slot"
+    );
+
+    for (packed, attestation) in report
+        .attestations
+        .included
+        .iter()
+        .zip(block.body.attestations.iter())
+    {
+        assert_eq!(packed.slot, attestation.data.slot);
+        assert_eq!(packed.index, attestation.data.index);
+    }
+
+    assert_eq!(
+        report.attestations.total_reward(),
+        reward.attestation_rewards,
+        "marginal rewards should sum to the block reward"
+    );
+    assert_eq!(
+        reward.new_attesters,
+        VALIDATOR_COUNT as u64 / slots_per_epoch
+    );
+    assert!(report.proposer_slashings.is_empty());
+    assert!(report.attester_slashings.is_empty());
+    assert!(report.voluntary_exits.is_empty());
+    assert!(report.total_time_us >= report.attestation_packing_time_us);
+}
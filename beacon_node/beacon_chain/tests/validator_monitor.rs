@@ -0,0 +1,110 @@
+#![cfg(not(debug_assertions))]
+
+#[macro_use]
+extern crate lazy_static;
+
+use beacon_chain::test_utils::{
+    AttestationStrategy, BeaconChainHarness, BlockStrategy, HarnessType,
+};
+use types::{Epoch, EthSpec, Keypair, MinimalEthSpec, PublicKeyBytes};
+
+pub const VALIDATOR_COUNT: usize = 24;
+
+lazy_static! {
+    /// A cached set of keys.
+    static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
+}
+
+fn get_harness() -> BeaconChainHarness<HarnessType<MinimalEthSpec>> {
+    let harness = BeaconChainHarness::new(MinimalEthSpec, KEYPAIRS.to_vec());
+
+    harness.advance_slot();
+
+    harness
+}
+
+#[test]
+fn monitors_attestations_and_proposals() {
+    let harness = get_harness();
+    let monitor = &harness.chain.validator_monitor;
+
+    monitor.add_validator_index(0);
+    monitor.add_validator_pubkey(PublicKeyBytes::from(KEYPAIRS[1].pk.clone()));
+    assert!(!monitor.is_empty());
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 4,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let validators = monitor.get_monitored_validators();
+    assert_eq!(validators.len(), 2, "both validators should be monitored");
+    assert_eq!(validators[0].index, Some(0));
+    assert_eq!(validators[1].index, Some(1), "pubkey should be resolved");
+    assert_eq!(
+        validators[1].pubkey,
+        Some(PublicKeyBytes::from(KEYPAIRS[1].pk.clone()))
+    );
+
+    for validator in &validators {
+        let index = validator.index.expect("index should be known");
+
+        for epoch in 1..3 {
+            let summary = monitor
+                .get_epoch_summary(index, Epoch::new(epoch))
+                .expect("should have summary");
+
+            assert!(summary.is_active, "validator should be active");
+            assert!(summary.is_attester, "attestation should be included");
+            assert!(summary.is_target_attester, "target should be correct");
+            assert!(summary.is_head_attester, "head should be correct");
+            assert_eq!(summary.inclusion_distance, Some(1));
+            assert!(summary.balance.is_some(), "balance should be known");
+            assert!(
+                summary.balance_delta.expect("delta should be known") > 0,
+                "validator should be rewarded"
+            );
+            assert!(summary.missed_slots.is_empty(), "no slots were skipped");
+        }
+    }
+}
+
+#[test]
+fn reports_missed_proposals() {
+    let harness = get_harness();
+    let monitor = &harness.chain.validator_monitor;
+
+    for index in 0..VALIDATOR_COUNT as u64 {
+        monitor.add_validator_index(index);
+    }
+
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+
+    // Produce blocks for the first epoch, skip the second and then resume.
+    harness.extend_chain(
+        slots_per_epoch as usize - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    for _ in 0..=slots_per_epoch {
+        harness.advance_slot();
+    }
+    harness.extend_chain(
+        slots_per_epoch as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let missed_slots = monitor
+        .get_monitored_validators()
+        .iter()
+        .filter_map(|validator| monitor.get_epoch_summary(validator.index?, Epoch::new(1)))
+        .map(|summary| summary.missed_slots.len() as u64)
+        .sum::<u64>();
+
+    assert_eq!(
+        missed_slots, slots_per_epoch,
+        "every slot in the skipped epoch should be reported as missed"
+    );
+}
@@ -10,7 +10,7 @@ use beacon_chain::{
     BeaconChain, BeaconChainTypes, Eth1ChainBackend, EventHandler,
 };
 use environment::RuntimeContext;
-use eth1::{Config as Eth1Config, Service as Eth1Service};
+use eth1::{Config as Eth1Config, DepositTreeSnapshot, Service as Eth1Service};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
 use futures::{future, Future, IntoFuture};
@@ -121,6 +121,8 @@ where
         let eth_spec_instance = self.eth_spec_instance.clone();
         let data_dir = config.data_dir.clone();
         let disabled_forks = config.disabled_forks.clone();
+        let validator_monitor_pubkeys = config.validator_monitor_pubkeys.clone();
+        let validator_monitor_indices = config.validator_monitor_indices.clone();
 
         future::ok(())
             .and_then(move |()| {
@@ -143,7 +145,8 @@ where
                     .store_migrator(store_migrator)
                     .data_dir(data_dir)
                     .custom_spec(spec.clone())
-                    .disabled_forks(disabled_forks);
+                    .disabled_forks(disabled_forks)
+                    .monitor_validators(validator_monitor_pubkeys, validator_monitor_indices);
 
                 Ok((builder, spec, context))
             })
@@ -632,7 +635,14 @@ where
     /// Specifies that the `BeaconChain` should cache eth1 blocks/logs from a remote eth1 node
     /// (e.g., Parity/Geth) and refer to that cache when collecting deposits or eth1 votes during
     /// block production.
-    pub fn caching_eth1_backend(mut self, config: Eth1Config) -> Result<Self, String> {
+    ///
+    /// If there is no eth1 cache from genesis or from a previous run, the deposit cache is
+    /// initialised from `deposit_snapshot` (when supplied).
+    pub fn caching_eth1_backend(
+        mut self,
+        config: Eth1Config,
+        deposit_snapshot: Option<DepositTreeSnapshot>,
+    ) -> Result<Self, String> {
         let context = self
             .runtime_context
             .as_ref()
@@ -672,8 +682,19 @@ where
                     )
                     .map(|chain| chain.into_backend())
                 })
-                .unwrap_or_else(|| {
-                    Ok(CachingEth1Backend::new(config, context.log.clone(), store))
+                .unwrap_or_else(|| match deposit_snapshot {
+                    Some(snapshot) => {
+                        info!(
+                            context.log,
+                            "Initialising deposit cache from snapshot";
+                            "deposit_count" => snapshot.deposit_count,
+                            "eth1_block_number" => snapshot.block_number,
+                            "deposit_root" => format!("{:?}", snapshot.deposit_root),
+                        );
+                        Eth1Service::from_deposit_snapshot(config, snapshot, context.log.clone())
+                            .map(|service| CachingEth1Backend::from_service(service, store))
+                    }
+                    None => Ok(CachingEth1Backend::new(config, context.log.clone(), store)),
                 })?
         };
 
@@ -2,6 +2,7 @@ use network::NetworkConfig;
 use serde_derive::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use types::PublicKeyBytes;
 
 pub const DEFAULT_DATADIR: &str = ".lighthouse";
 
@@ -55,6 +56,10 @@ pub struct Config {
     pub sync_eth1_chain: bool,
     /// A list of hard-coded forks that will be disabled.
     pub disabled_forks: Vec<String>,
+    /// Public keys of validators whose performance will be tracked by the validator monitor.
+    pub validator_monitor_pubkeys: Vec<PublicKeyBytes>,
+    /// Indices of validators whose performance will be tracked by the validator monitor.
+    pub validator_monitor_indices: Vec<u64>,
     #[serde(skip)]
     /// The `genesis` field is not serialized or deserialized by `serde` to ensure it is defined
     /// via the CLI at runtime, instead of from a configuration file saved to disk.
@@ -64,6 +69,10 @@ pub struct Config {
     pub rest_api: rest_api::Config,
     pub websocket_server: websocket_server::Config,
     pub eth1: eth1::Config,
+    #[serde(skip)]
+    /// A snapshot of the deposit tree used to initialise the deposit cache, instead of
+    /// downloading every deposit log since the deposit contract was deployed.
+    pub eth1_deposit_snapshot: Option<eth1::DepositTreeSnapshot>,
 }
 
 impl Default for Config {
@@ -83,7 +92,10 @@ impl Default for Config {
             dummy_eth1_backend: false,
             sync_eth1_chain: false,
             eth1: <_>::default(),
+            eth1_deposit_snapshot: None,
             disabled_forks: Vec::new(),
+            validator_monitor_pubkeys: Vec::new(),
+            validator_monitor_indices: Vec::new(),
         }
     }
 }
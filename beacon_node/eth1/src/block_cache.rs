@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use std::ops::RangeInclusive;
 use types::{Eth1Data, Hash256};
@@ -18,7 +19,7 @@ pub enum Error {
 /// A block of the eth1 chain.
 ///
 /// Contains all information required to add a `BlockCache` entry.
-#[derive(Debug, PartialEq, Clone, Eq, Hash, Encode, Decode)]
+#[derive(Debug, PartialEq, Clone, Eq, Hash, Encode, Decode, Serialize, Deserialize)]
 pub struct Eth1Block {
     pub hash: Hash256,
     pub timestamp: u64,
@@ -0,0 +1,308 @@
+//! Checks that the configured deposit contract address hosts the eth2 deposit contract and that
+//! the `DepositCache` agrees with the contract's deposit count and deposit root.
+//!
+//! A misconfigured address or deploy block does not cause any requests to fail, instead it causes
+//! deposits to be silently missed. These checks exist to make such misconfigurations loud.
+use crate::endpoints::first_success;
+use crate::http::{
+    get_block_number, get_code, get_deposit_count, get_deposit_logs_in_range, get_deposit_root,
+};
+use crate::inner::Inner;
+use crate::DepositLog;
+use deposit_contract::{testnet, BYTECODE};
+use futures::{
+    future::{self, Either},
+    Future,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tiny_keccak::keccak256;
+use types::Hash256;
+
+/// Timeout for each of the requests made whilst checking the deposit contract.
+const CONTRACT_CHECK_TIMEOUT_MILLIS: u64 = 15_000;
+
+/// The result of the latest check of the deposit contract.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum DepositContractStatus {
+    /// The deposit contract has not yet been checked.
+    Unknown,
+    /// The code at the deposit contract address is a known deposit contract, however the
+    /// `DepositCache` has not processed enough blocks to compare deposit counts.
+    CodeVerified,
+    /// The code is a known deposit contract and the `DepositCache` agrees with the contract's
+    /// deposit count and deposit root.
+    Verified,
+    /// The deposit contract address or deploy block is misconfigured.
+    Invalid(DepositContractError),
+}
+
+impl Default for DepositContractStatus {
+    fn default() -> Self {
+        DepositContractStatus::Unknown
+    }
+}
+
+/// Describes how the configured deposit contract differs from what was expected.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum DepositContractError {
+    /// There is no code at the deposit contract address.
+    NoCode,
+    /// The code at the deposit contract address is not a known deposit contract.
+    ///
+    /// The `code_hash` is the keccak256 hash of the code, as returned by `EXTCODEHASH`.
+    UnknownCode { code_hash: Hash256 },
+    /// The number of deposits known to the `DepositCache` (including the logs it has not yet
+    /// processed) differs from the contract's deposit count. This is likely due to the deploy
+    /// block being too high.
+    DepositCountMismatch {
+        block_number: u64,
+        contract_deposit_count: u64,
+        cached_deposit_count: u64,
+    },
+    /// The deposit root computed by the `DepositCache` (including the logs it has not yet
+    /// processed) differs from the contract's deposit root. This is likely due to the
+    /// `DepositCache` being initialised from an invalid deposit tree snapshot.
+    DepositRootMismatch {
+        block_number: u64,
+        contract_deposit_root: Hash256,
+        cached_deposit_root: Hash256,
+    },
+}
+
+/// Checks the code at the deposit contract address, then compares the deposit count and deposit
+/// root of the contract at the head of the eth1 chain with the `DepositCache`.
+///
+/// The deposit count and root are only compared once the `DepositCache` has processed all but
+/// `follow_distance + blocks_per_log_query` blocks, so that the unprocessed logs can be
+/// downloaded in a single request. The code, deposit count and root are only read from the latest
+/// block, so that the check does not require an archive node.
+pub fn check_deposit_contract(
+    inner: Arc<Inner>,
+) -> impl Future<Item = DepositContractStatus, Error = String> {
+    let timeout = Duration::from_millis(CONTRACT_CHECK_TIMEOUT_MILLIS);
+    let (address, max_unprocessed_blocks) = {
+        let config = inner.config.read();
+        (
+            config.deposit_contract_address.clone(),
+            config.follow_distance + config.blocks_per_log_query as u64,
+        )
+    };
+
+    first_success(inner.clone(), move |endpoint| {
+        let endpoint = endpoint.to_string();
+        let address = address.clone();
+        let inner = inner.clone();
+
+        get_code(&endpoint, &address, timeout).and_then(move |code| {
+            if let Err(e) = verify_code(&code) {
+                return Either::A(future::ok(DepositContractStatus::Invalid(e)));
+            }
+
+            let (last_processed_block, cached_deposit_count) = {
+                let deposits = inner.deposit_cache.read();
+                (deposits.last_processed_block, deposits.cache.len() as u64)
+            };
+
+            Either::B(get_block_number(&endpoint, timeout).and_then(move |head| {
+                let last_processed_block = match last_processed_block {
+                    Some(block) if block <= head && head - block <= max_unprocessed_blocks => block,
+                    _ => return Either::A(future::ok(DepositContractStatus::CodeVerified)),
+                };
+
+                let unprocessed_logs = if last_processed_block < head {
+                    Either::A(get_deposit_logs_in_range(
+                        &endpoint,
+                        &address,
+                        last_processed_block + 1..head,
+                        timeout,
+                    ))
+                } else {
+                    Either::B(future::ok(vec![]))
+                };
+
+                Either::B(
+                    get_deposit_count(&endpoint, &address, head, timeout)
+                        .join3(
+                            get_deposit_root(&endpoint, &address, head, timeout),
+                            unprocessed_logs,
+                        )
+                        .and_then(
+                            move |(contract_deposit_count, contract_deposit_root, logs)| {
+                                let (contract_deposit_count, contract_deposit_root) =
+                                    match (contract_deposit_count, contract_deposit_root) {
+                                        (Some(count), Some(root)) => (count, root),
+                                        _ => {
+                                            return Ok(DepositContractStatus::Invalid(
+                                                DepositContractError::NoCode,
+                                            ))
+                                        }
+                                    };
+
+                                // The range of a log request may or may not be inclusive, so filter
+                                // the logs explicitly.
+                                let unprocessed_logs = logs
+                                    .iter()
+                                    .filter(|log| {
+                                        log.block_number > last_processed_block
+                                            && log.block_number <= head
+                                    })
+                                    .map(DepositLog::from_log)
+                                    .collect::<Result<Vec<_>, _>>()?;
+                                let cached_deposit_count =
+                                    cached_deposit_count + unprocessed_logs.len() as u64;
+
+                                if contract_deposit_count != cached_deposit_count {
+                                    return Ok(DepositContractStatus::Invalid(
+                                        DepositContractError::DepositCountMismatch {
+                                            block_number: head,
+                                            contract_deposit_count,
+                                            cached_deposit_count,
+                                        },
+                                    ));
+                                }
+
+                                let cached_deposit_root = inner
+                                    .deposit_cache
+                                    .read()
+                                    .cache
+                                    .deposit_root_with_logs(&unprocessed_logs)
+                                    .map_err(|e| {
+                                        format!("Failed to compute deposit root: {:?}", e)
+                                    })?;
+
+                                if contract_deposit_root == cached_deposit_root {
+                                    Ok(DepositContractStatus::Verified)
+                                } else {
+                                    Ok(DepositContractStatus::Invalid(
+                                        DepositContractError::DepositRootMismatch {
+                                            block_number: head,
+                                            contract_deposit_root,
+                                            cached_deposit_root,
+                                        },
+                                    ))
+                                }
+                            },
+                        ),
+                )
+            }))
+        })
+    })
+}
+
+lazy_static! {
+    /// The runtime code of each of the known deposit contracts.
+    static ref KNOWN_RUNTIME_CODES: Vec<Vec<u8>> = [BYTECODE, testnet::BYTECODE]
+        .iter()
+        .filter_map(|bytecode| decode_bytecode(bytecode))
+        .filter_map(|bytecode| runtime_code(&bytecode).map(<[u8]>::to_vec))
+        .collect();
+}
+
+/// Returns `Ok(())` if `code` is exactly the runtime code of one of the known deposit contracts.
+fn verify_code(code: &[u8]) -> Result<(), DepositContractError> {
+    if code.is_empty() {
+        return Err(DepositContractError::NoCode);
+    }
+
+    if KNOWN_RUNTIME_CODES.iter().any(|runtime| runtime == code) {
+        Ok(())
+    } else {
+        Err(DepositContractError::UnknownCode {
+            code_hash: Hash256::from_slice(&keccak256(code)),
+        })
+    }
+}
+
+/// Returns the runtime code that is deployed by the deployment `bytecode`.
+///
+/// The deposit contracts are compiled by Vyper, which places the runtime code directly after the
+/// init code. The init code copies the runtime code into memory and returns it, which is the only
+/// `RETURN` in the init code.
+fn runtime_code(bytecode: &[u8]) -> Option<&[u8]> {
+    const PUSH1: u8 = 0x60;
+    const PUSH32: u8 = 0x7f;
+    const RETURN: u8 = 0xf3;
+
+    let mut i = 0;
+    while i < bytecode.len() {
+        match bytecode[i] {
+            RETURN => return bytecode.get(i + 1..).filter(|runtime| !runtime.is_empty()),
+            // skip the data of push instructions, which may contain the `RETURN` byte
+            op @ PUSH1..=PUSH32 => i += (op - PUSH1) as usize + 2,
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+/// Decodes a deployment bytecode file, which contains a quoted, `0x`-prefixed hex string.
+fn decode_bytecode(bytecode: &[u8]) -> Option<Vec<u8>> {
+    let hex = std::str::from_utf8(bytecode).ok()?.trim().trim_matches('"');
+    let hex = if hex.starts_with("0x") {
+        &hex[2..]
+    } else {
+        hex
+    };
+    hex::decode(hex).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deposit_contract_bytecode() -> Vec<u8> {
+        decode_bytecode(BYTECODE).expect("should decode deposit contract bytecode")
+    }
+
+    #[test]
+    fn empty_code() {
+        assert_eq!(verify_code(&[]), Err(DepositContractError::NoCode));
+    }
+
+    #[test]
+    fn known_code() {
+        let bytecode = deposit_contract_bytecode();
+        let runtime = runtime_code(&bytecode).expect("should find runtime code");
+        assert_eq!(verify_code(runtime), Ok(()));
+    }
+
+    #[test]
+    fn partial_code() {
+        let bytecode = deposit_contract_bytecode();
+        let runtime = runtime_code(&bytecode).expect("should find runtime code");
+
+        for code in &[&runtime[1..], &runtime[..runtime.len() - 1], &runtime[..1]] {
+            assert_eq!(
+                verify_code(code),
+                Err(DepositContractError::UnknownCode {
+                    code_hash: Hash256::from_slice(&keccak256(code)),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn deployment_code() {
+        let bytecode = deposit_contract_bytecode();
+        assert_eq!(
+            verify_code(&bytecode),
+            Err(DepositContractError::UnknownCode {
+                code_hash: Hash256::from_slice(&keccak256(&bytecode)),
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_code() {
+        let code = vec![0xfe; deposit_contract_bytecode().len() + 1];
+        assert_eq!(
+            verify_code(&code),
+            Err(DepositContractError::UnknownCode {
+                code_hash: Hash256::from_slice(&keccak256(&code)),
+            })
+        );
+    }
+}
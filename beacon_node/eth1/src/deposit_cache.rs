@@ -1,4 +1,5 @@
 use crate::DepositLog;
+use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use state_processing::common::DepositDataTree;
 use std::cmp::Ordering;
@@ -27,16 +28,91 @@ pub enum Error {
     DepositCountInvalid { deposit_count: u64, range_end: u64 },
     /// Error with the merkle tree for deposits.
     DepositTreeError(merkle_proof::MerkleTreeError),
+    /// The requested deposits are part of the snapshot the cache was initialised from, so their
+    /// logs (and proofs) are not available.
+    DepositsFinalized {
+        start: u64,
+        finalized_deposit_count: u64,
+    },
+    /// The deposit root of a `DepositTreeSnapshot` does not match its finalized hashes.
+    InvalidSnapshot {
+        expected_deposit_root: Hash256,
+        computed_deposit_root: Hash256,
+    },
     /// An unexpected condition was encountered.
     InternalError(String),
 }
 
+/// A compact representation of the deposit contract's Merkle tree after `deposit_count` deposits.
+///
+/// Rather than every deposit, it contains only the hashes of the full subtrees which contain
+/// them. This is sufficient for a `DepositCache` to add the deposits that follow and produce
+/// proofs for them, without downloading every deposit log since the contract was deployed.
+#[derive(Debug, PartialEq, Clone, Encode, Decode, Serialize, Deserialize)]
+pub struct DepositTreeSnapshot {
+    /// The hashes of the full subtrees which contain the first `deposit_count` deposits, from left
+    /// to right.
+    pub finalized: Vec<Hash256>,
+    /// The root of the deposit tree (with the deposit count mixed in), as returned by the deposit
+    /// contract's `get_deposit_root`.
+    pub deposit_root: Hash256,
+    pub deposit_count: u64,
+    /// An eth1 block at which the deposit contract had exactly `deposit_count` deposits.
+    pub block_number: u64,
+}
+
+impl DepositTreeSnapshot {
+    /// Returns the deposit tree represented by `self`, checking that its root matches
+    /// `self.deposit_root`.
+    fn to_deposit_tree(&self) -> Result<DepositDataTree, Error> {
+        let tree = DepositDataTree::from_finalized_snapshot(
+            &self.finalized,
+            self.deposit_count as usize,
+            DEPOSIT_TREE_DEPTH,
+        )
+        .map_err(Error::DepositTreeError)?;
+
+        if tree.root() == self.deposit_root {
+            Ok(tree)
+        } else {
+            Err(Error::InvalidSnapshot {
+                expected_deposit_root: self.deposit_root,
+                computed_deposit_root: tree.root(),
+            })
+        }
+    }
+}
+
+/// The layout of `SszDepositCache` before deposit tree snapshots were supported.
+///
+/// Only used to migrate caches persisted by earlier versions.
+#[derive(Encode, Decode, Clone)]
+pub struct SszDepositCacheV1 {
+    logs: Vec<DepositLog>,
+    leaves: Vec<Hash256>,
+    deposit_contract_deploy_block: u64,
+    deposit_roots: Vec<Hash256>,
+}
+
+impl From<SszDepositCacheV1> for SszDepositCache {
+    fn from(cache: SszDepositCacheV1) -> Self {
+        Self {
+            logs: cache.logs,
+            leaves: cache.leaves,
+            deposit_contract_deploy_block: cache.deposit_contract_deploy_block,
+            deposit_roots: cache.deposit_roots,
+            snapshot: None,
+        }
+    }
+}
+
 #[derive(Encode, Decode, Clone)]
 pub struct SszDepositCache {
     logs: Vec<DepositLog>,
     leaves: Vec<Hash256>,
     deposit_contract_deploy_block: u64,
     deposit_roots: Vec<Hash256>,
+    snapshot: Option<DepositTreeSnapshot>,
 }
 
 impl SszDepositCache {
@@ -46,12 +122,25 @@ impl SszDepositCache {
             leaves: cache.leaves.clone(),
             deposit_contract_deploy_block: cache.deposit_contract_deploy_block,
             deposit_roots: cache.deposit_roots.clone(),
+            snapshot: cache.snapshot.clone(),
         }
     }
 
     pub fn to_deposit_cache(&self) -> Result<DepositCache, String> {
-        let deposit_tree =
-            DepositDataTree::create(&self.leaves, self.leaves.len(), DEPOSIT_TREE_DEPTH);
+        let deposit_tree = match &self.snapshot {
+            Some(snapshot) => {
+                let mut deposit_tree = snapshot
+                    .to_deposit_tree()
+                    .map_err(|e| format!("Invalid SszDepositCache: {:?}", e))?;
+                for leaf in &self.leaves {
+                    deposit_tree
+                        .push_leaf(*leaf)
+                        .map_err(|e| format!("Invalid SszDepositCache: {:?}", e))?;
+                }
+                deposit_tree
+            }
+            None => DepositDataTree::create(&self.leaves, self.leaves.len(), DEPOSIT_TREE_DEPTH),
+        };
         // Check for invalid SszDepositCache conditions
         if self.leaves.len() != self.logs.len() {
             return Err("Invalid SszDepositCache: logs and leaves should have equal length".into());
@@ -69,6 +158,7 @@ impl SszDepositCache {
             deposit_contract_deploy_block: self.deposit_contract_deploy_block,
             deposit_tree,
             deposit_roots: self.deposit_roots.clone(),
+            snapshot: self.snapshot.clone(),
         })
     }
 }
@@ -76,7 +166,11 @@ impl SszDepositCache {
 /// Mirrors the merkle tree of deposits in the eth1 deposit contract.
 ///
 /// Provides `Deposit` objects with merkle proofs included.
+///
+/// If the cache was initialised from a `DepositTreeSnapshot`, only the logs of the deposits which
+/// follow the snapshot are stored (and only those deposits can be proven).
 pub struct DepositCache {
+    /// Logs of the deposits which follow the snapshot (if any), ordered by index.
     logs: Vec<DepositLog>,
     leaves: Vec<Hash256>,
     deposit_contract_deploy_block: u64,
@@ -84,8 +178,10 @@ pub struct DepositCache {
     /// deposit contract tree.
     deposit_tree: DepositDataTree,
     /// Vector of deposit roots. `deposit_roots[i]` denotes `deposit_root` at
-    /// `deposit_index` `i`.
+    /// `deposit_index` `i + self.finalized_deposit_count()`.
     deposit_roots: Vec<Hash256>,
+    /// The snapshot the cache was initialised from, if any.
+    snapshot: Option<DepositTreeSnapshot>,
 }
 
 impl Default for DepositCache {
@@ -98,6 +194,7 @@ impl Default for DepositCache {
             deposit_contract_deploy_block: 1,
             deposit_tree,
             deposit_roots,
+            snapshot: None,
         }
     }
 }
@@ -112,14 +209,89 @@ impl DepositCache {
         }
     }
 
-    /// Returns the number of deposits available in the cache.
+    /// Create a new `DepositCache` from a `snapshot` of the deposit contract tree.
+    ///
+    /// Logs for the deposits in the snapshot are not required (and will be ignored if inserted),
+    /// logs for subsequent deposits should be inserted starting from `snapshot.block_number + 1`.
+    ///
+    /// ## Errors
+    ///
+    /// - If the finalized hashes of the snapshot are invalid or do not match its deposit root.
+    pub fn from_snapshot(
+        deposit_contract_deploy_block: u64,
+        snapshot: DepositTreeSnapshot,
+    ) -> Result<Self, Error> {
+        Ok(DepositCache {
+            logs: Vec::new(),
+            leaves: Vec::new(),
+            deposit_contract_deploy_block,
+            deposit_tree: snapshot.to_deposit_tree()?,
+            deposit_roots: vec![snapshot.deposit_root],
+            snapshot: Some(snapshot),
+        })
+    }
+
+    /// Returns the snapshot the cache was initialised from, if any.
+    pub fn initial_snapshot(&self) -> Option<&DepositTreeSnapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// Returns the number of deposits in the snapshot the cache was initialised from, or zero if
+    /// it was not initialised from a snapshot.
+    ///
+    /// Deposits with a lower index do not have logs in the cache.
+    pub fn finalized_deposit_count(&self) -> u64 {
+        self.snapshot
+            .as_ref()
+            .map_or(0, |snapshot| snapshot.deposit_count)
+    }
+
+    /// Returns the number of deposits known to the cache, including those in the snapshot it was
+    /// initialised from.
     pub fn len(&self) -> usize {
-        self.logs.len()
+        self.finalized_deposit_count() as usize + self.logs.len()
     }
 
-    /// True if the cache does not store any blocks.
+    /// True if the cache does not know of any deposits.
     pub fn is_empty(&self) -> bool {
-        self.logs.is_empty()
+        self.len() == 0
+    }
+
+    /// Returns a snapshot of the deposit tree after the most deposits possible, up to
+    /// `max_deposit_count`.
+    ///
+    /// The snapshot can only be taken at an eth1 block boundary, since its `block_number` must be
+    /// a block at which the deposit contract had exactly `deposit_count` deposits.
+    ///
+    /// Returns `None` if the cache knows of no deposits, or not enough to take a snapshot of at
+    /// most `max_deposit_count` deposits.
+    pub fn get_snapshot(&self, max_deposit_count: u64) -> Option<DepositTreeSnapshot> {
+        let finalized_deposit_count = self.finalized_deposit_count();
+        let mut deposit_count = std::cmp::min(max_deposit_count, self.len() as u64);
+
+        // Reduce the deposit count until the next deposit is in a later block than the previous.
+        while deposit_count > finalized_deposit_count {
+            let previous = self.get(deposit_count - 1)?;
+            match self.get(deposit_count) {
+                Some(next) if next.block_number == previous.block_number => deposit_count -= 1,
+                _ => break,
+            }
+        }
+
+        if deposit_count == finalized_deposit_count {
+            return self.snapshot.clone();
+        } else if deposit_count < finalized_deposit_count {
+            return None;
+        }
+
+        let tree = self.build_tree(deposit_count, DEPOSIT_TREE_DEPTH).ok()?;
+
+        Some(DepositTreeSnapshot {
+            finalized: tree.get_finalized_hashes().ok()?,
+            deposit_root: tree.root(),
+            deposit_count,
+            block_number: self.get(deposit_count - 1)?.block_number,
+        })
     }
 
     /// Returns the block number for the most recent deposit in the cache.
@@ -132,9 +304,13 @@ impl DepositCache {
         self.logs.iter()
     }
 
-    /// Returns the i'th deposit log.
-    pub fn get(&self, i: usize) -> Option<&DepositLog> {
-        self.logs.get(i)
+    /// Returns the log of the deposit with index `i`.
+    ///
+    /// Returns `None` if the deposit is unknown or part of the snapshot the cache was initialised
+    /// from.
+    pub fn get(&self, i: u64) -> Option<&DepositLog> {
+        let offset = i.checked_sub(self.finalized_deposit_count())?;
+        self.logs.get(offset as usize)
     }
 
     /// Adds `log` to self.
@@ -146,8 +322,15 @@ impl DepositCache {
     ///
     /// - If a log with index `log.index - 1` is not already present in `self` (ignored when empty).
     /// - If a log with `log.index` is already known, but the given `log` is distinct to it.
+    ///
+    /// Logs for deposits within the snapshot the cache was initialised from are ignored, since
+    /// they cannot be checked.
     pub fn insert_log(&mut self, log: DepositLog) -> Result<(), Error> {
-        match log.index.cmp(&(self.logs.len() as u64)) {
+        if log.index < self.finalized_deposit_count() {
+            return Ok(());
+        }
+
+        match log.index.cmp(&(self.len() as u64)) {
             Ordering::Equal => {
                 let deposit = log.deposit_data.tree_hash_root();
                 self.leaves.push(deposit);
@@ -159,7 +342,7 @@ impl DepositCache {
                 Ok(())
             }
             Ordering::Less => {
-                if self.logs[log.index as usize] == log {
+                if self.get(log.index) == Some(&log) {
                     Ok(())
                 } else {
                     Err(Error::DuplicateDistinctLog(log.index))
@@ -167,7 +350,7 @@ impl DepositCache {
             }
             Ordering::Greater => Err(Error::NonConsecutive {
                 log_index: log.index,
-                expected: self.logs.len(),
+                expected: self.len(),
             }),
         }
     }
@@ -183,6 +366,7 @@ impl DepositCache {
     ///
     /// - If `deposit_count` is larger than `end`.
     /// - There are not sufficient deposits in the tree to generate the proof.
+    /// - If `start` is within the snapshot the cache was initialised from.
     pub fn get_deposits(
         &self,
         start: u64,
@@ -190,31 +374,35 @@ impl DepositCache {
         deposit_count: u64,
         tree_depth: usize,
     ) -> Result<(Hash256, Vec<Deposit>), Error> {
+        let finalized_deposit_count = self.finalized_deposit_count();
+
         if deposit_count < end {
             // It's invalid to ask for more deposits than should exist.
             Err(Error::DepositCountInvalid {
                 deposit_count,
                 range_end: end,
             })
-        } else if end > self.logs.len() as u64 {
+        } else if end > self.len() as u64 {
             // The range of requested deposits exceeds the deposits stored locally.
             Err(Error::InsufficientDeposits {
                 requested: end,
-                known_deposits: self.logs.len(),
+                known_deposits: self.len(),
             })
-        } else if deposit_count > self.leaves.len() as u64 {
+        } else if deposit_count > self.len() as u64 {
             // There are not `deposit_count` known deposit roots, so we can't build the merkle tree
             // to prove into.
             Err(Error::InsufficientDeposits {
                 requested: deposit_count,
-                known_deposits: self.logs.len(),
+                known_deposits: self.len(),
+            })
+        } else if start < finalized_deposit_count || deposit_count < finalized_deposit_count {
+            // The logs of finalized deposits are not known, nor can we build a tree with fewer
+            // deposits than the snapshot.
+            Err(Error::DepositsFinalized {
+                start,
+                finalized_deposit_count,
             })
         } else {
-            let leaves = self
-                .leaves
-                .get(0..deposit_count as usize)
-                .ok_or_else(|| Error::InternalError("Unable to get known leaves".into()))?;
-
             // Note: there is likely a more optimal solution than recreating the `DepositDataTree`
             // each time this function is called.
             //
@@ -222,11 +410,14 @@ impl DepositCache {
             // last finalized eth1 deposit count. Then, that tree could be cloned and extended for
             // each of these calls.
 
-            let tree = DepositDataTree::create(leaves, deposit_count as usize, tree_depth);
+            let tree = self.build_tree(deposit_count, tree_depth)?;
 
             let deposits = self
                 .logs
-                .get(start as usize..end as usize)
+                .get(
+                    (start - finalized_deposit_count) as usize
+                        ..(end - finalized_deposit_count) as usize,
+                )
                 .ok_or_else(|| Error::InternalError("Unable to get known log".into()))?
                 .iter()
                 .map(|deposit_log| {
@@ -243,12 +434,66 @@ impl DepositCache {
         }
     }
 
+    /// Returns the deposit root after the given `logs` have been added, without modifying the
+    /// cache.
+    ///
+    /// Logs for deposits that are already known are ignored.
+    pub fn deposit_root_with_logs(&self, logs: &[DepositLog]) -> Result<Hash256, Error> {
+        let mut next_index = self.len() as u64;
+        let mut tree = self.build_tree(next_index, DEPOSIT_TREE_DEPTH)?;
+
+        for log in logs.iter().filter(|log| log.index >= self.len() as u64) {
+            if log.index != next_index {
+                return Err(Error::NonConsecutive {
+                    log_index: log.index,
+                    expected: next_index as usize,
+                });
+            }
+            tree.push_leaf(log.deposit_data.tree_hash_root())
+                .map_err(Error::DepositTreeError)?;
+            next_index += 1;
+        }
+
+        Ok(tree.root())
+    }
+
+    /// Returns a new deposit tree of the given `depth`, containing the first `deposit_count`
+    /// deposits.
+    fn build_tree(&self, deposit_count: u64, depth: usize) -> Result<DepositDataTree, Error> {
+        let finalized_deposit_count = self.finalized_deposit_count();
+        let leaves = deposit_count
+            .checked_sub(finalized_deposit_count)
+            .and_then(|num_leaves| self.leaves.get(0..num_leaves as usize))
+            .ok_or_else(|| Error::InternalError("Unable to get known leaves".into()))?;
+
+        match &self.snapshot {
+            Some(snapshot) => {
+                let mut tree = DepositDataTree::from_finalized_snapshot(
+                    &snapshot.finalized,
+                    finalized_deposit_count as usize,
+                    depth,
+                )
+                .map_err(Error::DepositTreeError)?;
+                for leaf in leaves {
+                    tree.push_leaf(*leaf).map_err(Error::DepositTreeError)?;
+                }
+                Ok(tree)
+            }
+            None => Ok(DepositDataTree::create(
+                leaves,
+                deposit_count as usize,
+                depth,
+            )),
+        }
+    }
+
     /// Gets the deposit count at block height = block_number.
     ///
     /// Fetches the `DepositLog` that was emitted at or just before `block_number`
     /// and returns the deposit count as `index + 1`.
     ///
-    /// Returns `None` if block number queried is 0 or less than deposit_contract_deployed block.
+    /// Returns `None` if block number queried is 0 or less than deposit_contract_deployed block,
+    /// or is earlier than the block of the snapshot the cache was initialised from.
     pub fn get_deposit_count_from_cache(&self, block_number: u64) -> Option<u64> {
         // Contract cannot be deployed in 0'th block
         if block_number == 0 {
@@ -257,10 +502,15 @@ impl DepositCache {
         if block_number < self.deposit_contract_deploy_block {
             return None;
         }
-        // Return 0 if block_num queried is before first deposit
+        let finalized_deposit_count = match &self.snapshot {
+            Some(snapshot) if block_number < snapshot.block_number => return None,
+            Some(snapshot) => snapshot.deposit_count,
+            None => 0,
+        };
+        // Return the finalized deposit count if block_num queried is before first deposit
         if let Some(first_deposit) = self.logs.first() {
             if first_deposit.block_number > block_number {
-                return Some(0);
+                return Some(finalized_deposit_count);
             }
         }
         let index = self
@@ -271,7 +521,7 @@ impl DepositCache {
             Err(next) => Some(
                 self.logs
                     .get(next.saturating_sub(1))
-                    .map_or(0, |x| x.index + 1),
+                    .map_or(finalized_deposit_count, |x| x.index + 1),
             ),
         }
     }
@@ -282,7 +532,8 @@ impl DepositCache {
     /// and queries the `deposit_roots` map to get the corresponding `deposit_root`.
     pub fn get_deposit_root_from_cache(&self, block_number: u64) -> Option<Hash256> {
         let index = self.get_deposit_count_from_cache(block_number)?;
-        Some(*self.deposit_roots.get(index as usize)?)
+        let offset = index.checked_sub(self.finalized_deposit_count())?;
+        Some(*self.deposit_roots.get(offset as usize)?)
     }
 }
 
@@ -291,6 +542,7 @@ pub mod tests {
     use super::*;
     use crate::deposit_log::tests::EXAMPLE_LOG;
     use crate::http::Log;
+    use ssz::{Decode, Encode};
 
     pub const TREE_DEPTH: usize = 32;
 
@@ -439,4 +691,147 @@ pub mod tests {
         // Range higher than count.
         assert!(tree.get_deposits(0, 4, 2, TREE_DEPTH).is_err());
     }
+
+    fn get_logs(n: u64, deposits_per_block: u64) -> Vec<DepositLog> {
+        (0..n)
+            .map(|i| {
+                let mut log = example_log();
+                log.index = i;
+                log.block_number = 1 + i / deposits_per_block;
+                log.deposit_data.withdrawal_credentials = Hash256::from_low_u64_be(i);
+                log
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_at_block_boundary() {
+        let mut tree = DepositCache::default();
+        for log in get_logs(10, 3) {
+            tree.insert_log(log).expect("should add consecutive logs");
+        }
+
+        let snapshot = tree.get_snapshot(8).expect("should get snapshot");
+        assert_eq!(
+            snapshot.deposit_count, 6,
+            "deposits 6, 7 and 8 share a block, so the snapshot should stop before them"
+        );
+        assert_eq!(snapshot.block_number, 2);
+        assert_eq!(
+            Some(snapshot.deposit_root),
+            tree.get_deposit_root_from_cache(2)
+        );
+
+        let snapshot = tree.get_snapshot(100).expect("should get snapshot");
+        assert_eq!(snapshot.deposit_count, 10);
+        assert_eq!(snapshot.block_number, 4);
+
+        assert_eq!(
+            tree.get_snapshot(2),
+            None,
+            "should not snapshot a partial block"
+        );
+    }
+
+    #[test]
+    fn from_snapshot() {
+        let n = 100;
+        let logs = get_logs(n, 3);
+
+        let mut full_tree = DepositCache::default();
+        for log in logs.clone() {
+            full_tree
+                .insert_log(log)
+                .expect("should add consecutive logs");
+        }
+
+        let snapshot = full_tree.get_snapshot(n / 2).expect("should get snapshot");
+        let finalized = snapshot.deposit_count;
+
+        let mut tree = DepositCache::from_snapshot(1, snapshot.clone())
+            .expect("should create cache from snapshot");
+        assert_eq!(tree.len() as u64, finalized);
+        assert_eq!(tree.get_snapshot(n), Some(snapshot.clone()));
+
+        for log in logs {
+            tree.insert_log(log).expect("should add consecutive logs");
+        }
+        assert_eq!(tree.len() as u64, n);
+
+        assert_eq!(
+            tree.get_deposits(finalized, n, n, TREE_DEPTH),
+            full_tree.get_deposits(finalized, n, n, TREE_DEPTH),
+            "should produce the same deposits and proofs as the full tree"
+        );
+        assert_eq!(
+            tree.get_deposits(finalized - 1, n, n, TREE_DEPTH),
+            Err(Error::DepositsFinalized {
+                start: finalized - 1,
+                finalized_deposit_count: finalized,
+            })
+        );
+
+        for block_number in 1..=n / 3 + 1 {
+            let expected = if block_number < snapshot.block_number {
+                None
+            } else {
+                full_tree.get_deposit_root_from_cache(block_number)
+            };
+            assert_eq!(
+                tree.get_deposit_root_from_cache(block_number),
+                expected,
+                "deposit root at block {}",
+                block_number
+            );
+        }
+
+        assert_eq!(tree.get_snapshot(n), full_tree.get_snapshot(n));
+    }
+
+    #[test]
+    fn from_invalid_snapshot() {
+        let mut tree = DepositCache::default();
+        for log in get_logs(16, 1) {
+            tree.insert_log(log).expect("should add consecutive logs");
+        }
+
+        let mut snapshot = tree.get_snapshot(16).expect("should get snapshot");
+        snapshot.deposit_root = Hash256::zero();
+
+        assert!(DepositCache::from_snapshot(1, snapshot).is_err());
+    }
+
+    #[test]
+    fn migrate_ssz_v1() {
+        let mut tree = DepositCache::default();
+        for log in get_logs(16, 1) {
+            tree.insert_log(log).expect("should add consecutive logs");
+        }
+
+        let current = SszDepositCache::from_deposit_cache(&tree);
+        let v1 = SszDepositCacheV1 {
+            logs: current.logs.clone(),
+            leaves: current.leaves.clone(),
+            deposit_contract_deploy_block: current.deposit_contract_deploy_block,
+            deposit_roots: current.deposit_roots.clone(),
+        };
+
+        let bytes = v1.as_ssz_bytes();
+        assert!(
+            SszDepositCache::from_ssz_bytes(&bytes).is_err(),
+            "the v1 layout should not decode as the current layout"
+        );
+
+        let migrated = SszDepositCache::from(
+            SszDepositCacheV1::from_ssz_bytes(&bytes).expect("should decode v1 layout"),
+        )
+        .to_deposit_cache()
+        .expect("should convert migrated cache");
+
+        assert_eq!(migrated.get_snapshot(16), tree.get_snapshot(16));
+        assert_eq!(
+            migrated.get_deposit_root_from_cache(16),
+            tree.get_deposit_root_from_cache(16)
+        );
+    }
 }
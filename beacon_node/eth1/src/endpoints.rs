@@ -0,0 +1,318 @@
+//! Tracks the health of each of the configured eth1 endpoints and provides automatic failover
+//! between them.
+//!
+//! Endpoints are used in the order they are configured, except that endpoints which failed their
+//! most recent health check (or request) are only tried once all others have failed. Endpoints
+//! on the wrong network are never used.
+use crate::http::{get_block, get_block_number, get_chain_id, get_network_id, get_syncing};
+use crate::inner::Inner;
+use crate::metrics;
+use crate::Config;
+use futures::{
+    future::{self, loop_fn, Either, Loop},
+    Future, IntoFuture,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Timeout for each of the requests made during an endpoint health check.
+const HEALTH_CHECK_TIMEOUT_MILLIS: u64 = 5_000;
+
+/// The health of an eth1 endpoint, as determined by its latest health check or request.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum EndpointHealth {
+    /// The endpoint has not yet been checked.
+    Unknown,
+    /// The endpoint passed its latest health check and has not failed a request since.
+    Healthy,
+    /// The endpoint failed its latest health check or a request since.
+    Unhealthy(EndpointError),
+}
+
+/// The health of a configured endpoint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct EndpointStatus {
+    pub endpoint: String,
+    pub health: EndpointHealth,
+}
+
+/// The reason an endpoint is unhealthy.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum EndpointError {
+    /// The endpoint could not be reached or returned an invalid response.
+    RequestFailed(String),
+    /// The endpoint's `net_version` differs from the configured network id.
+    WrongNetworkId { expected: u64, found: u64 },
+    /// The endpoint's `eth_chainId` differs from the configured chain id.
+    WrongChainId { expected: u64, found: u64 },
+    /// The endpoint reports that it is still syncing.
+    Syncing,
+    /// The endpoint's head block is older than `Config::max_head_age_secs`.
+    StaleHead {
+        head_block_number: u64,
+        head_age_secs: u64,
+    },
+}
+
+impl EndpointHealth {
+    /// Returns `false` if the endpoint must never be used, regardless of the health of the other
+    /// endpoints.
+    pub fn is_usable(&self) -> bool {
+        match self {
+            EndpointHealth::Unhealthy(EndpointError::WrongNetworkId { .. })
+            | EndpointHealth::Unhealthy(EndpointError::WrongChainId { .. }) => false,
+            _ => true,
+        }
+    }
+
+    /// Endpoints with a lower rank are tried first.
+    fn rank(&self) -> u8 {
+        match self {
+            EndpointHealth::Healthy => 0,
+            EndpointHealth::Unknown => 1,
+            EndpointHealth::Unhealthy(_) => 2,
+        }
+    }
+}
+
+/// Stores the latest known health of each eth1 endpoint, keyed by URL.
+#[derive(Default)]
+pub struct Endpoints {
+    health: HashMap<String, EndpointHealth>,
+}
+
+impl Endpoints {
+    /// Returns the health of `endpoint`.
+    pub fn health(&self, endpoint: &str) -> EndpointHealth {
+        self.health
+            .get(endpoint)
+            .cloned()
+            .unwrap_or(EndpointHealth::Unknown)
+    }
+
+    /// Sets the health of `endpoint`, returning the previous value.
+    pub fn set_health(&mut self, endpoint: &str, health: EndpointHealth) -> EndpointHealth {
+        metrics::set_gauge_vec(
+            &metrics::ENDPOINT_HEALTHY,
+            &[endpoint],
+            (health == EndpointHealth::Healthy) as i64,
+        );
+
+        self.health
+            .insert(endpoint.to_string(), health)
+            .unwrap_or(EndpointHealth::Unknown)
+    }
+
+    /// Returns the usable endpoints from `config` in the order they should be tried.
+    pub fn ordered(&self, config: &Config) -> Vec<String> {
+        let mut endpoints = config
+            .endpoints()
+            .map(|endpoint| (self.health(endpoint), endpoint.to_string()))
+            .filter(|(health, _)| health.is_usable())
+            .collect::<Vec<_>>();
+
+        // A stable sort preserves the configured priority of endpoints with equal health.
+        endpoints.sort_by_key(|(health, _)| health.rank());
+
+        endpoints
+            .into_iter()
+            .map(|(_, endpoint)| endpoint)
+            .collect()
+    }
+}
+
+/// Calls `func` with each usable endpoint, in order, until it succeeds.
+///
+/// Endpoints which fail are marked as unhealthy until their next health check. If all endpoints
+/// fail, the error contains the error from each of them.
+pub fn first_success<F, R, T>(inner: Arc<Inner>, func: F) -> impl Future<Item = T, Error = String>
+where
+    F: Fn(&str) -> R,
+    R: IntoFuture<Item = T, Error = String>,
+{
+    let endpoints = inner.endpoints.read().ordered(&inner.config.read());
+
+    if endpoints.is_empty() {
+        return Either::A(future::err("No usable eth1 endpoints".to_string()));
+    }
+
+    let future = loop_fn(
+        (endpoints.into_iter().enumerate(), vec![]),
+        move |(mut remaining, mut errors)| match remaining.next() {
+            None => Either::A(future::err(format!(
+                "All eth1 endpoints failed: {}",
+                errors.join(", ")
+            ))),
+            Some((i, endpoint)) => {
+                let inner = inner.clone();
+
+                metrics::inc_counter_vec(&metrics::ENDPOINT_REQUESTS, &[&endpoint]);
+
+                Either::B(
+                    func(&endpoint)
+                        .into_future()
+                        .then(move |result| match result {
+                            Ok(item) => {
+                                if i > 0 {
+                                    metrics::inc_counter(&metrics::ENDPOINT_FAILOVERS);
+                                }
+                                Ok(Loop::Break(item))
+                            }
+                            Err(e) => {
+                                metrics::inc_counter_vec(
+                                    &metrics::ENDPOINT_REQUEST_ERRORS,
+                                    &[&endpoint],
+                                );
+                                inner.endpoints.write().set_health(
+                                    &endpoint,
+                                    EndpointHealth::Unhealthy(EndpointError::RequestFailed(
+                                        e.clone(),
+                                    )),
+                                );
+                                errors.push(format!("{} ({})", endpoint, e));
+                                Ok(Loop::Continue((remaining, errors)))
+                            }
+                        }),
+                )
+            }
+        },
+    );
+
+    Either::B(future)
+}
+
+/// Checks the network id, chain id, sync status and head block age of `endpoint`.
+///
+/// The network and chain ids are only checked if they are specified in `config`.
+pub fn check_endpoint(
+    endpoint: String,
+    config: &Config,
+) -> impl Future<Item = EndpointHealth, Error = String> {
+    let timeout = Duration::from_millis(HEALTH_CHECK_TIMEOUT_MILLIS);
+    let expected_network_id = config.network_id;
+    let expected_chain_id = config.chain_id;
+    let max_head_age_secs = config.max_head_age_secs;
+    let endpoint_1 = endpoint.clone();
+    let endpoint_2 = endpoint.clone();
+    let endpoint_3 = endpoint.clone();
+
+    let network_id_check = match expected_network_id {
+        Some(expected) => Either::A(
+            get_network_id(&endpoint, timeout)
+                .map_err(EndpointError::RequestFailed)
+                .and_then(move |found| {
+                    if found == expected {
+                        Ok(())
+                    } else {
+                        Err(EndpointError::WrongNetworkId { expected, found })
+                    }
+                }),
+        ),
+        None => Either::B(future::ok(())),
+    };
+
+    network_id_check
+        .and_then(move |()| match expected_chain_id {
+            Some(expected) => Either::A(
+                get_chain_id(&endpoint_1, timeout)
+                    .map_err(EndpointError::RequestFailed)
+                    .and_then(move |found| {
+                        if found == expected {
+                            Ok(())
+                        } else {
+                            Err(EndpointError::WrongChainId { expected, found })
+                        }
+                    }),
+            ),
+            None => Either::B(future::ok(())),
+        })
+        .and_then(move |()| {
+            get_syncing(&endpoint_2, timeout)
+                .map_err(EndpointError::RequestFailed)
+                .and_then(|syncing| {
+                    if syncing {
+                        Err(EndpointError::Syncing)
+                    } else {
+                        Ok(())
+                    }
+                })
+        })
+        .and_then(move |()| {
+            get_block_number(&endpoint_3, timeout)
+                .and_then(move |number| get_block(&endpoint_3, number, timeout))
+                .map_err(EndpointError::RequestFailed)
+        })
+        .and_then(move |head| {
+            metrics::set_gauge_vec(
+                &metrics::ENDPOINT_HEAD_BLOCK_NUMBER,
+                &[&endpoint],
+                head.number as i64,
+            );
+
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| EndpointError::RequestFailed(format!("Bad system time: {:?}", e)))?
+                .as_secs();
+            let head_age_secs = now.saturating_sub(head.timestamp);
+
+            match max_head_age_secs {
+                Some(max) if head_age_secs > max => Err(EndpointError::StaleHead {
+                    head_block_number: head.number,
+                    head_age_secs,
+                }),
+                _ => Ok(()),
+            }
+        })
+        .then(|result| {
+            Ok(match result {
+                Ok(()) => EndpointHealth::Healthy,
+                Err(e) => EndpointHealth::Unhealthy(e),
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            endpoint: "a".into(),
+            fallback_endpoints: vec!["b".into(), "c".into(), "d".into()],
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn ordered_by_priority_when_unchecked() {
+        assert_eq!(
+            Endpoints::default().ordered(&config()),
+            vec!["a", "b", "c", "d"]
+        );
+    }
+
+    #[test]
+    fn ordered_by_health_then_priority() {
+        let mut endpoints = Endpoints::default();
+        endpoints.set_health(
+            "a",
+            EndpointHealth::Unhealthy(EndpointError::RequestFailed("down".into())),
+        );
+        endpoints.set_health(
+            "b",
+            EndpointHealth::Unhealthy(EndpointError::WrongChainId {
+                expected: 1,
+                found: 5,
+            }),
+        );
+        endpoints.set_health("d", EndpointHealth::Healthy);
+
+        assert_eq!(
+            endpoints.ordered(&config()),
+            vec!["d", "c", "a"],
+            "healthy endpoints should be first and wrong-chain endpoints should be excluded"
+        );
+    }
+}
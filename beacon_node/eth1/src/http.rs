@@ -1,17 +1,17 @@
 //! Provides a very minimal set of functions for interfacing with the eth2 deposit contract via an
-//! eth1 HTTP JSON-RPC endpoint.
+//! eth1 JSON-RPC endpoint.
 //!
 //! All remote functions return a future (i.e., are async).
 //!
-//! Does not use a web3 library, instead it uses `reqwest` (`hyper`) to call the remote endpoint
-//! and `serde` to decode the response.
+//! Does not use a web3 library, instead it uses `reqwest` (`hyper`), a websocket or an IPC socket
+//! to call the remote endpoint (see the `transport` module) and `serde` to decode the response.
 //!
 //! ## Note
 //!
 //! There is no ABI parsing here, all function signatures and topics are hard-coded as constants.
 
-use futures::{Future, Stream};
-use reqwest::{header::CONTENT_TYPE, r#async::ClientBuilder, StatusCode};
+use crate::transport::Transport;
+use futures::{Future, IntoFuture};
 use serde_json::{json, Value};
 use std::ops::Range;
 use std::time::Duration;
@@ -56,6 +56,61 @@ pub fn get_block_number(
         .map_err(|e| format!("Failed to get block number: {}", e))
 }
 
+/// Returns the network id reported by `net_version`.
+///
+/// Uses HTTP JSON RPC at `endpoint`. E.g., `http://localhost:8545`.
+pub fn get_network_id(
+    endpoint: &str,
+    timeout: Duration,
+) -> impl Future<Item = u64, Error = String> {
+    send_rpc_request(endpoint, "net_version", json!([]), timeout)
+        .and_then(|response_body| {
+            response_result(&response_body)?
+                .ok_or_else(|| "No result field was returned for network id".to_string())?
+                .as_str()
+                .ok_or_else(|| "Data was not string")?
+                .parse::<u64>()
+                .map_err(|e| format!("Failed to parse network id: {:?}", e))
+        })
+        .map_err(|e| format!("Failed to get network id: {}", e))
+}
+
+/// Returns the chain id reported by `eth_chainId`.
+///
+/// Uses HTTP JSON RPC at `endpoint`. E.g., `http://localhost:8545`.
+pub fn get_chain_id(endpoint: &str, timeout: Duration) -> impl Future<Item = u64, Error = String> {
+    send_rpc_request(endpoint, "eth_chainId", json!([]), timeout)
+        .and_then(|response_body| {
+            hex_to_u64_be(
+                response_result(&response_body)?
+                    .ok_or_else(|| "No result field was returned for chain id".to_string())?
+                    .as_str()
+                    .ok_or_else(|| "Data was not string")?,
+            )
+        })
+        .map_err(|e| format!("Failed to get chain id: {}", e))
+}
+
+/// Returns `true` if the node reports that it is syncing via `eth_syncing`.
+///
+/// Uses HTTP JSON RPC at `endpoint`. E.g., `http://localhost:8545`.
+pub fn get_syncing(endpoint: &str, timeout: Duration) -> impl Future<Item = bool, Error = String> {
+    send_rpc_request(endpoint, "eth_syncing", json!([]), timeout)
+        .and_then(|response_body| {
+            let result = response_result(&response_body)?
+                .ok_or_else(|| "No result field was returned for sync status".to_string())?;
+
+            // The node returns `false` when it is not syncing, otherwise an object describing the
+            // sync progress.
+            match result {
+                Value::Bool(syncing) => Ok(syncing),
+                Value::Object(_) => Ok(true),
+                other => Err(format!("Unexpected sync status: {}", other)),
+            }
+        })
+        .map_err(|e| format!("Failed to get sync status: {}", e))
+}
+
 /// Gets a block hash by block number.
 ///
 /// Uses HTTP JSON RPC at `endpoint`. E.g., `http://localhost:8545`.
@@ -154,6 +209,26 @@ pub fn get_deposit_count(
     })
 }
 
+/// Returns the code deployed at `address` in the latest block.
+///
+/// Uses HTTP JSON RPC at `endpoint`. E.g., `http://localhost:8545`.
+pub fn get_code(
+    endpoint: &str,
+    address: &str,
+    timeout: Duration,
+) -> impl Future<Item = Vec<u8>, Error = String> {
+    send_rpc_request(endpoint, "eth_getCode", json!([address, "latest"]), timeout)
+        .and_then(|response_body| {
+            hex_to_bytes(
+                response_result(&response_body)?
+                    .ok_or_else(|| "No result field was returned for code".to_string())?
+                    .as_str()
+                    .ok_or_else(|| "Data was not string")?,
+            )
+        })
+        .map_err(|e| format!("Failed to get code: {}", e))
+}
+
 /// Returns the value of the `get_hash_tree_root()` call at the given `block_number`.
 ///
 /// Assumes that the `address` has the same ABI as the eth2 deposit contract.
@@ -282,7 +357,8 @@ pub fn get_deposit_logs_in_range(
         .map_err(|e| format!("Failed to get logs in range: {}", e))
 }
 
-/// Sends an RPC request to `endpoint`, using a POST with the given `body`.
+/// Sends an RPC request to `endpoint`, using the transport determined by the format of
+/// `endpoint` (see `Transport::from_endpoint`).
 ///
 /// Tries to receive the response and parse the body as a `String`.
 pub fn send_rpc_request(
@@ -296,59 +372,11 @@ pub fn send_rpc_request(
         "method": method,
         "params": params,
         "id": 1
-    })
-    .to_string();
-
-    // Note: it is not ideal to create a new client for each request.
-    //
-    // A better solution would be to create some struct that contains a built client and pass it
-    // around (similar to the `web3` crate's `Transport` structs).
-    ClientBuilder::new()
-        .timeout(timeout)
-        .build()
-        .expect("The builder should always build a client")
-        .post(endpoint)
-        .header(CONTENT_TYPE, "application/json")
-        .body(body)
-        .send()
-        .map_err(|e| format!("Request failed: {:?}", e))
-        .and_then(|response| {
-            if response.status() != StatusCode::OK {
-                Err(format!(
-                    "Response HTTP status was not 200 OK:  {}.",
-                    response.status()
-                ))
-            } else {
-                Ok(response)
-            }
-        })
-        .and_then(|response| {
-            response
-                .headers()
-                .get(CONTENT_TYPE)
-                .ok_or_else(|| "No content-type header in response".to_string())
-                .and_then(|encoding| {
-                    encoding
-                        .to_str()
-                        .map(|s| s.to_string())
-                        .map_err(|e| format!("Failed to parse content-type header: {}", e))
-                })
-                .map(|encoding| (response, encoding))
-        })
-        .and_then(|(response, encoding)| {
-            response
-                .into_body()
-                .concat2()
-                .map(|chunk| chunk.iter().cloned().collect::<Vec<u8>>())
-                .map_err(|e| format!("Failed to receive body: {:?}", e))
-                .and_then(move |bytes| match encoding.as_str() {
-                    "application/json" => Ok(bytes),
-                    "application/json; charset=utf-8" => Ok(bytes),
-                    other => Err(format!("Unsupported encoding: {}", other)),
-                })
-                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
-                .map_err(|e| format!("Failed to receive body: {:?}", e))
-        })
+    });
+
+    Transport::from_endpoint(endpoint)
+        .into_future()
+        .and_then(move |transport| transport.send(body, timeout))
 }
 
 /// Accepts an entire HTTP body (as a string) and returns the `result` field, as a serde `Value`.
@@ -1,11 +1,14 @@
 use crate::Config;
 use crate::{
     block_cache::BlockCache,
-    deposit_cache::{DepositCache, SszDepositCache},
+    contract_check::DepositContractStatus,
+    deposit_cache::{DepositCache, SszDepositCache, SszDepositCacheV1},
+    endpoints::Endpoints,
 };
 use parking_lot::RwLock;
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
+use std::time::Instant;
 
 #[derive(Default)]
 pub struct DepositUpdater {
@@ -28,6 +31,12 @@ pub struct Inner {
     pub block_cache: RwLock<BlockCache>,
     pub deposit_cache: RwLock<DepositUpdater>,
     pub config: RwLock<Config>,
+    pub endpoints: RwLock<Endpoints>,
+    pub deposit_contract_status: RwLock<DepositContractStatus>,
+    /// The time at which the deposit contract was last reported as misconfigured.
+    pub deposit_contract_error_logged_at: RwLock<Option<Instant>>,
+    /// The block number of the head of the eth1 chain, as seen during the latest update.
+    pub remote_head_block: RwLock<Option<u64>>,
 }
 
 impl Inner {
@@ -47,13 +56,39 @@ impl Inner {
     }
 
     /// Recover `Inner` given byte representation of eth1 deposit and block caches.
+    ///
+    /// Caches persisted in the layout used before deposit tree snapshots were supported are
+    /// migrated to the current layout.
     pub fn from_bytes(bytes: &[u8], config: Config) -> Result<Self, String> {
         let ssz_cache = SszEth1Cache::from_ssz_bytes(bytes)
+            .or_else(|e| {
+                SszEth1CacheV1::from_ssz_bytes(bytes)
+                    .map(SszEth1Cache::from)
+                    .map_err(|_| e)
+            })
             .map_err(|e| format!("Ssz decoding error: {:?}", e))?;
         Ok(ssz_cache.to_inner(config)?)
     }
 }
 
+/// The layout of `SszEth1Cache` before deposit tree snapshots were supported.
+#[derive(Encode, Decode, Clone)]
+pub struct SszEth1CacheV1 {
+    block_cache: BlockCache,
+    deposit_cache: SszDepositCacheV1,
+    last_processed_block: Option<u64>,
+}
+
+impl From<SszEth1CacheV1> for SszEth1Cache {
+    fn from(cache: SszEth1CacheV1) -> Self {
+        Self {
+            block_cache: cache.block_cache,
+            deposit_cache: cache.deposit_cache.into(),
+            last_processed_block: cache.last_processed_block,
+        }
+    }
+}
+
 #[derive(Encode, Decode, Clone)]
 pub struct SszEth1Cache {
     block_cache: BlockCache,
@@ -80,6 +115,10 @@ impl SszEth1Cache {
                 last_processed_block: self.last_processed_block,
             }),
             config: RwLock::new(config),
+            endpoints: RwLock::new(Endpoints::default()),
+            deposit_contract_status: RwLock::new(DepositContractStatus::default()),
+            deposit_contract_error_logged_at: RwLock::new(None),
+            remote_head_block: RwLock::new(None),
         })
     }
 }
@@ -2,15 +2,23 @@
 extern crate lazy_static;
 
 mod block_cache;
+mod contract_check;
 mod deposit_cache;
 mod deposit_log;
+mod endpoints;
 pub mod http;
 mod inner;
 mod metrics;
 mod service;
+mod transport;
 
 pub use block_cache::{BlockCache, Eth1Block};
-pub use deposit_cache::DepositCache;
+pub use contract_check::{DepositContractError, DepositContractStatus};
+pub use deposit_cache::{DepositCache, DepositTreeSnapshot};
 pub use deposit_log::DepositLog;
+pub use endpoints::{EndpointError, EndpointHealth, EndpointStatus};
 pub use inner::SszEth1Cache;
-pub use service::{BlockCacheUpdateOutcome, Config, DepositCacheUpdateOutcome, Error, Service};
+pub use service::{
+    BlockCacheUpdateOutcome, Config, DepositCacheUpdateOutcome, Error, Service, Status,
+};
+pub use transport::Transport;
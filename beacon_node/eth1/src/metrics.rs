@@ -16,4 +16,32 @@ lazy_static! {
         try_create_int_gauge("eth1_deposit_cache_len", "Number of deposits in the eth1 cache");
     pub static ref HIGHEST_PROCESSED_DEPOSIT_BLOCK: Result<IntGauge> =
         try_create_int_gauge("eth1_highest_processed_deposit_block", "Number of the last block checked for deposits");
+
+    /*
+     * Eth1 endpoints
+     */
+    pub static ref ENDPOINT_HEALTHY: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "eth1_endpoint_healthy",
+        "Set to 1 if the eth1 endpoint passed its latest health check, otherwise 0",
+        &["endpoint"]
+    );
+    pub static ref ENDPOINT_HEAD_BLOCK_NUMBER: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "eth1_endpoint_head_block_number",
+        "Number of the head block of the eth1 endpoint at its latest health check",
+        &["endpoint"]
+    );
+    pub static ref ENDPOINT_REQUESTS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "eth1_endpoint_requests_total",
+        "Count of requests made to the eth1 endpoint",
+        &["endpoint"]
+    );
+    pub static ref ENDPOINT_REQUEST_ERRORS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "eth1_endpoint_request_errors_total",
+        "Count of requests to the eth1 endpoint that failed",
+        &["endpoint"]
+    );
+    pub static ref ENDPOINT_FAILOVERS: Result<IntCounter> = try_create_int_counter(
+        "eth1_endpoint_failovers_total",
+        "Count of requests that were served by an endpoint other than the first one tried"
+    );
 }
@@ -1,18 +1,23 @@
 use crate::metrics;
 use crate::{
     block_cache::{BlockCache, Error as BlockCacheError, Eth1Block},
-    deposit_cache::Error as DepositCacheError,
-    http::{get_block, get_block_number, get_deposit_logs_in_range},
+    contract_check::{check_deposit_contract, DepositContractStatus},
+    deposit_cache::{DepositCache, DepositTreeSnapshot, Error as DepositCacheError},
+    endpoints::{check_endpoint, first_success, EndpointError, EndpointHealth, EndpointStatus},
+    http::{get_block, get_block_number, get_deposit_logs_in_range, DEPOSIT_EVENT_TOPIC},
     inner::{DepositUpdater, Inner},
+    transport::{Notifications, Transport},
     DepositLog,
 };
 use futures::{
-    future::{loop_fn, Loop},
-    stream, Future, Stream,
+    future,
+    future::{loop_fn, Either, Loop},
+    stream, Future, IntoFuture, Stream,
 };
 use parking_lot::{RwLock, RwLockReadGuard};
 use serde::{Deserialize, Serialize};
-use slog::{debug, error, trace, Logger};
+use serde_json::json;
+use slog::{debug, error, info, trace, warn, Logger};
 use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -26,6 +31,8 @@ const BLOCK_NUMBER_TIMEOUT_MILLIS: u64 = STANDARD_TIMEOUT_MILLIS;
 const GET_BLOCK_TIMEOUT_MILLIS: u64 = STANDARD_TIMEOUT_MILLIS;
 /// Timeout when doing an eth_getLogs to read the deposit contract logs.
 const GET_DEPOSIT_LOG_TIMEOUT_MILLIS: u64 = STANDARD_TIMEOUT_MILLIS;
+/// The minimum time between repeated error logs whilst the deposit contract is misconfigured.
+const DEPOSIT_CONTRACT_ERROR_LOG_INTERVAL: Duration = Duration::from_secs(600);
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
@@ -76,10 +83,27 @@ pub enum DepositCacheUpdateOutcome {
     Success { logs_imported: usize },
 }
 
+/// The health of the eth1 endpoints and the deposit contract.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Status {
+    /// The endpoints, in order of priority.
+    pub endpoints: Vec<EndpointStatus>,
+    pub deposit_contract: DepositContractStatus,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// An Eth1 node (e.g., Geth) running a HTTP JSON-RPC endpoint.
     pub endpoint: String,
+    /// Additional Eth1 nodes to use when `endpoint` is unhealthy, in order of priority.
+    #[serde(default)]
+    pub fallback_endpoints: Vec<String>,
+    /// If set, endpoints that report a different `net_version` are never used.
+    pub network_id: Option<u64>,
+    /// If set, endpoints that report a different `eth_chainId` are never used.
+    pub chain_id: Option<u64>,
+    /// If set, endpoints whose head block is older than this are considered unhealthy.
+    pub max_head_age_secs: Option<u64>,
     /// The address the `BlockCache` and `DepositCache` should assume is the canonical deposit contract.
     pub deposit_contract_address: String,
     /// Defines the first block that the `DepositCache` will start searching for deposit logs.
@@ -110,6 +134,10 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             endpoint: "http://localhost:8545".into(),
+            fallback_endpoints: vec![],
+            network_id: None,
+            chain_id: None,
+            max_head_age_secs: Some(600),
             deposit_contract_address: "0x0000000000000000000000000000000000000000".into(),
             deposit_contract_deploy_block: 1,
             lowest_cached_block_number: 1,
@@ -123,6 +151,13 @@ impl Default for Config {
     }
 }
 
+impl Config {
+    /// Returns all configured endpoints, in order of priority.
+    pub fn endpoints(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.endpoint).chain(self.fallback_endpoints.iter())
+    }
+}
+
 /// Provides a set of Eth1 caches and async functions to update them.
 ///
 /// Stores the following caches:
@@ -150,6 +185,33 @@ impl Service {
         }
     }
 
+    /// Creates a new service with a deposit cache initialised from `snapshot`, rather than
+    /// from the deploy block of the deposit contract. Does not attempt to connect to the eth1
+    /// node.
+    ///
+    /// Deposit logs are downloaded starting from the block following `snapshot.block_number`.
+    pub fn from_deposit_snapshot(
+        config: Config,
+        snapshot: DepositTreeSnapshot,
+        log: Logger,
+    ) -> Result<Self, String> {
+        let last_processed_block = snapshot.block_number;
+        let cache = DepositCache::from_snapshot(config.deposit_contract_deploy_block, snapshot)
+            .map_err(|e| format!("Invalid deposit tree snapshot: {:?}", e))?;
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                deposit_cache: RwLock::new(DepositUpdater {
+                    cache,
+                    last_processed_block: Some(last_processed_block),
+                }),
+                config: RwLock::new(config),
+                ..Inner::default()
+            }),
+            log,
+        })
+    }
+
     /// Return byte representation of deposit and block caches.
     pub fn as_bytes(&self) -> Vec<u8> {
         self.inner.as_bytes()
@@ -204,11 +266,162 @@ impl Service {
         self.deposits().read().cache.len()
     }
 
+    /// Returns a snapshot of the deposit tree containing at most `max_deposit_count` deposits.
+    ///
+    /// See `DepositCache::get_snapshot`.
+    pub fn deposit_snapshot(&self, max_deposit_count: u64) -> Option<DepositTreeSnapshot> {
+        self.deposits().read().cache.get_snapshot(max_deposit_count)
+    }
+
+    /// Returns the block number of the head of the eth1 chain, as seen during the latest update.
+    pub fn remote_head_block(&self) -> Option<u64> {
+        *self.inner.remote_head_block.read()
+    }
+
     /// Read the service's configuration.
     pub fn config(&self) -> RwLockReadGuard<Config> {
         self.inner.config.read()
     }
 
+    /// Returns the latest known health of each configured endpoint, in order of priority.
+    pub fn endpoint_health(&self) -> Vec<(String, EndpointHealth)> {
+        let endpoints = self.inner.endpoints.read();
+        self.config()
+            .endpoints()
+            .map(|endpoint| (endpoint.clone(), endpoints.health(endpoint)))
+            .collect()
+    }
+
+    /// Checks the health of each configured endpoint, updating the order in which they will be
+    /// used for future requests.
+    ///
+    /// Emits a log whenever the health of an endpoint changes.
+    pub fn check_endpoints(
+        &self,
+    ) -> impl Future<Item = Vec<(String, EndpointHealth)>, Error = String> {
+        let inner = self.inner.clone();
+        let log = self.log.clone();
+        let config = self.config().clone();
+
+        future::join_all(
+            config
+                .endpoints()
+                .map(|endpoint| {
+                    let endpoint_1 = endpoint.clone();
+                    check_endpoint(endpoint.clone(), &config)
+                        .map(move |health| (endpoint_1, health))
+                })
+                .collect::<Vec<_>>(),
+        )
+        .map(move |results| {
+            let mut endpoints = inner.endpoints.write();
+
+            for (endpoint, health) in &results {
+                let previous = endpoints.set_health(endpoint, health.clone());
+
+                if previous != *health {
+                    match health {
+                        EndpointHealth::Unhealthy(EndpointError::WrongChainId {
+                            expected,
+                            found,
+                        })
+                        | EndpointHealth::Unhealthy(EndpointError::WrongNetworkId {
+                            expected,
+                            found,
+                        }) => error!(
+                            log,
+                            "Eth1 endpoint is on the wrong chain";
+                            "msg" => "the endpoint will not be used, check the --eth1-endpoint",
+                            "endpoint" => endpoint,
+                            "expected" => expected,
+                            "found" => found,
+                            "reason" => format!("{:?}", health),
+                        ),
+                        EndpointHealth::Healthy => info!(
+                            log,
+                            "Eth1 endpoint is healthy";
+                            "endpoint" => endpoint,
+                        ),
+                        EndpointHealth::Unhealthy(e) => warn!(
+                            log,
+                            "Eth1 endpoint is unhealthy";
+                            "endpoint" => endpoint,
+                            "reason" => format!("{:?}", e),
+                        ),
+                        EndpointHealth::Unknown => (),
+                    }
+                }
+            }
+
+            results
+        })
+    }
+
+    /// Returns the result of the latest check of the deposit contract.
+    pub fn deposit_contract_status(&self) -> DepositContractStatus {
+        self.inner.deposit_contract_status.read().clone()
+    }
+
+    /// Returns the health of the endpoints and the deposit contract.
+    pub fn status(&self) -> Status {
+        Status {
+            endpoints: self
+                .endpoint_health()
+                .into_iter()
+                .map(|(endpoint, health)| EndpointStatus { endpoint, health })
+                .collect(),
+            deposit_contract: self.deposit_contract_status(),
+        }
+    }
+
+    /// Checks that the deposit contract address hosts a known deposit contract and that the
+    /// deposit cache agrees with its deposit count. See `contract_check::check_deposit_contract`.
+    ///
+    /// Emits an error log if the deposit contract is misconfigured, repeated at most once every
+    /// `DEPOSIT_CONTRACT_ERROR_LOG_INTERVAL` whilst it remains misconfigured in the same way.
+    pub fn check_deposit_contract(
+        &self,
+    ) -> impl Future<Item = DepositContractStatus, Error = String> {
+        let inner = self.inner.clone();
+        let log = self.log.clone();
+
+        check_deposit_contract(self.inner.clone()).map(move |status| {
+            let previous =
+                std::mem::replace(&mut *inner.deposit_contract_status.write(), status.clone());
+
+            match &status {
+                DepositContractStatus::Invalid(e) => {
+                    let mut logged_at = inner.deposit_contract_error_logged_at.write();
+                    let should_log = previous != status
+                        || logged_at.map_or(true, |logged_at| {
+                            logged_at.elapsed() >= DEPOSIT_CONTRACT_ERROR_LOG_INTERVAL
+                        });
+
+                    if should_log {
+                        *logged_at = Some(Instant::now());
+                        error!(
+                            log,
+                            "Eth1 deposit contract is misconfigured";
+                            "msg" => "deposits may be missed, check the deposit contract address \
+                                      and deploy block",
+                            "address" => &inner.config.read().deposit_contract_address,
+                            "deploy_block" => inner.config.read().deposit_contract_deploy_block,
+                            "error" => format!("{:?}", e),
+                        )
+                    }
+                }
+                DepositContractStatus::Verified if previous != status => info!(
+                    log,
+                    "Eth1 deposit contract verified";
+                    "address" => &inner.config.read().deposit_contract_address,
+                ),
+                _ => (),
+            }
+
+            status
+        })
+    }
+
     /// Updates the configuration in `self to be `new_config`.
     ///
     /// Will truncate the block cache if the new configure specifies truncation.
@@ -304,8 +517,13 @@ impl Service {
         deposit_future.join(block_future)
     }
 
-    /// A looping future that updates the cache, then waits `config.auto_update_interval` before
-    /// updating it again.
+    /// A looping future that checks the health of the endpoints, updates the cache and checks
+    /// the deposit contract (until it is verified), then waits `config.auto_update_interval`
+    /// before doing so again.
+    ///
+    /// If a websocket or IPC endpoint is configured, the service also subscribes to new heads and
+    /// deposit logs from it and updates as soon as a notification is received, rather than waiting
+    /// for the full interval.
     ///
     /// ## Returns
     ///
@@ -321,13 +539,18 @@ impl Service {
         let log = self.log.clone();
         let update_interval = Duration::from_millis(self.config().auto_update_interval_millis);
 
-        let loop_future = loop_fn((), move |()| {
+        let loop_future = loop_fn(None, move |notifications: Option<Notifications>| {
             let service = service.clone();
             let log_a = log.clone();
             let log_b = log.clone();
 
+            let service_1 = service.clone();
+            let service_2 = service.clone();
+            let service_3 = service.clone();
+
             service
-                .update()
+                .check_endpoints()
+                .then(move |_| service_1.update())
                 .then(move |update_result| {
                     match update_result {
                         Err(e) => error!(
@@ -348,21 +571,133 @@ impl Service {
                     // Do not break the loop if there is an update failure.
                     Ok(())
                 })
-                .and_then(move |_| Delay::new(Instant::now() + update_interval))
-                .then(move |timer_result| {
-                    if let Err(e) = timer_result {
-                        error!(
-                            log_b,
-                            "Failed to trigger eth1 cache update delay";
-                            "error" => format!("{:?}", e),
-                        );
+                .and_then(move |()| {
+                    // Once the deposit contract has been verified there is no need to check it
+                    // again.
+                    if service_2.deposit_contract_status() == DepositContractStatus::Verified {
+                        Either::A(future::ok(()))
+                    } else {
+                        Either::B(service_2.check_deposit_contract().then(|_| Ok(())))
+                    }
+                })
+                .and_then(move |()| match notifications {
+                    Some(notifications) => Either::A(future::ok(Some(notifications))),
+                    None => Either::B(service_3.subscribe()),
+                })
+                .and_then(move |notifications| {
+                    let delay = Delay::new(Instant::now() + update_interval);
+
+                    match notifications {
+                        None => Either::A(delay.then(move |timer_result| {
+                            if let Err(e) = timer_result {
+                                error!(
+                                    log_b,
+                                    "Failed to trigger eth1 cache update delay";
+                                    "error" => format!("{:?}", e),
+                                );
+                            }
+                            // Do not break the loop if there is an timer failure.
+                            Ok(Loop::Continue(None))
+                        })),
+                        // Update when either the delay expires or a notification is received,
+                        // whichever comes first.
+                        Some(notifications) => {
+                            Either::B(delay.select2(notifications.into_future()).then(
+                                move |result| match result {
+                                    Ok(Either::A(((), next))) => {
+                                        Ok(Loop::Continue(next.into_inner()))
+                                    }
+                                    Ok(Either::B(((Some(()), notifications), _))) => {
+                                        Ok(Loop::Continue(Some(notifications)))
+                                    }
+                                    Ok(Either::B(((None, _), _))) => {
+                                        warn!(log_b, "Eth1 subscription closed");
+                                        Ok(Loop::Continue(None))
+                                    }
+                                    Err(Either::A((e, next))) => {
+                                        error!(
+                                            log_b,
+                                            "Failed to trigger eth1 cache update delay";
+                                            "error" => format!("{:?}", e),
+                                        );
+                                        Ok(Loop::Continue(next.into_inner()))
+                                    }
+                                    Err(Either::B(((e, _), _))) => {
+                                        warn!(
+                                            log_b,
+                                            "Eth1 subscription failed";
+                                            "error" => e,
+                                        );
+                                        Ok(Loop::Continue(None))
+                                    }
+                                },
+                            ))
+                        }
                     }
-                    // Do not break the loop if there is an timer failure.
-                    Ok(Loop::Continue(()))
                 })
         });
 
-        loop_future.select(exit).map(|_| ()).map_err(|_| ())
+        loop_future
+            .select(exit.map_err(|_| ()))
+            .map(|_| ())
+            .map_err(|_| ())
+    }
+
+    /// Subscribes to new heads and deposit logs from the first usable endpoint which supports
+    /// subscriptions (i.e., a websocket or IPC endpoint).
+    ///
+    /// Resolves with `None` if there is no such endpoint or the subscription fails.
+    fn subscribe(&self) -> impl Future<Item = Option<Notifications>, Error = ()> {
+        let log = self.log.clone();
+        let (endpoint, address) = {
+            let config = self.config();
+            let endpoint = self
+                .inner
+                .endpoints
+                .read()
+                .ordered(&config)
+                .into_iter()
+                .find(|endpoint| {
+                    Transport::from_endpoint(endpoint)
+                        .map_or(false, |transport| transport.supports_subscriptions())
+                });
+            (endpoint, config.deposit_contract_address.clone())
+        };
+
+        let endpoint = match endpoint {
+            Some(endpoint) => endpoint,
+            None => return Either::A(future::ok(None)),
+        };
+
+        let subscriptions = vec![
+            json!(["newHeads"]),
+            json!(["logs", { "address": address, "topics": [DEPOSIT_EVENT_TOPIC] }]),
+        ];
+
+        Either::B(
+            Transport::from_endpoint(&endpoint)
+                .into_future()
+                .and_then(|transport| transport.subscribe(subscriptions))
+                .then(move |result| match result {
+                    Ok(notifications) => {
+                        info!(
+                            log,
+                            "Subscribed to eth1 endpoint";
+                            "endpoint" => &endpoint,
+                        );
+                        Ok(Some(notifications))
+                    }
+                    Err(e) => {
+                        warn!(
+                            log,
+                            "Failed to subscribe to eth1 endpoint";
+                            "endpoint" => &endpoint,
+                            "error" => e,
+                        );
+                        Ok(None)
+                    }
+                }),
+        )
     }
 
     /// Contacts the remote eth1 node and attempts to import deposit logs up to the configured
@@ -396,7 +731,7 @@ impl Service {
             .unwrap_or_else(|| self.config().deposit_contract_deploy_block);
 
         get_new_block_numbers(
-            &self.config().endpoint,
+            self.inner.clone(),
             next_required_block,
             self.config().follow_distance,
         )
@@ -422,13 +757,17 @@ impl Service {
                 move |mut chunks| match chunks.next() {
                     Some(chunk) => {
                         let chunk_1 = chunk.clone();
+                        let deposit_contract_address =
+                            service_1.config().deposit_contract_address.clone();
                         Some(
-                            get_deposit_logs_in_range(
-                                &service_1.config().endpoint,
-                                &service_1.config().deposit_contract_address,
-                                chunk,
-                                Duration::from_millis(GET_DEPOSIT_LOG_TIMEOUT_MILLIS),
-                            )
+                            first_success(service_1.inner.clone(), move |endpoint| {
+                                get_deposit_logs_in_range(
+                                    endpoint,
+                                    &deposit_contract_address,
+                                    chunk.clone(),
+                                    Duration::from_millis(GET_DEPOSIT_LOG_TIMEOUT_MILLIS),
+                                )
+                            })
                             .map_err(Error::GetDepositLogsFailed)
                             .map(|logs| (chunk_1, logs))
                             .map(|logs| (logs, chunks)),
@@ -521,7 +860,7 @@ impl Service {
             .unwrap_or_else(|| self.config().lowest_cached_block_number);
 
         get_new_block_numbers(
-            &self.config().endpoint,
+            self.inner.clone(),
             next_required_block,
             self.config().follow_distance,
         )
@@ -624,39 +963,44 @@ impl Service {
 /// Determine the range of blocks that need to be downloaded, given the remotes best block and
 /// the locally stored best block.
 fn get_new_block_numbers<'a>(
-    endpoint: &str,
+    inner: Arc<Inner>,
     next_required_block: u64,
     follow_distance: u64,
 ) -> impl Future<Item = Option<RangeInclusive<u64>>, Error = Error> + 'a {
-    get_block_number(endpoint, Duration::from_millis(BLOCK_NUMBER_TIMEOUT_MILLIS))
-        .map_err(Error::GetBlockNumberFailed)
-        .and_then(move |remote_highest_block| {
-            let remote_follow_block = remote_highest_block.saturating_sub(follow_distance);
-
-            if next_required_block <= remote_follow_block {
-                Ok(Some(next_required_block..=remote_follow_block))
-            } else if next_required_block > remote_highest_block + 1 {
-                // If this is the case, the node must have gone "backwards" in terms of it's sync
-                // (i.e., it's head block is lower than it was before).
-                //
-                // We assume that the `follow_distance` should be sufficient to ensure this never
-                // happens, otherwise it is an error.
-                Err(Error::RemoteNotSynced {
-                    next_required_block,
-                    remote_highest_block,
-                    follow_distance,
-                })
-            } else {
-                // Return an empty range.
-                Ok(None)
-            }
-        })
+    first_success(inner.clone(), |endpoint| {
+        get_block_number(endpoint, Duration::from_millis(BLOCK_NUMBER_TIMEOUT_MILLIS))
+    })
+    .map_err(Error::GetBlockNumberFailed)
+    .and_then(move |remote_highest_block| {
+        *inner.remote_head_block.write() = Some(remote_highest_block);
+
+        let remote_follow_block = remote_highest_block.saturating_sub(follow_distance);
+
+        if next_required_block <= remote_follow_block {
+            Ok(Some(next_required_block..=remote_follow_block))
+        } else if next_required_block > remote_highest_block + 1 {
+            // If this is the case, the node must have gone "backwards" in terms of it's sync
+            // (i.e., it's head block is lower than it was before).
+            //
+            // We assume that the `follow_distance` should be sufficient to ensure this never
+            // happens, otherwise it is an error.
+            Err(Error::RemoteNotSynced {
+                next_required_block,
+                remote_highest_block,
+                follow_distance,
+            })
+        } else {
+            // Return an empty range.
+            Ok(None)
+        }
+    })
 }
 
-/// Downloads the `(block, deposit_root, deposit_count)` tuple from an eth1 node for the given
-/// `block_number`.
+/// Downloads the block with the given `block_number` from an eth1 node, pairing it with the
+/// `deposit_root` and `deposit_count` at that block from the local deposit cache.
 ///
-/// Performs three async calls to an Eth1 HTTP JSON RPC endpoint.
+/// Performs a single `eth_getBlockByNumber` call, failing over to the next endpoint if the call
+/// fails.
 fn download_eth1_block<'a>(
     cache: Arc<Inner>,
     block_number: u64,
@@ -672,11 +1016,13 @@ fn download_eth1_block<'a>(
         .cache
         .get_deposit_count_from_cache(block_number);
     // Performs a `get_blockByNumber` call to an eth1 node.
-    get_block(
-        &cache.config.read().endpoint,
-        block_number,
-        Duration::from_millis(GET_BLOCK_TIMEOUT_MILLIS),
-    )
+    first_success(cache, move |endpoint| {
+        get_block(
+            endpoint,
+            block_number,
+            Duration::from_millis(GET_BLOCK_TIMEOUT_MILLIS),
+        )
+    })
     .map_err(Error::BlockDownloadFailed)
     .map(move |http_block| Eth1Block {
         hash: http_block.hash,
@@ -0,0 +1,557 @@
+//! Provides the transports over which JSON-RPC requests are sent to an eth1 node.
+//!
+//! The transport is determined by the endpoint:
+//!
+//! - `http://` or `https://`: HTTP, using `reqwest`.
+//! - `ws://` or `wss://`: a websocket.
+//! - `ipc://` followed by a path, or the path of an existing file: an IPC socket (e.g.,
+//!   `ipc:///home/eth/.ethereum/geth.ipc`).
+//!
+//! Any other endpoint is rejected.
+//!
+//! Requests over a websocket or IPC transport share a single long-lived connection per endpoint,
+//! which is opened by the first request and re-opened by the first request after it closes. Only
+//! the websocket and IPC transports support subscriptions, which use their own connection.
+use bytes::BytesMut;
+use futures::sync::{mpsc, oneshot};
+use futures::{future, stream, Async, AsyncSink, Future, IntoFuture, Poll, Sink, Stream};
+use parking_lot::Mutex;
+use reqwest::{header::CONTENT_TYPE, r#async::ClientBuilder, StatusCode};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::codec::{Decoder, Encoder};
+use tokio::timer::Timeout;
+use websocket::{ClientBuilder as WsClientBuilder, OwnedMessage};
+
+/// A stream of JSON values received from a connection.
+pub type JsonStream = Box<dyn Stream<Item = Value, Error = String> + Send>;
+/// A sink for JSON values to be sent over a connection.
+pub type JsonSink = Box<dyn Sink<SinkItem = Value, SinkError = String> + Send>;
+/// A stream which yields each time a subscription notification is received.
+pub type Notifications = Box<dyn Stream<Item = (), Error = String> + Send>;
+
+/// A request sent to a shared connection, with the channel on which to return the response.
+type Request = (Value, oneshot::Sender<Result<Value, String>>);
+
+lazy_static! {
+    /// The shared connections to websocket and IPC endpoints.
+    static ref CONNECTIONS: Mutex<HashMap<Transport, mpsc::UnboundedSender<Request>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// The transport used to reach an eth1 endpoint.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum Transport {
+    Http(String),
+    Ws(String),
+    Ipc(PathBuf),
+}
+
+impl Transport {
+    /// Determine the transport from the format of `endpoint`.
+    ///
+    /// Returns an error if `endpoint` has an unknown scheme and is not the path of an existing
+    /// file.
+    pub fn from_endpoint(endpoint: &str) -> Result<Self, String> {
+        if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+            Ok(Transport::Http(endpoint.to_string()))
+        } else if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") {
+            Ok(Transport::Ws(endpoint.to_string()))
+        } else if endpoint.starts_with("ipc://") {
+            Ok(Transport::Ipc(PathBuf::from(&endpoint["ipc://".len()..])))
+        } else if Path::new(endpoint).exists() {
+            Ok(Transport::Ipc(PathBuf::from(endpoint)))
+        } else {
+            Err(format!(
+                "Unsupported eth1 endpoint {}, expected an http://, https://, ws://, wss:// or \
+                 ipc:// endpoint or the path of an existing IPC socket",
+                endpoint
+            ))
+        }
+    }
+
+    /// Returns `true` if the transport supports `eth_subscribe`.
+    pub fn supports_subscriptions(&self) -> bool {
+        match self {
+            Transport::Http(_) => false,
+            Transport::Ws(_) | Transport::Ipc(_) => true,
+        }
+    }
+
+    /// Sends a JSON-RPC request with the given `body`, returning the body of the response.
+    pub fn send(
+        &self,
+        body: Value,
+        timeout: Duration,
+    ) -> impl Future<Item = String, Error = String> {
+        match self {
+            Transport::Http(url) => future::Either::A(send_http(url, body.to_string(), timeout)),
+            Transport::Ws(_) | Transport::Ipc(_) => {
+                future::Either::B(send_duplex(self.clone(), body, timeout))
+            }
+        }
+    }
+
+    /// Opens a long-lived connection and sends an `eth_subscribe` request for each of the given
+    /// `subscriptions`, resolving with a stream that yields once for each notification.
+    ///
+    /// The stream ends when the connection is closed.
+    pub fn subscribe(
+        &self,
+        subscriptions: Vec<Value>,
+    ) -> impl Future<Item = Notifications, Error = String> {
+        let requests = subscriptions
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "eth_subscribe",
+                    "params": params,
+                    "id": i + 1,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        self.connect().and_then(move |(sink, stream)| {
+            sink.send_all(stream::iter_ok::<_, String>(requests))
+                .map(move |(sink, _)| {
+                    let notifications = stream
+                        .and_then(|message| match message.get("error") {
+                            Some(error) => Err(format!("Subscription failed: {}", error)),
+                            None => Ok(message),
+                        })
+                        .filter(|message| {
+                            message.get("method").and_then(Value::as_str)
+                                == Some("eth_subscription")
+                        })
+                        .map(move |_| {
+                            // Hold the sink for as long as the stream, so the connection (and
+                            // therefore the subscription) is not closed.
+                            let _ = &sink;
+                        });
+
+                    Box::new(notifications) as Notifications
+                })
+        })
+    }
+
+    /// Opens a connection over a websocket or IPC transport.
+    fn connect(&self) -> impl Future<Item = (JsonSink, JsonStream), Error = String> + Send {
+        match self {
+            Transport::Http(url) => future::Either::A(future::Either::A(future::err(format!(
+                "Cannot open a connection to HTTP endpoint {}",
+                url
+            )))),
+            Transport::Ws(url) => future::Either::A(future::Either::B(connect_ws(url))),
+            Transport::Ipc(path) => future::Either::B(connect_ipc(path.clone())),
+        }
+    }
+}
+
+/// Sends `body` over the shared connection to `transport`, resolving with the response to the
+/// request.
+///
+/// Opens the shared connection if there is none, or if the previous one has closed.
+fn send_duplex(
+    transport: Transport,
+    body: Value,
+    timeout: Duration,
+) -> impl Future<Item = String, Error = String> {
+    let response = future::lazy(move || {
+        let (sender, receiver) = oneshot::channel();
+        let mut connections = CONNECTIONS.lock();
+
+        let request = match connections.get(&transport) {
+            Some(connection) => connection
+                .unbounded_send((body, sender))
+                .map_err(|e| e.into_inner()),
+            None => Err((body, sender)),
+        };
+
+        // The connection has closed (or was never opened), open a new one.
+        if let Err(request) = request {
+            let (connection, requests) = mpsc::unbounded();
+            connection
+                .unbounded_send(request)
+                .expect("the receiver has not been dropped");
+            tokio::spawn(SharedConnection::new(transport.connect(), requests));
+            connections.insert(transport, connection);
+        }
+
+        receiver
+            .map_err(|_| "Connection closed before response".to_string())
+            .and_then(|response| response)
+    })
+    .map(|response| response.to_string());
+
+    Timeout::new(response, timeout).map_err(|e| {
+        if e.is_elapsed() {
+            "Request timed out".to_string()
+        } else {
+            e.into_inner()
+                .unwrap_or_else(|| "Request timer failed".to_string())
+        }
+    })
+}
+
+/// A websocket or IPC connection which is shared by many requests.
+///
+/// Each request is sent with a new `id` so that concurrent requests can be matched with their
+/// responses. The `id` of the original request is restored in the response.
+///
+/// Resolves when the connection closes, failing all requests that are waiting for a response.
+struct SharedConnection {
+    state: ConnectionState,
+    requests: mpsc::UnboundedReceiver<Request>,
+    /// Requests that have been received but not yet accepted by the sink.
+    outgoing: VecDeque<Value>,
+    /// Requests that are waiting for a response, by the `id` they were sent with.
+    pending: HashMap<u64, (Value, oneshot::Sender<Result<Value, String>>)>,
+    next_id: u64,
+}
+
+enum ConnectionState {
+    Connecting(Box<dyn Future<Item = (JsonSink, JsonStream), Error = String> + Send>),
+    Connected(JsonSink, JsonStream),
+}
+
+impl SharedConnection {
+    fn new(
+        connection: impl Future<Item = (JsonSink, JsonStream), Error = String> + Send + 'static,
+        requests: mpsc::UnboundedReceiver<Request>,
+    ) -> Self {
+        Self {
+            state: ConnectionState::Connecting(Box::new(connection)),
+            requests,
+            outgoing: VecDeque::new(),
+            pending: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Stops accepting requests and fails every request which has not been responded to.
+    fn close(&mut self, error: String) {
+        self.requests.close();
+
+        while let Ok(Async::Ready(Some((_, sender)))) = self.requests.poll() {
+            let _ = sender.send(Err(error.clone()));
+        }
+        for (_, (_, sender)) in self.pending.drain() {
+            let _ = sender.send(Err(error.clone()));
+        }
+    }
+
+    /// Sends new requests and dispatches responses, returning `Ready` once the connection has
+    /// closed.
+    fn poll_connected(&mut self) -> Poll<(), String> {
+        // Stop waiting for responses to requests that have timed out.
+        self.pending.retain(|_, (_, sender)| !sender.is_canceled());
+
+        let (sink, stream) = match &mut self.state {
+            ConnectionState::Connected(sink, stream) => (sink, stream),
+            ConnectionState::Connecting(_) => unreachable!("the connection is open"),
+        };
+
+        while let Async::Ready(request) = self.requests.poll().map_err(|()| "Channel failed")? {
+            match request {
+                Some((mut body, sender)) => {
+                    let id = self.next_id;
+                    self.next_id += 1;
+
+                    let original_id = body
+                        .as_object_mut()
+                        .and_then(|body| body.insert("id".to_string(), id.into()))
+                        .unwrap_or(Value::Null);
+
+                    self.outgoing.push_back(body);
+                    self.pending.insert(id, (original_id, sender));
+                }
+                // There are no more senders, close the connection once all requests are done.
+                None if self.pending.is_empty() => return Ok(Async::Ready(())),
+                None => break,
+            }
+        }
+
+        while let Some(body) = self.outgoing.pop_front() {
+            if let AsyncSink::NotReady(body) = sink.start_send(body)? {
+                self.outgoing.push_front(body);
+                break;
+            }
+        }
+        sink.poll_complete()?;
+
+        while let Async::Ready(message) = stream.poll()? {
+            let mut message = match message {
+                Some(message) => message,
+                None => return Ok(Async::Ready(())),
+            };
+
+            // Ignore any messages that are not the response to one of our requests.
+            let id = match message.get("id").and_then(Value::as_u64) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            if let Some((original_id, sender)) = self.pending.remove(&id) {
+                if let Some(message) = message.as_object_mut() {
+                    message.insert("id".to_string(), original_id);
+                }
+                let _ = sender.send(Ok(message));
+            }
+        }
+
+        Ok(Async::NotReady)
+    }
+}
+
+impl Future for SharedConnection {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        if let ConnectionState::Connecting(connection) = &mut self.state {
+            match connection.poll() {
+                Ok(Async::Ready((sink, stream))) => {
+                    self.state = ConnectionState::Connected(sink, stream)
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.close(e);
+                    return Ok(Async::Ready(()));
+                }
+            }
+        }
+
+        match self.poll_connected() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(())) => {
+                self.close("Connection closed before response".to_string());
+                Ok(Async::Ready(()))
+            }
+            Err(e) => {
+                self.close(e);
+                Ok(Async::Ready(()))
+            }
+        }
+    }
+}
+
+/// Sends `body` to `url` using an HTTP POST, resolving with the body of the response.
+fn send_http(
+    url: &str,
+    body: String,
+    timeout: Duration,
+) -> impl Future<Item = String, Error = String> {
+    // Note: it is not ideal to create a new client for each request.
+    //
+    // A better solution would be to create some struct that contains a built client and pass it
+    // around (similar to the `web3` crate's `Transport` structs).
+    ClientBuilder::new()
+        .timeout(timeout)
+        .build()
+        .expect("The builder should always build a client")
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .map_err(|e| format!("Request failed: {:?}", e))
+        .and_then(|response| {
+            if response.status() != StatusCode::OK {
+                Err(format!(
+                    "Response HTTP status was not 200 OK:  {}.",
+                    response.status()
+                ))
+            } else {
+                Ok(response)
+            }
+        })
+        .and_then(|response| {
+            response
+                .headers()
+                .get(CONTENT_TYPE)
+                .ok_or_else(|| "No content-type header in response".to_string())
+                .and_then(|encoding| {
+                    encoding
+                        .to_str()
+                        .map(|s| s.to_string())
+                        .map_err(|e| format!("Failed to parse content-type header: {}", e))
+                })
+                .map(|encoding| (response, encoding))
+        })
+        .and_then(|(response, encoding)| {
+            response
+                .into_body()
+                .concat2()
+                .map(|chunk| chunk.iter().cloned().collect::<Vec<u8>>())
+                .map_err(|e| format!("Failed to receive body: {:?}", e))
+                .and_then(move |bytes| match encoding.as_str() {
+                    "application/json" => Ok(bytes),
+                    "application/json; charset=utf-8" => Ok(bytes),
+                    other => Err(format!("Unsupported encoding: {}", other)),
+                })
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .map_err(|e| format!("Failed to receive body: {:?}", e))
+        })
+}
+
+/// Opens a websocket connection to `url`.
+fn connect_ws(url: &str) -> impl Future<Item = (JsonSink, JsonStream), Error = String> + Send {
+    let url = url.to_string();
+
+    WsClientBuilder::new(&url)
+        .map_err(|e| format!("Invalid websocket endpoint {}: {:?}", url, e))
+        .into_future()
+        .and_then(|builder| {
+            builder
+                .async_connect(None)
+                .map_err(|e| format!("Failed to connect to websocket: {:?}", e))
+        })
+        .map(|(client, _headers)| {
+            let (sink, stream) = client.split();
+
+            let sink = sink
+                .sink_map_err(|e| format!("Failed to send websocket message: {:?}", e))
+                .with(|value: Value| Ok::<_, String>(OwnedMessage::Text(value.to_string())));
+
+            let stream = stream
+                .map_err(|e| format!("Failed to receive websocket message: {:?}", e))
+                .filter_map(|message| match message {
+                    OwnedMessage::Text(text) => Some(text.into_bytes()),
+                    OwnedMessage::Binary(bytes) => Some(bytes),
+                    _ => None,
+                })
+                .and_then(|bytes| {
+                    serde_json::from_slice::<Value>(&bytes)
+                        .map_err(|e| format!("Invalid JSON in websocket message: {:?}", e))
+                });
+
+            (Box::new(sink) as JsonSink, Box::new(stream) as JsonStream)
+        })
+}
+
+/// Opens a connection to the IPC socket at `path`.
+#[cfg(unix)]
+fn connect_ipc(path: PathBuf) -> impl Future<Item = (JsonSink, JsonStream), Error = String> + Send {
+    use tokio::codec::Framed;
+    use tokio::net::UnixStream;
+
+    UnixStream::connect(&path)
+        .map_err(move |e| format!("Failed to connect to IPC socket {:?}: {:?}", path, e))
+        .map(|socket| {
+            let (sink, stream) = Framed::new(socket, JsonCodec).split();
+
+            let sink = sink.sink_map_err(|e| format!("Failed to send IPC message: {:?}", e));
+            let stream = stream.map_err(|e| format!("Failed to receive IPC message: {:?}", e));
+
+            (Box::new(sink) as JsonSink, Box::new(stream) as JsonStream)
+        })
+}
+
+/// IPC sockets are only supported on unix.
+#[cfg(not(unix))]
+fn connect_ipc(path: PathBuf) -> impl Future<Item = (JsonSink, JsonStream), Error = String> + Send {
+    future::err(format!(
+        "IPC endpoint {:?} is not supported on this platform",
+        path
+    ))
+}
+
+/// Frames a byte stream as a sequence of JSON values.
+///
+/// The JSON-RPC IPC protocol does not delimit messages, each message is simply a JSON value.
+struct JsonCodec;
+
+impl Decoder for JsonCodec {
+    type Item = Value;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Value>, io::Error> {
+        let mut values = serde_json::Deserializer::from_slice(&buf[..]).into_iter::<Value>();
+
+        match values.next() {
+            Some(Ok(value)) => {
+                let len = values.byte_offset();
+                buf.split_to(len);
+                Ok(Some(value))
+            }
+            // The buffer contains only part of a value, wait for more bytes.
+            Some(Err(ref e)) if e.is_eof() => Ok(None),
+            Some(Err(e)) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            // The buffer is empty or contains only whitespace.
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Value>, io::Error> {
+        // Ignore any trailing whitespace or partial value when the connection is closed.
+        self.decode(buf)
+    }
+}
+
+impl Encoder for JsonCodec {
+    type Item = Value;
+    type Error = io::Error;
+
+    fn encode(&mut self, value: Value, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.extend_from_slice(value.to_string().as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transport_from_endpoint() {
+        assert_eq!(
+            Transport::from_endpoint("http://localhost:8545"),
+            Ok(Transport::Http("http://localhost:8545".into()))
+        );
+        assert_eq!(
+            Transport::from_endpoint("wss://localhost:8546"),
+            Ok(Transport::Ws("wss://localhost:8546".into()))
+        );
+        assert_eq!(
+            Transport::from_endpoint("ipc:///home/eth/.ethereum/geth.ipc"),
+            Ok(Transport::Ipc("/home/eth/.ethereum/geth.ipc".into()))
+        );
+        assert_eq!(
+            Transport::from_endpoint("/"),
+            Ok(Transport::Ipc("/".into()))
+        );
+        assert!(Transport::from_endpoint("localhost:8545").is_err());
+        assert!(Transport::from_endpoint("/does/not/exist/geth.ipc").is_err());
+    }
+
+    #[test]
+    fn json_codec_decodes_consecutive_values() {
+        let mut buf = BytesMut::from(&b"{\"id\":1}\n{\"id\":2}{\"id\""[..]);
+
+        assert_eq!(
+            JsonCodec.decode(&mut buf).expect("should decode"),
+            Some(json!({"id": 1}))
+        );
+        assert_eq!(
+            JsonCodec.decode(&mut buf).expect("should decode"),
+            Some(json!({"id": 2}))
+        );
+        assert_eq!(
+            JsonCodec
+                .decode(&mut buf)
+                .expect("should not decode partial value"),
+            None
+        );
+
+        buf.extend_from_slice(b":3}");
+        assert_eq!(
+            JsonCodec.decode(&mut buf).expect("should decode"),
+            Some(json!({"id": 3}))
+        );
+        assert_eq!(JsonCodec.decode(&mut buf).expect("should decode"), None);
+    }
+}
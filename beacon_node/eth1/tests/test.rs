@@ -640,6 +640,49 @@ mod http {
             );
         }
     }
+
+    #[test]
+    fn websocket_requests() {
+        let mut env = new_env();
+        let runtime = env.runtime();
+
+        let eth1 = runtime
+            .block_on(GanacheEth1Instance::new())
+            .expect("should start eth1 environment");
+        let web3 = eth1.web3();
+
+        // Ganache serves websockets on the same port as HTTP.
+        let ws_endpoint = eth1.endpoint().replacen("http://", "ws://", 1);
+
+        for i in 1..=4 {
+            eth1.deposit_contract
+                .deposit(runtime, random_deposit_data())
+                .expect("should perform a deposit");
+
+            let block_number = get_block_number(runtime, &web3);
+
+            // Send several concurrent requests, which share a single connection.
+            let (ws_block_number, ws_block, ws_deposit_count) = runtime
+                .block_on(eth1::http::get_block_number(&ws_endpoint, timeout()).join3(
+                    eth1::http::get_block(&ws_endpoint, block_number, timeout()),
+                    get_deposit_count(
+                        &ws_endpoint,
+                        &eth1.deposit_contract.address(),
+                        block_number,
+                        timeout(),
+                    ),
+                ))
+                .expect("should send requests over websocket");
+
+            assert_eq!(ws_block_number, block_number, "should get the block number");
+            assert_eq!(
+                ws_block,
+                get_block(runtime, &eth1, block_number),
+                "should get the same block as over HTTP"
+            );
+            assert_eq!(ws_deposit_count, Some(i), "should get the deposit count");
+        }
+    }
 }
 
 mod fast {
@@ -792,3 +835,201 @@ mod persist {
         );
     }
 }
+
+mod fallback {
+    use super::*;
+    use eth1::{EndpointError, EndpointHealth};
+
+    #[test]
+    fn fallback_when_primary_is_down() {
+        let mut env = new_env();
+        let log = env.core_context().log;
+        let runtime = env.runtime();
+
+        let primary = runtime
+            .block_on(GanacheEth1Instance::new())
+            .expect("should start primary eth1 environment");
+        let fallback = runtime
+            .block_on(GanacheEth1Instance::new())
+            .expect("should start fallback eth1 environment");
+        let deposit_contract = &fallback.deposit_contract;
+        let web3 = fallback.web3();
+
+        let now = get_block_number(runtime, &web3);
+        let service = Service::new(
+            Config {
+                endpoint: primary.endpoint(),
+                fallback_endpoints: vec![fallback.endpoint()],
+                deposit_contract_address: deposit_contract.address(),
+                deposit_contract_deploy_block: now,
+                lowest_cached_block_number: now,
+                follow_distance: 0,
+                block_cache_truncation: None,
+                ..Config::default()
+            },
+            log,
+        );
+
+        let primary_endpoint = primary.endpoint();
+        let fallback_endpoint = fallback.endpoint();
+
+        assert_eq!(
+            runtime
+                .block_on(service.check_endpoints())
+                .expect("should check endpoints"),
+            vec![
+                (primary_endpoint.clone(), EndpointHealth::Healthy),
+                (fallback_endpoint.clone(), EndpointHealth::Healthy),
+            ],
+            "both endpoints should be healthy"
+        );
+
+        // Stop the primary, so the deposits are only visible via the fallback.
+        drop(primary);
+
+        let n = 4;
+        let deposits: Vec<_> = (0..n).map(|_| random_deposit_data()).collect();
+        for deposit in &deposits {
+            deposit_contract
+                .deposit(runtime, deposit.clone())
+                .expect("should perform a deposit");
+        }
+
+        runtime
+            .block_on(service.update_deposit_cache())
+            .expect("should update deposit cache via the fallback");
+        runtime
+            .block_on(service.update_block_cache())
+            .expect("should update block cache via the fallback");
+
+        assert_eq!(
+            service.deposit_cache_len(),
+            n,
+            "should have imported the deposits from the fallback"
+        );
+        assert!(
+            service.block_cache_len() >= n,
+            "should have imported the blocks from the fallback"
+        );
+
+        let health = runtime
+            .block_on(service.check_endpoints())
+            .expect("should check endpoints");
+        match &health[0] {
+            (endpoint, EndpointHealth::Unhealthy(EndpointError::RequestFailed(_))) => {
+                assert_eq!(*endpoint, primary_endpoint)
+            }
+            other => panic!("primary should be unhealthy, not {:?}", other),
+        }
+        assert_eq!(
+            health[1],
+            (fallback_endpoint, EndpointHealth::Healthy),
+            "fallback should be healthy"
+        );
+        assert_eq!(
+            service.endpoint_health(),
+            health,
+            "service should store the latest health"
+        );
+    }
+}
+
+mod deposit_contract_check {
+    use super::*;
+    use eth1::{DepositContractError, DepositContractStatus};
+
+    #[test]
+    fn detects_misconfiguration() {
+        let mut env = new_env();
+        let log = env.core_context().log;
+        let runtime = env.runtime();
+
+        let eth1 = runtime
+            .block_on(GanacheEth1Instance::new())
+            .expect("should start eth1 environment");
+        let deposit_contract = &eth1.deposit_contract;
+        let web3 = eth1.web3();
+
+        let deploy_block = get_block_number(runtime, &web3);
+
+        let n = 4;
+        for _ in 0..n {
+            deposit_contract
+                .deposit(runtime, random_deposit_data())
+                .expect("should perform a deposit");
+        }
+
+        let config = Config {
+            endpoint: eth1.endpoint(),
+            deposit_contract_address: deposit_contract.address(),
+            deposit_contract_deploy_block: deploy_block,
+            lowest_cached_block_number: deploy_block,
+            follow_distance: 0,
+            ..Config::default()
+        };
+
+        // A correctly configured contract.
+        let service = Service::new(config.clone(), log.clone());
+        assert_eq!(
+            runtime
+                .block_on(service.check_deposit_contract())
+                .expect("should check contract"),
+            DepositContractStatus::CodeVerified,
+            "should not compare deposit counts before the deposit cache is updated"
+        );
+        runtime
+            .block_on(service.update_deposit_cache())
+            .expect("should update deposit cache");
+        assert_eq!(
+            runtime
+                .block_on(service.check_deposit_contract())
+                .expect("should check contract"),
+            DepositContractStatus::Verified
+        );
+        assert_eq!(
+            service.status().deposit_contract,
+            DepositContractStatus::Verified
+        );
+
+        // An address without a contract.
+        let service = Service::new(
+            Config {
+                deposit_contract_address: "0x0000000000000000000000000000000000000001".into(),
+                ..config.clone()
+            },
+            log.clone(),
+        );
+        assert_eq!(
+            runtime
+                .block_on(service.check_deposit_contract())
+                .expect("should check contract"),
+            DepositContractStatus::Invalid(DepositContractError::NoCode)
+        );
+
+        // A deploy block after the first deposits.
+        let service = Service::new(
+            Config {
+                deposit_contract_deploy_block: get_block_number(runtime, &web3),
+                ..config
+            },
+            log,
+        );
+        runtime
+            .block_on(service.update_deposit_cache())
+            .expect("should update deposit cache");
+        match runtime
+            .block_on(service.check_deposit_contract())
+            .expect("should check contract")
+        {
+            DepositContractStatus::Invalid(DepositContractError::DepositCountMismatch {
+                contract_deposit_count,
+                cached_deposit_count,
+                ..
+            }) => {
+                assert_eq!(contract_deposit_count, n);
+                assert!(cached_deposit_count < n, "should have missed deposits");
+            }
+            other => panic!("should detect missed deposits, not {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Decodes the SSZ payload of each RPC request, as is done by the inbound codec.
+//!
+//! The first byte of the input selects the request type.
+#![no_main]
+use eth2_libp2p::rpc::methods::{
+    BlocksByRangeRequest, BlocksByRootRequest, GoodbyeReason, Ping, StatusMessage,
+};
+use libfuzzer_sys::fuzz_target;
+use ssz::{Decode, Encode};
+use types::Hash256;
+
+fuzz_target!(|data: &[u8]| {
+    let (selector, packet) = match data.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+
+    match selector % 5 {
+        0 => {
+            if let Ok(status) = StatusMessage::from_ssz_bytes(packet) {
+                assert_eq!(status.as_ssz_bytes(), packet);
+            }
+        }
+        1 => {
+            let _ = GoodbyeReason::from_ssz_bytes(packet);
+        }
+        2 => {
+            if let Ok(request) = BlocksByRangeRequest::from_ssz_bytes(packet) {
+                assert_eq!(request.as_ssz_bytes(), packet);
+            }
+        }
+        3 => {
+            if let Ok(block_roots) = Vec::<Hash256>::from_ssz_bytes(packet) {
+                let request = BlocksByRootRequest { block_roots };
+                assert_eq!(request.block_roots.as_ssz_bytes(), packet);
+            }
+        }
+        _ => {
+            if let Ok(ping) = Ping::from_ssz_bytes(packet) {
+                assert_eq!(ping.as_ssz_bytes(), packet);
+            }
+        }
+    }
+});
@@ -1,8 +1,11 @@
 use crate::discovery::{enr::Eth2Enr, Discovery};
-use crate::peer_manager::{PeerManager, PeerManagerEvent};
+use crate::peer_manager::{PeerManager, PeerManagerEvent, PeerScoreParams};
 use crate::rpc::*;
 use crate::types::{GossipEncoding, GossipKind, GossipTopic};
-use crate::{error, Enr, NetworkConfig, NetworkGlobals, PubsubMessage, TopicHash};
+use crate::{
+    error, Enr, MessageAcceptance, NetworkConfig, NetworkGlobals, PubsubMessage, TopicHash,
+    TrustedPeer,
+};
 use futures::prelude::*;
 use libp2p::{
     core::{identity::Keypair, ConnectedPoint},
@@ -14,8 +17,9 @@ use libp2p::{
     NetworkBehaviour, PeerId,
 };
 use lru::LruCache;
-use slog::{crit, debug, o, warn};
+use slog::{crit, debug, info, o, warn};
 use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr};
 use std::sync::Arc;
 use types::{EnrForkId, EthSpec, SubnetId};
 
@@ -51,6 +55,14 @@ pub struct Behaviour<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> {
     #[behaviour(ignore)]
     // TODO: Remove this
     seen_gossip_messages: LruCache<MessageId, ()>,
+    /// The topics of received gossip messages which are awaiting validation, used to score the
+    /// peers that delivered them.
+    #[behaviour(ignore)]
+    unvalidated_messages: LruCache<MessageId, GossipKind>,
+    /// The topics of recently rejected gossip messages. Peers that deliver these again are
+    /// penalised without validating the message.
+    #[behaviour(ignore)]
+    rejected_messages: LruCache<MessageId, GossipKind>,
     /// A collections of variables accessible outside the network service.
     #[behaviour(ignore)]
     network_globals: Arc<NetworkGlobals<TSpec>>,
@@ -98,19 +110,27 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
             attnets,
         };
 
-        Ok(Behaviour {
+        let mut behaviour = Behaviour {
             eth2_rpc: RPC::new(log.clone()),
             gossipsub: Gossipsub::new(local_peer_id, net_conf.gs_config.clone()),
             discovery: Discovery::new(local_key, net_conf, network_globals.clone(), log)?,
             identify,
-            peer_manager: PeerManager::new(network_globals.clone(), log),
+            peer_manager: PeerManager::new(network_globals.clone(), net_conf.max_peers, log),
             events: Vec::new(),
             seen_gossip_messages: LruCache::new(100_000),
+            unvalidated_messages: LruCache::new(100_000),
+            rejected_messages: LruCache::new(10_000),
             meta_data,
             network_globals,
             enr_fork_id,
             log: behaviour_log,
-        })
+        };
+
+        for trusted_peer in &net_conf.trusted_peers {
+            behaviour.add_trusted_peer(trusted_peer.clone());
+        }
+
+        Ok(behaviour)
     }
 
     /// Obtain a reference to the discovery protocol.
@@ -205,11 +225,75 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
         }
     }
 
-    /// Forwards a message that is waiting in gossipsub's mcache. Messages are only propagated
-    /// once validated by the beacon chain.
-    pub fn propagate_message(&mut self, propagation_source: &PeerId, message_id: MessageId) {
-        self.gossipsub
-            .propagate_message(&message_id, propagation_source);
+    /// Reports the outcome of validating a gossip message received from `propagation_source`.
+    ///
+    /// Accepted messages are forwarded from gossipsub's mcache and reward the peer. Ignored
+    /// messages are removed from our cache of seen messages. Gossipsub keeps them in its own
+    /// duplicate cache, so a later delivery is only processed again once gossipsub has forgotten
+    /// the message. Rejected messages penalise the peer, as well as any peer that delivers them
+    /// again.
+    pub fn report_message_validation_result(
+        &mut self,
+        propagation_source: &PeerId,
+        message_id: MessageId,
+        validation_result: MessageAcceptance,
+    ) {
+        let accepted = validation_result == MessageAcceptance::Accept;
+        if self.record_validation_result(propagation_source, &message_id, validation_result)
+            && accepted
+        {
+            self.gossipsub
+                .propagate_message(&message_id, propagation_source);
+        }
+    }
+
+    /// Reports the outcome of validating a gossip message like `report_message_validation_result`,
+    /// except that an accepted message is not propagated.
+    pub fn report_message_validation_result_without_propagation(
+        &mut self,
+        propagation_source: &PeerId,
+        message_id: MessageId,
+        validation_result: MessageAcceptance,
+    ) {
+        self.record_validation_result(propagation_source, &message_id, validation_result);
+    }
+
+    /// Scores the peer that delivered the message and updates our message caches according to
+    /// the validation result. Returns `false` if the message was not waiting for validation.
+    fn record_validation_result(
+        &mut self,
+        propagation_source: &PeerId,
+        message_id: &MessageId,
+        validation_result: MessageAcceptance,
+    ) -> bool {
+        let kind = match self.unvalidated_messages.pop(message_id) {
+            Some(kind) => kind,
+            None => {
+                debug!(self.log, "Validation result for an unknown gossip message"; "message_id" => message_id.to_string());
+                return false;
+            }
+        };
+
+        match validation_result {
+            MessageAcceptance::Accept => {
+                self.peer_manager
+                    .gossip_message_validated(propagation_source, &kind);
+            }
+            MessageAcceptance::Ignore => {
+                self.seen_gossip_messages.pop(message_id);
+            }
+            MessageAcceptance::Reject => {
+                self.peer_manager
+                    .gossip_message_invalid(propagation_source, &kind);
+                self.rejected_messages.put(message_id.clone(), kind);
+            }
+        }
+        true
+    }
+
+    /// Sets the parameters used to score peers by the gossip messages they deliver.
+    pub fn update_gossip_score_params(&mut self, params: PeerScoreParams) {
+        self.peer_manager.update_gossip_score_params(params);
     }
 
     /* Eth2 RPC behaviour functions */
@@ -231,6 +315,39 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
         self.discovery.peer_unbanned(peer_id);
     }
 
+    /// Adds a peer which is kept connected and never banned.
+    pub fn add_trusted_peer(&mut self, trusted_peer: TrustedPeer) {
+        let peer_id = match trusted_peer.peer_id() {
+            Some(peer_id) => peer_id,
+            None => {
+                warn!(self.log, "Ignoring trusted peer without a peer id"; "peer" => format!("{:?}", trusted_peer));
+                return;
+            }
+        };
+
+        if let TrustedPeer::Enr(enr) = &trusted_peer {
+            self.discovery.add_enr(enr.clone());
+        }
+        self.discovery
+            .add_trusted_peer(peer_id.clone(), trusted_peer.addresses());
+        self.peer_manager.add_trusted_peer(peer_id);
+    }
+
+    /// Returns true if the peer is trusted, in which case it must not be banned.
+    pub fn is_trusted_peer(&self, peer_id: &PeerId) -> bool {
+        self.peer_manager.is_trusted(peer_id)
+    }
+
+    /// Refuses connections from an IP address, banning any peers connected from it.
+    pub fn ban_ip(&mut self, ip: IpAddr) {
+        self.peer_manager.ban_ip(ip);
+    }
+
+    /// Accepts connections from an IP address which was banned.
+    pub fn unban_ip(&mut self, ip: &IpAddr) {
+        self.peer_manager.unban_ip(ip);
+    }
+
     /// Returns an iterator over all enr entries in the DHT.
     pub fn enr_entries(&mut self) -> impl Iterator<Item = &Enr> {
         self.discovery.enr_entries()
@@ -252,6 +369,19 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
         self.update_metadata();
     }
 
+    /// Advertises the external address of a NAT port mapping in the local ENR.
+    pub fn update_enr_external_address(&mut self, ip: Ipv4Addr, tcp_port: u16, udp_port: u16) {
+        match self
+            .discovery
+            .update_enr_external_address(ip, tcp_port, udp_port)
+        {
+            Ok(()) => {
+                info!(self.log, "Updated the ENR address"; "ip" => format!("{}", ip), "tcp_port" => tcp_port, "udp_port" => udp_port)
+            }
+            Err(e) => warn!(self.log, "Could not update the ENR address"; "error" => e),
+        }
+    }
+
     /// A request to search for peers connected to a long-lived subnet.
     pub fn peers_request(&mut self, subnet_id: SubnetId) {
         self.discovery.peers_request(subnet_id);
@@ -335,14 +465,28 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
     fn inject_event(&mut self, event: GossipsubEvent) {
         match event {
             GossipsubEvent::Message(propagation_source, id, gs_msg) => {
+                if self.peer_manager.is_graylisted(&propagation_source) {
+                    debug!(self.log, "Ignoring gossipsub message from a graylisted peer"; "peer_id" => format!("{}", propagation_source));
+                    return;
+                }
+
                 // Note: We are keeping track here of the peer that sent us the message, not the
                 // peer that originally published the message.
                 if self.seen_gossip_messages.put(id.clone(), ()).is_none() {
                     match PubsubMessage::decode(&gs_msg.topics, &gs_msg.data) {
                         Err(e) => {
-                            debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e))
+                            debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e));
+                            if let Some(topic) = gs_msg
+                                .topics
+                                .iter()
+                                .find_map(|topic| GossipTopic::decode(topic.as_str()).ok())
+                            {
+                                self.peer_manager
+                                    .gossip_message_invalid(&propagation_source, topic.kind());
+                            }
                         }
                         Ok(msg) => {
+                            self.unvalidated_messages.put(id.clone(), msg.kind());
                             // if this message isn't a duplicate, notify the network
                             self.events.push(BehaviourEvent::PubsubMessage {
                                 id,
@@ -352,13 +496,21 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
                             });
                         }
                     }
+                } else if let Some(kind) = self.rejected_messages.get(&id).cloned() {
+                    debug!(self.log, "Peer delivered a rejected gossipsub message"; "peer_id" => format!("{}", propagation_source));
+                    self.peer_manager
+                        .gossip_message_invalid(&propagation_source, &kind);
                 } else {
                     match PubsubMessage::<TSpec>::decode(&gs_msg.topics, &gs_msg.data) {
                         Err(e) => {
                             debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e))
                         }
                         Ok(msg) => {
-                            crit!(self.log, "A duplicate gossipsub message was received"; "message_source" => format!("{}", gs_msg.source), "propagated_peer" => format!("{}",propagation_source), "message" => format!("{}", msg));
+                            // gossipsub filters duplicates it has recently seen, so the peer is
+                            // replaying an older message
+                            debug!(self.log, "A duplicate gossipsub message was received"; "message_source" => format!("{}", gs_msg.source), "propagated_peer" => format!("{}",propagation_source), "message" => format!("{}", msg));
+                            self.peer_manager
+                                .gossip_message_duplicated(&propagation_source, &msg.kind());
                         }
                     }
                 }
@@ -382,8 +534,12 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
             // peer manager. After a behaviour re-write remove these:
             RPCMessage::PeerConnectedHack(peer_id, connected_point) => {
                 match connected_point {
-                    ConnectedPoint::Dialer { .. } => self.peer_manager.connect_outgoing(&peer_id),
-                    ConnectedPoint::Listener { .. } => self.peer_manager.connect_ingoing(&peer_id),
+                    ConnectedPoint::Dialer { address } => {
+                        self.peer_manager.connect_outgoing(&peer_id, &address)
+                    }
+                    ConnectedPoint::Listener { send_back_addr, .. } => {
+                        self.peer_manager.connect_ingoing(&peer_id, &send_back_addr)
+                    }
                 };
 
                 // Find ENR info about a peer if possible.
@@ -484,11 +640,23 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
                     PeerManagerEvent::MetaData(peer_id) => {
                         self.send_meta_data_request(peer_id);
                     }
-                    PeerManagerEvent::_DisconnectPeer(_peer_id) => {
-                        //TODO: Implement
+                    PeerManagerEvent::Dial(peer_id) => {
+                        return Async::Ready(NetworkBehaviourAction::DialPeer { peer_id });
+                    }
+                    PeerManagerEvent::DisconnectPeer(peer_id) => {
+                        // disconnecting is handled by the service, which owns the swarm
+                        return Async::Ready(NetworkBehaviourAction::GenerateEvent(
+                            BehaviourEvent::DisconnectPeer(peer_id),
+                        ));
+                    }
+                    PeerManagerEvent::BanPeer(peer_id) => {
+                        // banning is handled by the service, which owns the swarm
+                        return Async::Ready(NetworkBehaviourAction::GenerateEvent(
+                            BehaviourEvent::BanPeer(peer_id),
+                        ));
                     }
-                    PeerManagerEvent::_BanPeer(_peer_id) => {
-                        //TODO: Implement
+                    PeerManagerEvent::DiscoverSubnetPeers(subnet_id) => {
+                        self.discovery.peers_request(subnet_id);
                     }
                 },
                 Ok(Async::NotReady) => break,
@@ -573,4 +741,201 @@ pub enum BehaviourEvent<TSpec: EthSpec> {
     PeerSubscribed(PeerId, TopicHash),
     /// Inform the network to send a Status to this peer.
     StatusPeer(PeerId),
+    /// The peer manager requests that the peer is disconnected.
+    DisconnectPeer(PeerId),
+    /// The peer manager requests that the peer is disconnected and banned, e.g., because it
+    /// connected from a banned IP or its reputation fell too low.
+    BanPeer(PeerId),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::enr::build_enr;
+    use libp2p::core::{muxing::StreamMuxerBox, nodes::Substream};
+    use libp2p::discv5::enr::CombinedKey;
+    use libp2p::gossipsub::GossipsubMessage;
+    use std::convert::TryInto;
+    use types::{BeaconBlock, MinimalEthSpec, Signature, SignedBeaconBlock, Slot};
+
+    type E = MinimalEthSpec;
+    type TestBehaviour = Behaviour<Substream<StreamMuxerBox>, E>;
+
+    fn build_behaviour() -> TestBehaviour {
+        let log = slog::Logger::root(slog::Discard, o!());
+        let mut config = NetworkConfig::default();
+        config.listen_address = "127.0.0.1".parse().unwrap();
+        // let the OS choose the discovery port
+        config.discovery_port = 0;
+
+        let local_key = Keypair::generate_secp256k1();
+        let enr_key: CombinedKey = local_key
+            .clone()
+            .try_into()
+            .expect("should convert secp256k1 key");
+        let enr =
+            build_enr::<E>(&enr_key, &config, EnrForkId::default()).expect("should build enr");
+        let network_globals = Arc::new(NetworkGlobals::new(
+            enr,
+            config.libp2p_port,
+            config.discovery_port,
+            &log,
+        ));
+
+        let mut behaviour = Behaviour::new(&local_key, &config, network_globals, &log)
+            .expect("should build behaviour");
+        behaviour
+            .update_gossip_score_params(PeerScoreParams::new::<E>(&E::default_spec(), 100_000));
+        behaviour
+    }
+
+    /// Returns the id and the gossipsub message of a block at `slot`.
+    fn block_message(slot: u64) -> (MessageId, GossipsubMessage) {
+        let mut block = BeaconBlock::empty(&E::default_spec());
+        block.slot = Slot::new(slot);
+        let message = PubsubMessage::<E>::BeaconBlock(Box::new(SignedBeaconBlock {
+            message: block,
+            signature: Signature::empty_signature(),
+        }));
+        let topics = message
+            .topics(GossipEncoding::default(), EnrForkId::default().fork_digest)
+            .into_iter()
+            .map(|topic| {
+                let topic: String = topic.into();
+                TopicHash::from_raw(topic)
+            })
+            .collect();
+        let gs_msg = GossipsubMessage {
+            source: PeerId::random(),
+            data: message
+                .encode(GossipEncoding::default())
+                .expect("should encode block"),
+            sequence_number: slot,
+            topics,
+        };
+        (MessageId(format!("block-{}", slot)), gs_msg)
+    }
+
+    /// Delivers the message from `peer_id` and returns the number of messages passed on to the
+    /// network for validation.
+    fn deliver(
+        behaviour: &mut TestBehaviour,
+        peer_id: &PeerId,
+        (id, gs_msg): &(MessageId, GossipsubMessage),
+    ) -> usize {
+        behaviour.inject_event(GossipsubEvent::Message(
+            peer_id.clone(),
+            id.clone(),
+            gs_msg.clone(),
+        ));
+        behaviour
+            .events
+            .drain(..)
+            .filter(|event| {
+                if let BehaviourEvent::PubsubMessage { .. } = event {
+                    true
+                } else {
+                    false
+                }
+            })
+            .count()
+    }
+
+    #[test]
+    fn accepted_message_rewards_peer() {
+        let mut behaviour = build_behaviour();
+        let peer_id = PeerId::random();
+        let message = block_message(1);
+
+        assert_eq!(deliver(&mut behaviour, &peer_id, &message), 1);
+        behaviour.report_message_validation_result(
+            &peer_id,
+            message.0.clone(),
+            MessageAcceptance::Accept,
+        );
+        assert!(behaviour.peer_manager.gossip_score(&peer_id) > 0.0);
+
+        // a later delivery of the message is a duplicate
+        let other_peer_id = PeerId::random();
+        assert_eq!(deliver(&mut behaviour, &other_peer_id, &message), 0);
+        assert!(behaviour.peer_manager.gossip_score(&other_peer_id) < 0.0);
+    }
+
+    #[test]
+    fn unpropagated_accepted_message_rewards_peer() {
+        let mut behaviour = build_behaviour();
+        let peer_id = PeerId::random();
+        let message = block_message(1);
+
+        assert_eq!(deliver(&mut behaviour, &peer_id, &message), 1);
+        behaviour.report_message_validation_result_without_propagation(
+            &peer_id,
+            message.0.clone(),
+            MessageAcceptance::Accept,
+        );
+        assert!(behaviour.peer_manager.gossip_score(&peer_id) > 0.0);
+        assert!(behaviour.unvalidated_messages.get(&message.0).is_none());
+    }
+
+    #[test]
+    fn ignored_message_is_processed_again() {
+        let mut behaviour = build_behaviour();
+        let peer_id = PeerId::random();
+        let message = block_message(1);
+
+        assert_eq!(deliver(&mut behaviour, &peer_id, &message), 1);
+        behaviour.report_message_validation_result(
+            &peer_id,
+            message.0.clone(),
+            MessageAcceptance::Ignore,
+        );
+        assert_eq!(behaviour.peer_manager.gossip_score(&peer_id), 0.0);
+
+        // the message is validated again, without penalising the peer that delivers it
+        let other_peer_id = PeerId::random();
+        assert_eq!(deliver(&mut behaviour, &other_peer_id, &message), 1);
+        assert_eq!(behaviour.peer_manager.gossip_score(&other_peer_id), 0.0);
+    }
+
+    #[test]
+    fn rejected_message_penalises_every_delivery() {
+        let mut behaviour = build_behaviour();
+        let peer_id = PeerId::random();
+        let message = block_message(1);
+
+        assert_eq!(deliver(&mut behaviour, &peer_id, &message), 1);
+        behaviour.report_message_validation_result(
+            &peer_id,
+            message.0.clone(),
+            MessageAcceptance::Reject,
+        );
+        let rejected_score = behaviour.peer_manager.gossip_score(&peer_id);
+        assert!(rejected_score < 0.0);
+
+        // peers delivering the message again are penalised without validating it
+        let other_peer_id = PeerId::random();
+        assert_eq!(deliver(&mut behaviour, &other_peer_id, &message), 0);
+        assert_eq!(
+            behaviour.peer_manager.gossip_score(&other_peer_id),
+            rejected_score
+        );
+        assert_eq!(deliver(&mut behaviour, &peer_id, &message), 0);
+        assert!(behaviour.peer_manager.gossip_score(&peer_id) < rejected_score);
+
+        // other messages from the peers are still validated
+        assert_eq!(deliver(&mut behaviour, &peer_id, &block_message(2)), 1);
+    }
+
+    #[test]
+    fn unknown_validation_result_is_ignored() {
+        let mut behaviour = build_behaviour();
+        let peer_id = PeerId::random();
+
+        behaviour.report_message_validation_result(
+            &peer_id,
+            block_message(1).0,
+            MessageAcceptance::Reject,
+        );
+        assert_eq!(behaviour.peer_manager.gossip_score(&peer_id), 0.0);
+    }
 }
@@ -2,10 +2,13 @@ use crate::types::GossipKind;
 use crate::Enr;
 use libp2p::discv5::{Discv5Config, Discv5ConfigBuilder};
 use libp2p::gossipsub::{GossipsubConfig, GossipsubConfigBuilder, GossipsubMessage, MessageId};
-use libp2p::Multiaddr;
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
 use serde_derive::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
 pub const GOSSIP_MAX_SIZE: usize = 1_048_576;
@@ -17,7 +20,8 @@ pub struct Config {
     /// Data directory where node's keyfile is stored
     pub network_dir: PathBuf,
 
-    /// IP address to listen on.
+    /// IP address to listen on. Both libp2p and discovery listen on this address, which may be
+    /// IPv4 or IPv6.
     pub listen_address: std::net::IpAddr,
 
     /// The TCP port that libp2p listens on.
@@ -26,6 +30,16 @@ pub struct Config {
     /// UDP port that discovery listens on.
     pub discovery_port: u16,
 
+    /// An IPv6 address on which libp2p additionally listens, when `listen_address` is IPv4.
+    ///
+    /// Discovery only listens on `listen_address`, so no `udp6` port is advertised.
+    pub listen_address_v6: Option<Ipv6Addr>,
+
+    /// The TCP port that libp2p listens on at `listen_address_v6`. This must differ from
+    /// `libp2p_port`, as a socket bound to an unspecified IPv6 address also accepts IPv4
+    /// connections on most platforms.
+    pub libp2p_port_v6: u16,
+
     /// The address to broadcast to peers about which address we are listening on. None indicates
     /// that no discovery address has been set in the CLI args.
     pub enr_address: Option<std::net::IpAddr>,
@@ -36,9 +50,23 @@ pub struct Config {
     /// The tcp port to broadcast to peers in order to reach back for libp2p services.
     pub enr_tcp_port: Option<u16>,
 
+    /// The IPv6 address to broadcast to peers alongside `enr_address`.
+    pub enr_address_v6: Option<Ipv6Addr>,
+
+    /// The tcp port to broadcast to peers in order to reach back for libp2p services over
+    /// `listen_address_v6`.
+    pub enr_tcp6_port: Option<u16>,
+
     /// Target number of connected peers.
     pub max_peers: usize,
 
+    /// Map the libp2p and discovery ports on the NAT gateway with UPnP or NAT-PMP, advertising
+    /// the external address in the ENR.
+    pub nat_port_mapping: bool,
+
+    /// The gateway from which to request NAT-PMP mappings. If `None`, the default gateway is used.
+    pub nat_pmp_gateway: Option<Ipv4Addr>,
+
     /// A secp256k1 secret key, as bytes in ASCII-encoded hex.
     ///
     /// With or without `0x` prefix.
@@ -59,6 +87,10 @@ pub struct Config {
     /// List of libp2p nodes to initially connect to.
     pub libp2p_nodes: Vec<Multiaddr>,
 
+    /// List of peers which are kept connected. These are redialed whenever they disconnect, do
+    /// not count towards `max_peers` and are never banned.
+    pub trusted_peers: Vec<TrustedPeer>,
+
     /// Client version
     pub client_version: String,
 
@@ -126,18 +158,152 @@ impl Default for Config {
             listen_address: "0.0.0.0".parse().expect("valid ip address"),
             libp2p_port: 9000,
             discovery_port: 9000,
+            listen_address_v6: None,
+            libp2p_port_v6: 9090,
             enr_address: None,
             enr_udp_port: None,
             enr_tcp_port: None,
+            enr_address_v6: None,
+            enr_tcp6_port: None,
             max_peers: 50,
+            nat_port_mapping: false,
+            nat_pmp_gateway: None,
             secret_key_hex: None,
             gs_config,
             discv5_config,
             boot_nodes: vec![],
             libp2p_nodes: vec![],
+            trusted_peers: vec![],
             client_version: version::version(),
             topics,
             propagation_percentage: None,
         }
     }
 }
+
+impl Config {
+    /// Checks that `listen_address_v6` is only set alongside an IPv4 `listen_address`, on another
+    /// TCP port. A port of 0 lets the OS choose a free port, so both ports may be 0.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.listen_address_v6.is_some() {
+            if self.listen_address.is_ipv6() {
+                return Err(format!(
+                    "Cannot listen on a second IPv6 address when listening on {}",
+                    self.listen_address
+                ));
+            }
+            if self.libp2p_port != 0 && self.libp2p_port_v6 == self.libp2p_port {
+                return Err(format!(
+                    "The IPv6 TCP port must differ from the IPv4 TCP port {}",
+                    self.libp2p_port
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The TCP multiaddrs on which libp2p listens, with `listen_address` first.
+    pub fn listen_multiaddrs(&self) -> Vec<Multiaddr> {
+        let mut primary = Multiaddr::from(self.listen_address);
+        primary.push(Protocol::Tcp(self.libp2p_port));
+
+        let mut multiaddrs = vec![primary];
+        if let Some(listen_address_v6) = self.listen_address_v6 {
+            let mut multiaddr = Multiaddr::from(listen_address_v6);
+            multiaddr.push(Protocol::Tcp(self.libp2p_port_v6));
+            multiaddrs.push(multiaddr);
+        }
+        multiaddrs
+    }
+}
+
+/// A peer which the node keeps connected, given either as an ENR or as a multiaddr which ends in
+/// the `/p2p/<peer-id>` of the peer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum TrustedPeer {
+    Enr(Enr),
+    Multiaddr(Multiaddr),
+}
+
+impl TrustedPeer {
+    /// Returns the `PeerId` of the peer, or `None` if a multiaddr does not identify the peer.
+    pub fn peer_id(&self) -> Option<PeerId> {
+        match self {
+            TrustedPeer::Enr(enr) => Some(enr.peer_id()),
+            TrustedPeer::Multiaddr(multiaddr) => match multiaddr.iter().last() {
+                Some(Protocol::P2p(multihash)) => PeerId::from_multihash(multihash).ok(),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns the addresses at which the peer can be dialed.
+    pub fn addresses(&self) -> Vec<Multiaddr> {
+        match self {
+            // ignore the udp multiaddr of the ENR, if it exists
+            TrustedPeer::Enr(enr) => enr
+                .multiaddr()
+                .into_iter()
+                .filter(|multiaddr| match multiaddr.iter().nth(1) {
+                    Some(Protocol::Udp(_)) => false,
+                    _ => true,
+                })
+                .collect(),
+            TrustedPeer::Multiaddr(multiaddr) => vec![multiaddr.clone()],
+        }
+    }
+}
+
+impl FromStr for TrustedPeer {
+    type Err = String;
+
+    /// Parses a base64-encoded ENR, prefixed with `enr:`, or a multiaddr which ends in
+    /// `/p2p/<peer-id>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("enr:") {
+            s.parse()
+                .map(TrustedPeer::Enr)
+                .map_err(|_| format!("Invalid ENR: {}", s))
+        } else {
+            let peer = s
+                .parse()
+                .map(TrustedPeer::Multiaddr)
+                .map_err(|_| format!("Invalid Multiaddr: {}", s))?;
+
+            if peer.peer_id().is_none() {
+                return Err(format!("Multiaddr does not end in a /p2p/ peer id: {}", s));
+            }
+
+            Ok(peer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_dual_stack() {
+        let mut config = Config::default();
+        config.listen_address_v6 = Some(Ipv6Addr::UNSPECIFIED);
+        assert_eq!(config.validate(), Ok(()));
+
+        config.libp2p_port_v6 = config.libp2p_port;
+        assert!(config.validate().is_err(), "should require distinct ports");
+
+        config.libp2p_port = 0;
+        config.libp2p_port_v6 = 0;
+        assert_eq!(
+            config.validate(),
+            Ok(()),
+            "should allow the OS to choose both ports"
+        );
+
+        config.listen_address = Ipv6Addr::UNSPECIFIED.into();
+        assert!(
+            config.validate().is_err(),
+            "should require an IPv4 listen address"
+        );
+    }
+}
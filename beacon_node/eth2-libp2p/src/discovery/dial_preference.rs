@@ -0,0 +1,156 @@
+//! Orders the addresses of a peer so that IP families which are known to work are dialed first.
+//!
+//! Peers may advertise both IPv4 and IPv6 addresses, whilst the local node may only have a route
+//! for one of them. libp2p dials the addresses of a peer in order, so placing addresses of
+//! working families first avoids waiting on a timeout for every connection.
+use libp2p::multiaddr::Protocol;
+use libp2p::Multiaddr;
+
+/// The number of consecutive failed dials after which a family is tried last.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// The IP family of a multiaddr.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IpFamily {
+    V4,
+    V6,
+}
+
+impl IpFamily {
+    fn of(multiaddr: &Multiaddr) -> Option<Self> {
+        match multiaddr.iter().next() {
+            Some(Protocol::Ip4(_)) => Some(IpFamily::V4),
+            Some(Protocol::Ip6(_)) => Some(IpFamily::V6),
+            _ => None,
+        }
+    }
+}
+
+/// Whether dialing addresses of a family is expected to work.
+#[derive(Debug, Default)]
+struct FamilyStatus {
+    /// Whether the local node listens on an address of this family, which indicates that it has a
+    /// route for it.
+    listening: bool,
+    /// The number of dials that failed since the last successful dial.
+    consecutive_failures: u32,
+}
+
+impl FamilyStatus {
+    /// The position of this family when ordering addresses, lower ranks are dialed first.
+    fn rank(&self) -> u8 {
+        let failing = self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES;
+        (!self.listening as u8) * 2 + failing as u8
+    }
+}
+
+/// Tracks which IP families can be dialed.
+#[derive(Debug)]
+pub struct DialPreference {
+    ipv4: FamilyStatus,
+    ipv6: FamilyStatus,
+}
+
+impl DialPreference {
+    /// Creates a preference for the families of the addresses that libp2p listens on.
+    pub fn new(listen_multiaddrs: &[Multiaddr]) -> Self {
+        let mut preference = DialPreference {
+            ipv4: FamilyStatus::default(),
+            ipv6: FamilyStatus::default(),
+        };
+        for multiaddr in listen_multiaddrs {
+            if let Some(status) = preference.status_mut(multiaddr) {
+                status.listening = true;
+            }
+        }
+        preference
+    }
+
+    /// Records a successful connection to `multiaddr`.
+    pub fn dial_succeeded(&mut self, multiaddr: &Multiaddr) {
+        if let Some(status) = self.status_mut(multiaddr) {
+            status.consecutive_failures = 0;
+        }
+    }
+
+    /// Records a failed dial of `multiaddr`.
+    pub fn dial_failed(&mut self, multiaddr: &Multiaddr) {
+        if let Some(status) = self.status_mut(multiaddr) {
+            status.consecutive_failures = status.consecutive_failures.saturating_add(1);
+        }
+    }
+
+    /// Orders `multiaddrs` so that those of preferred families are first. The order of addresses
+    /// within a family, and of addresses without an IP, is kept.
+    pub fn sort(&self, multiaddrs: &mut [Multiaddr]) {
+        multiaddrs.sort_by_key(|multiaddr| match IpFamily::of(multiaddr) {
+            Some(IpFamily::V4) => self.ipv4.rank(),
+            Some(IpFamily::V6) => self.ipv6.rank(),
+            None => 0,
+        });
+    }
+
+    fn status_mut(&mut self, multiaddr: &Multiaddr) -> Option<&mut FamilyStatus> {
+        match IpFamily::of(multiaddr)? {
+            IpFamily::V4 => Some(&mut self.ipv4),
+            IpFamily::V6 => Some(&mut self.ipv6),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multiaddr(s: &str) -> Multiaddr {
+        s.parse().expect("valid multiaddr")
+    }
+
+    fn addresses() -> Vec<Multiaddr> {
+        vec![
+            multiaddr("/ip4/192.0.2.1/tcp/9000"),
+            multiaddr("/ip6/2001:db8::1/tcp/9000"),
+        ]
+    }
+
+    #[test]
+    fn prefers_listening_family() {
+        let preference = DialPreference::new(&[multiaddr("/ip6/::/tcp/9000")]);
+        let mut multiaddrs = addresses();
+        preference.sort(&mut multiaddrs);
+        assert_eq!(multiaddrs[0], multiaddr("/ip6/2001:db8::1/tcp/9000"));
+    }
+
+    #[test]
+    fn keeps_order_when_dual_stack() {
+        let preference = DialPreference::new(&[
+            multiaddr("/ip4/0.0.0.0/tcp/9000"),
+            multiaddr("/ip6/::/tcp/9090"),
+        ]);
+        let mut multiaddrs = addresses();
+        preference.sort(&mut multiaddrs);
+        assert_eq!(multiaddrs, addresses());
+    }
+
+    #[test]
+    fn deprioritises_failing_family() {
+        let mut preference = DialPreference::new(&[
+            multiaddr("/ip4/0.0.0.0/tcp/9000"),
+            multiaddr("/ip6/::/tcp/9090"),
+        ]);
+        let ipv4 = multiaddr("/ip4/192.0.2.2/tcp/9000");
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            preference.dial_failed(&ipv4);
+        }
+
+        let mut multiaddrs = addresses();
+        preference.sort(&mut multiaddrs);
+        assert_eq!(multiaddrs[0], multiaddr("/ip6/2001:db8::1/tcp/9000"));
+
+        // a single success restores the family
+        preference.dial_succeeded(&ipv4);
+        let mut multiaddrs = addresses();
+        preference.sort(&mut multiaddrs);
+        assert_eq!(multiaddrs, addresses());
+    }
+}
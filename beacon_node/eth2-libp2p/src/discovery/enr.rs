@@ -12,6 +12,7 @@ use ssz_types::BitVector;
 use std::convert::TryInto;
 use std::fs::File;
 use std::io::prelude::*;
+use std::net::IpAddr;
 use std::path::Path;
 use std::str::FromStr;
 use types::{EnrForkId, EthSpec};
@@ -106,6 +107,12 @@ pub fn build_or_load_enr<T: EthSpec>(
 }
 
 /// Builds a lighthouse ENR given a `NetworkConfig`.
+///
+/// The ports of `listen_address` are advertised in the `tcp` and `udp` fields if it is an IPv4
+/// address, or the `tcp6` and `udp6` fields if it is an IPv6 address. The port of
+/// `listen_address_v6` (which is only used alongside an IPv4 `listen_address`) is advertised in
+/// the `tcp6` field. Discovery does not listen on `listen_address_v6` yet, so a dual-stack node
+/// has no `udp6` port and is only discoverable over IPv4.
 pub fn build_enr<T: EthSpec>(
     enr_key: &CombinedKey,
     config: &NetworkConfig,
@@ -115,13 +122,26 @@ pub fn build_enr<T: EthSpec>(
     if let Some(enr_address) = config.enr_address {
         builder.ip(enr_address);
     }
+    if let Some(enr_address_v6) = config.enr_address_v6 {
+        builder.ip(enr_address_v6.into());
+    }
+
+    let (tcp_key, udp_key) = match config.listen_address {
+        IpAddr::V4(_) => ("tcp", "udp"),
+        IpAddr::V6(_) => ("tcp6", "udp6"),
+    };
     if let Some(udp_port) = config.enr_udp_port {
-        builder.udp(udp_port);
+        builder.add_value(udp_key.into(), udp_port.to_be_bytes().to_vec());
     }
-    // we always give it our listening tcp port
-    // TODO: Add uPnP support to map udp and tcp ports
+    // we always give it our listening tcp ports
     let tcp_port = config.enr_tcp_port.unwrap_or_else(|| config.libp2p_port);
-    builder.tcp(tcp_port);
+    builder.add_value(tcp_key.into(), tcp_port.to_be_bytes().to_vec());
+    if config.listen_address.is_ipv4() && config.listen_address_v6.is_some() {
+        let tcp6_port = config
+            .enr_tcp6_port
+            .unwrap_or_else(|| config.libp2p_port_v6);
+        builder.add_value("tcp6".into(), tcp6_port.to_be_bytes().to_vec());
+    }
 
     // set the `eth2` field on our ENR
     builder.add_value(ETH2_ENR_KEY.into(), enr_fork_id.as_ssz_bytes());
@@ -132,7 +152,6 @@ pub fn build_enr<T: EthSpec>(
     builder.add_value(BITFIELD_ENR_KEY.into(), bitfield.as_ssz_bytes());
 
     builder
-        .tcp(config.libp2p_port)
         .build(enr_key)
         .map_err(|e| format!("Could not build Local ENR: {:?}", e))
 }
@@ -142,12 +161,16 @@ pub fn build_enr<T: EthSpec>(
 fn compare_enr(local_enr: &Enr, disk_enr: &Enr) -> bool {
     // take preference over disk_enr address if one is not specified
     (local_enr.ip().is_none() || local_enr.ip() == disk_enr.ip())
+        && (local_enr.ip6().is_none() || local_enr.ip6() == disk_enr.ip6())
         // tcp ports must match
         && local_enr.tcp() == disk_enr.tcp()
+        && local_enr.tcp6() == disk_enr.tcp6()
         // must match on the same fork
         && local_enr.get(ETH2_ENR_KEY) == disk_enr.get(ETH2_ENR_KEY)
         // take preference over disk udp port if one is not specified
         && (local_enr.udp().is_none() || local_enr.udp() == disk_enr.udp())
+        // discovery only listens on IPv6 if configured to, so the udp6 port must match
+        && local_enr.udp6() == disk_enr.udp6()
         // we need the BITFIELD_ENR_KEY key to match, otherwise we use a new ENR. This will likely only
         // be true for non-validating nodes
         && local_enr.get(BITFIELD_ENR_KEY) == disk_enr.get(BITFIELD_ENR_KEY)
@@ -170,3 +193,57 @@ pub fn save_enr_to_disk(dir: &Path, enr: &Enr, log: &slog::Logger) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+    use types::MinimalEthSpec;
+
+    fn build_enr_for(config: &NetworkConfig) -> Enr {
+        let enr_key: CombinedKey = Keypair::generate_secp256k1()
+            .try_into()
+            .expect("should convert secp256k1 key");
+        build_enr::<MinimalEthSpec>(&enr_key, config, EnrForkId::default())
+            .expect("should build enr")
+    }
+
+    #[test]
+    fn ipv4_ports() {
+        let mut config = NetworkConfig::default();
+        config.enr_udp_port = Some(9001);
+
+        let enr = build_enr_for(&config);
+        assert_eq!(enr.tcp(), Some(9000));
+        assert_eq!(enr.udp(), Some(9001));
+        assert_eq!(enr.tcp6(), None);
+        assert_eq!(enr.udp6(), None);
+    }
+
+    #[test]
+    fn ipv6_ports() {
+        let mut config = NetworkConfig::default();
+        config.listen_address = Ipv6Addr::UNSPECIFIED.into();
+        config.enr_udp_port = Some(9001);
+
+        let enr = build_enr_for(&config);
+        assert_eq!(enr.tcp(), None);
+        assert_eq!(enr.udp(), None);
+        assert_eq!(enr.tcp6(), Some(9000));
+        assert_eq!(enr.udp6(), Some(9001));
+    }
+
+    #[test]
+    fn dual_stack_ports() {
+        let mut config = NetworkConfig::default();
+        config.listen_address_v6 = Some(Ipv6Addr::UNSPECIFIED);
+        config.enr_udp_port = Some(9001);
+
+        let enr = build_enr_for(&config);
+        assert_eq!(enr.tcp(), Some(9000));
+        assert_eq!(enr.udp(), Some(9001));
+        assert_eq!(enr.tcp6(), Some(9090));
+        // discovery only listens on the IPv4 address
+        assert_eq!(enr.udp6(), None);
+    }
+}
@@ -1,4 +1,5 @@
 ///! This manages the discovery and management of peers.
+mod dial_preference;
 pub(crate) mod enr;
 
 // Allow external use of the lighthouse ENR builder
@@ -6,6 +7,7 @@ pub use enr::{build_enr, CombinedKey, Keypair};
 
 use crate::metrics;
 use crate::{error, Enr, NetworkConfig, NetworkGlobals};
+use dial_preference::DialPreference;
 use enr::{Eth2Enr, BITFIELD_ENR_KEY, ETH2_ENR_KEY};
 use futures::prelude::*;
 use libp2p::core::{ConnectedPoint, Multiaddr, PeerId};
@@ -16,8 +18,9 @@ use libp2p::swarm::{NetworkBehaviour, NetworkBehaviourAction, PollParameters, Pr
 use slog::{crit, debug, info, warn};
 use ssz::{Decode, Encode};
 use ssz_types::BitVector;
-use std::collections::{HashSet, VecDeque};
-use std::net::SocketAddr;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -32,7 +35,11 @@ const INITIAL_SEARCH_DELAY: u64 = 5;
 /// Local ENR storage filename.
 pub const ENR_FILENAME: &str = "enr.dat";
 /// Number of peers we'd like to have connected to a given long-lived subnet.
-const TARGET_SUBNET_PEERS: u64 = 3;
+pub(crate) const TARGET_SUBNET_PEERS: u64 = 3;
+/// The number of peers above `max_peers` which may be dialed for subnet queries. The peer manager
+/// prunes these back to `max_peers`, disconnecting the peers which contribute least to our subnet
+/// coverage.
+const SUBNET_PEER_EXCESS: usize = 5;
 
 /// Lighthouse discovery behaviour. This provides peer management and discovery using the Discv5
 /// libp2p protocol.
@@ -43,9 +50,18 @@ pub struct Discovery<TSubstream, TSpec: EthSpec> {
     /// The currently banned peers.
     banned_peers: HashSet<PeerId>,
 
+    /// The configured addresses of trusted peers, which may not be in the DHT.
+    trusted_peer_addresses: HashMap<PeerId, Vec<Multiaddr>>,
+
+    /// Orders the addresses of peers by IP family when dialing.
+    dial_preference: DialPreference,
+
     /// The target number of connected peers on the libp2p interface.
     max_peers: usize,
 
+    /// The targets of the running queries for subnet peers.
+    subnet_queries: HashSet<NodeId>,
+
     /// The directory where the ENR is stored.
     enr_dir: String,
 
@@ -122,7 +138,10 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
         Ok(Self {
             events: VecDeque::with_capacity(16),
             banned_peers: HashSet::new(),
+            trusted_peer_addresses: HashMap::new(),
+            dial_preference: DialPreference::new(&config.listen_multiaddrs()),
             max_peers: config.max_peers,
+            subnet_queries: HashSet::new(),
             peer_discovery_delay: Delay::new(Instant::now()),
             past_discovery_delay: INITIAL_SEARCH_DELAY,
             tcp_port: config.libp2p_port,
@@ -156,6 +175,11 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
         });
     }
 
+    /// Stores the addresses of a trusted peer, so that it can be dialed by its `PeerId`.
+    pub fn add_trusted_peer(&mut self, peer_id: PeerId, addresses: Vec<Multiaddr>) {
+        self.trusted_peer_addresses.insert(peer_id, addresses);
+    }
+
     /// The peer has been banned. Add this peer to the banned list to prevent any future
     /// re-connections.
     // TODO: Remove the peer from the DHT if present
@@ -218,6 +242,35 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
         Ok(())
     }
 
+    /// Sets the IP address and ports of our local ENR to an external address, such as that of a
+    /// NAT port mapping.
+    pub fn update_enr_external_address(
+        &mut self,
+        ip: Ipv4Addr,
+        tcp_port: u16,
+        udp_port: u16,
+    ) -> Result<(), String> {
+        self.discovery
+            .enr_insert("ip", ip.octets().to_vec())
+            .map_err(|e| format!("Could not update ENR ip: {:?}", e))?;
+        self.discovery
+            .enr_insert("tcp", tcp_port.to_be_bytes().to_vec())
+            .map_err(|e| format!("Could not update ENR tcp port: {:?}", e))?;
+        self.discovery
+            .enr_insert("udp", udp_port.to_be_bytes().to_vec())
+            .map_err(|e| format!("Could not update ENR udp port: {:?}", e))?;
+
+        // observed addresses are reported with the external port
+        self.tcp_port = tcp_port;
+
+        let enr = self.discovery.local_enr();
+        enr::save_enr_to_disk(Path::new(&self.enr_dir), enr, &self.log);
+
+        // replace the global version
+        *self.network_globals.local_enr.write() = self.discovery.local_enr().clone();
+        Ok(())
+    }
+
     /// Updates the `eth2` field of our local ENR.
     pub fn update_eth2_enr(&mut self, enr_fork_id: EnrForkId) {
         // to avoid having a reference to the spec constant, for the logging we assume
@@ -293,7 +346,9 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
             };
 
             // start the query
-            self.start_query(subnet_predicate, target_peers as usize);
+            if let Some(target) = self.start_query(subnet_predicate, target_peers as usize) {
+                self.subnet_queries.insert(target);
+            }
         } else {
             debug!(self.log, "Discovery ignored";
                 "reason" => "Already connected to desired peers",
@@ -318,7 +373,9 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
     /// This can optionally search for peers for a given predicate. Regardless of the predicate
     /// given, this will only search for peers on the same enr_fork_id as specified in the local
     /// ENR.
-    fn start_query<F>(&mut self, enr_predicate: F, num_nodes: usize)
+    ///
+    /// Returns the target of the query, if it was started.
+    fn start_query<F>(&mut self, enr_predicate: F, num_nodes: usize) -> Option<NodeId>
     where
         F: Fn(&Enr) -> bool + Send + 'static + Clone,
     {
@@ -329,7 +386,7 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
             Ok(v) => v,
             Err(e) => {
                 crit!(self.log, "Local ENR has no fork id"; "error" => e);
-                return;
+                return None;
             }
         };
         // predicate for finding nodes with a matching fork
@@ -339,6 +396,7 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
         // general predicate
         self.discovery
             .find_enr_predicate(random_node, predicate, num_nodes);
+        Some(random_node)
     }
 }
 
@@ -356,10 +414,29 @@ where
 
     fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
         // Let discovery track possible known peers.
-        self.discovery.addresses_of_peer(peer_id)
+        let mut addresses = self.discovery.addresses_of_peer(peer_id);
+        if let Some(trusted_addresses) = self.trusted_peer_addresses.get(peer_id) {
+            addresses.extend(trusted_addresses.iter().cloned());
+        }
+        // dial the IP families that work first
+        self.dial_preference.sort(&mut addresses);
+        addresses
+    }
+
+    fn inject_connected(&mut self, _peer_id: PeerId, endpoint: ConnectedPoint) {
+        if let ConnectedPoint::Dialer { address } = endpoint {
+            self.dial_preference.dial_succeeded(&address);
+        }
     }
 
-    fn inject_connected(&mut self, _peer_id: PeerId, _endpoint: ConnectedPoint) {}
+    fn inject_addr_reach_failure(
+        &mut self,
+        _peer_id: Option<&PeerId>,
+        addr: &Multiaddr,
+        _error: &dyn Error,
+    ) {
+        self.dial_preference.dial_failed(addr);
+    }
 
     fn inject_disconnected(&mut self, _peer_id: &PeerId, _endpoint: ConnectedPoint) {}
 
@@ -393,7 +470,7 @@ where
         loop {
             match self.peer_discovery_delay.poll() {
                 Ok(Async::Ready(_)) => {
-                    if self.network_globals.connected_peers() < self.max_peers {
+                    if self.network_globals.connected_untrusted_peers() < self.max_peers {
                         self.find_peers();
                     }
                     // Set to maximum, and update to earlier, once we get our results back.
@@ -438,7 +515,9 @@ where
                                 address,
                             });
                         }
-                        Discv5Event::FindNodeResult { closer_peers, .. } => {
+                        Discv5Event::FindNodeResult {
+                            key, closer_peers, ..
+                        } => {
                             debug!(self.log, "Discovery query completed"; "peers_found" => closer_peers.len());
                             // update the time to the next query
                             if self.past_discovery_delay < MAX_TIME_BETWEEN_PEER_SEARCHES {
@@ -451,11 +530,19 @@ where
                             self.peer_discovery_delay
                                 .reset(Instant::now() + Duration::from_secs(delay));
 
+                            // peers on subnets may be dialed beyond `max_peers`, the peer manager
+                            // prunes the peers which are least useful
+                            let peer_limit = if self.subnet_queries.remove(&key) {
+                                self.max_peers + SUBNET_PEER_EXCESS
+                            } else {
+                                self.max_peers
+                            };
+
                             for peer_id in closer_peers {
                                 // if we need more peers, attempt a connection
 
-                                if self.network_globals.connected_or_dialing_peers()
-                                    < self.max_peers
+                                if self.network_globals.connected_or_dialing_untrusted_peers()
+                                    < peer_limit
                                     && !self
                                         .network_globals
                                         .peers
@@ -14,12 +14,12 @@ pub mod rpc;
 mod service;
 pub mod types;
 
-pub use crate::types::{error, Enr, GossipTopic, NetworkGlobals, PubsubMessage};
+pub use crate::types::{error, Enr, GossipTopic, MessageAcceptance, NetworkGlobals, PubsubMessage};
 pub use behaviour::BehaviourEvent;
-pub use config::Config as NetworkConfig;
+pub use config::{Config as NetworkConfig, TrustedPeer};
 pub use libp2p::gossipsub::{MessageId, Topic, TopicHash};
 pub use libp2p::{multiaddr, Multiaddr};
 pub use libp2p::{PeerId, Swarm};
-pub use peer_manager::{PeerDB, PeerInfo, PeerSyncStatus, SyncInfo};
+pub use peer_manager::{PeerDB, PeerInfo, PeerScoreParams, PeerSyncStatus, SyncInfo};
 pub use rpc::RPCEvent;
 pub use service::{Service, NETWORK_KEY_FILENAME};
@@ -17,4 +17,9 @@ lazy_static! {
         "libp2p_peer_disconnect_event_total",
         "Count of libp2p peer disconnect events"
     );
+    pub static ref PEERS_PER_SUBNET: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "libp2p_peers_per_subnet",
+        "Count of connected peers subscribed to each long-lived attestation subnet",
+        &["subnet_id"]
+    );
 }
@@ -1,26 +1,34 @@
 //! Implementation of a Lighthouse's peer management system.
 
 pub use self::peerdb::*;
+use crate::discovery::TARGET_SUBNET_PEERS;
 use crate::metrics;
+use crate::multiaddr::Protocol;
 use crate::rpc::MetaData;
-use crate::{NetworkGlobals, PeerId};
+use crate::types::GossipKind;
+use crate::{Multiaddr, NetworkGlobals, PeerId};
 use futures::prelude::*;
 use futures::Stream;
 use hashmap_delay::HashSetDelay;
 use libp2p::identify::IdentifyInfo;
 use slog::{crit, debug, error, warn};
 use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use types::EthSpec;
+use tokio::timer::Interval;
+use types::{EthSpec, SubnetId};
 
 mod client;
 mod peer_info;
 mod peer_sync_status;
 mod peerdb;
+mod score;
 
 pub use peer_info::PeerInfo;
 pub use peer_sync_status::{PeerSyncStatus, SyncInfo};
+pub use score::{PeerScoreParams, TopicScoreParams};
 /// The minimum reputation before a peer is disconnected.
 // Most likely this needs tweaking
 const _MINIMUM_REPUTATION_BEFORE_BAN: Rep = 20;
@@ -29,6 +37,15 @@ const STATUS_INTERVAL: u64 = 300;
 /// The time in seconds between PING events. We do not send a ping if the other peer as PING'd us within
 /// this time frame (Seconds)
 const PING_INTERVAL: u64 = 30;
+/// The time in seconds before a disconnected trusted peer is first redialed.
+const MIN_TRUSTED_PEER_REDIAL_DELAY: u64 = 5;
+/// The maximum time in seconds between redials of a trusted peer. The delay is doubled after
+/// each unsuccessful dial until this is reached.
+const MAX_TRUSTED_PEER_REDIAL_DELAY: u64 = 300;
+/// The time in seconds between checks of the peer count and the subnet coverage of our peers.
+const HEARTBEAT_INTERVAL: u64 = 30;
+/// The maximum number of subnets searched for peers in each heartbeat.
+const MAX_SUBNET_QUERIES_PER_HEARTBEAT: usize = 2;
 
 /// The main struct that handles peer's reputation and connection status.
 pub struct PeerManager<TSpec: EthSpec> {
@@ -40,6 +57,27 @@ pub struct PeerManager<TSpec: EthSpec> {
     ping_peers: HashSetDelay<PeerId>,
     /// A collection of peers awaiting to be Status'd.
     status_peers: HashSetDelay<PeerId>,
+    /// The trusted peers and the current delay before they are redialed.
+    trusted_peers: HashMap<PeerId, Duration>,
+    /// A collection of trusted peers awaiting to be redialed.
+    redial_peers: HashSetDelay<PeerId>,
+    /// IP addresses from which connections are refused.
+    banned_ips: HashSet<IpAddr>,
+    /// The target number of connected peers, excluding trusted peers.
+    max_peers: usize,
+    /// Prunes excess peers and searches for peers on subnets with too few peers.
+    heartbeat: Interval,
+    /// The subnet from which the next search for subnet peers starts, so that subnets with equal
+    /// coverage are searched in turn.
+    next_subnet_query: usize,
+    /// The parameters used to score peers by their gossipsub messages. Peers are not scored until
+    /// these are set.
+    gossip_score_params: Option<PeerScoreParams>,
+    /// The gossipsub scores of peers. Scores are kept after a peer disconnects until they decay
+    /// to zero.
+    gossip_scores: HashMap<PeerId, score::PeerScore>,
+    /// Decays the gossipsub scores every `decay_interval`.
+    gossip_score_decay: Option<Interval>,
     /// Last updated moment.
     last_updated: Instant,
     /// The logger associated with the `PeerManager`.
@@ -69,26 +107,164 @@ pub enum PeerManagerEvent {
     Ping(PeerId),
     /// Request METADATA from a peer.
     MetaData(PeerId),
+    /// Dial a peer which is not connected.
+    Dial(PeerId),
     /// The peer should be disconnected.
-    _DisconnectPeer(PeerId),
+    DisconnectPeer(PeerId),
     /// The peer should be disconnected and banned.
-    _BanPeer(PeerId),
+    BanPeer(PeerId),
+    /// Search for peers on a subnet which has too few connected peers.
+    DiscoverSubnetPeers(SubnetId),
 }
 
 impl<TSpec: EthSpec> PeerManager<TSpec> {
-    pub fn new(network_globals: Arc<NetworkGlobals<TSpec>>, log: &slog::Logger) -> Self {
+    pub fn new(
+        network_globals: Arc<NetworkGlobals<TSpec>>,
+        max_peers: usize,
+        log: &slog::Logger,
+    ) -> Self {
         PeerManager {
             network_globals,
             events: SmallVec::new(),
             last_updated: Instant::now(),
             ping_peers: HashSetDelay::new(Duration::from_secs(PING_INTERVAL)),
             status_peers: HashSetDelay::new(Duration::from_secs(STATUS_INTERVAL)),
+            trusted_peers: HashMap::new(),
+            redial_peers: HashSetDelay::new(Duration::from_secs(MIN_TRUSTED_PEER_REDIAL_DELAY)),
+            banned_ips: HashSet::new(),
+            max_peers,
+            heartbeat: Interval::new_interval(Duration::from_secs(HEARTBEAT_INTERVAL)),
+            next_subnet_query: 0,
+            gossip_score_params: None,
+            gossip_scores: HashMap::new(),
+            gossip_score_decay: None,
             log: log.clone(),
         }
     }
 
     /* Public accessible functions */
 
+    /// Adds a trusted peer. Trusted peers are dialed immediately if not connected, redialed with
+    /// a backoff whenever they disconnect, and never banned.
+    pub fn add_trusted_peer(&mut self, peer_id: PeerId) {
+        let is_connected = {
+            let mut peerdb = self.network_globals.peers.write();
+            peerdb.set_trusted(&peer_id);
+            peerdb.is_connected_or_dialing(&peer_id)
+        };
+
+        debug!(self.log, "Adding trusted peer"; "peer_id" => format!("{}", peer_id), "connected" => is_connected);
+        self.trusted_peers.insert(
+            peer_id.clone(),
+            Duration::from_secs(MIN_TRUSTED_PEER_REDIAL_DELAY),
+        );
+        if !is_connected {
+            self.redial_peers.insert_at(peer_id, Duration::from_secs(0));
+        }
+    }
+
+    /// Returns true if the peer is trusted.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.trusted_peers.contains_key(peer_id)
+    }
+
+    /// Refuses connections from the IP address and requests that peers connected from it are
+    /// banned. Trusted peers are exempt.
+    pub fn ban_ip(&mut self, ip: IpAddr) {
+        let peers_to_ban = self
+            .network_globals
+            .peers
+            .read()
+            .connected_peers()
+            .filter(|(peer_id, info)| {
+                !self.is_trusted(peer_id) && info.seen_address.as_ref().and_then(ip_of) == Some(ip)
+            })
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect::<Vec<_>>();
+
+        debug!(self.log, "Banning IP address"; "ip" => format!("{}", ip), "connected_peers" => peers_to_ban.len());
+        self.banned_ips.insert(ip);
+        for peer_id in peers_to_ban {
+            self.events.push(PeerManagerEvent::BanPeer(peer_id));
+        }
+    }
+
+    /// Accepts connections from an IP address which was banned.
+    pub fn unban_ip(&mut self, ip: &IpAddr) {
+        debug!(self.log, "Unbanning IP address"; "ip" => format!("{}", ip));
+        self.banned_ips.remove(ip);
+    }
+
+    /// Sets the parameters used to score peers by their gossipsub messages. This enables scoring
+    /// if it was not already.
+    pub fn update_gossip_score_params(&mut self, params: PeerScoreParams) {
+        let decay_interval = self
+            .gossip_score_params
+            .as_ref()
+            .map(|params| params.decay_interval);
+        if decay_interval != Some(params.decay_interval) {
+            self.gossip_score_decay = Some(Interval::new_interval(params.decay_interval));
+        }
+        self.gossip_score_params = Some(params);
+    }
+
+    /// A gossipsub message which the peer delivered first has been validated.
+    pub fn gossip_message_validated(&mut self, peer_id: &PeerId, kind: &GossipKind) {
+        if let Some(params) = &self.gossip_score_params {
+            self.gossip_scores
+                .entry(peer_id.clone())
+                .or_default()
+                .first_message_delivered(params, kind);
+            self.update_gossip_score(peer_id);
+        }
+    }
+
+    /// The peer delivered an invalid gossipsub message.
+    pub fn gossip_message_invalid(&mut self, peer_id: &PeerId, kind: &GossipKind) {
+        if let Some(params) = &self.gossip_score_params {
+            debug!(self.log, "Peer delivered an invalid gossipsub message"; "peer_id" => format!("{}", peer_id), "topic" => format!("{}", kind));
+            self.gossip_scores
+                .entry(peer_id.clone())
+                .or_default()
+                .invalid_message_delivered(params, kind);
+            self.update_gossip_score(peer_id);
+        }
+    }
+
+    /// The peer delivered a gossipsub message which was already delivered.
+    pub fn gossip_message_duplicated(&mut self, peer_id: &PeerId, kind: &GossipKind) {
+        if let Some(params) = &self.gossip_score_params {
+            debug!(self.log, "Peer delivered a duplicate gossipsub message"; "peer_id" => format!("{}", peer_id), "topic" => format!("{}", kind));
+            self.gossip_scores
+                .entry(peer_id.clone())
+                .or_default()
+                .duplicate_message_delivered(params, kind);
+            self.update_gossip_score(peer_id);
+        }
+    }
+
+    /// Returns the gossipsub score of the peer.
+    pub fn gossip_score(&self, peer_id: &PeerId) -> f64 {
+        match (&self.gossip_score_params, self.gossip_scores.get(peer_id)) {
+            (Some(params), Some(score)) => score.score(params),
+            _ => 0.0,
+        }
+    }
+
+    /// Returns true if the peer should not be in our gossipsub meshes or be sent gossip.
+    pub fn below_gossip_threshold(&self, peer_id: &PeerId) -> bool {
+        self.gossip_score_params.as_ref().map_or(false, |params| {
+            self.gossip_score(peer_id) < params.gossip_threshold
+        })
+    }
+
+    /// Returns true if gossipsub messages from the peer should be ignored.
+    pub fn is_graylisted(&self, peer_id: &PeerId) -> bool {
+        self.gossip_score_params.as_ref().map_or(false, |params| {
+            self.gossip_score(peer_id) < params.graylist_threshold
+        })
+    }
+
     /// A ping request has been received.
     // NOTE: The behaviour responds with a PONG automatically
     // TODO: Update last seen
@@ -169,7 +345,16 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
 
     /// Checks the reputation of a peer and if it is too low, bans it and
     /// sends the corresponding event. Informs if it got banned
+    ///
+    /// NOTE: The `BanPeer` event is enforced by the network service, which disconnects the peer
+    /// and refuses its connections for `BAN_PEER_TIMEOUT`. Enabling this therefore enforces
+    /// reputation bans.
     fn _gets_banned(&mut self, peer_id: &PeerId) -> bool {
+        // trusted peers are never banned
+        if self.is_trusted(peer_id) {
+            return false;
+        }
+
         // if the peer was already banned don't inform again
         let mut peerdb = self.network_globals.peers.write();
 
@@ -178,8 +363,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                 && !connection_status.is_banned()
             {
                 peerdb.ban(peer_id);
-                self.events
-                    .push(PeerManagerEvent::_BanPeer(peer_id.clone()));
+                self.events.push(PeerManagerEvent::BanPeer(peer_id.clone()));
                 return true;
             }
         }
@@ -189,7 +373,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     /// Requests that a peer get disconnected.
     pub fn _disconnect_peer(&mut self, peer_id: &PeerId) {
         self.events
-            .push(PeerManagerEvent::_DisconnectPeer(peer_id.clone()));
+            .push(PeerManagerEvent::DisconnectPeer(peer_id.clone()));
     }
 
     /// Updates the state of the peer as disconnected.
@@ -204,6 +388,14 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         // remove the ping and status timer for the peer
         self.ping_peers.remove(peer_id);
         self.status_peers.remove(peer_id);
+
+        // schedule a trusted peer to be redialed, unless it already is
+        if let Some(redial_delay) = self.trusted_peers.get(peer_id) {
+            if !self.redial_peers.contains(peer_id) {
+                self.redial_peers.insert_at(peer_id.clone(), *redial_delay);
+            }
+        }
+
         metrics::inc_counter(&metrics::PEER_DISCONNECT_EVENT_COUNT);
         metrics::set_gauge(
             &metrics::PEERS_CONNECTED,
@@ -211,16 +403,16 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         );
     }
 
-    /// Sets a peer as connected as long as their reputation allows it
+    /// Sets a peer as connected as long as their reputation and address allow it
     /// Informs if the peer was accepted
-    pub fn connect_ingoing(&mut self, peer_id: &PeerId) -> bool {
-        self.connect_peer(peer_id, false)
+    pub fn connect_ingoing(&mut self, peer_id: &PeerId, address: &Multiaddr) -> bool {
+        self.connect_peer(peer_id, address, false)
     }
 
-    /// Sets a peer as connected as long as their reputation allows it
+    /// Sets a peer as connected as long as their reputation and address allow it
     /// Informs if the peer was accepted
-    pub fn connect_outgoing(&mut self, peer_id: &PeerId) -> bool {
-        self.connect_peer(peer_id, true)
+    pub fn connect_outgoing(&mut self, peer_id: &PeerId, address: &Multiaddr) -> bool {
+        self.connect_peer(peer_id, address, true)
     }
 
     /// Provides a given peer's reputation if it exists.
@@ -267,17 +459,33 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
 
     /* Internal functions */
 
-    /// Registers a peer as connected. The `ingoing` parameter determines if the peer is being
-    /// dialed or connecting to us.
+    /// Registers a peer as connected at `address`. The `ingoing` parameter determines if the peer
+    /// is being dialed or connecting to us.
     ///
     /// This is called by `connect_ingoing` and `connect_outgoing`.
     ///
     /// This informs if the peer was accepted in to the db or not.
-    // TODO: Drop peers if over max_peer limit
-    fn connect_peer(&mut self, peer_id: &PeerId, outgoing: bool) -> bool {
+    fn connect_peer(&mut self, peer_id: &PeerId, address: &Multiaddr, outgoing: bool) -> bool {
         // TODO: Call this on a timer
         self.update_reputations();
 
+        if let Some(ip) = ip_of(address) {
+            if self.banned_ips.contains(&ip) && !self.is_trusted(peer_id) {
+                debug!(self.log, "Refusing peer with a banned IP"; "peer_id" => format!("{}", peer_id), "ip" => format!("{}", ip));
+                self.events.push(PeerManagerEvent::BanPeer(peer_id.clone()));
+                return false;
+            }
+        }
+
+        // gossipsub meshes every connected peer, so peers are kept out of our meshes until their
+        // score recovers above the gossip threshold
+        if self.below_gossip_threshold(peer_id) && !self.is_trusted(peer_id) {
+            debug!(self.log, "Refusing peer with a low gossipsub score"; "peer_id" => format!("{}", peer_id), "score" => self.gossip_score(peer_id));
+            self.events
+                .push(PeerManagerEvent::DisconnectPeer(peer_id.clone()));
+            return false;
+        }
+
         {
             let mut peerdb = self.network_globals.peers.write();
             if peerdb.connection_status(peer_id).map(|c| c.is_banned()) == Some(true) {
@@ -290,6 +498,15 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             } else {
                 peerdb.connect_outgoing(peer_id);
             }
+
+            if let Some(info) = peerdb.peer_info_mut(peer_id) {
+                info.seen_address = Some(address.clone());
+            }
+        }
+
+        // the next disconnection of a trusted peer is redialed after the minimum delay
+        if let Some(redial_delay) = self.trusted_peers.get_mut(peer_id) {
+            *redial_delay = Duration::from_secs(MIN_TRUSTED_PEER_REDIAL_DELAY);
         }
 
         // start a ping and status timer for the peer
@@ -310,6 +527,114 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     pub fn _dialing_peer(&mut self, peer_id: &PeerId) {
         self.network_globals.peers.write().dialing_peer(peer_id);
     }
+
+    /// Records the gossipsub score of the peer in the `PeerDB`. The peer is disconnected if the
+    /// score fell below the `gossip_threshold` and banned if it fell below the `ban_threshold`.
+    fn update_gossip_score(&mut self, peer_id: &PeerId) {
+        let params = match &self.gossip_score_params {
+            Some(params) => params,
+            None => return,
+        };
+        let score = self.gossip_score(peer_id);
+
+        let mut peerdb = self.network_globals.peers.write();
+        let is_connected = peerdb.is_connected(peer_id);
+        if let Some(info) = peerdb.peer_info_mut(peer_id) {
+            // only disconnect or ban the peer once, as the score falls below a threshold
+            if is_connected && !info.is_trusted {
+                if score < params.ban_threshold && info.gossipsub_score >= params.ban_threshold {
+                    debug!(self.log, "Banning peer with a low gossipsub score"; "peer_id" => format!("{}", peer_id), "score" => score);
+                    self.events.push(PeerManagerEvent::BanPeer(peer_id.clone()));
+                } else if score < params.gossip_threshold
+                    && info.gossipsub_score >= params.gossip_threshold
+                {
+                    debug!(self.log, "Disconnecting peer with a low gossipsub score"; "peer_id" => format!("{}", peer_id), "score" => score);
+                    self.events
+                        .push(PeerManagerEvent::DisconnectPeer(peer_id.clone()));
+                }
+            }
+            info.gossipsub_score = score;
+        }
+    }
+
+    /// Decays the gossipsub scores of all peers, forgetting the scores of disconnected peers once
+    /// they decay to zero.
+    fn decay_gossip_scores(&mut self) {
+        let params = match &self.gossip_score_params {
+            Some(params) => params,
+            None => return,
+        };
+
+        let mut peerdb = self.network_globals.peers.write();
+        self.gossip_scores.retain(|peer_id, score| {
+            score.decay(params);
+            let is_connected = match peerdb.peer_info_mut(peer_id) {
+                Some(info) => {
+                    info.gossipsub_score = score.score(params);
+                    info.connection_status.is_connected()
+                }
+                None => false,
+            };
+            is_connected || !score.is_empty()
+        });
+    }
+
+    /// Disconnects the peers in excess of `max_peers` which contribute least to our subnet
+    /// coverage, searches for peers on the subnets with the fewest peers and updates the subnet
+    /// metrics.
+    fn heartbeat(&mut self) {
+        let excess_peers = self
+            .network_globals
+            .connected_untrusted_peers()
+            .saturating_sub(self.max_peers);
+
+        let (peers_per_subnet, peers_to_prune) = {
+            let peerdb = self.network_globals.peers.read();
+            (
+                peerdb.peers_per_subnet(),
+                peerdb.peers_to_prune(excess_peers, TARGET_SUBNET_PEERS as usize),
+            )
+        };
+
+        for peer_id in peers_to_prune {
+            debug!(self.log, "Pruning excess peer"; "peer_id" => format!("{}", peer_id));
+            self.events.push(PeerManagerEvent::DisconnectPeer(peer_id));
+        }
+
+        for (subnet, peers) in peers_per_subnet.iter().enumerate() {
+            metrics::set_gauge_vec(
+                &metrics::PEERS_PER_SUBNET,
+                &[&subnet.to_string()],
+                *peers as i64,
+            );
+        }
+
+        // search the least covered subnets first, starting from `next_subnet_query` amongst
+        // subnets with equal coverage
+        let subnet_count = peers_per_subnet.len();
+        let mut subnets_to_query = peers_per_subnet
+            .iter()
+            .enumerate()
+            .filter(|(_, peers)| (**peers as u64) < TARGET_SUBNET_PEERS)
+            .map(|(subnet, peers)| {
+                let turn = (subnet + subnet_count - self.next_subnet_query) % subnet_count;
+                (*peers, turn, subnet)
+            })
+            .collect::<Vec<_>>();
+        subnets_to_query.sort();
+
+        for (peers, _, subnet) in subnets_to_query
+            .into_iter()
+            .take(MAX_SUBNET_QUERIES_PER_HEARTBEAT)
+        {
+            debug!(self.log, "Searching for subnet peers"; "subnet_id" => subnet, "connected_peers" => peers);
+            self.next_subnet_query = (subnet + 1) % subnet_count;
+            self.events
+                .push(PeerManagerEvent::DiscoverSubnetPeers(SubnetId::new(
+                    subnet as u64,
+                )));
+        }
+    }
 }
 
 impl<TSpec: EthSpec> Stream for PeerManager<TSpec> {
@@ -336,6 +661,44 @@ impl<TSpec: EthSpec> Stream for PeerManager<TSpec> {
             self.events.push(PeerManagerEvent::Status(peer_id));
         }
 
+        while let Async::Ready(Some(peer_id)) = self.redial_peers.poll().map_err(|e| {
+            error!(self.log, "Failed to check for trusted peers to redial"; "error" => format!("{}",e));
+        })? {
+            if self.network_globals.peers.read().is_connected(&peer_id) {
+                continue;
+            }
+
+            if let Some(redial_delay) = self.trusted_peers.get_mut(&peer_id) {
+                debug!(self.log, "Dialing trusted peer"; "peer_id" => format!("{}", peer_id), "next_attempt_secs" => redial_delay.as_secs());
+                // check again after the delay, in case the dial fails
+                self.redial_peers.insert_at(peer_id.clone(), *redial_delay);
+                *redial_delay = std::cmp::min(
+                    *redial_delay * 2,
+                    Duration::from_secs(MAX_TRUSTED_PEER_REDIAL_DELAY),
+                );
+
+                self.network_globals.peers.write().dialing_peer(&peer_id);
+                self.events.push(PeerManagerEvent::Dial(peer_id));
+            }
+        }
+
+        loop {
+            match self.gossip_score_decay.as_mut().map(|decay| decay.poll()) {
+                Some(Ok(Async::Ready(Some(_)))) => self.decay_gossip_scores(),
+                Some(Err(e)) => {
+                    error!(self.log, "Failed to poll the gossipsub score decay"; "error" => format!("{}",e));
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        while let Async::Ready(Some(_)) = self.heartbeat.poll().map_err(|e| {
+            error!(self.log, "Failed to poll the peer manager heartbeat"; "error" => format!("{}",e));
+        })? {
+            self.heartbeat();
+        }
+
         if !self.events.is_empty() {
             return Ok(Async::Ready(Some(self.events.remove(0))));
         } else {
@@ -345,3 +708,12 @@ impl<TSpec: EthSpec> Stream for PeerManager<TSpec> {
         Ok(Async::NotReady)
     }
 }
+
+/// Returns the IP address of a multiaddr, if it has one.
+fn ip_of(address: &Multiaddr) -> Option<IpAddr> {
+    address.iter().find_map(|protocol| match protocol {
+        Protocol::Ip4(ip) => Some(ip.into()),
+        Protocol::Ip6(ip) => Some(ip.into()),
+        _ => None,
+    })
+}
@@ -19,18 +19,25 @@ pub struct PeerInfo<T: EthSpec> {
     _status: PeerStatus,
     /// The peers reputation
     pub reputation: Rep,
+    /// The score of the peer from the gossipsub messages it delivered.
+    pub gossipsub_score: f64,
     /// Client managing this peer
     pub client: Client,
     /// Connection status of this peer
     pub connection_status: PeerConnectionStatus,
     /// The known listening addresses of this peer.
     pub listening_addresses: Vec<Multiaddr>,
+    /// The address of the current (or last) connection to the peer.
+    pub seen_address: Option<Multiaddr>,
     /// The current syncing state of the peer. The state may be determined after it's initial
     /// connection.
     pub sync_status: PeerSyncStatus,
     /// The ENR subnet bitfield of the peer. This may be determined after it's initial
     /// connection.
     pub meta_data: Option<MetaData<T>>,
+    /// Whether the peer is trusted. Trusted peers are kept connected, do not count towards the
+    /// peer limit and are never banned.
+    pub is_trusted: bool,
 }
 
 impl<TSpec: EthSpec> Default for PeerInfo<TSpec> {
@@ -38,11 +45,14 @@ impl<TSpec: EthSpec> Default for PeerInfo<TSpec> {
         PeerInfo {
             _status: Default::default(),
             reputation: DEFAULT_REPUTATION,
+            gossipsub_score: 0.0,
             client: Client::default(),
             connection_status: Default::default(),
             listening_addresses: vec![],
+            seen_address: None,
             sync_status: PeerSyncStatus::Unknown,
             meta_data: None,
+            is_trusted: false,
         }
     }
 }
@@ -58,6 +68,20 @@ impl<T: EthSpec> PeerInfo<T> {
         }
         false
     }
+
+    /// Returns the indices of the subnets that the peer is subscribed to, or `None` if neither
+    /// its metadata nor the attestation bitfield of its ENR is known yet.
+    pub fn subnets(&self) -> Option<Vec<usize>> {
+        self.meta_data.as_ref().map(|meta_data| {
+            meta_data
+                .attnets
+                .iter()
+                .enumerate()
+                .filter(|(_, on_subnet)| *on_subnet)
+                .map(|(subnet, _)| subnet)
+                .collect()
+        })
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
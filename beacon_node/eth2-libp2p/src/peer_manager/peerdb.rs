@@ -3,9 +3,10 @@ use super::peer_sync_status::PeerSyncStatus;
 use crate::rpc::methods::MetaData;
 use crate::PeerId;
 use slog::{crit, warn};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::time::Instant;
-use types::{EthSpec, SubnetId};
+use types::{typenum::Unsigned, EthSpec, SubnetId};
 
 /// A peer's reputation.
 pub type Rep = i32;
@@ -87,6 +88,14 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
             .map(|(peer_id, _)| peer_id)
     }
 
+    /// Gives the ids of all trusted peers, whatever their connection status.
+    pub fn trusted_peers(&self) -> impl Iterator<Item = &PeerId> {
+        self.peers
+            .iter()
+            .filter(|(_, info)| info.is_trusted)
+            .map(|(peer_id, _)| peer_id)
+    }
+
     /// Connected or dialing peers
     pub fn connected_or_dialing_peers(&self) -> impl Iterator<Item = &PeerId> {
         self.peers
@@ -121,6 +130,66 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
             .map(|(peer_id, _)| peer_id)
     }
 
+    /// Gives the number of connected peers on each subnet, indexed by subnet id.
+    pub fn peers_per_subnet(&self) -> Vec<usize> {
+        let mut peers_per_subnet = vec![0; TSpec::SubnetBitfieldLength::to_usize()];
+        for (_, info) in self.connected_peers() {
+            for subnet in info.subnets().unwrap_or_default() {
+                peers_per_subnet[subnet] += 1;
+            }
+        }
+        peers_per_subnet
+    }
+
+    /// Returns up to `count` connected, untrusted peers which contribute least to the subnet
+    /// coverage of the node, in the order they should be disconnected.
+    ///
+    /// A peer contributes to each of its subnets which has at most `target_subnet_peers`
+    /// connected peers. Peers with equal contributions are ordered by gossipsub score and then by
+    /// reputation, lowest first. Peers whose subnets are not known yet are never pruned, as they
+    /// may well be the only peers on some subnets.
+    pub fn peers_to_prune(&self, count: usize, target_subnet_peers: usize) -> Vec<PeerId> {
+        let mut peers_per_subnet = self.peers_per_subnet();
+        let mut candidates = self
+            .connected_peers()
+            .filter(|(_, info)| !info.is_trusted)
+            .filter_map(|(peer_id, info)| info.subnets().map(|subnets| (peer_id, subnets, info)))
+            .collect::<Vec<_>>();
+
+        let mut to_prune = Vec::with_capacity(count);
+        while to_prune.len() < count {
+            let least_useful = candidates
+                .iter()
+                .enumerate()
+                .map(|(index, (_, subnets, info))| {
+                    let contribution = subnets
+                        .iter()
+                        .filter(|subnet| peers_per_subnet[**subnet] <= target_subnet_peers)
+                        .count();
+                    (index, contribution, info.gossipsub_score, info.reputation)
+                })
+                .min_by(|a, b| {
+                    (a.1, a.2, a.3)
+                        .partial_cmp(&(b.1, b.2, b.3))
+                        .unwrap_or(Ordering::Equal)
+                })
+                .map(|(index, ..)| index);
+
+            let index = match least_useful {
+                Some(index) => index,
+                None => break,
+            };
+
+            // once pruned, the peer no longer counts towards the coverage of its subnets
+            let (peer_id, subnets, _) = candidates.swap_remove(index);
+            for subnet in subnets {
+                peers_per_subnet[subnet] -= 1;
+            }
+            to_prune.push(peer_id.clone());
+        }
+        to_prune
+    }
+
     /// Gives the ids of all known disconnected peers.
     pub fn disconnected_peers(&self) -> impl Iterator<Item = &PeerId> {
         self.peers
@@ -188,8 +257,28 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         }
     }
 
+    /// Returns true if the peer is trusted.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.peers
+            .get(peer_id)
+            .map_or(false, |info| info.is_trusted)
+    }
+
     /* Setters */
 
+    /// Marks a peer as trusted, adding it to the db as disconnected if it is unknown.
+    pub fn set_trusted(&mut self, peer_id: &PeerId) {
+        let n_dc = &mut self.n_dc;
+        let info = self.peers.entry(peer_id.clone()).or_insert_with(|| {
+            *n_dc += 1;
+            let mut info = PeerInfo::default();
+            info.connection_status.disconnect();
+            info
+        });
+        info.is_trusted = true;
+        self.shrink_to_fit();
+    }
+
     /// A peer is being dialed.
     pub fn dialing_peer(&mut self, peer_id: &PeerId) {
         let info = self
@@ -248,17 +337,21 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
     }
 
     /// Drops the peers with the lowest reputation so that the number of
-    /// disconnected peers is less than MAX_DC_PEERS
+    /// disconnected peers is less than MAX_DC_PEERS. Trusted peers are never dropped.
     pub fn shrink_to_fit(&mut self) {
         // for caution, but the difference should never be > 1
         while self.n_dc > MAX_DC_PEERS {
-            let to_drop = self
+            let to_drop = match self
                 .peers
                 .iter()
-                .filter(|(_, info)| info.connection_status.is_disconnected())
+                .filter(|(_, info)| info.connection_status.is_disconnected() && !info.is_trusted)
                 .min_by_key(|(_, info)| info.reputation)
                 .map(|(id, _)| id.clone())
-                .unwrap(); // should be safe since n_dc > MAX_DC_PEERS > 0
+            {
+                Some(to_drop) => to_drop,
+                // only trusted peers are disconnected, these are always remembered
+                None => break,
+            };
             self.peers.remove(&to_drop);
             self.n_dc -= 1;
         }
@@ -321,6 +414,7 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::EnrBitfield;
     use slog::{o, Drain};
     use types::MinimalEthSpec;
     type M = MinimalEthSpec;
@@ -426,6 +520,33 @@ mod tests {
         assert_eq!(pdb.n_dc, MAX_DC_PEERS);
     }
 
+    #[test]
+    fn test_trusted_peers_are_not_dropped() {
+        let mut pdb = get_db();
+
+        let trusted_peer = PeerId::random();
+        pdb.set_trusted(&trusted_peer);
+        assert!(pdb.is_trusted(&trusted_peer));
+        assert!(pdb
+            .connection_status(&trusted_peer)
+            .unwrap()
+            .is_disconnected());
+        assert_eq!(pdb.n_dc, 1);
+
+        // give the trusted peer the lowest reputation of all disconnected peers
+        pdb.set_reputation(&trusted_peer, Rep::min_value());
+        for _ in 0..MAX_DC_PEERS + 1 {
+            let p = PeerId::random();
+            pdb.connect_ingoing(&p);
+            pdb.disconnect(&p);
+        }
+
+        assert_eq!(pdb.n_dc, MAX_DC_PEERS);
+        assert_eq!(pdb.n_dc, pdb.disconnected_peers().count());
+        assert!(pdb.is_trusted(&trusted_peer));
+        assert!(pdb.trusted_peers().eq(vec![&trusted_peer]));
+    }
+
     #[test]
     fn test_best_peers() {
         let mut pdb = get_db();
@@ -496,4 +617,99 @@ mod tests {
         pdb.disconnect(&random_peer);
         assert_eq!(pdb.n_dc, pdb.disconnected_peers().count());
     }
+
+    fn connect_on_subnets(pdb: &mut PeerDB<M>, subnets: &[usize]) -> PeerId {
+        let peer_id = PeerId::random();
+        pdb.connect_ingoing(&peer_id);
+        let mut attnets = EnrBitfield::<M>::new();
+        for subnet in subnets {
+            attnets.set(*subnet, true).unwrap();
+        }
+        pdb.add_metadata(
+            &peer_id,
+            MetaData {
+                seq_number: 0,
+                attnets,
+            },
+        );
+        peer_id
+    }
+
+    #[test]
+    fn test_peers_per_subnet() {
+        let mut pdb = get_db();
+
+        connect_on_subnets(&mut pdb, &[0, 1]);
+        connect_on_subnets(&mut pdb, &[1]);
+        let disconnected = connect_on_subnets(&mut pdb, &[2]);
+        pdb.disconnect(&disconnected);
+
+        let peers_per_subnet = pdb.peers_per_subnet();
+        assert_eq!(peers_per_subnet.len(), 64);
+        assert_eq!(peers_per_subnet[0], 1);
+        assert_eq!(peers_per_subnet[1], 2);
+        assert_eq!(peers_per_subnet[2], 0);
+    }
+
+    #[test]
+    fn test_prune_keeps_subnet_coverage() {
+        let mut pdb = get_db();
+
+        // the only peer on subnet 0
+        let unique = connect_on_subnets(&mut pdb, &[0]);
+        // many peers share subnet 1
+        let shared = (0..4)
+            .map(|_| connect_on_subnets(&mut pdb, &[1]))
+            .collect::<Vec<_>>();
+        let no_subnets = connect_on_subnets(&mut pdb, &[]);
+        pdb.set_reputation(&no_subnets, 10);
+        let trusted = connect_on_subnets(&mut pdb, &[]);
+        pdb.set_trusted(&trusted);
+
+        let to_prune = pdb.peers_to_prune(3, 2);
+        assert_eq!(to_prune.len(), 3);
+        assert_eq!(to_prune[0], no_subnets);
+        assert!(to_prune[1..].iter().all(|peer_id| shared.contains(peer_id)));
+        assert!(!to_prune.contains(&unique));
+        assert!(!to_prune.contains(&trusted));
+    }
+
+    #[test]
+    fn test_prune_skips_peers_with_unknown_subnets() {
+        let mut pdb = get_db();
+
+        let unknown = PeerId::random();
+        pdb.connect_ingoing(&unknown);
+        pdb.set_reputation(&unknown, 0);
+        let known = connect_on_subnets(&mut pdb, &[]);
+
+        assert_eq!(pdb.peers_to_prune(2, 2), vec![known]);
+    }
+
+    #[test]
+    fn test_prune_by_reputation() {
+        let mut pdb = get_db();
+
+        let good = connect_on_subnets(&mut pdb, &[]);
+        let bad = connect_on_subnets(&mut pdb, &[]);
+        pdb.set_reputation(&good, 100);
+        pdb.set_reputation(&bad, 10);
+
+        assert_eq!(pdb.peers_to_prune(1, 2), vec![bad]);
+        // cannot prune more peers than are connected
+        assert_eq!(pdb.peers_to_prune(5, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_prune_by_gossipsub_score() {
+        let mut pdb = get_db();
+
+        let spammer = connect_on_subnets(&mut pdb, &[]);
+        let honest = connect_on_subnets(&mut pdb, &[]);
+        pdb.peer_info_mut(&spammer).unwrap().gossipsub_score = -1000.0;
+        // a lower reputation does not outweigh the gossipsub score
+        pdb.set_reputation(&honest, 10);
+
+        assert_eq!(pdb.peers_to_prune(1, 2), vec![spammer]);
+    }
 }
@@ -0,0 +1,401 @@
+//! Scores peers by the gossipsub messages they deliver, in the style of gossipsub v1.1 peer
+//! scoring.
+//!
+//! Each topic has parameters derived from the `ChainSpec`. The expected message rate of a topic
+//! bounds the reward for delivering its messages first, whilst invalid messages and messages which
+//! were already delivered are penalised quadratically. The counters of each peer decay every slot.
+//!
+//! The gossipsub behaviour does not expose its mesh, so the score is applied around it:
+//! - A peer below the `gossip_threshold` is disconnected, which removes it from our meshes and
+//!   stops gossip being emitted to it. It is refused if it reconnects before its score recovers.
+//! - Messages from a peer below the `graylist_threshold` are ignored.
+//! - A peer below the `ban_threshold` is banned.
+//! - Peers with lower scores are pruned first when over the peer limit.
+use crate::types::GossipKind;
+use std::collections::HashMap;
+use std::time::Duration;
+use types::{typenum::Unsigned, ChainSpec, EthSpec, SubnetId};
+
+/// Counters are set to zero once they decay below this value.
+const DECAY_TO_ZERO: f64 = 0.01;
+/// The score for delivering every expected message of a topic first, before the topic weight is
+/// applied.
+const MAX_FIRST_MESSAGE_DELIVERIES_SCORE: f64 = 40.0;
+/// The penalty of a single invalid message, after the topic weight is applied. This cancels the
+/// highest score a peer can obtain from first deliveries.
+const INVALID_MESSAGE_PENALTY: f64 = 100.0;
+/// The penalty of a single duplicate message, after the topic weight is applied.
+const DUPLICATE_MESSAGE_PENALTY: f64 = 10.0;
+/// The number of peers in a gossipsub mesh, amongst which first deliveries are expected to be
+/// shared.
+const MESH_PEERS: f64 = 6.0;
+/// The number of epochs over which first deliveries decay to zero.
+const FIRST_MESSAGE_DELIVERIES_DECAY_EPOCHS: f64 = 1.0;
+/// The number of epochs over which invalid messages decay to zero.
+const INVALID_MESSAGE_DELIVERIES_DECAY_EPOCHS: f64 = 50.0;
+/// The number of epochs over which duplicate messages decay to zero.
+const DUPLICATE_MESSAGE_DELIVERIES_DECAY_EPOCHS: f64 = 5.0;
+
+const BEACON_BLOCK_WEIGHT: f64 = 0.5;
+const BEACON_AGGREGATE_AND_PROOF_WEIGHT: f64 = 0.5;
+/// The weight of all attestation subnets, which is shared equally between them.
+const ATTESTATION_SUBNETS_WEIGHT: f64 = 1.0;
+const OPERATION_WEIGHT: f64 = 0.05;
+
+/// The scoring parameters of a single topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicScoreParams {
+    /// The weight applied to the score of the topic.
+    pub topic_weight: f64,
+    /// The number of messages expected on the topic each slot.
+    pub expected_message_rate: f64,
+    /// The reward for each message delivered first.
+    pub first_message_deliveries_weight: f64,
+    /// The factor applied to first deliveries every slot.
+    pub first_message_deliveries_decay: f64,
+    /// The maximum number of first deliveries which are rewarded.
+    pub first_message_deliveries_cap: f64,
+    /// The penalty for the square of the number of invalid messages. This is negative.
+    pub invalid_message_deliveries_weight: f64,
+    /// The factor applied to invalid messages every slot.
+    pub invalid_message_deliveries_decay: f64,
+    /// The penalty for the square of the number of duplicate messages. This is negative.
+    pub duplicate_message_deliveries_weight: f64,
+    /// The factor applied to duplicate messages every slot.
+    pub duplicate_message_deliveries_decay: f64,
+}
+
+impl TopicScoreParams {
+    fn new(topic_weight: f64, expected_message_rate: f64, slots_per_epoch: f64) -> Self {
+        let first_message_deliveries_decay =
+            decay_factor(FIRST_MESSAGE_DELIVERIES_DECAY_EPOCHS * slots_per_epoch);
+        // the number of first deliveries a peer accumulates when delivering its share of the
+        // expected messages
+        let first_message_deliveries_cap =
+            (expected_message_rate / (1.0 - first_message_deliveries_decay) / MESH_PEERS).max(1.0);
+
+        TopicScoreParams {
+            topic_weight,
+            expected_message_rate,
+            first_message_deliveries_weight: MAX_FIRST_MESSAGE_DELIVERIES_SCORE
+                / first_message_deliveries_cap,
+            first_message_deliveries_decay,
+            first_message_deliveries_cap,
+            invalid_message_deliveries_weight: -INVALID_MESSAGE_PENALTY / topic_weight,
+            invalid_message_deliveries_decay: decay_factor(
+                INVALID_MESSAGE_DELIVERIES_DECAY_EPOCHS * slots_per_epoch,
+            ),
+            duplicate_message_deliveries_weight: -DUPLICATE_MESSAGE_PENALTY / topic_weight,
+            duplicate_message_deliveries_decay: decay_factor(
+                DUPLICATE_MESSAGE_DELIVERIES_DECAY_EPOCHS * slots_per_epoch,
+            ),
+        }
+    }
+}
+
+/// The parameters used to score peers by the gossipsub messages they deliver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerScoreParams {
+    /// The parameters of each scored topic.
+    pub topics: HashMap<GossipKind, TopicScoreParams>,
+    /// The time between decays of the counters, which is one slot.
+    pub decay_interval: Duration,
+    /// Peers below this score are disconnected, to remove them from our meshes and not send them
+    /// gossip.
+    pub gossip_threshold: f64,
+    /// Messages from peers below this score are ignored.
+    pub graylist_threshold: f64,
+    /// Peers below this score are banned.
+    pub ban_threshold: f64,
+}
+
+impl PeerScoreParams {
+    /// Derives the parameters of each topic from the `spec` and the number of active validators,
+    /// which determines the expected rate of attestations and aggregates.
+    pub fn new<T: EthSpec>(spec: &ChainSpec, active_validators: usize) -> Self {
+        let slots_per_epoch = T::slots_per_epoch() as f64;
+        let validators_per_slot = (active_validators as f64 / slots_per_epoch).max(1.0);
+        let committees_per_slot = (validators_per_slot / spec.target_committee_size as f64)
+            .floor()
+            .max(1.0)
+            .min(spec.max_committees_per_slot as f64);
+        let aggregates_per_slot = (committees_per_slot
+            * spec.target_aggregators_per_committee as f64)
+            .min(validators_per_slot);
+        let subnet_count = spec.attestation_subnet_count;
+        let attestations_per_subnet = validators_per_slot / subnet_count as f64;
+
+        let mut topics = HashMap::new();
+        topics.insert(
+            GossipKind::BeaconBlock,
+            TopicScoreParams::new(BEACON_BLOCK_WEIGHT, 1.0, slots_per_epoch),
+        );
+        topics.insert(
+            GossipKind::BeaconAggregateAndProof,
+            TopicScoreParams::new(
+                BEACON_AGGREGATE_AND_PROOF_WEIGHT,
+                aggregates_per_slot,
+                slots_per_epoch,
+            ),
+        );
+        for subnet_id in 0..subnet_count {
+            topics.insert(
+                GossipKind::CommitteeIndex(SubnetId::new(subnet_id)),
+                TopicScoreParams::new(
+                    ATTESTATION_SUBNETS_WEIGHT / subnet_count as f64,
+                    attestations_per_subnet,
+                    slots_per_epoch,
+                ),
+            );
+        }
+        // operations are rare, at most as many as fit in a block are expected each slot
+        topics.insert(
+            GossipKind::VoluntaryExit,
+            TopicScoreParams::new(
+                OPERATION_WEIGHT,
+                T::MaxVoluntaryExits::to_u64() as f64,
+                slots_per_epoch,
+            ),
+        );
+        topics.insert(
+            GossipKind::ProposerSlashing,
+            TopicScoreParams::new(
+                OPERATION_WEIGHT,
+                T::MaxProposerSlashings::to_u64() as f64,
+                slots_per_epoch,
+            ),
+        );
+        topics.insert(
+            GossipKind::AttesterSlashing,
+            TopicScoreParams::new(
+                OPERATION_WEIGHT,
+                T::MaxAttesterSlashings::to_u64() as f64,
+                slots_per_epoch,
+            ),
+        );
+
+        PeerScoreParams {
+            topics,
+            decay_interval: Duration::from_millis(spec.milliseconds_per_slot),
+            gossip_threshold: -40.0 * INVALID_MESSAGE_PENALTY,
+            graylist_threshold: -160.0 * INVALID_MESSAGE_PENALTY,
+            ban_threshold: -320.0 * INVALID_MESSAGE_PENALTY,
+        }
+    }
+}
+
+/// The counters of a peer on a single topic.
+#[derive(Debug, Default, Clone)]
+struct TopicCounters {
+    first_message_deliveries: f64,
+    invalid_message_deliveries: f64,
+    duplicate_message_deliveries: f64,
+}
+
+/// The gossipsub score of a single peer.
+#[derive(Debug, Default, Clone)]
+pub struct PeerScore {
+    topics: HashMap<GossipKind, TopicCounters>,
+}
+
+impl PeerScore {
+    /// Rewards the peer for delivering a valid message first. Messages on unscored topics are
+    /// ignored.
+    pub fn first_message_delivered(&mut self, params: &PeerScoreParams, kind: &GossipKind) {
+        if let Some(topic_params) = params.topics.get(kind) {
+            let counters = self.topics.entry(kind.clone()).or_default();
+            counters.first_message_deliveries = (counters.first_message_deliveries + 1.0)
+                .min(topic_params.first_message_deliveries_cap);
+        }
+    }
+
+    /// Penalises the peer for delivering an invalid message. Messages on unscored topics are
+    /// ignored.
+    pub fn invalid_message_delivered(&mut self, params: &PeerScoreParams, kind: &GossipKind) {
+        if params.topics.contains_key(kind) {
+            self.topics
+                .entry(kind.clone())
+                .or_default()
+                .invalid_message_deliveries += 1.0;
+        }
+    }
+
+    /// Penalises the peer for delivering a message which was already delivered, after gossipsub
+    /// stopped filtering it as a duplicate. Messages on unscored topics are ignored.
+    pub fn duplicate_message_delivered(&mut self, params: &PeerScoreParams, kind: &GossipKind) {
+        if params.topics.contains_key(kind) {
+            self.topics
+                .entry(kind.clone())
+                .or_default()
+                .duplicate_message_deliveries += 1.0;
+        }
+    }
+
+    /// Returns the score of the peer.
+    pub fn score(&self, params: &PeerScoreParams) -> f64 {
+        self.topics
+            .iter()
+            .filter_map(|(kind, counters)| {
+                let topic_params = params.topics.get(kind)?;
+                let first_deliveries = counters
+                    .first_message_deliveries
+                    .min(topic_params.first_message_deliveries_cap);
+                let topic_score = topic_params.first_message_deliveries_weight * first_deliveries
+                    + topic_params.invalid_message_deliveries_weight
+                        * counters.invalid_message_deliveries.powi(2)
+                    + topic_params.duplicate_message_deliveries_weight
+                        * counters.duplicate_message_deliveries.powi(2);
+                Some(topic_params.topic_weight * topic_score)
+            })
+            .sum()
+    }
+
+    /// Decays the counters of the peer, which is done once every `decay_interval`.
+    pub fn decay(&mut self, params: &PeerScoreParams) {
+        for (kind, counters) in self.topics.iter_mut() {
+            if let Some(topic_params) = params.topics.get(kind) {
+                counters.first_message_deliveries = decay(
+                    counters.first_message_deliveries,
+                    topic_params.first_message_deliveries_decay,
+                );
+                counters.invalid_message_deliveries = decay(
+                    counters.invalid_message_deliveries,
+                    topic_params.invalid_message_deliveries_decay,
+                );
+                counters.duplicate_message_deliveries = decay(
+                    counters.duplicate_message_deliveries,
+                    topic_params.duplicate_message_deliveries_decay,
+                );
+            } else {
+                *counters = TopicCounters::default();
+            }
+        }
+        self.topics.retain(|_, counters| {
+            counters.first_message_deliveries > 0.0
+                || counters.invalid_message_deliveries > 0.0
+                || counters.duplicate_message_deliveries > 0.0
+        });
+    }
+
+    /// Returns true if all counters have decayed to zero.
+    pub fn is_empty(&self) -> bool {
+        self.topics.is_empty()
+    }
+}
+
+/// Returns the factor which decays a counter to `DECAY_TO_ZERO` after `slots` decays.
+fn decay_factor(slots: f64) -> f64 {
+    DECAY_TO_ZERO.powf(1.0 / slots)
+}
+
+fn decay(counter: f64, factor: f64) -> f64 {
+    let decayed = counter * factor;
+    if decayed < DECAY_TO_ZERO {
+        0.0
+    } else {
+        decayed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::MainnetEthSpec;
+
+    type E = MainnetEthSpec;
+
+    fn params() -> PeerScoreParams {
+        PeerScoreParams::new::<E>(&E::default_spec(), 100_000)
+    }
+
+    #[test]
+    fn scores_every_topic() {
+        let params = params();
+        let spec = E::default_spec();
+        assert_eq!(
+            params.topics.len() as u64,
+            spec.attestation_subnet_count + 5
+        );
+        for topic_params in params.topics.values() {
+            assert!(topic_params.first_message_deliveries_cap >= 1.0);
+            assert!(topic_params.invalid_message_deliveries_weight < 0.0);
+            assert!(topic_params.duplicate_message_deliveries_weight < 0.0);
+        }
+        assert_eq!(
+            params.decay_interval,
+            Duration::from_millis(spec.milliseconds_per_slot)
+        );
+    }
+
+    #[test]
+    fn first_deliveries_are_capped() {
+        let params = params();
+        let mut score = PeerScore::default();
+        for _ in 0..10_000 {
+            score.first_message_delivered(&params, &GossipKind::BeaconBlock);
+        }
+        let expected = BEACON_BLOCK_WEIGHT * MAX_FIRST_MESSAGE_DELIVERIES_SCORE;
+        assert!((score.score(&params) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid_messages_reach_thresholds() {
+        let params = params();
+        let kind = GossipKind::CommitteeIndex(SubnetId::new(3));
+        let mut score = PeerScore::default();
+
+        score.invalid_message_delivered(&params, &kind);
+        assert!((score.score(&params) + INVALID_MESSAGE_PENALTY).abs() < 1e-9);
+        assert!(score.score(&params) > params.gossip_threshold);
+
+        for _ in 0..14 {
+            score.invalid_message_delivered(&params, &kind);
+        }
+        assert!(score.score(&params) < params.graylist_threshold);
+        assert!(score.score(&params) > params.ban_threshold);
+    }
+
+    #[test]
+    fn duplicate_messages_are_penalised() {
+        let params = params();
+        let mut score = PeerScore::default();
+
+        score.duplicate_message_delivered(&params, &GossipKind::BeaconBlock);
+        assert!((score.score(&params) + DUPLICATE_MESSAGE_PENALTY).abs() < 1e-9);
+
+        // replaying many messages is penalised quadratically
+        for _ in 0..100 {
+            score.duplicate_message_delivered(&params, &GossipKind::BeaconBlock);
+        }
+        assert!(score.score(&params) < params.gossip_threshold);
+
+        let mut score = PeerScore::default();
+        score.duplicate_message_delivered(&params, &GossipKind::BeaconBlock);
+        let decays = (DUPLICATE_MESSAGE_DELIVERIES_DECAY_EPOCHS as u64 + 1) * E::slots_per_epoch();
+        for _ in 0..decays {
+            score.decay(&params);
+        }
+        assert!(score.is_empty());
+    }
+
+    #[test]
+    fn counters_decay_to_zero() {
+        let params = params();
+        let mut score = PeerScore::default();
+        score.first_message_delivered(&params, &GossipKind::BeaconBlock);
+        score.invalid_message_delivered(&params, &GossipKind::VoluntaryExit);
+
+        let slots_per_epoch = E::slots_per_epoch();
+        for _ in 0..slots_per_epoch {
+            score.decay(&params);
+        }
+        // first deliveries have decayed, invalid messages have not
+        assert!(score.score(&params) < 0.0);
+
+        for _ in 0..((INVALID_MESSAGE_DELIVERIES_DECAY_EPOCHS as u64 + 1) * slots_per_epoch) {
+            score.decay(&params);
+        }
+        assert!(score.is_empty());
+        assert_eq!(score.score(&params), 0.0);
+    }
+}
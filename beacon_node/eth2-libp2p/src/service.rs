@@ -2,9 +2,10 @@ use crate::behaviour::{Behaviour, BehaviourEvent};
 use crate::discovery::enr;
 use crate::multiaddr::Protocol;
 use crate::types::{error, GossipKind};
-use crate::{NetworkConfig, NetworkGlobals};
+use crate::{Enr, NetworkConfig, NetworkGlobals};
 use futures::prelude::*;
 use futures::Stream;
+use hashmap_delay::HashSetDelay;
 use libp2p::core::{
     identity::Keypair,
     multiaddr::Multiaddr,
@@ -16,6 +17,7 @@ use libp2p::core::{
 };
 use libp2p::{core, noise, secio, swarm::NetworkBehaviour, PeerId, Swarm, Transport};
 use slog::{crit, debug, error, info, trace, warn};
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::{Error, ErrorKind};
@@ -31,6 +33,9 @@ pub const NETWORK_KEY_FILENAME: &str = "key";
 /// The time in milliseconds to wait before banning a peer. This allows for any Goodbye messages to be
 /// flushed and protocols to be negotiated.
 const BAN_PEER_WAIT_TIMEOUT: u64 = 200;
+/// The time in seconds that a disconnected peer is prevented from reconnecting. Peers cannot be
+/// disconnected without being banned, so a short ban is used.
+const DISCONNECT_PEER_TIMEOUT: u64 = 30;
 
 /// The configuration and state of the libp2p components for the beacon node.
 pub struct Service<TSpec: EthSpec> {
@@ -45,7 +50,10 @@ pub struct Service<TSpec: EthSpec> {
     peers_to_ban: DelayQueue<PeerId>,
 
     /// A list of timeouts after which peers become unbanned.
-    peer_ban_timeout: DelayQueue<PeerId>,
+    peer_ban_timeout: HashSetDelay<PeerId>,
+
+    /// Peers banned with `ban_peer`, which remain banned until `unban_peer` is called.
+    banned_peers: HashSet<PeerId>,
 
     /// The libp2p logger handle.
     pub log: slog::Logger,
@@ -59,6 +67,8 @@ impl<TSpec: EthSpec> Service<TSpec> {
     ) -> error::Result<(Arc<NetworkGlobals<TSpec>>, Self)> {
         trace!(log, "Libp2p Service starting");
 
+        config.validate()?;
+
         // initialise the node's ID
         let local_keypair = if let Some(hex_bytes) = &config.secret_key_hex {
             keypair_from_hex(hex_bytes)?
@@ -80,7 +90,7 @@ impl<TSpec: EthSpec> Service<TSpec> {
         ));
 
         info!(log, "Libp2p Service"; "peer_id" => format!("{:?}", enr.peer_id()));
-        debug!(log, "Attempting to open listening ports"; "address" => format!("{}", config.listen_address), "tcp_port" => config.libp2p_port, "udp_port" => config.discovery_port);
+        debug!(log, "Attempting to open listening ports"; "address" => format!("{}", config.listen_address), "tcp_port" => config.libp2p_port, "udp_port" => config.discovery_port, "address_v6" => format!("{:?}", config.listen_address_v6), "tcp_port_v6" => config.libp2p_port_v6);
 
         let mut swarm = {
             // Set up the transport - tcp/ws with noise/secio and mplex/yamux
@@ -90,29 +100,25 @@ impl<TSpec: EthSpec> Service<TSpec> {
             Swarm::new(transport, behaviour, local_peer_id.clone())
         };
 
-        // listen on the specified address
-        let listen_multiaddr = {
-            let mut m = Multiaddr::from(config.listen_address);
-            m.push(Protocol::Tcp(config.libp2p_port));
-            m
-        };
-
-        match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
-            Ok(_) => {
-                let mut log_address = listen_multiaddr;
-                log_address.push(Protocol::P2p(local_peer_id.clone().into()));
-                info!(log, "Listening established"; "address" => format!("{}", log_address));
-            }
-            Err(err) => {
-                crit!(
-                    log,
-                    "Unable to listen on libp2p address";
-                    "error" => format!("{:?}", err),
-                    "listen_multiaddr" => format!("{}", listen_multiaddr),
-                );
-                return Err("Libp2p was unable to listen on the given listen address.".into());
-            }
-        };
+        // listen on the specified addresses
+        for listen_multiaddr in config.listen_multiaddrs() {
+            match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
+                Ok(_) => {
+                    let mut log_address = listen_multiaddr;
+                    log_address.push(Protocol::P2p(local_peer_id.clone().into()));
+                    info!(log, "Listening established"; "address" => format!("{}", log_address));
+                }
+                Err(err) => {
+                    crit!(
+                        log,
+                        "Unable to listen on libp2p address";
+                        "error" => format!("{:?}", err),
+                        "listen_multiaddr" => format!("{}", listen_multiaddr),
+                    );
+                    return Err("Libp2p was unable to listen on the given listen address.".into());
+                }
+            };
+        }
 
         // helper closure for dialing peers
         let mut dial_addr = |multiaddr: &Multiaddr| {
@@ -161,21 +167,105 @@ impl<TSpec: EthSpec> Service<TSpec> {
             local_peer_id,
             swarm,
             peers_to_ban: DelayQueue::new(),
-            peer_ban_timeout: DelayQueue::new(),
+            peer_ban_timeout: HashSetDelay::default(),
+            banned_peers: HashSet::new(),
             log,
         };
 
         Ok((network_globals, service))
     }
 
-    /// Adds a peer to be banned for a period of time, specified by a timeout.
+    /// Adds a peer to be banned for a period of time, specified by a timeout. Trusted peers are
+    /// never banned and peers banned with `ban_peer` remain banned indefinitely.
     pub fn disconnect_and_ban_peer(&mut self, peer_id: PeerId, timeout: Duration) {
+        if self.swarm.is_trusted_peer(&peer_id) {
+            warn!(self.log, "Not banning trusted peer"; "peer_id" => format!("{:?}", peer_id));
+            return;
+        }
+        if self.banned_peers.contains(&peer_id) {
+            debug!(self.log, "Peer is already banned"; "peer_id" => format!("{:?}", peer_id));
+            return;
+        }
+
         error!(self.log, "Disconnecting and banning peer"; "peer_id" => format!("{:?}", peer_id), "timeout" => format!("{:?}", timeout));
         self.peers_to_ban.insert(
             peer_id.clone(),
             Duration::from_millis(BAN_PEER_WAIT_TIMEOUT),
         );
-        self.peer_ban_timeout.insert(peer_id, timeout);
+        self.peer_ban_timeout.insert_at(peer_id, timeout);
+    }
+
+    /// Disconnects a peer which is no longer required, such as when we are over the peer limit.
+    /// The peer may reconnect after a short timeout. Trusted peers are never disconnected, and
+    /// peers which are already banned keep their existing ban.
+    pub fn disconnect_peer(&mut self, peer_id: PeerId) {
+        if self.swarm.is_trusted_peer(&peer_id) {
+            warn!(self.log, "Not disconnecting trusted peer"; "peer_id" => format!("{:?}", peer_id));
+            return;
+        }
+        if self.banned_peers.contains(&peer_id) || self.peer_ban_timeout.contains(&peer_id) {
+            debug!(self.log, "Peer is already banned"; "peer_id" => format!("{:?}", peer_id));
+            return;
+        }
+
+        debug!(self.log, "Disconnecting peer"; "peer_id" => format!("{:?}", peer_id));
+        self.peers_to_ban.insert(
+            peer_id.clone(),
+            Duration::from_millis(BAN_PEER_WAIT_TIMEOUT),
+        );
+        self.peer_ban_timeout
+            .insert_at(peer_id, Duration::from_secs(DISCONNECT_PEER_TIMEOUT));
+    }
+
+    /// Disconnects and bans a peer until it is unbanned with `unban_peer`. Trusted peers are never
+    /// banned.
+    pub fn ban_peer(&mut self, peer_id: PeerId) {
+        if self.swarm.is_trusted_peer(&peer_id) {
+            warn!(self.log, "Not banning trusted peer"; "peer_id" => format!("{:?}", peer_id));
+            return;
+        }
+
+        info!(self.log, "Disconnecting and banning peer"; "peer_id" => format!("{:?}", peer_id));
+        // the ban is indefinite, so remove any pending timeout which would lift it
+        self.peer_ban_timeout.remove(&peer_id);
+        self.banned_peers.insert(peer_id.clone());
+        self.peers_to_ban
+            .insert(peer_id, Duration::from_millis(BAN_PEER_WAIT_TIMEOUT));
+    }
+
+    /// Allows a banned peer to reconnect.
+    pub fn unban_peer(&mut self, peer_id: PeerId) {
+        debug!(self.log, "Peer has been unbanned"; "peer" => format!("{:?}", peer_id));
+        self.peer_ban_timeout.remove(&peer_id);
+        self.banned_peers.remove(&peer_id);
+        self.swarm.peer_unbanned(&peer_id);
+        Swarm::unban_peer_id(&mut self.swarm, peer_id);
+    }
+
+    /// Dials a libp2p multiaddr.
+    pub fn dial(&mut self, multiaddr: Multiaddr) {
+        match Swarm::dial_addr(&mut self.swarm, multiaddr.clone()) {
+            Ok(()) => {
+                debug!(self.log, "Dialing libp2p peer"; "address" => format!("{}", multiaddr))
+            }
+            Err(err) => debug!(
+                self.log,
+                "Could not connect to peer"; "address" => format!("{}", multiaddr), "error" => format!("{:?}", err)
+            ),
+        };
+    }
+
+    /// Adds the ENR to the DHT and dials its TCP multiaddr.
+    pub fn dial_enr(&mut self, enr: Enr) {
+        self.swarm.add_enr(enr.clone());
+        for multiaddr in enr.multiaddr() {
+            // ignore udp multiaddr if it exists
+            let components = multiaddr.iter().collect::<Vec<_>>();
+            if let Protocol::Udp(_) = components[1] {
+                continue;
+            }
+            self.dial(multiaddr);
+        }
     }
 }
 
@@ -224,7 +314,6 @@ impl<TSpec: EthSpec> Stream for Service<TSpec> {
         loop {
             match self.peer_ban_timeout.poll() {
                 Ok(Async::Ready(Some(peer_id))) => {
-                    let peer_id = peer_id.into_inner();
                     debug!(self.log, "Peer has been unbanned"; "peer" => format!("{:?}", peer_id));
                     self.swarm.peer_unbanned(&peer_id);
                     Swarm::unban_peer_id(&mut self.swarm, peer_id);
@@ -1,7 +1,7 @@
 //! A collection of variables that are accessible outside of the network thread itself.
 use crate::peer_manager::PeerDB;
 use crate::rpc::methods::MetaData;
-use crate::types::SyncState;
+use crate::types::{PortMapping, SyncState};
 use crate::{discovery::enr::Eth2Enr, Enr, GossipTopic, Multiaddr, PeerId};
 use parking_lot::RwLock;
 use std::collections::HashSet;
@@ -27,6 +27,8 @@ pub struct NetworkGlobals<TSpec: EthSpec> {
     pub gossipsub_subscriptions: RwLock<HashSet<GossipTopic>>,
     /// The current sync status of the node.
     pub sync_state: RwLock<SyncState>,
+    /// The ports mapped on the NAT gateway, if any.
+    pub port_mapping: RwLock<Option<PortMapping>>,
 }
 
 impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
@@ -49,6 +51,7 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
             peers: RwLock::new(PeerDB::new(log)),
             gossipsub_subscriptions: RwLock::new(HashSet::new()),
             sync_state: RwLock::new(SyncState::Stalled),
+            port_mapping: RwLock::new(None),
         }
     }
 
@@ -88,6 +91,31 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
         self.peers.read().connected_or_dialing_peers().count()
     }
 
+    /// Returns the number of connected peers which count towards the peer limit, i.e. those which
+    /// are not trusted.
+    pub fn connected_untrusted_peers(&self) -> usize {
+        let peers = self.peers.read();
+        peers
+            .connected_peer_ids()
+            .filter(|peer_id| !peers.is_trusted(peer_id))
+            .count()
+    }
+
+    /// Returns the number of connected or dialing peers which count towards the peer limit, i.e.
+    /// those which are not trusted.
+    pub fn connected_or_dialing_untrusted_peers(&self) -> usize {
+        let peers = self.peers.read();
+        peers
+            .connected_or_dialing_peers()
+            .filter(|peer_id| !peers.is_trusted(peer_id))
+            .count()
+    }
+
+    /// Returns the ports mapped on the NAT gateway, if any.
+    pub fn port_mapping(&self) -> Option<PortMapping> {
+        self.port_mapping.read().clone()
+    }
+
     /// Returns in the node is syncing.
     pub fn is_syncing(&self) -> bool {
         self.sync_state.read().is_syncing()
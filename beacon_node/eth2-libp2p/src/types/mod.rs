@@ -1,5 +1,6 @@
 pub mod error;
 mod globals;
+mod port_mapping;
 mod pubsub;
 mod sync_state;
 mod topics;
@@ -12,6 +13,7 @@ pub type EnrBitfield<T: EthSpec> = BitVector<T::SubnetBitfieldLength>;
 pub type Enr = libp2p::discv5::enr::Enr<libp2p::discv5::enr::CombinedKey>;
 
 pub use globals::NetworkGlobals;
-pub use pubsub::PubsubMessage;
+pub use port_mapping::{PortMapping, PortMappingMethod};
+pub use pubsub::{MessageAcceptance, PubsubMessage};
 pub use sync_state::SyncState;
 pub use topics::{GossipEncoding, GossipKind, GossipTopic};
@@ -0,0 +1,21 @@
+use serde_derive::Serialize;
+use std::net::Ipv4Addr;
+
+/// The protocol with which ports were mapped on the NAT gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum PortMappingMethod {
+    Upnp,
+    NatPmp,
+}
+
+/// The external address at which the libp2p and discovery ports of the node are mapped on the NAT
+/// gateway.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PortMapping {
+    pub method: PortMappingMethod,
+    pub external_ip: Ipv4Addr,
+    /// The external TCP port which forwards to the libp2p port.
+    pub tcp_port: u16,
+    /// The external UDP port which forwards to the discovery port.
+    pub udp_port: u16,
+}
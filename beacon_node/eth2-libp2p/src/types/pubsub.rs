@@ -12,6 +12,18 @@ use types::{
     SignedBeaconBlock, VoluntaryExit,
 };
 
+/// The outcome of validating a gossipsub message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageAcceptance {
+    /// The message is valid. It is propagated and the peer that delivered it is rewarded.
+    Accept,
+    /// The message is not propagated, without penalising the peer that delivered it. The message
+    /// may be processed again if it is received later.
+    Ignore,
+    /// The message is invalid. It is not propagated and the peers that deliver it are penalised.
+    Reject,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum PubsubMessage<T: EthSpec> {
     /// Gossipsub message providing notification of a new block.
@@ -75,7 +75,7 @@ impl Eth1GenesisService {
                 .deposits()
                 .read()
                 .cache
-                .get(min_genesis_active_validator_count.saturating_sub(1))
+                .get(min_genesis_active_validator_count.saturating_sub(1) as u64)
                 .map(|log| log.block_number)
         }
     }
@@ -0,0 +1,862 @@
+//! Provides the `BeaconProcessor`, which receives work originating from the network (gossip
+//! blocks and attestations, RPC block batches and `Status` messages) and processes it on a fixed
+//! pool of long-lived worker threads.
+//!
+//! Incoming work is sorted into a queue per type of work. Whenever a worker is free, the manager
+//! takes an item from the highest priority queue which is not empty, in the following order:
+//!
+//! 1. Gossip blocks, which are verified and propagated as soon as possible to reduce block
+//!    propagation latency.
+//! 2. RPC block batches from range sync and parent lookups.
+//! 3. Gossip aggregated attestations.
+//! 4. `Status` messages, which are cheap to process and drive syncing.
+//! 5. Attestations which referenced an unknown block, re-processed once the sync manager has
+//!    imported the block.
+//! 6. Gossip unaggregated attestations.
+//!
+//! Each queue except the queue of RPC block batches is bounded. The attestation queues process the
+//! newest attestation first and drop the oldest attestation when full, since old attestations are
+//! the least valuable to forward and to fork choice. All other queues are processed in order and
+//! drop new work when full.
+//!
+//! Work from the sync manager arrives on its own unbounded channel, so that block batches and
+//! parent lookups are never dropped when gossip fills the bounded channel of network work. Sync
+//! only has a batch per chain and per parent lookup in flight, and stalls if one is dropped.
+//!
+//! When more than one attestation is waiting in an attestation queue, a worker is given a batch
+//! of attestations so that their signatures can be verified together.
+
+use crate::metrics;
+use crate::service::NetworkMessage;
+use crate::sync::block_processor::ProcessId;
+use crate::sync::SyncMessage;
+use beacon_chain::{AttestationType, BeaconChain, BeaconChainTypes};
+use eth2_libp2p::rpc::methods::StatusMessage;
+use eth2_libp2p::{MessageId, PeerId};
+use futures::prelude::*;
+use slog::{crit, debug, error, info};
+use std::cmp;
+use std::collections::VecDeque;
+use std::sync::mpsc as std_mpsc;
+use std::sync::Weak;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use types::{Attestation, EthSpec, SignedAggregateAndProof, SignedBeaconBlock};
+use worker::Worker;
+
+mod worker;
+
+/// The maximum number of work events that can be waiting to be sorted into a queue by the
+/// manager.
+pub const MAX_WORK_EVENT_QUEUE_LEN: usize = 16_384;
+
+/// The maximum number of queued gossip blocks.
+const MAX_GOSSIP_BLOCK_QUEUE_LEN: usize = 1_024;
+
+/// The maximum number of queued RPC block batches. Sync bounds the number of batches in flight
+/// and stalls if one is dropped, so this queue is effectively unbounded.
+const MAX_CHAIN_SEGMENT_QUEUE_LEN: usize = usize::max_value();
+
+/// The maximum number of queued aggregated attestations.
+const MAX_AGGREGATED_ATTESTATION_QUEUE_LEN: usize = 1_024;
+
+/// The maximum number of queued `Status` messages.
+const MAX_STATUS_QUEUE_LEN: usize = 1_024;
+
+/// The maximum number of queued unaggregated attestations.
+const MAX_UNAGGREGATED_ATTESTATION_QUEUE_LEN: usize = 16_384;
+
+/// The maximum number of queued attestations which are waiting to be re-processed.
+const MAX_REPROCESS_ATTESTATION_QUEUE_LEN: usize = 16_384;
+
+/// The maximum number of attestations given to a worker as a single batch.
+const MAX_GOSSIP_ATTESTATION_BATCH_SIZE: usize = 64;
+
+/// The name of the threads which process work.
+const WORKER_THREAD_NAME: &str = "beacon_processor";
+
+/// Queue names, used as the label of the beacon processor metrics.
+const GOSSIP_BLOCK_QUEUE: &str = "gossip_block";
+const CHAIN_SEGMENT_QUEUE: &str = "chain_segment";
+const GOSSIP_AGGREGATE_QUEUE: &str = "gossip_aggregate";
+const STATUS_QUEUE: &str = "status";
+const GOSSIP_ATTESTATION_QUEUE: &str = "gossip_attestation";
+const REPROCESS_ATTESTATION_QUEUE: &str = "reprocess_attestation";
+
+/// Labels for batches of work, which are processed by a worker after being taken from a queue.
+const GOSSIP_AGGREGATE_BATCH: &str = "gossip_aggregate_batch";
+const GOSSIP_ATTESTATION_BATCH: &str = "gossip_attestation_batch";
+
+/// An item of work to be processed by the `BeaconProcessor`.
+#[derive(Debug)]
+pub struct WorkEvent<E: EthSpec> {
+    work: Work<E>,
+    /// The time the work was created, used to measure how long it waited to be processed.
+    created: Instant,
+}
+
+impl<E: EthSpec> WorkEvent<E> {
+    /// A block received over gossip, to be verified, propagated and imported.
+    pub fn gossip_beacon_block(
+        message_id: MessageId,
+        peer_id: PeerId,
+        block: Box<SignedBeaconBlock<E>>,
+    ) -> Self {
+        Self::new(Work::GossipBlock {
+            message_id,
+            peer_id,
+            block,
+        })
+    }
+
+    /// A batch of blocks downloaded by range sync or a parent lookup, to be imported.
+    pub fn chain_segment(process_id: ProcessId, blocks: Vec<SignedBeaconBlock<E>>) -> Self {
+        Self::new(Work::ChainSegment { process_id, blocks })
+    }
+
+    /// An aggregated attestation received over gossip.
+    pub fn gossip_aggregate(
+        message_id: MessageId,
+        peer_id: PeerId,
+        aggregate: Box<SignedAggregateAndProof<E>>,
+    ) -> Self {
+        Self::new(Work::GossipAggregate {
+            message_id,
+            peer_id,
+            aggregate,
+        })
+    }
+
+    /// A `Status` message received from a peer, either as a request or a response.
+    pub fn status_message(peer_id: PeerId, status: StatusMessage) -> Self {
+        Self::new(Work::Status { peer_id, status })
+    }
+
+    /// An unaggregated attestation received over gossip.
+    pub fn gossip_attestation(
+        message_id: MessageId,
+        peer_id: PeerId,
+        attestation: Box<Attestation<E>>,
+    ) -> Self {
+        Self::new(Work::GossipAttestation {
+            message_id,
+            peer_id,
+            attestation,
+        })
+    }
+
+    /// An attestation which referenced an unknown block, to be processed again once the block has
+    /// been imported. Its gossip validation result has already been reported.
+    fn reprocess_attestation(
+        peer_id: PeerId,
+        attestation: Box<Attestation<E>>,
+        attestation_type: AttestationType,
+    ) -> Self {
+        Self::new(Work::ReprocessAttestation {
+            peer_id,
+            attestation,
+            attestation_type,
+        })
+    }
+
+    fn new(work: Work<E>) -> Self {
+        Self {
+            work,
+            created: Instant::now(),
+        }
+    }
+
+    /// The name of the queue this work is placed in.
+    pub fn queue_name(&self) -> &'static str {
+        self.work.queue_name()
+    }
+}
+
+/// An attestation received over gossip, awaiting processing as part of a batch.
+#[derive(Debug)]
+struct GossipAttestationPackage<E: EthSpec> {
+    message_id: MessageId,
+    peer_id: PeerId,
+    attestation: Attestation<E>,
+}
+
+/// The types of work processed by the `BeaconProcessor`.
+#[derive(Debug)]
+enum Work<E: EthSpec> {
+    GossipBlock {
+        message_id: MessageId,
+        peer_id: PeerId,
+        block: Box<SignedBeaconBlock<E>>,
+    },
+    ChainSegment {
+        process_id: ProcessId,
+        blocks: Vec<SignedBeaconBlock<E>>,
+    },
+    GossipAggregate {
+        message_id: MessageId,
+        peer_id: PeerId,
+        aggregate: Box<SignedAggregateAndProof<E>>,
+    },
+    Status {
+        peer_id: PeerId,
+        status: StatusMessage,
+    },
+    GossipAttestation {
+        message_id: MessageId,
+        peer_id: PeerId,
+        attestation: Box<Attestation<E>>,
+    },
+    ReprocessAttestation {
+        peer_id: PeerId,
+        attestation: Box<Attestation<E>>,
+        attestation_type: AttestationType,
+    },
+    GossipAggregateBatch {
+        packages: Vec<GossipAttestationPackage<E>>,
+    },
+    GossipAttestationBatch {
+        packages: Vec<GossipAttestationPackage<E>>,
+    },
+}
+
+impl<E: EthSpec> Work<E> {
+    /// The name of the queue this work is placed in, or the label of a batch of queued work.
+    fn queue_name(&self) -> &'static str {
+        match self {
+            Work::GossipBlock { .. } => GOSSIP_BLOCK_QUEUE,
+            Work::ChainSegment { .. } => CHAIN_SEGMENT_QUEUE,
+            Work::GossipAggregate { .. } => GOSSIP_AGGREGATE_QUEUE,
+            Work::Status { .. } => STATUS_QUEUE,
+            Work::GossipAttestation { .. } => GOSSIP_ATTESTATION_QUEUE,
+            Work::ReprocessAttestation { .. } => REPROCESS_ATTESTATION_QUEUE,
+            Work::GossipAggregateBatch { .. } => GOSSIP_AGGREGATE_BATCH,
+            Work::GossipAttestationBatch { .. } => GOSSIP_ATTESTATION_BATCH,
+        }
+    }
+
+    /// Returns the attestation carried by a single gossip aggregate or attestation, ready to be
+    /// processed in a batch.
+    fn into_attestation_package(self) -> Option<GossipAttestationPackage<E>> {
+        match self {
+            Work::GossipAggregate {
+                message_id,
+                peer_id,
+                aggregate,
+            } => Some(GossipAttestationPackage {
+                message_id,
+                peer_id,
+                attestation: aggregate.message.aggregate,
+            }),
+            Work::GossipAttestation {
+                message_id,
+                peer_id,
+                attestation,
+            } => Some(GossipAttestationPackage {
+                message_id,
+                peer_id,
+                attestation: *attestation,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A queue which processes work in the order it was received and drops new work when full.
+struct FifoQueue<T> {
+    queue: VecDeque<T>,
+    max_length: usize,
+}
+
+impl<T> FifoQueue<T> {
+    fn new(max_length: usize) -> Self {
+        Self {
+            queue: VecDeque::default(),
+            max_length,
+        }
+    }
+
+    /// Adds `item` to the back of the queue, returning it if the queue is full.
+    fn push(&mut self, item: T) -> Option<T> {
+        if self.queue.len() >= self.max_length {
+            Some(item)
+        } else {
+            self.queue.push_back(item);
+            None
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.queue.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+/// A queue which processes the newest work first and drops the oldest work when full.
+struct LifoQueue<T> {
+    queue: VecDeque<T>,
+    max_length: usize,
+}
+
+impl<T> LifoQueue<T> {
+    fn new(max_length: usize) -> Self {
+        Self {
+            queue: VecDeque::default(),
+            max_length,
+        }
+    }
+
+    /// Adds `item` to the front of the queue, returning the oldest item if the queue is full.
+    fn push(&mut self, item: T) -> Option<T> {
+        let dropped = if self.queue.len() >= self.max_length {
+            self.queue.pop_back()
+        } else {
+            None
+        };
+        self.queue.push_front(item);
+        dropped
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.queue.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+/// The prioritised queues of work waiting for a free worker.
+struct WorkQueues<E: EthSpec> {
+    gossip_block_queue: FifoQueue<WorkEvent<E>>,
+    chain_segment_queue: FifoQueue<WorkEvent<E>>,
+    aggregate_queue: LifoQueue<WorkEvent<E>>,
+    status_queue: FifoQueue<WorkEvent<E>>,
+    attestation_queue: LifoQueue<WorkEvent<E>>,
+    reprocess_attestation_queue: LifoQueue<WorkEvent<E>>,
+}
+
+impl<E: EthSpec> WorkQueues<E> {
+    fn new() -> Self {
+        Self {
+            gossip_block_queue: FifoQueue::new(MAX_GOSSIP_BLOCK_QUEUE_LEN),
+            chain_segment_queue: FifoQueue::new(MAX_CHAIN_SEGMENT_QUEUE_LEN),
+            aggregate_queue: LifoQueue::new(MAX_AGGREGATED_ATTESTATION_QUEUE_LEN),
+            status_queue: FifoQueue::new(MAX_STATUS_QUEUE_LEN),
+            attestation_queue: LifoQueue::new(MAX_UNAGGREGATED_ATTESTATION_QUEUE_LEN),
+            reprocess_attestation_queue: LifoQueue::new(MAX_REPROCESS_ATTESTATION_QUEUE_LEN),
+        }
+    }
+
+    /// Places `work_event` in its queue, returning the work which was dropped if the queue is
+    /// full. Batches of work are never queued and are returned as dropped.
+    fn push(&mut self, work_event: WorkEvent<E>) -> Option<WorkEvent<E>> {
+        match work_event.work {
+            Work::GossipBlock { .. } => self.gossip_block_queue.push(work_event),
+            Work::ChainSegment { .. } => self.chain_segment_queue.push(work_event),
+            Work::GossipAggregate { .. } => self.aggregate_queue.push(work_event),
+            Work::Status { .. } => self.status_queue.push(work_event),
+            Work::GossipAttestation { .. } => self.attestation_queue.push(work_event),
+            Work::ReprocessAttestation { .. } => self.reprocess_attestation_queue.push(work_event),
+            // batches are only created once work leaves a queue
+            Work::GossipAggregateBatch { .. } | Work::GossipAttestationBatch { .. } => {
+                Some(work_event)
+            }
+        }
+    }
+
+    /// Returns the next work in priority order, if any. When more than one attestation is queued,
+    /// a batch of attestations is returned instead of a single attestation.
+    fn next_work(&mut self) -> Option<Work<E>> {
+        if let Some(work_event) = self
+            .gossip_block_queue
+            .pop()
+            .or_else(|| self.chain_segment_queue.pop())
+        {
+            Some(dequeue(work_event))
+        } else if self.aggregate_queue.len() > 1 {
+            Some(Work::GossipAggregateBatch {
+                packages: pop_attestation_batch(&mut self.aggregate_queue),
+            })
+        } else if let Some(work_event) = self
+            .aggregate_queue
+            .pop()
+            .or_else(|| self.status_queue.pop())
+            .or_else(|| self.reprocess_attestation_queue.pop())
+        {
+            Some(dequeue(work_event))
+        } else if self.attestation_queue.len() > 1 {
+            Some(Work::GossipAttestationBatch {
+                packages: pop_attestation_batch(&mut self.attestation_queue),
+            })
+        } else {
+            self.attestation_queue.pop().map(dequeue)
+        }
+    }
+
+    fn update_metrics(&self) {
+        for (queue_name, len) in &[
+            (GOSSIP_BLOCK_QUEUE, self.gossip_block_queue.len()),
+            (CHAIN_SEGMENT_QUEUE, self.chain_segment_queue.len()),
+            (GOSSIP_AGGREGATE_QUEUE, self.aggregate_queue.len()),
+            (STATUS_QUEUE, self.status_queue.len()),
+            (GOSSIP_ATTESTATION_QUEUE, self.attestation_queue.len()),
+            (
+                REPROCESS_ATTESTATION_QUEUE,
+                self.reprocess_attestation_queue.len(),
+            ),
+        ] {
+            metrics::set_gauge_vec(
+                &metrics::BEACON_PROCESSOR_QUEUE_LENGTH,
+                &[*queue_name],
+                *len as i64,
+            );
+        }
+    }
+}
+
+/// Sorts `WorkEvent`s into prioritised queues and dispatches them to a fixed pool of long-lived
+/// worker threads.
+pub struct BeaconProcessor<T: BeaconChainTypes> {
+    /// A weak reference to the beacon chain, allowing it to be dropped during shutdown.
+    beacon_chain: Weak<BeaconChain<T>>,
+    /// A channel to the network service, to report gossip validation results and disconnect
+    /// peers.
+    network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+    /// A channel to the sync manager, to report processing results.
+    sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    /// Receives new work from the network.
+    work_recv: mpsc::Receiver<WorkEvent<T::EthSpec>>,
+    /// Receives new work from the sync manager.
+    sync_work_recv: mpsc::UnboundedReceiver<WorkEvent<T::EthSpec>>,
+    /// Given to each worker, which sends its id each time its work is complete.
+    idle_send: mpsc::UnboundedSender<usize>,
+    /// Receives the id of a worker each time it completes its work.
+    idle_recv: mpsc::UnboundedReceiver<usize>,
+    queues: WorkQueues<T::EthSpec>,
+    /// The channels on which each worker receives work, indexed by worker id. Dropping a channel
+    /// stops its worker.
+    workers: Vec<std_mpsc::Sender<Work<T::EthSpec>>>,
+    /// The ids of the workers which are waiting for work.
+    idle_workers: Vec<usize>,
+    /// The `BeaconProcessor` logger.
+    log: slog::Logger,
+}
+
+/// Spawns the `BeaconProcessor` manager and its workers, which process the work sent on
+/// `work_recv` and `sync_work_recv`. The manager shuts down once all senders of network work are
+/// dropped.
+pub fn spawn<T: BeaconChainTypes>(
+    executor: &tokio::runtime::TaskExecutor,
+    beacon_chain: Weak<BeaconChain<T>>,
+    network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+    sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    work_recv: mpsc::Receiver<WorkEvent<T::EthSpec>>,
+    sync_work_recv: mpsc::UnboundedReceiver<WorkEvent<T::EthSpec>>,
+    log: slog::Logger,
+) {
+    let (idle_send, idle_recv) = mpsc::unbounded_channel();
+
+    let mut beacon_processor = BeaconProcessor {
+        beacon_chain,
+        network_send,
+        sync_send,
+        work_recv,
+        sync_work_recv,
+        idle_send,
+        idle_recv,
+        queues: WorkQueues::new(),
+        workers: Vec::new(),
+        idle_workers: Vec::new(),
+        log: log.clone(),
+    };
+
+    for _ in 0..num_cpus::get() {
+        let id = beacon_processor.workers.len();
+        match beacon_processor.spawn_worker(id) {
+            Ok(work_send) => {
+                beacon_processor.workers.push(work_send);
+                beacon_processor.idle_workers.push(id);
+            }
+            Err(e) => error!(
+                log,
+                "Failed to spawn beacon processor worker";
+                "error" => e,
+            ),
+        }
+    }
+    if beacon_processor.workers.is_empty() {
+        crit!(log, "Beacon processor has no workers");
+    }
+
+    debug!(log, "Beacon processor started"; "workers" => beacon_processor.workers.len());
+    executor.spawn(beacon_processor.then(move |result| {
+        match result {
+            Ok(()) => info!(log, "Beacon processor shutdown"),
+            Err(e) => crit!(log, "Beacon processor failed"; "error" => e),
+        }
+        Ok(())
+    }));
+}
+
+impl<T: BeaconChainTypes> BeaconProcessor<T> {
+    /// Places `work_event` in its queue, dropping work if the queue is full.
+    fn queue_work(&mut self, work_event: WorkEvent<T::EthSpec>) {
+        let queue_name = work_event.queue_name();
+        metrics::inc_counter_vec(
+            &metrics::BEACON_PROCESSOR_WORK_EVENTS_RX_COUNT,
+            &[queue_name],
+        );
+
+        if let Some(dropped) = self.queues.push(work_event) {
+            metrics::inc_counter_vec(
+                &metrics::BEACON_PROCESSOR_WORK_EVENTS_DROPPED_COUNT,
+                &[queue_name],
+            );
+            debug!(
+                self.log,
+                "Beacon processor queue full, dropping work";
+                "queue" => queue_name,
+                "age_ms" => dropped.created.elapsed().as_millis() as u64,
+            );
+        }
+    }
+
+    /// Spawns a worker thread with the given `id`, returning the channel on which it receives
+    /// work. The worker upgrades its reference to the beacon chain for each item of work, and
+    /// stops once the channel is dropped.
+    fn spawn_worker(&self, id: usize) -> Result<std_mpsc::Sender<Work<T::EthSpec>>, String> {
+        let (work_send, work_recv) = std_mpsc::channel::<Work<T::EthSpec>>();
+        let beacon_chain = self.beacon_chain.clone();
+        let network_send = self.network_send.clone();
+        let sync_send = self.sync_send.clone();
+        let mut idle_send = self.idle_send.clone();
+        let log = self.log.clone();
+
+        std::thread::Builder::new()
+            .name(format!("{}_{}", WORKER_THREAD_NAME, id))
+            .spawn(move || {
+                for work in work_recv {
+                    let queue_name = work.queue_name();
+                    match beacon_chain.upgrade() {
+                        Some(chain) => {
+                            let start = Instant::now();
+                            let worker = Worker::new(
+                                chain,
+                                network_send.clone(),
+                                sync_send.clone(),
+                                log.clone(),
+                            );
+                            worker.process(work);
+                            metrics::observe_vec(
+                                &metrics::BEACON_PROCESSOR_WORKER_TIME,
+                                &[queue_name],
+                                start.elapsed().as_secs_f64(),
+                            );
+                        }
+                        None => debug!(
+                            log,
+                            "Beacon chain dropped, discarding work";
+                            "queue" => queue_name,
+                        ),
+                    }
+                    // the manager has shut down if this fails
+                    if idle_send.try_send(id).is_err() {
+                        break;
+                    }
+                }
+            })
+            .map(|_| work_send)
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    /// Sends `work` to the idle worker with the given `id`.
+    fn dispatch(&mut self, id: usize, work: Work<T::EthSpec>) {
+        if let Err(std_mpsc::SendError(work)) = self.workers[id].send(work) {
+            // the worker thread has panicked, its work is lost
+            crit!(
+                self.log,
+                "Beacon processor worker stopped";
+                "worker" => id,
+                "queue" => work.queue_name(),
+            );
+        }
+    }
+
+    fn update_metrics(&self) {
+        self.queues.update_metrics();
+        metrics::set_gauge(
+            &metrics::BEACON_PROCESSOR_WORKERS_ACTIVE_TOTAL,
+            (self.workers.len() - self.idle_workers.len()) as i64,
+        );
+    }
+}
+
+impl<T: BeaconChainTypes> Future for BeaconProcessor<T> {
+    type Item = ();
+    type Error = String;
+
+    fn poll(&mut self) -> Result<Async<Self::Item>, Self::Error> {
+        // return the workers which have completed their work to the idle pool
+        loop {
+            match self.idle_recv.poll() {
+                Ok(Async::Ready(Some(id))) => self.idle_workers.push(id),
+                Ok(Async::NotReady) => break,
+                // the manager holds a sender, so the channel never closes
+                Ok(Async::Ready(None)) | Err(_) => {
+                    return Err("Worker idle channel closed".into());
+                }
+            }
+        }
+
+        // sort all new work into the queues
+        loop {
+            match self.sync_work_recv.poll() {
+                Ok(Async::Ready(Some(work_event))) => self.queue_work(work_event),
+                // the sync manager shuts down alongside the network
+                Ok(Async::NotReady) | Ok(Async::Ready(None)) => break,
+                Err(e) => {
+                    return Err(format!("Sync work channel failed: {:?}", e));
+                }
+            }
+        }
+        loop {
+            match self.work_recv.poll() {
+                Ok(Async::Ready(Some(work_event))) => self.queue_work(work_event),
+                Ok(Async::NotReady) => break,
+                Ok(Async::Ready(None)) => {
+                    // all senders have been dropped, the node is shutting down
+                    return Ok(Async::Ready(()));
+                }
+                Err(e) => {
+                    return Err(format!("Work channel failed: {:?}", e));
+                }
+            }
+        }
+
+        // hand the highest priority work to the idle workers
+        while let Some(id) = self.idle_workers.pop() {
+            match self.queues.next_work() {
+                Some(work) => self.dispatch(id, work),
+                None => {
+                    self.idle_workers.push(id);
+                    break;
+                }
+            }
+        }
+
+        self.update_metrics();
+
+        Ok(Async::NotReady)
+    }
+}
+
+/// Takes the work from `work_event` once it leaves its queue, recording how long it was queued.
+fn dequeue<E: EthSpec>(work_event: WorkEvent<E>) -> Work<E> {
+    metrics::observe_vec(
+        &metrics::BEACON_PROCESSOR_QUEUE_TIME,
+        &[work_event.queue_name()],
+        work_event.created.elapsed().as_secs_f64(),
+    );
+    work_event.work
+}
+
+/// Takes up to `MAX_GOSSIP_ATTESTATION_BATCH_SIZE` of the newest attestations from `queue`.
+fn pop_attestation_batch<E: EthSpec>(
+    queue: &mut LifoQueue<WorkEvent<E>>,
+) -> Vec<GossipAttestationPackage<E>> {
+    let mut packages = Vec::with_capacity(cmp::min(queue.len(), MAX_GOSSIP_ATTESTATION_BATCH_SIZE));
+    while packages.len() < MAX_GOSSIP_ATTESTATION_BATCH_SIZE {
+        match queue.pop() {
+            Some(work_event) => {
+                // the attestation queues only ever hold attestations
+                if let Some(package) = dequeue(work_event).into_attestation_package() {
+                    packages.push(package)
+                }
+            }
+            None => break,
+        }
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::test_utils::{SeedableRng, TestRandom, XorShiftRng};
+    use types::{BeaconBlock, Epoch, Hash256, MinimalEthSpec, Signature, Slot};
+
+    type E = MinimalEthSpec;
+
+    fn message_id() -> MessageId {
+        MessageId("message".into())
+    }
+
+    fn gossip_block() -> WorkEvent<E> {
+        let block = SignedBeaconBlock {
+            message: BeaconBlock::empty(&E::default_spec()),
+            signature: Signature::empty_signature(),
+        };
+        WorkEvent::gossip_beacon_block(message_id(), PeerId::random(), Box::new(block))
+    }
+
+    fn chain_segment() -> WorkEvent<E> {
+        let process_id = ProcessId::ParentLookup(PeerId::random(), Hash256::zero());
+        WorkEvent::chain_segment(process_id, vec![])
+    }
+
+    fn status() -> WorkEvent<E> {
+        let status = StatusMessage {
+            fork_digest: [0; 4],
+            finalized_root: Hash256::zero(),
+            finalized_epoch: Epoch::new(0),
+            head_root: Hash256::zero(),
+            head_slot: Slot::new(0),
+        };
+        WorkEvent::status_message(PeerId::random(), status)
+    }
+
+    fn aggregate(rng: &mut XorShiftRng) -> WorkEvent<E> {
+        let aggregate = SignedAggregateAndProof::random_for_test(rng);
+        WorkEvent::gossip_aggregate(message_id(), PeerId::random(), Box::new(aggregate))
+    }
+
+    fn attestation(rng: &mut XorShiftRng) -> WorkEvent<E> {
+        let attestation = Attestation::random_for_test(rng);
+        WorkEvent::gossip_attestation(message_id(), PeerId::random(), Box::new(attestation))
+    }
+
+    fn reprocess_attestation(rng: &mut XorShiftRng) -> WorkEvent<E> {
+        let attestation = Attestation::random_for_test(rng);
+        WorkEvent::reprocess_attestation(
+            PeerId::random(),
+            Box::new(attestation),
+            AttestationType::Aggregated,
+        )
+    }
+
+    /// Takes all work from the queues, returning the name of each item of work.
+    fn drain(queues: &mut WorkQueues<E>) -> Vec<&'static str> {
+        std::iter::from_fn(|| queues.next_work())
+            .map(|work| work.queue_name())
+            .collect()
+    }
+
+    #[test]
+    fn next_work_is_in_priority_order() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queues = WorkQueues::new();
+
+        // queue the work in reverse priority order
+        assert!(queues.push(attestation(&mut rng)).is_none());
+        assert!(queues.push(reprocess_attestation(&mut rng)).is_none());
+        assert!(queues.push(status()).is_none());
+        assert!(queues.push(aggregate(&mut rng)).is_none());
+        assert!(queues.push(chain_segment()).is_none());
+        assert!(queues.push(gossip_block()).is_none());
+
+        assert_eq!(
+            drain(&mut queues),
+            vec![
+                GOSSIP_BLOCK_QUEUE,
+                CHAIN_SEGMENT_QUEUE,
+                GOSSIP_AGGREGATE_QUEUE,
+                STATUS_QUEUE,
+                REPROCESS_ATTESTATION_QUEUE,
+                GOSSIP_ATTESTATION_QUEUE,
+            ]
+        );
+    }
+
+    #[test]
+    fn queued_attestations_are_batched() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queues = WorkQueues::new();
+
+        let mut newest = None;
+        for _ in 0..MAX_GOSSIP_ATTESTATION_BATCH_SIZE + 2 {
+            let work_event = attestation(&mut rng);
+            if let Work::GossipAttestation { attestation, .. } = &work_event.work {
+                newest = Some(attestation.as_ref().clone());
+            }
+            queues.push(work_event);
+        }
+        queues.push(aggregate(&mut rng));
+        queues.push(aggregate(&mut rng));
+
+        let batch_sizes = std::iter::from_fn(|| queues.next_work())
+            .map(|work| match work {
+                Work::GossipAggregateBatch { packages } => (GOSSIP_AGGREGATE_BATCH, packages),
+                Work::GossipAttestationBatch { packages } => (GOSSIP_ATTESTATION_BATCH, packages),
+                work => panic!("unexpected work {:?}", work),
+            })
+            .map(|(name, packages)| {
+                if name == GOSSIP_ATTESTATION_BATCH && packages.len() > 2 {
+                    // the newest attestations are batched first
+                    assert_eq!(Some(&packages[0].attestation), newest.as_ref());
+                }
+                (name, packages.len())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            batch_sizes,
+            vec![
+                (GOSSIP_AGGREGATE_BATCH, 2),
+                (GOSSIP_ATTESTATION_BATCH, MAX_GOSSIP_ATTESTATION_BATCH_SIZE),
+                (GOSSIP_ATTESTATION_BATCH, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_attestations_are_not_batched() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queues = WorkQueues::new();
+
+        queues.push(aggregate(&mut rng));
+        queues.push(attestation(&mut rng));
+
+        assert_eq!(
+            drain(&mut queues),
+            vec![GOSSIP_AGGREGATE_QUEUE, GOSSIP_ATTESTATION_QUEUE]
+        );
+    }
+
+    #[test]
+    fn chain_segments_are_never_dropped() {
+        let mut queues = WorkQueues::<E>::new();
+        for _ in 0..MAX_WORK_EVENT_QUEUE_LEN {
+            assert!(queues.push(chain_segment()).is_none());
+        }
+        assert_eq!(queues.chain_segment_queue.len(), MAX_WORK_EVENT_QUEUE_LEN);
+    }
+
+    #[test]
+    fn fifo_queue_drops_new_items_when_full() {
+        let mut queue = FifoQueue::new(2);
+        assert_eq!(queue.push(1), None);
+        assert_eq!(queue.push(2), None);
+        assert_eq!(queue.push(3), Some(3));
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn lifo_queue_drops_oldest_items_when_full() {
+        let mut queue = LifoQueue::new(2);
+        assert_eq!(queue.push(1), None);
+        assert_eq!(queue.push(2), None);
+        assert_eq!(queue.push(3), Some(1));
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), None);
+    }
+}
@@ -0,0 +1,512 @@
+use super::{GossipAttestationPackage, Work, WorkEvent};
+use crate::router::processor::{HandlerNetworkContext, FUTURE_SLOT_TOLERANCE};
+use crate::service::NetworkMessage;
+use crate::sync::block_processor::process_chain_segment;
+use crate::sync::{PeerSyncInfo, SyncMessage};
+use beacon_chain::{
+    AttestationProcessingOutcome, AttestationType, BeaconChain, BeaconChainError, BeaconChainTypes,
+    BlockError, BlockProcessingOutcome,
+};
+use eth2_libp2p::rpc::methods::{GoodbyeReason, StatusMessage};
+use eth2_libp2p::{MessageAcceptance, MessageId, PeerId};
+use slog::{debug, error, trace, warn};
+use ssz::Encode;
+use std::sync::Arc;
+use store::Store;
+use tokio::sync::mpsc;
+use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// Processes a single item of work from the `BeaconProcessor` on a worker thread.
+pub struct Worker<T: BeaconChainTypes> {
+    /// A reference to the underlying beacon chain.
+    chain: Arc<BeaconChain<T>>,
+    /// A network context to report gossip validation results and disconnect peers.
+    network: HandlerNetworkContext<T::EthSpec>,
+    /// A channel to the sync manager.
+    sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    /// The `BeaconProcessor` logger.
+    log: slog::Logger,
+}
+
+impl<T: BeaconChainTypes> Worker<T> {
+    pub fn new(
+        chain: Arc<BeaconChain<T>>,
+        network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+        sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+        log: slog::Logger,
+    ) -> Self {
+        Worker {
+            chain,
+            network: HandlerNetworkContext::new(network_send, log.clone()),
+            sync_send,
+            log,
+        }
+    }
+
+    /// Processes `work`, consuming the worker.
+    pub(super) fn process(mut self, work: Work<T::EthSpec>) {
+        match work {
+            Work::GossipBlock {
+                message_id,
+                peer_id,
+                block,
+            } => self.process_gossip_block(message_id, peer_id, block),
+            Work::ChainSegment { process_id, blocks } => process_chain_segment(
+                Arc::downgrade(&self.chain),
+                process_id,
+                blocks,
+                self.sync_send,
+                &self.log,
+            ),
+            Work::GossipAggregate {
+                message_id,
+                peer_id,
+                aggregate,
+            } => self.process_gossip_attestation(
+                message_id,
+                peer_id,
+                aggregate.message.aggregate,
+                AttestationType::Aggregated,
+            ),
+            Work::Status { peer_id, status } => self.process_status(peer_id, status),
+            Work::GossipAttestation {
+                message_id,
+                peer_id,
+                attestation,
+            } => self.process_gossip_attestation(
+                message_id,
+                peer_id,
+                *attestation,
+                AttestationType::Unaggregated { should_store: true },
+            ),
+            Work::ReprocessAttestation {
+                peer_id,
+                attestation,
+                attestation_type,
+            } => self.reprocess_attestation(peer_id, *attestation, attestation_type),
+            Work::GossipAggregateBatch { packages } => {
+                self.process_gossip_attestation_batch(packages, AttestationType::Aggregated)
+            }
+            Work::GossipAttestationBatch { packages } => self.process_gossip_attestation_batch(
+                packages,
+                AttestationType::Unaggregated { should_store: true },
+            ),
+        }
+    }
+
+    fn send_to_sync(&mut self, message: SyncMessage<T::EthSpec>) {
+        self.sync_send.try_send(message).unwrap_or_else(|_| {
+            warn!(
+                self.log,
+                "Could not send message to the sync service";
+            )
+        });
+    }
+
+    /// Verify a block received over gossip, report whether it should be propagated and import it
+    /// into the beacon chain.
+    fn process_gossip_block(
+        &mut self,
+        message_id: MessageId,
+        peer_id: PeerId,
+        block: Box<SignedBeaconBlock<T::EthSpec>>,
+    ) {
+        let verified_block = match self.chain.verify_block_for_gossip(*block.clone()) {
+            Ok(verified_block) => verified_block,
+            Err(BlockError::ParentUnknown(block_hash)) => {
+                // if we don't know the parent, start a parent lookup
+                // TODO: Modify the return to avoid the block clone.
+                debug!(self.log, "Unknown block received. Starting a parent lookup"; "block_slot" => block.message.slot, "block_hash" => format!("{}", block_hash));
+                self.send_to_sync(SyncMessage::UnknownBlock(peer_id.clone(), block));
+                return self.network.report_validation_result(
+                    message_id,
+                    peer_id,
+                    MessageAcceptance::Ignore,
+                );
+            }
+            Err(e @ BlockError::GenesisBlock)
+            | Err(e @ BlockError::ProposalSignatureInvalid)
+            | Err(e @ BlockError::InvalidSignature)
+            | Err(e @ BlockError::StateRootMismatch { .. })
+            | Err(e @ BlockError::BlockIsNotLaterThanParent { .. })
+            | Err(e @ BlockError::PerBlockProcessingError(_)) => {
+                warn!(self.log, "Received an invalid block over gossip";
+                    "peer_id" => format!("{}", peer_id),
+                    "error" => format!("{:?}", e));
+                return self.network.report_validation_result(
+                    message_id,
+                    peer_id,
+                    MessageAcceptance::Reject,
+                );
+            }
+            Err(e) => {
+                warn!(self.log, "Could not verify block for gossip";
+                    "error" => format!("{:?}", e));
+                return self.network.report_validation_result(
+                    message_id,
+                    peer_id,
+                    MessageAcceptance::Ignore,
+                );
+            }
+        };
+
+        // propagate the block before importing it, to reduce block propagation latency
+        self.network.report_validation_result(
+            message_id,
+            peer_id.clone(),
+            MessageAcceptance::Accept,
+        );
+
+        let block = Box::new(verified_block.block.clone());
+        match BlockProcessingOutcome::shim(self.chain.process_block(verified_block)) {
+            Ok(outcome) => match outcome {
+                BlockProcessingOutcome::Processed { block_root } => {
+                    trace!(self.log, "Gossipsub block processed";
+                            "peer_id" => format!("{:?}",peer_id));
+
+                    // release any attestations which were waiting for this block
+                    self.send_to_sync(SyncMessage::BlockImported(block_root));
+
+                    match self.chain.fork_choice() {
+                        Ok(()) => trace!(
+                            self.log,
+                            "Fork choice success";
+                            "location" => "block gossip"
+                        ),
+                        Err(e) => error!(
+                            self.log,
+                            "Fork choice failed";
+                            "error" => format!("{:?}", e),
+                            "location" => "block gossip"
+                        ),
+                    }
+                }
+                BlockProcessingOutcome::ParentUnknown { .. } => {
+                    // Inform the sync manager to find parents for this block
+                    // This should not occur. It is checked by `verify_block_for_gossip`
+                    error!(self.log, "Block with unknown parent attempted to be processed";
+                            "peer_id" => format!("{:?}",peer_id));
+                    self.send_to_sync(SyncMessage::UnknownBlock(peer_id, block));
+                }
+                other => {
+                    warn!(
+                        self.log,
+                        "Invalid gossip beacon block";
+                        "outcome" => format!("{:?}", other),
+                        "block root" => format!("{}", block.canonical_root()),
+                        "block slot" => block.slot()
+                    );
+                    trace!(
+                        self.log,
+                        "Invalid gossip beacon block ssz";
+                        "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
+                    );
+                }
+            },
+            Err(_) => {
+                // error is logged during the processing therefore no error is logged here
+                trace!(
+                    self.log,
+                    "Erroneous gossip beacon block ssz";
+                    "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
+                );
+            }
+        }
+    }
+
+    /// Process an attestation received over gossip and report whether it should be propagated.
+    fn process_gossip_attestation(
+        &mut self,
+        message_id: MessageId,
+        peer_id: PeerId,
+        attestation: Attestation<T::EthSpec>,
+        attestation_type: AttestationType,
+    ) {
+        let outcome = self
+            .chain
+            .process_attestation(attestation.clone(), attestation_type.clone());
+        let validation_result =
+            self.attestation_acceptance(peer_id.clone(), &attestation, &attestation_type, outcome);
+        self.network
+            .report_validation_result(message_id, peer_id, validation_result);
+    }
+
+    /// Process a batch of attestations received over gossip, verifying their signatures together,
+    /// and report whether each attestation should be propagated.
+    fn process_gossip_attestation_batch(
+        &mut self,
+        packages: Vec<GossipAttestationPackage<T::EthSpec>>,
+        attestation_type: AttestationType,
+    ) {
+        let attestations = packages
+            .iter()
+            .map(|package| (package.attestation.clone(), attestation_type.clone()))
+            .collect();
+
+        match self.chain.process_attestation_batch(attestations) {
+            Ok(outcomes) => {
+                for (package, outcome) in packages.into_iter().zip(outcomes) {
+                    let validation_result = self.attestation_acceptance(
+                        package.peer_id.clone(),
+                        &package.attestation,
+                        &attestation_type,
+                        outcome,
+                    );
+                    self.network.report_validation_result(
+                        package.message_id,
+                        package.peer_id,
+                        validation_result,
+                    );
+                }
+            }
+            Err(e) => {
+                error!(
+                    self.log,
+                    "Failed to process attestation batch";
+                    "batch_size" => packages.len(),
+                    "error" => format!("{:?}", e),
+                );
+                for package in packages {
+                    self.network.report_validation_result(
+                        package.message_id,
+                        package.peer_id,
+                        MessageAcceptance::Ignore,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Handle the outcome of processing an attestation received from gossipsub.
+    ///
+    /// Returns whether the attestation should be propagated, or whether the peer that delivered it
+    /// should be penalised.
+    fn attestation_acceptance(
+        &mut self,
+        peer_id: PeerId,
+        msg: &Attestation<T::EthSpec>,
+        attestation_type: &AttestationType,
+        outcome: Result<AttestationProcessingOutcome, BeaconChainError>,
+    ) -> MessageAcceptance {
+        match outcome {
+            Ok(outcome) => match outcome {
+                AttestationProcessingOutcome::Processed => {
+                    debug!(
+                        self.log,
+                        "Processed attestation";
+                        "source" => "gossip",
+                        "peer" => format!("{:?}",peer_id),
+                        "block_root" => format!("{}", msg.data.beacon_block_root),
+                        "slot" => format!("{}", msg.data.slot),
+                    );
+                    MessageAcceptance::Accept
+                }
+                AttestationProcessingOutcome::UnknownHeadBlock { beacon_block_root }
+                | AttestationProcessingOutcome::UnknownTargetRoot(beacon_block_root) => {
+                    debug!(
+                    self.log,
+                    "Attestation for unknown block";
+                    "peer_id" => format!("{:?}", peer_id),
+                    "block" => format!("{}", beacon_block_root)
+                    );
+                    // we don't know the attested or target block, get the sync manager to handle
+                    // the block lookup
+                    // and to re-process the attestation once the block is imported
+                    let dependent_work = WorkEvent::reprocess_attestation(
+                        peer_id.clone(),
+                        Box::new(msg.clone()),
+                        attestation_type.clone(),
+                    );
+                    self.send_to_sync(SyncMessage::UnknownBlockHash {
+                        peer_id,
+                        block_hash: beacon_block_root,
+                        dependent_work: Box::new(dependent_work),
+                    });
+                    MessageAcceptance::Ignore
+                }
+                AttestationProcessingOutcome::FutureEpoch { .. }
+                | AttestationProcessingOutcome::PastEpoch { .. }
+                | AttestationProcessingOutcome::FinalizedSlot { .. } => MessageAcceptance::Ignore,
+                AttestationProcessingOutcome::Invalid { .. }
+                | AttestationProcessingOutcome::EmptyAggregationBitfield { .. }
+                | AttestationProcessingOutcome::AttestsToFutureBlock { .. }
+                | AttestationProcessingOutcome::InvalidSignature
+                | AttestationProcessingOutcome::NoCommitteeForSlotAndIndex { .. }
+                | AttestationProcessingOutcome::BadTargetEpoch { .. } => {
+                    // the peer has sent a bad attestation, it is penalised by gossipsub scoring
+                    debug!(
+                        self.log,
+                        "Invalid gossip attestation";
+                        "peer_id" => format!("{:?}", peer_id),
+                        "outcome" => format!("{:?}", outcome),
+                    );
+                    MessageAcceptance::Reject
+                }
+            },
+            Err(_) => {
+                // error is logged during the processing therefore no error is logged here
+                trace!(
+                    self.log,
+                    "Erroneous gossip attestation ssz";
+                    "ssz" => format!("0x{}", hex::encode(msg.as_ssz_bytes())),
+                );
+                MessageAcceptance::Ignore
+            }
+        }
+    }
+
+    /// Process an attestation which referenced a block that has since been imported.
+    ///
+    /// The gossip validation result of the attestation has already been reported, so the outcome
+    /// is only logged. A block which is still unknown does not start another lookup.
+    fn reprocess_attestation(
+        &mut self,
+        peer_id: PeerId,
+        attestation: Attestation<T::EthSpec>,
+        attestation_type: AttestationType,
+    ) {
+        let block_root = attestation.data.beacon_block_root;
+        match self
+            .chain
+            .process_attestation(attestation, attestation_type)
+        {
+            Ok(AttestationProcessingOutcome::Processed) => debug!(
+                self.log,
+                "Processed attestation";
+                "source" => "block lookup",
+                "peer" => format!("{:?}", peer_id),
+                "block_root" => format!("{}", block_root),
+            ),
+            Ok(outcome) => debug!(
+                self.log,
+                "Dropping re-processed attestation";
+                "peer" => format!("{:?}", peer_id),
+                "block_root" => format!("{}", block_root),
+                "outcome" => format!("{:?}", outcome),
+            ),
+            Err(e) => debug!(
+                self.log,
+                "Failed to re-process attestation";
+                "block_root" => format!("{}", block_root),
+                "error" => format!("{:?}", e),
+            ),
+        }
+    }
+
+    /// Process a `Status` message, requesting new blocks if appropriate.
+    ///
+    /// Disconnects the peer if required.
+    fn process_status(&mut self, peer_id: PeerId, status: StatusMessage) {
+        let remote = PeerSyncInfo::from(status);
+        let local = match PeerSyncInfo::from_chain(&self.chain) {
+            Some(local) => local,
+            None => {
+                return error!(
+                    self.log,
+                    "Failed to get peer sync info";
+                    "msg" => "likely due to head lock contention"
+                )
+            }
+        };
+
+        let start_slot = |epoch: Epoch| epoch.start_slot(T::EthSpec::slots_per_epoch());
+
+        if local.fork_digest != remote.fork_digest {
+            // The node is on a different network/fork, disconnect them.
+            debug!(
+                self.log, "Handshake Failure";
+                "peer" => format!("{:?}", peer_id),
+                "reason" => "incompatible forks",
+                "our_fork" => hex::encode(local.fork_digest),
+                "their_fork" => hex::encode(remote.fork_digest)
+            );
+
+            self.network
+                .disconnect(peer_id, GoodbyeReason::IrrelevantNetwork);
+        } else if remote.head_slot
+            > self.chain.slot().unwrap_or_else(|_| Slot::from(0u64)) + FUTURE_SLOT_TOLERANCE
+        {
+            // Note: If the slot_clock cannot be read, this will not error. Other system
+            // components will deal with an invalid slot clock error.
+
+            // The remotes head is on a slot that is significantly ahead of ours. This could be
+            // because they are using a different genesis time, or that theirs or our system
+            // clock is incorrect.
+            debug!(
+            self.log, "Handshake Failure";
+            "peer" => format!("{:?}", peer_id),
+            "reason" => "different system clocks or genesis time"
+            );
+            self.network
+                .disconnect(peer_id, GoodbyeReason::IrrelevantNetwork);
+        } else if remote.finalized_epoch <= local.finalized_epoch
+            && remote.finalized_root != Hash256::zero()
+            && local.finalized_root != Hash256::zero()
+            && self
+                .chain
+                .root_at_slot(start_slot(remote.finalized_epoch))
+                .map(|root_opt| root_opt != Some(remote.finalized_root))
+                .unwrap_or_else(|_| false)
+        {
+            // The remotes finalized epoch is less than or greater than ours, but the block root is
+            // different to the one in our chain.
+            //
+            // Therefore, the node is on a different chain and we should not communicate with them.
+            debug!(
+                self.log, "Handshake Failure";
+                "peer" => format!("{:?}", peer_id),
+                "reason" => "different finalized chain"
+            );
+            self.network
+                .disconnect(peer_id, GoodbyeReason::IrrelevantNetwork);
+        } else if remote.finalized_epoch < local.finalized_epoch {
+            // The node has a lower finalized epoch, their chain is not useful to us. There are two
+            // cases where a node can have a lower finalized epoch:
+            //
+            // ## The node is on the same chain
+            //
+            // If a node is on the same chain but has a lower finalized epoch, their head must be
+            // lower than ours. Therefore, we have nothing to request from them.
+            //
+            // ## The node is on a fork
+            //
+            // If a node is on a fork that has a lower finalized epoch, switching to that fork would
+            // cause us to revert a finalized block. This is not permitted, therefore we have no
+            // interest in their blocks.
+            debug!(
+                self.log,
+                "NaivePeer";
+                "peer" => format!("{:?}", peer_id),
+                "reason" => "lower finalized epoch"
+            );
+        } else if self
+            .chain
+            .store
+            .exists::<SignedBeaconBlock<T::EthSpec>>(&remote.head_root)
+            .unwrap_or_else(|_| false)
+        {
+            debug!(
+                self.log, "Peer with known chain found";
+                "peer" => format!("{:?}", peer_id),
+                "remote_head_slot" => remote.head_slot,
+                "remote_latest_finalized_epoch" => remote.finalized_epoch,
+            );
+
+            // If the node's best-block is already known to us and they are close to our current
+            // head, treat them as a fully sync'd peer.
+            self.send_to_sync(SyncMessage::AddPeer(peer_id, remote));
+        } else {
+            // The remote node has an equal or great finalized epoch and we don't know it's head.
+            //
+            // Therefore, there are some blocks between the local finalized epoch and the remote
+            // head that are worth downloading.
+            debug!(
+                self.log, "UsefulPeer";
+                "peer" => format!("{:?}", peer_id),
+                "local_finalized_epoch" => local.finalized_epoch,
+                "remote_latest_finalized_epoch" => remote.finalized_epoch,
+            );
+            self.send_to_sync(SyncMessage::AddPeer(peer_id, remote));
+        }
+    }
+}
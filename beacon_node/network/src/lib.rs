@@ -1,8 +1,14 @@
 /// This crate provides the network server for Lighthouse.
+#[macro_use]
+extern crate lazy_static;
+
 pub mod error;
 pub mod service;
 
 mod attestation_service;
+mod beacon_processor;
+mod metrics;
+mod nat;
 mod persisted_dht;
 mod router;
 mod sync;
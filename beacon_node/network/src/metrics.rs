@@ -0,0 +1,39 @@
+pub use lighthouse_metrics::*;
+
+lazy_static! {
+    /*
+     * Beacon processor
+     */
+    pub static ref BEACON_PROCESSOR_WORK_EVENTS_RX_COUNT: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_processor_work_events_rx_count",
+            "Count of work events received by the beacon processor, by queue",
+            &["queue"]
+        );
+    pub static ref BEACON_PROCESSOR_WORK_EVENTS_DROPPED_COUNT: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_processor_work_events_dropped_count",
+            "Count of work events dropped by the beacon processor because a queue was full or \
+             the manager was overloaded, by queue",
+            &["queue"]
+        );
+    pub static ref BEACON_PROCESSOR_QUEUE_LENGTH: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "beacon_processor_queue_length",
+        "Count of work events waiting in each beacon processor queue",
+        &["queue"]
+    );
+    pub static ref BEACON_PROCESSOR_QUEUE_TIME: Result<HistogramVec> = try_create_histogram_vec(
+        "beacon_processor_queue_time_seconds",
+        "Time a work event spent queued before being given to a worker, by queue",
+        &["queue"]
+    );
+    pub static ref BEACON_PROCESSOR_WORKER_TIME: Result<HistogramVec> = try_create_histogram_vec(
+        "beacon_processor_worker_time_seconds",
+        "Time taken by a worker to process a work event, by queue",
+        &["queue"]
+    );
+    pub static ref BEACON_PROCESSOR_WORKERS_ACTIVE_TOTAL: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_workers_active_total",
+        "Count of beacon processor workers currently processing a work event"
+    );
+}
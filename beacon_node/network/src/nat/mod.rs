@@ -0,0 +1,214 @@
+//! Maps the libp2p TCP port and the discovery UDP port on the NAT gateway, so that peers outside
+//! the local network can reach this node.
+//!
+//! UPnP is attempted first, falling back to NAT-PMP. Mappings are leased and renewed by a
+//! dedicated thread, which reports the external address to the network service so that it can be
+//! advertised in our ENR. The mappings are removed when the network service shuts down.
+mod natpmp;
+mod upnp;
+
+use crate::{NetworkConfig, NetworkMessage};
+use eth2_libp2p::types::{PortMapping, PortMappingMethod};
+use igd::PortMappingProtocol;
+use natpmp::{Client as NatPmpClient, Protocol, NAT_PMP_PORT};
+use slog::{debug, info, warn};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::mpsc::{self as std_mpsc, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use types::EthSpec;
+use upnp::Upnp;
+
+/// The lease duration of port mappings in seconds. Mappings are renewed after half this time.
+const LEASE_DURATION: u32 = 3600;
+
+/// The ports to map on the NAT gateway.
+#[derive(Debug, Clone)]
+pub struct NatConfig {
+    /// The libp2p TCP port.
+    pub tcp_port: u16,
+    /// The discovery UDP port.
+    pub udp_port: u16,
+    /// The NAT-PMP gateway, if not the default gateway.
+    pub nat_pmp_gateway: Option<Ipv4Addr>,
+}
+
+impl From<&NetworkConfig> for NatConfig {
+    fn from(config: &NetworkConfig) -> Self {
+        Self {
+            tcp_port: config.libp2p_port,
+            udp_port: config.discovery_port,
+            nat_pmp_gateway: config.nat_pmp_gateway,
+        }
+    }
+}
+
+/// A gateway on which our ports are mapped.
+enum Gateway {
+    Upnp(Upnp),
+    NatPmp(NatPmpClient),
+}
+
+impl Gateway {
+    /// Finds a gateway which supports UPnP or NAT-PMP.
+    fn find(config: &NatConfig, log: &slog::Logger) -> Result<Self, String> {
+        match Upnp::search() {
+            Ok(upnp) => return Ok(Gateway::Upnp(upnp)),
+            Err(e) => debug!(log, "UPnP unavailable"; "error" => e),
+        }
+
+        let gateway = config
+            .nat_pmp_gateway
+            .or_else(natpmp::default_gateway)
+            .ok_or_else(|| "No UPnP or NAT-PMP gateway found".to_string())?;
+        let client = NatPmpClient::new(SocketAddr::new(gateway.into(), NAT_PMP_PORT))?;
+        // a gateway which answers this request supports NAT-PMP
+        client.external_address()?;
+
+        Ok(Gateway::NatPmp(client))
+    }
+
+    /// Maps or renews the mapping of our ports, returning the external address.
+    fn map_ports(&self, config: &NatConfig) -> Result<PortMapping, String> {
+        match self {
+            Gateway::Upnp(upnp) => {
+                upnp.map_port(PortMappingProtocol::TCP, config.tcp_port, LEASE_DURATION)?;
+                upnp.map_port(PortMappingProtocol::UDP, config.udp_port, LEASE_DURATION)?;
+                Ok(PortMapping {
+                    method: PortMappingMethod::Upnp,
+                    external_ip: upnp.external_ip()?,
+                    tcp_port: config.tcp_port,
+                    udp_port: config.udp_port,
+                })
+            }
+            Gateway::NatPmp(client) => {
+                // the gateway may assign other external ports than those requested
+                let tcp = client.map_port(
+                    Protocol::Tcp,
+                    config.tcp_port,
+                    config.tcp_port,
+                    LEASE_DURATION,
+                )?;
+                let udp = client.map_port(
+                    Protocol::Udp,
+                    config.udp_port,
+                    config.udp_port,
+                    LEASE_DURATION,
+                )?;
+                Ok(PortMapping {
+                    method: PortMappingMethod::NatPmp,
+                    external_ip: client.external_address()?,
+                    tcp_port: tcp.external_port,
+                    udp_port: udp.external_port,
+                })
+            }
+        }
+    }
+
+    /// Removes the mappings of our ports.
+    fn unmap_ports(&self, config: &NatConfig) -> Result<(), String> {
+        match self {
+            Gateway::Upnp(upnp) => {
+                upnp.unmap_port(PortMappingProtocol::TCP, config.tcp_port)?;
+                upnp.unmap_port(PortMappingProtocol::UDP, config.udp_port)
+            }
+            Gateway::NatPmp(client) => {
+                client.unmap_port(Protocol::Tcp, config.tcp_port)?;
+                client.unmap_port(Protocol::Udp, config.udp_port)
+            }
+        }
+    }
+}
+
+/// A handle to the port mapping thread, which removes the mappings when it is shut down (or
+/// dropped).
+pub struct PortMappingHandle {
+    shutdown: std_mpsc::Sender<()>,
+}
+
+impl PortMappingHandle {
+    /// Stops renewing the mappings and signals the thread to remove them from the gateway.
+    ///
+    /// The thread is not joined: searching the gateway and removing the mappings can block for
+    /// several seconds, which must not stall the network service's task.
+    pub fn shutdown(self) {
+        // the thread may have already exited if no gateway was found
+        let _ = self.shutdown.send(());
+    }
+}
+
+/// Spawns a thread which maps our ports on the NAT gateway and renews the mappings until it is
+/// shut down with the returned handle. Each mapping is sent to the network service as a
+/// `NetworkMessage::PortMapped`.
+///
+/// The gateway protocols are blocking, hence a thread rather than a task.
+pub fn spawn_port_mapping<T: EthSpec>(
+    config: NatConfig,
+    mut network_send: mpsc::UnboundedSender<NetworkMessage<T>>,
+    log: slog::Logger,
+) -> Result<PortMappingHandle, String> {
+    let (shutdown, shutdown_recv) = std_mpsc::channel();
+
+    thread::Builder::new()
+        .name("nat".to_string())
+        .spawn(move || {
+            let gateway = match Gateway::find(&config, &log) {
+                Ok(gateway) => gateway,
+                Err(e) => {
+                    warn!(log, "Could not map ports on the NAT gateway"; "error" => e);
+                    return;
+                }
+            };
+
+            // the network service may have shut down while the gateway was being searched for
+            match shutdown_recv.try_recv() {
+                Err(TryRecvError::Empty) => {}
+                Ok(()) | Err(TryRecvError::Disconnected) => return,
+            }
+
+            let renewal_interval = Duration::from_secs(u64::from(LEASE_DURATION / 2));
+            let mut mapped = false;
+            loop {
+                match gateway.map_ports(&config) {
+                    Ok(mapping) => {
+                        if !mapped {
+                            info!(log, "Mapped ports on the NAT gateway";
+                                "method" => format!("{:?}", mapping.method),
+                                "external_ip" => format!("{}", mapping.external_ip),
+                                "tcp_port" => mapping.tcp_port,
+                                "udp_port" => mapping.udp_port,
+                            );
+                            mapped = true;
+                        }
+                        if network_send
+                            .try_send(NetworkMessage::PortMapped { mapping })
+                            .is_err()
+                        {
+                            // the network service has shut down
+                            break;
+                        }
+                    }
+                    Err(e) if !mapped => {
+                        warn!(log, "Could not map ports on the NAT gateway"; "error" => e);
+                        return;
+                    }
+                    // the previous mapping may still be valid, try again at the next renewal
+                    Err(e) => warn!(log, "Could not renew NAT port mappings"; "error" => e),
+                }
+
+                match shutdown_recv.recv_timeout(renewal_interval) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    // the network service is shutting down
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+
+            match gateway.unmap_ports(&config) {
+                Ok(()) => debug!(log, "Removed NAT port mappings"),
+                Err(e) => debug!(log, "Could not remove NAT port mappings"; "error" => e),
+            }
+        })
+        .map(|_| PortMappingHandle { shutdown })
+        .map_err(|e| format!("Unable to spawn NAT thread: {:?}", e))
+}
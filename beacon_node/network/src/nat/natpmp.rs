@@ -0,0 +1,260 @@
+//! A minimal NAT-PMP client, as specified in RFC 6886.
+use std::fs;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::Duration;
+
+/// The port on which NAT-PMP gateways listen.
+pub const NAT_PMP_PORT: u16 = 5351;
+
+const VERSION: u8 = 0;
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+/// Responses have the opcode of their request plus this offset.
+const RESPONSE_OFFSET: u8 = 128;
+/// The number of times a request is sent before giving up.
+const MAX_ATTEMPTS: u32 = 4;
+/// The time to wait for the first response, which is doubled for each further attempt.
+const INITIAL_TIMEOUT_MILLIS: u64 = 250;
+
+/// The protocol of a port mapping.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Udp = 1,
+    Tcp = 2,
+}
+
+/// A port mapping which was created on the gateway.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mapping {
+    pub internal_port: u16,
+    /// The external port, which may differ from the requested port.
+    pub external_port: u16,
+    /// The lifetime of the mapping in seconds, which may differ from the requested lifetime.
+    pub lifetime: u32,
+}
+
+/// A NAT-PMP client for a single gateway.
+pub struct Client {
+    socket: UdpSocket,
+}
+
+impl Client {
+    /// Creates a client for the gateway at `gateway`, which is usually port `NAT_PMP_PORT` of the
+    /// default gateway.
+    pub fn new(gateway: SocketAddr) -> Result<Self, String> {
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .map_err(|e| format!("Unable to bind NAT-PMP socket: {:?}", e))?;
+        socket
+            .connect(gateway)
+            .map_err(|e| format!("Unable to connect to NAT-PMP gateway {}: {:?}", gateway, e))?;
+
+        Ok(Self { socket })
+    }
+
+    /// Requests the external IP address of the gateway.
+    pub fn external_address(&self) -> Result<Ipv4Addr, String> {
+        let response = self.request(&[VERSION, OP_EXTERNAL_ADDRESS], 12)?;
+
+        Ok(Ipv4Addr::new(
+            response[8],
+            response[9],
+            response[10],
+            response[11],
+        ))
+    }
+
+    /// Requests a mapping of `external_port` to `internal_port` on this host, for `lifetime`
+    /// seconds. A lifetime of zero removes the mapping.
+    pub fn map_port(
+        &self,
+        protocol: Protocol,
+        internal_port: u16,
+        external_port: u16,
+        lifetime: u32,
+    ) -> Result<Mapping, String> {
+        let mut request = vec![VERSION, protocol as u8, 0, 0];
+        request.extend_from_slice(&internal_port.to_be_bytes());
+        request.extend_from_slice(&external_port.to_be_bytes());
+        request.extend_from_slice(&lifetime.to_be_bytes());
+
+        let response = self.request(&request, 16)?;
+
+        Ok(Mapping {
+            internal_port: u16::from_be_bytes([response[8], response[9]]),
+            external_port: u16::from_be_bytes([response[10], response[11]]),
+            lifetime: u32::from_be_bytes([response[12], response[13], response[14], response[15]]),
+        })
+    }
+
+    /// Removes the mapping of `internal_port`.
+    pub fn unmap_port(&self, protocol: Protocol, internal_port: u16) -> Result<(), String> {
+        self.map_port(protocol, internal_port, 0, 0).map(|_| ())
+    }
+
+    /// Sends `request`, retrying with an increasing timeout, and returns the successful response,
+    /// which is `response_len` bytes long.
+    fn request(&self, request: &[u8], response_len: usize) -> Result<Vec<u8>, String> {
+        let opcode = request[1];
+        let mut timeout = Duration::from_millis(INITIAL_TIMEOUT_MILLIS);
+        let mut buf = [0; 16];
+
+        for _ in 0..MAX_ATTEMPTS {
+            self.socket
+                .send(request)
+                .map_err(|e| format!("Unable to send NAT-PMP request: {:?}", e))?;
+            self.socket
+                .set_read_timeout(Some(timeout))
+                .map_err(|e| format!("Unable to set NAT-PMP timeout: {:?}", e))?;
+
+            match self.socket.recv(&mut buf) {
+                Ok(len) => {
+                    // ignore responses to other requests
+                    if len < 4 || buf[1] != opcode + RESPONSE_OFFSET {
+                        continue;
+                    }
+
+                    let result_code = u16::from_be_bytes([buf[2], buf[3]]);
+                    if result_code != 0 {
+                        return Err(format!(
+                            "NAT-PMP gateway refused request: {}",
+                            result_code_description(result_code)
+                        ));
+                    }
+
+                    if len < response_len {
+                        return Err(format!("NAT-PMP response is too short: {} bytes", len));
+                    }
+
+                    return Ok(buf[..response_len].to_vec());
+                }
+                Err(_) => timeout *= 2,
+            }
+        }
+
+        Err("No response from NAT-PMP gateway".to_string())
+    }
+}
+
+fn result_code_description(result_code: u16) -> String {
+    match result_code {
+        1 => "unsupported version".to_string(),
+        2 => "not authorized".to_string(),
+        3 => "network failure".to_string(),
+        4 => "out of resources".to_string(),
+        5 => "unsupported opcode".to_string(),
+        other => format!("unknown result code {}", other),
+    }
+}
+
+/// Returns the IPv4 default gateway from the kernel routing table, which is only available on
+/// Linux.
+pub fn default_gateway() -> Option<Ipv4Addr> {
+    fs::read_to_string("/proc/net/route")
+        .ok()
+        .and_then(|route_table| parse_default_gateway(&route_table))
+}
+
+/// Parses the gateway of the default route from the contents of `/proc/net/route`, in which
+/// addresses are little-endian hex.
+fn parse_default_gateway(route_table: &str) -> Option<Ipv4Addr> {
+    route_table.lines().skip(1).find_map(|line| {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        match (fields.get(1), fields.get(2)) {
+            (Some(&"00000000"), Some(gateway)) => u32::from_str_radix(gateway, 16)
+                .ok()
+                .map(|gateway| Ipv4Addr::from(gateway.to_le_bytes())),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const EXTERNAL_IP: [u8; 4] = [203, 0, 113, 7];
+
+    /// Spawns a NAT-PMP gateway on localhost which answers `requests` requests, refusing mappings
+    /// of port 1 and offsetting the external port of all other mappings by 1000.
+    fn mock_gateway(requests: usize) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("should bind mock gateway");
+        let addr = socket.local_addr().expect("should have local addr");
+
+        thread::spawn(move || {
+            let mut buf = [0; 12];
+            for _ in 0..requests {
+                let (len, client) = socket.recv_from(&mut buf).expect("should receive");
+                let opcode = buf[1];
+                let mut response = vec![VERSION, opcode + RESPONSE_OFFSET, 0, 0, 0, 0, 0, 42];
+
+                if opcode == OP_EXTERNAL_ADDRESS {
+                    response.extend_from_slice(&EXTERNAL_IP);
+                } else {
+                    assert_eq!(len, 12);
+                    let internal_port = u16::from_be_bytes([buf[4], buf[5]]);
+                    let external_port = u16::from_be_bytes([buf[6], buf[7]]);
+                    if internal_port == 1 {
+                        response[3] = 2;
+                    }
+                    response.extend_from_slice(&buf[4..6]);
+                    response.extend_from_slice(&(external_port + 1000).to_be_bytes());
+                    response.extend_from_slice(&buf[8..12]);
+                }
+
+                socket.send_to(&response, client).expect("should respond");
+            }
+        });
+
+        addr
+    }
+
+    #[test]
+    fn external_address() {
+        let client = Client::new(mock_gateway(1)).expect("should create client");
+
+        assert_eq!(client.external_address(), Ok(Ipv4Addr::from(EXTERNAL_IP)));
+    }
+
+    #[test]
+    fn map_port() {
+        let client = Client::new(mock_gateway(2)).expect("should create client");
+
+        assert_eq!(
+            client.map_port(Protocol::Tcp, 9000, 9000, 3600),
+            Ok(Mapping {
+                internal_port: 9000,
+                external_port: 10000,
+                lifetime: 3600,
+            })
+        );
+        assert!(client
+            .map_port(Protocol::Udp, 1, 1, 3600)
+            .unwrap_err()
+            .contains("not authorized"));
+    }
+
+    #[test]
+    fn no_gateway() {
+        // nothing answers on this port once the socket is dropped
+        let addr = UdpSocket::bind("127.0.0.1:0")
+            .and_then(|socket| socket.local_addr())
+            .expect("should bind");
+        let client = Client::new(addr).expect("should create client");
+
+        assert!(client.external_address().is_err());
+    }
+
+    #[test]
+    fn parse_route_table() {
+        let route_table =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                           eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+                           eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+
+        assert_eq!(
+            parse_default_gateway(route_table),
+            Some(Ipv4Addr::new(192, 168, 1, 1))
+        );
+        assert_eq!(parse_default_gateway("Iface\tDestination\tGateway\n"), None);
+    }
+}
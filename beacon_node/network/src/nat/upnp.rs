@@ -0,0 +1,247 @@
+//! Port mapping with a UPnP Internet Gateway Device.
+use igd::{Gateway, PortMappingProtocol, SearchOptions};
+use std::net::{IpAddr, Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::time::Duration;
+
+/// The time to wait for a gateway to answer the SSDP search.
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The description of our port mappings, as displayed by the gateway.
+const DESCRIPTION: &str = "lighthouse";
+
+/// A UPnP gateway on which ports are mapped to this host.
+pub struct Upnp {
+    gateway: Gateway,
+    local_ip: Ipv4Addr,
+}
+
+impl Upnp {
+    /// Searches the local network for an Internet Gateway Device.
+    pub fn search() -> Result<Self, String> {
+        let gateway = igd::search_gateway(SearchOptions {
+            timeout: Some(SEARCH_TIMEOUT),
+            ..Default::default()
+        })
+        .map_err(|e| format!("No UPnP gateway found: {}", e))?;
+        let local_ip = local_ip_towards(gateway.addr)?;
+
+        Ok(Self { gateway, local_ip })
+    }
+
+    /// Returns the external IP address of the gateway.
+    pub fn external_ip(&self) -> Result<Ipv4Addr, String> {
+        self.gateway
+            .get_external_ip()
+            .map_err(|e| format!("Unable to get external IP from UPnP gateway: {}", e))
+    }
+
+    /// Maps the same external port to `port` on this host for `lease_duration` seconds.
+    pub fn map_port(
+        &self,
+        protocol: PortMappingProtocol,
+        port: u16,
+        lease_duration: u32,
+    ) -> Result<(), String> {
+        self.gateway
+            .add_port(
+                protocol,
+                port,
+                SocketAddrV4::new(self.local_ip, port),
+                lease_duration,
+                DESCRIPTION,
+            )
+            .map_err(|e| format!("Unable to map {} port {}: {}", protocol, port, e))
+    }
+
+    /// Removes the mapping of `port`.
+    pub fn unmap_port(&self, protocol: PortMappingProtocol, port: u16) -> Result<(), String> {
+        self.gateway
+            .remove_port(protocol, port)
+            .map_err(|e| format!("Unable to unmap {} port {}: {}", protocol, port, e))
+    }
+}
+
+/// Returns the address of the local interface which routes to `addr`.
+///
+/// Connecting a UDP socket sends no packets, it only selects the route.
+fn local_ip_towards(addr: SocketAddrV4) -> Result<Ipv4Addr, String> {
+    let socket =
+        UdpSocket::bind("0.0.0.0:0").map_err(|e| format!("Unable to bind socket: {:?}", e))?;
+    socket
+        .connect(addr)
+        .map_err(|e| format!("No route to UPnP gateway: {:?}", e))?;
+
+    match socket.local_addr().map(|addr| addr.ip()) {
+        Ok(IpAddr::V4(ip)) => Ok(ip),
+        _ => Err("Unable to determine the local IPv4 address".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread;
+
+    const EXTERNAL_IP: Ipv4Addr = Ipv4Addr::new(203, 0, 113, 7);
+    const SERVICE: &str = "urn:schemas-upnp-org:service:WANIPConnection:1";
+
+    /// Spawns a UPnP gateway on localhost which answers `requests` SOAP requests, refusing mappings
+    /// of port 1. Returns the action and arguments of each request once they are answered.
+    fn mock_gateway(requests: usize) -> (Upnp, thread::JoinHandle<Vec<(String, String)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("should bind mock gateway");
+        let addr = match listener.local_addr() {
+            Ok(SocketAddr::V4(addr)) => addr,
+            other => panic!("should have an IPv4 addr, got {:?}", other),
+        };
+
+        let handle = thread::spawn(move || {
+            let mut received = vec![];
+            for _ in 0..requests {
+                let (stream, _) = listener.accept().expect("should accept");
+                let mut reader = BufReader::new(stream);
+
+                let mut action = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).expect("should read header");
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let (name, value) = line.split_at(line.find(':').unwrap_or(0));
+                    let value = value.trim_start_matches(':').trim();
+                    match name.to_ascii_lowercase().as_str() {
+                        "soapaction" => {
+                            action = value.trim_matches('"').rsplit('#').next().unwrap().into()
+                        }
+                        "content-length" => {
+                            content_length = value.parse().expect("should parse length")
+                        }
+                        _ => {}
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).expect("should read body");
+                let body = String::from_utf8(body).expect("body should be utf8");
+
+                let (status, response) = match action.as_str() {
+                    "AddPortMapping" if body.contains("<NewInternalPort>1</NewInternalPort>") => (
+                        "500 Internal Server Error",
+                        "<s:Fault><faultcode>s:Client</faultcode><faultstring>UPnPError</faultstring>\
+                         <detail><UPnPError xmlns=\"urn:schemas-upnp-org:control-1-0\">\
+                         <errorCode>718</errorCode><errorDescription>ConflictInMappingEntry\
+                         </errorDescription></UPnPError></detail></s:Fault>"
+                            .to_string(),
+                    ),
+                    "GetExternalIPAddress" => (
+                        "200 OK",
+                        format!(
+                            "<u:GetExternalIPAddressResponse xmlns:u=\"{}\">\
+                             <NewExternalIPAddress>{}</NewExternalIPAddress>\
+                             </u:GetExternalIPAddressResponse>",
+                            SERVICE, EXTERNAL_IP
+                        ),
+                    ),
+                    "AddPortMapping" | "DeletePortMapping" => (
+                        "200 OK",
+                        format!("<u:{}Response xmlns:u=\"{}\"/>", action, SERVICE),
+                    ),
+                    other => panic!("unexpected action {}", other),
+                };
+                let envelope = format!(
+                    "<?xml version=\"1.0\"?>\
+                     <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\">\
+                     <s:Body>{}</s:Body></s:Envelope>",
+                    response
+                );
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {}\r\nContent-Type: text/xml\r\nContent-Length: {}\r\n\
+                     Connection: close\r\n\r\n{}",
+                    status,
+                    envelope.len(),
+                    envelope
+                )
+                .expect("should respond");
+
+                received.push((action, body));
+            }
+            received
+        });
+
+        let arguments = |arguments: &[&str]| arguments.iter().map(|a| a.to_string()).collect();
+        let mut control_schema = HashMap::new();
+        control_schema.insert(
+            "AddPortMapping".to_string(),
+            arguments(&[
+                "NewRemoteHost",
+                "NewExternalPort",
+                "NewProtocol",
+                "NewInternalPort",
+                "NewInternalClient",
+                "NewEnabled",
+                "NewPortMappingDescription",
+                "NewLeaseDuration",
+            ]),
+        );
+        control_schema.insert(
+            "DeletePortMapping".to_string(),
+            arguments(&["NewRemoteHost", "NewExternalPort", "NewProtocol"]),
+        );
+
+        let upnp = Upnp {
+            gateway: Gateway {
+                addr,
+                root_url: "/rootDesc.xml".to_string(),
+                control_url: "/ctl/IPConn".to_string(),
+                control_schema_url: "/WANIPCn.xml".to_string(),
+                control_schema,
+            },
+            local_ip: Ipv4Addr::new(192, 168, 0, 2),
+        };
+
+        (upnp, handle)
+    }
+
+    #[test]
+    fn external_ip() {
+        let (upnp, _) = mock_gateway(1);
+
+        assert_eq!(upnp.external_ip(), Ok(EXTERNAL_IP));
+    }
+
+    #[test]
+    fn map_and_unmap_port() {
+        let (upnp, handle) = mock_gateway(3);
+
+        assert_eq!(upnp.map_port(PortMappingProtocol::TCP, 9000, 3600), Ok(()));
+        assert!(upnp
+            .map_port(PortMappingProtocol::UDP, 1, 3600)
+            .unwrap_err()
+            .contains("conflicts"));
+        assert_eq!(upnp.unmap_port(PortMappingProtocol::TCP, 9000), Ok(()));
+
+        let received = handle.join().expect("mock gateway should not panic");
+        let (action, body) = &received[0];
+        assert_eq!(action, "AddPortMapping");
+        for argument in &[
+            "<NewExternalPort>9000</NewExternalPort>",
+            "<NewInternalPort>9000</NewInternalPort>",
+            "<NewInternalClient>192.168.0.2</NewInternalClient>",
+            "<NewProtocol>TCP</NewProtocol>",
+            "<NewLeaseDuration>3600</NewLeaseDuration>",
+            "<NewPortMappingDescription>lighthouse</NewPortMappingDescription>",
+        ] {
+            assert!(body.contains(argument), "should request {}", argument);
+        }
+
+        let (action, body) = &received[2];
+        assert_eq!(action, "DeletePortMapping");
+        assert!(body.contains("<NewExternalPort>9000</NewExternalPort>"));
+        assert!(body.contains("<NewProtocol>TCP</NewProtocol>"));
+    }
+}
@@ -8,10 +8,10 @@ pub mod processor;
 
 use crate::error;
 use crate::service::NetworkMessage;
-use beacon_chain::{AttestationType, BeaconChain, BeaconChainTypes, BlockError};
+use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::{
     rpc::{RPCError, RPCErrorResponse, RPCRequest, RPCResponse, RequestId, ResponseTermination},
-    MessageId, NetworkGlobals, PeerId, PubsubMessage, RPCEvent,
+    MessageAcceptance, MessageId, NetworkGlobals, PeerId, PubsubMessage, RPCEvent,
 };
 use futures::future::Future;
 use futures::stream::Stream;
@@ -234,80 +234,67 @@ impl<T: BeaconChainTypes> Router<T> {
         peer_id: PeerId,
         gossip_message: PubsubMessage<T::EthSpec>,
     ) {
-        match gossip_message {
-            // Attestations should never reach the router.
+        let validation_result = match gossip_message {
+            // Blocks and attestations are validated by the beacon processor, which reports the
+            // validation result.
             PubsubMessage::AggregateAndProofAttestation(aggregate_and_proof) => {
-                if self
-                    .processor
-                    .should_forward_aggregate_attestation(&aggregate_and_proof)
-                {
-                    self.propagate_message(id, peer_id.clone());
-                }
-                self.processor.process_attestation_gossip(
+                return self.processor.on_aggregated_attestation_gossip(
+                    id,
                     peer_id,
-                    aggregate_and_proof.message.aggregate,
-                    AttestationType::Aggregated,
+                    aggregate_and_proof,
                 );
             }
             PubsubMessage::Attestation(subnet_attestation) => {
-                if self
-                    .processor
-                    .should_forward_attestation(&subnet_attestation.1)
-                {
-                    self.propagate_message(id, peer_id.clone());
-                }
-                self.processor.process_attestation_gossip(
+                return self.processor.on_unaggregated_attestation_gossip(
+                    id,
                     peer_id,
-                    subnet_attestation.1,
-                    AttestationType::Unaggregated { should_store: true },
+                    Box::new(subnet_attestation.1),
                 );
             }
             PubsubMessage::BeaconBlock(block) => {
-                match self.processor.should_forward_block(&peer_id, block) {
-                    Ok(verified_block) => {
-                        self.propagate_message(id, peer_id.clone());
-                        self.processor.on_block_gossip(peer_id, verified_block);
-                    }
-                    Err(BlockError::ParentUnknown { .. }) => {} // performing a parent lookup
-                    Err(e) => {
-                        // performing a parent lookup
-                        warn!(self.log, "Could not verify block for gossip";
-                            "error" => format!("{:?}", e));
-                    }
-                }
+                return self.processor.on_block_gossip(id, peer_id, block);
             }
             PubsubMessage::VoluntaryExit(_exit) => {
                 // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
                 // TODO: Handle exits
                 debug!(self.log, "Received a voluntary exit"; "peer_id" => format!("{}", peer_id) );
+                MessageAcceptance::Accept
             }
             PubsubMessage::ProposerSlashing(_proposer_slashing) => {
                 // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
                 // TODO: Handle proposer slashings
                 debug!(self.log, "Received a proposer slashing"; "peer_id" => format!("{}", peer_id) );
+                MessageAcceptance::Accept
             }
             PubsubMessage::AttesterSlashing(_attester_slashing) => {
                 // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
                 // TODO: Handle attester slashings
                 debug!(self.log, "Received an attester slashing"; "peer_id" => format!("{}", peer_id) );
+                MessageAcceptance::Accept
             }
-        }
+        };
+
+        self.report_validation_result(id, peer_id, validation_result);
     }
 
-    /// Informs the network service that the message should be forwarded to other peers.
-    fn propagate_message(&mut self, message_id: MessageId, propagation_source: PeerId) {
+    /// Informs the network service of the outcome of validating a gossip message, which
+    /// determines whether it is forwarded to other peers.
+    fn report_validation_result(
+        &mut self,
+        message_id: MessageId,
+        propagation_source: PeerId,
+        validation_result: MessageAcceptance,
+    ) {
         self.network_send
-            .try_send(NetworkMessage::Propagate {
+            .try_send(NetworkMessage::Validate {
                 propagation_source,
                 message_id,
+                validation_result,
             })
             .unwrap_or_else(|_| {
                 warn!(
                     self.log,
-                    "Could not send propagation request to the network service"
+                    "Could not send a validation result to the network service"
                 )
             });
     }
@@ -1,21 +1,16 @@
+use crate::beacon_processor::{self, WorkEvent as BeaconWorkEvent, MAX_WORK_EVENT_QUEUE_LEN};
+use crate::metrics;
 use crate::service::NetworkMessage;
-use crate::sync::{PeerSyncInfo, SyncMessage};
-use beacon_chain::{
-    AttestationProcessingOutcome, AttestationType, BeaconChain, BeaconChainTypes, BlockError,
-    BlockProcessingOutcome, GossipVerifiedBlock,
-};
+use crate::sync::SyncMessage;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::rpc::methods::*;
 use eth2_libp2p::rpc::{RPCEvent, RPCRequest, RPCResponse, RequestId};
-use eth2_libp2p::{NetworkGlobals, PeerId};
+use eth2_libp2p::{MessageAcceptance, MessageId, NetworkGlobals, PeerId};
 use slog::{debug, error, o, trace, warn};
-use ssz::Encode;
 use std::sync::Arc;
 use store::Store;
 use tokio::sync::{mpsc, oneshot};
-use types::{
-    Attestation, ChainSpec, Epoch, EthSpec, Hash256, SignedAggregateAndProof, SignedBeaconBlock,
-    Slot,
-};
+use types::{Attestation, ChainSpec, EthSpec, SignedAggregateAndProof, SignedBeaconBlock, Slot};
 
 //TODO: Rate limit requests
 
@@ -34,6 +29,9 @@ pub struct Processor<T: BeaconChainTypes> {
     _sync_exit: oneshot::Sender<()>,
     /// A network context to return and handle RPC requests.
     network: HandlerNetworkContext<T::EthSpec>,
+    /// A channel to the beacon processor, which processes gossip blocks and attestations, block
+    /// batches and `Status` messages.
+    beacon_processor_send: mpsc::Sender<BeaconWorkEvent<T::EthSpec>>,
     /// The `RPCHandler` logger.
     log: slog::Logger,
 }
@@ -48,6 +46,11 @@ impl<T: BeaconChainTypes> Processor<T> {
         log: &slog::Logger,
     ) -> Self {
         let sync_logger = log.new(o!("service"=> "sync"));
+        let (beacon_processor_send, beacon_processor_recv) =
+            mpsc::channel(MAX_WORK_EVENT_QUEUE_LEN);
+        // sync has its own channel, so that its work is not dropped when gossip fills the
+        // bounded channel
+        let (sync_work_send, sync_work_recv) = mpsc::unbounded_channel();
 
         // spawn the sync thread
         let (sync_send, _sync_exit) = crate::sync::manager::spawn(
@@ -55,14 +58,26 @@ impl<T: BeaconChainTypes> Processor<T> {
             beacon_chain.clone(),
             network_globals,
             network_send.clone(),
+            sync_work_send,
             sync_logger,
         );
 
+        beacon_processor::spawn(
+            executor,
+            Arc::downgrade(&beacon_chain),
+            network_send.clone(),
+            sync_send.clone(),
+            beacon_processor_recv,
+            sync_work_recv,
+            log.new(o!("service" => "beacon_processor")),
+        );
+
         Processor {
             chain: beacon_chain,
             sync_send,
             _sync_exit,
             network: HandlerNetworkContext::new(network_send, log.clone()),
+            beacon_processor_send,
             log: log.clone(),
         }
     }
@@ -76,6 +91,23 @@ impl<T: BeaconChainTypes> Processor<T> {
         });
     }
 
+    fn send_beacon_processor_work(&mut self, work: BeaconWorkEvent<T::EthSpec>) {
+        let queue_name = work.queue_name();
+        self.beacon_processor_send
+            .try_send(work)
+            .unwrap_or_else(|_| {
+                metrics::inc_counter_vec(
+                    &metrics::BEACON_PROCESSOR_WORK_EVENTS_DROPPED_COUNT,
+                    &[queue_name],
+                );
+                warn!(
+                    self.log,
+                    "Could not send work to the beacon processor";
+                    "queue" => queue_name,
+                )
+            });
+    }
+
     /// Handle a peer disconnect.
     ///
     /// Removes the peer from the manager.
@@ -140,7 +172,7 @@ impl<T: BeaconChainTypes> Processor<T> {
             );
         }
 
-        self.process_status(peer_id, status);
+        self.send_beacon_processor_work(BeaconWorkEvent::status_message(peer_id, status));
     }
 
     /// Process a `Status` response from a peer.
@@ -157,124 +189,7 @@ impl<T: BeaconChainTypes> Processor<T> {
         );
 
         // Process the status message, without sending back another status.
-        self.process_status(peer_id, status);
-    }
-
-    /// Process a `Status` message, requesting new blocks if appropriate.
-    ///
-    /// Disconnects the peer if required.
-    fn process_status(&mut self, peer_id: PeerId, status: StatusMessage) {
-        let remote = PeerSyncInfo::from(status);
-        let local = match PeerSyncInfo::from_chain(&self.chain) {
-            Some(local) => local,
-            None => {
-                return error!(
-                    self.log,
-                    "Failed to get peer sync info";
-                    "msg" => "likely due to head lock contention"
-                )
-            }
-        };
-
-        let start_slot = |epoch: Epoch| epoch.start_slot(T::EthSpec::slots_per_epoch());
-
-        if local.fork_digest != remote.fork_digest {
-            // The node is on a different network/fork, disconnect them.
-            debug!(
-                self.log, "Handshake Failure";
-                "peer" => format!("{:?}", peer_id),
-                "reason" => "incompatible forks",
-                "our_fork" => hex::encode(local.fork_digest),
-                "their_fork" => hex::encode(remote.fork_digest)
-            );
-
-            self.network
-                .disconnect(peer_id, GoodbyeReason::IrrelevantNetwork);
-        } else if remote.head_slot
-            > self.chain.slot().unwrap_or_else(|_| Slot::from(0u64)) + FUTURE_SLOT_TOLERANCE
-        {
-            // Note: If the slot_clock cannot be read, this will not error. Other system
-            // components will deal with an invalid slot clock error.
-
-            // The remotes head is on a slot that is significantly ahead of ours. This could be
-            // because they are using a different genesis time, or that theirs or our system
-            // clock is incorrect.
-            debug!(
-            self.log, "Handshake Failure";
-            "peer" => format!("{:?}", peer_id),
-            "reason" => "different system clocks or genesis time"
-            );
-            self.network
-                .disconnect(peer_id, GoodbyeReason::IrrelevantNetwork);
-        } else if remote.finalized_epoch <= local.finalized_epoch
-            && remote.finalized_root != Hash256::zero()
-            && local.finalized_root != Hash256::zero()
-            && self
-                .chain
-                .root_at_slot(start_slot(remote.finalized_epoch))
-                .map(|root_opt| root_opt != Some(remote.finalized_root))
-                .unwrap_or_else(|_| false)
-        {
-            // The remotes finalized epoch is less than or greater than ours, but the block root is
-            // different to the one in our chain.
-            //
-            // Therefore, the node is on a different chain and we should not communicate with them.
-            debug!(
-                self.log, "Handshake Failure";
-                "peer" => format!("{:?}", peer_id),
-                "reason" => "different finalized chain"
-            );
-            self.network
-                .disconnect(peer_id, GoodbyeReason::IrrelevantNetwork);
-        } else if remote.finalized_epoch < local.finalized_epoch {
-            // The node has a lower finalized epoch, their chain is not useful to us. There are two
-            // cases where a node can have a lower finalized epoch:
-            //
-            // ## The node is on the same chain
-            //
-            // If a node is on the same chain but has a lower finalized epoch, their head must be
-            // lower than ours. Therefore, we have nothing to request from them.
-            //
-            // ## The node is on a fork
-            //
-            // If a node is on a fork that has a lower finalized epoch, switching to that fork would
-            // cause us to revert a finalized block. This is not permitted, therefore we have no
-            // interest in their blocks.
-            debug!(
-                self.log,
-                "NaivePeer";
-                "peer" => format!("{:?}", peer_id),
-                "reason" => "lower finalized epoch"
-            );
-        } else if self
-            .chain
-            .store
-            .exists::<SignedBeaconBlock<T::EthSpec>>(&remote.head_root)
-            .unwrap_or_else(|_| false)
-        {
-            debug!(
-                self.log, "Peer with known chain found";
-                "peer" => format!("{:?}", peer_id),
-                "remote_head_slot" => remote.head_slot,
-                "remote_latest_finalized_epoch" => remote.finalized_epoch,
-            );
-
-            // If the node's best-block is already known to us and they are close to our current
-            // head, treat them as a fully sync'd peer.
-            self.send_to_sync(SyncMessage::AddPeer(peer_id, remote));
-        } else {
-            // The remote node has an equal or great finalized epoch and we don't know it's head.
-            //
-            // Therefore, there are some blocks between the local finalized epoch and the remote
-            // head that are worth downloading.
-            debug!(
-                self.log, "UsefulPeer";
-                "peer" => format!("{:?}", peer_id),
-                "local_finalized_epoch" => local.finalized_epoch,
-                "remote_latest_finalized_epoch" => remote.finalized_epoch,
-            );
-            self.send_to_sync(SyncMessage::AddPeer(peer_id, remote));
-        }
+        self.send_beacon_processor_work(BeaconWorkEvent::status_message(peer_id, status));
     }
 
     /// Handle a `BlocksByRoot` request from the peer.
@@ -458,167 +373,44 @@ impl<T: BeaconChainTypes> Processor<T> {
         });
     }
 
-    /// Template function to be called on a block to determine if the block should be propagated
-    /// across the network.
-    pub fn should_forward_block(
+    /// Queues a block received over gossip to be verified and imported by the beacon processor.
+    pub fn on_block_gossip(
         &mut self,
-        peer_id: &PeerId,
+        message_id: MessageId,
+        peer_id: PeerId,
         block: Box<SignedBeaconBlock<T::EthSpec>>,
-    ) -> Result<GossipVerifiedBlock<T>, BlockError> {
-        let result = self.chain.verify_block_for_gossip(*block.clone());
-
-        if let Err(BlockError::ParentUnknown(block_hash)) = result {
-            // if we don't know the parent, start a parent lookup
-            // TODO: Modify the return to avoid the block clone.
-            debug!(self.log, "Unknown block received. Starting a parent lookup"; "block_slot" => block.message.slot, "block_hash" => format!("{}", block_hash));
-            self.send_to_sync(SyncMessage::UnknownBlock(peer_id.clone(), block));
-        }
-        result
+    ) {
+        self.send_beacon_processor_work(BeaconWorkEvent::gossip_beacon_block(
+            message_id, peer_id, block,
+        ))
     }
 
-    /// Process a gossip message declaring a new block.
-    ///
-    /// Attempts to apply to block to the beacon chain. May queue the block for later processing.
-    ///
-    /// Returns a `bool` which, if `true`, indicates we should forward the block to our peers.
-    pub fn on_block_gossip(
+    /// Queues an aggregated attestation received over gossip to be processed by the beacon
+    /// processor.
+    pub fn on_aggregated_attestation_gossip(
         &mut self,
+        message_id: MessageId,
         peer_id: PeerId,
-        verified_block: GossipVerifiedBlock<T>,
-    ) -> bool {
-        let block = Box::new(verified_block.block.clone());
-        match BlockProcessingOutcome::shim(self.chain.process_block(verified_block)) {
-            Ok(outcome) => match outcome {
-                BlockProcessingOutcome::Processed { .. } => {
-                    trace!(self.log, "Gossipsub block processed";
-                            "peer_id" => format!("{:?}",peer_id));
-
-                    // TODO: It would be better if we can run this _after_ we publish the block to
-                    // reduce block propagation latency.
-                    //
-                    // The `MessageHandler` would be the place to put this, however it doesn't seem
-                    // to have a reference to the `BeaconChain`. I will leave this for future
-                    // works.
-                    match self.chain.fork_choice() {
-                        Ok(()) => trace!(
-                            self.log,
-                            "Fork choice success";
-                            "location" => "block gossip"
-                        ),
-                        Err(e) => error!(
-                            self.log,
-                            "Fork choice failed";
-                            "error" => format!("{:?}", e),
-                            "location" => "block gossip"
-                        ),
-                    }
-                }
-                BlockProcessingOutcome::ParentUnknown { .. } => {
-                    // Inform the sync manager to find parents for this block
-                    // This should not occur. It should be checked by `should_forward_block`
-                    error!(self.log, "Block with unknown parent attempted to be processed";
-                            "peer_id" => format!("{:?}",peer_id));
-                    self.send_to_sync(SyncMessage::UnknownBlock(peer_id, block));
-                }
-                other => {
-                    warn!(
-                        self.log,
-                        "Invalid gossip beacon block";
-                        "outcome" => format!("{:?}", other),
-                        "block root" => format!("{}", block.canonical_root()),
-                        "block slot" => block.slot()
-                    );
-                    trace!(
-                        self.log,
-                        "Invalid gossip beacon block ssz";
-                        "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
-                    );
-                }
-            },
-            Err(_) => {
-                // error is logged during the processing therefore no error is logged here
-                trace!(
-                    self.log,
-                    "Erroneous gossip beacon block ssz";
-                    "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
-                );
-            }
-        }
-        // TODO: Update with correct block gossip checking
-        true
-    }
-
-    /// Verifies the Aggregate attestation before propagating.
-    pub fn should_forward_aggregate_attestation(
-        &self,
-        _aggregate_and_proof: &Box<SignedAggregateAndProof<T::EthSpec>>,
-    ) -> bool {
-        // TODO: Implement
-        true
-    }
-
-    /// Verifies the attestation before propagating.
-    pub fn should_forward_attestation(&self, _aggregate: &Attestation<T::EthSpec>) -> bool {
-        // TODO: Implement
-        true
+        aggregate: Box<SignedAggregateAndProof<T::EthSpec>>,
+    ) {
+        self.send_beacon_processor_work(BeaconWorkEvent::gossip_aggregate(
+            message_id, peer_id, aggregate,
+        ))
     }
 
-    /// Process a new attestation received from gossipsub.
-    pub fn process_attestation_gossip(
+    /// Queues an unaggregated attestation received over gossip to be processed by the beacon
+    /// processor.
+    pub fn on_unaggregated_attestation_gossip(
         &mut self,
+        message_id: MessageId,
         peer_id: PeerId,
-        msg: Attestation<T::EthSpec>,
-        attestation_type: AttestationType,
+        attestation: Box<Attestation<T::EthSpec>>,
     ) {
-        match self
-            .chain
-            .process_attestation(msg.clone(), attestation_type)
-        {
-            Ok(outcome) => match outcome {
-                AttestationProcessingOutcome::Processed => {
-                    debug!(
-                        self.log,
-                        "Processed attestation";
-                        "source" => "gossip",
-                        "peer" => format!("{:?}",peer_id),
-                        "block_root" => format!("{}", msg.data.beacon_block_root),
-                        "slot" => format!("{}", msg.data.slot),
-                    );
-                }
-                AttestationProcessingOutcome::UnknownHeadBlock { beacon_block_root } => {
-                    // TODO: Maintain this attestation and re-process once sync completes
-                    debug!(
-                    self.log,
-                    "Attestation for unknown block";
-                    "peer_id" => format!("{:?}", peer_id),
-                    "block" => format!("{}", beacon_block_root)
-                    );
-                    // we don't know the block, get the sync manager to handle the block lookup
-                    self.send_to_sync(SyncMessage::UnknownBlockHash(peer_id, beacon_block_root));
-                }
-                AttestationProcessingOutcome::FutureEpoch { .. }
-                | AttestationProcessingOutcome::PastEpoch { .. }
-                | AttestationProcessingOutcome::UnknownTargetRoot { .. }
-                | AttestationProcessingOutcome::FinalizedSlot { .. } => {} // ignore the attestation
-                AttestationProcessingOutcome::Invalid { .. }
-                | AttestationProcessingOutcome::EmptyAggregationBitfield { .. }
-                | AttestationProcessingOutcome::AttestsToFutureBlock { .. }
-                | AttestationProcessingOutcome::InvalidSignature
-                | AttestationProcessingOutcome::NoCommitteeForSlotAndIndex { .. }
-                | AttestationProcessingOutcome::BadTargetEpoch { .. } => {
-                    // the peer has sent a bad attestation. Remove them.
-                    self.network.disconnect(peer_id, GoodbyeReason::Fault);
-                }
-            },
-            Err(_) => {
-                // error is logged during the processing therefore no error is logged here
-                trace!(
-                    self.log,
-                    "Erroneous gossip attestation ssz";
-                    "ssz" => format!("0x{}", hex::encode(msg.as_ssz_bytes())),
-                );
-            }
-        };
+        self.send_beacon_processor_work(BeaconWorkEvent::gossip_attestation(
+            message_id,
+            peer_id,
+            attestation,
+        ))
     }
 }
 
@@ -642,7 +434,7 @@ pub(crate) fn status_message<T: BeaconChainTypes>(
 }
 
 /// Wraps a Network Channel to employ various RPC related network functionality for the
-/// processor.
+/// processor, and to report gossip validation results.
 /// The Processor doesn't manage it's own request Id's and can therefore only send
 /// responses or requests with 0 request Ids.
 pub struct HandlerNetworkContext<T: EthSpec> {
@@ -705,6 +497,28 @@ impl<T: EthSpec> HandlerNetworkContext<T> {
         self.send_rpc_event(peer_id, RPCEvent::Response(request_id, rpc_error_response));
     }
 
+    /// Informs the network service of the outcome of validating a gossip message, which
+    /// determines whether it is forwarded to other peers.
+    pub fn report_validation_result(
+        &mut self,
+        message_id: MessageId,
+        propagation_source: PeerId,
+        validation_result: MessageAcceptance,
+    ) {
+        self.network_send
+            .try_send(NetworkMessage::Validate {
+                propagation_source,
+                message_id,
+                validation_result,
+            })
+            .unwrap_or_else(|_| {
+                warn!(
+                    self.log,
+                    "Could not send a validation result to the network service"
+                )
+            });
+    }
+
     fn send_rpc_event(&mut self, peer_id: PeerId, rpc_event: RPCEvent<T>) {
         self.network_send
             .try_send(NetworkMessage::RPC(peer_id, rpc_event))
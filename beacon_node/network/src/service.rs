@@ -1,4 +1,5 @@
 use crate::error;
+use crate::nat::{self, NatConfig};
 use crate::persisted_dht::{load_dht, persist_dht};
 use crate::router::{Router, RouterMessage};
 use crate::{
@@ -6,18 +7,22 @@ use crate::{
     NetworkConfig,
 };
 use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2_libp2p::types::PortMapping;
 use eth2_libp2p::Service as LibP2PService;
 use eth2_libp2p::{rpc::RPCRequest, BehaviourEvent, Enr, MessageId, NetworkGlobals, PeerId, Swarm};
-use eth2_libp2p::{PubsubMessage, RPCEvent};
+use eth2_libp2p::{
+    MessageAcceptance, Multiaddr, PeerScoreParams, PubsubMessage, RPCEvent, TrustedPeer,
+};
 use futures::prelude::*;
 use futures::Stream;
 use rest_types::ValidatorSubscription;
 use slog::{debug, error, info, trace};
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::runtime::TaskExecutor;
 use tokio::sync::{mpsc, oneshot};
-use tokio::timer::Delay;
+use tokio::timer::{Delay, Interval};
 use types::EthSpec;
 
 mod tests;
@@ -46,6 +51,13 @@ pub struct NetworkService<T: BeaconChainTypes> {
     initial_delay: Delay,
     /// A delay that expires when a new fork takes place.
     next_fork_update: Option<Delay>,
+    /// Updates the gossipsub scoring parameters with the number of active validators every epoch.
+    gossip_score_update: Interval,
+    /// The thread which maps our ports on the NAT gateway, if enabled.
+    port_mapping: Option<nat::PortMappingHandle>,
+    /// Whether the external address of a port mapping is advertised in our ENR. It is not if the
+    /// ENR address was set explicitly.
+    advertise_port_mapping: bool,
     /// The logger for the network service.
     log: slog::Logger,
     /// A probability of propagation.
@@ -84,6 +96,14 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             libp2p.swarm.add_enr(enr);
         }
 
+        // score peers by the gossipsub messages they deliver
+        if let Some(params) = gossip_score_params(&beacon_chain) {
+            libp2p.swarm.update_gossip_score_params(params);
+        }
+        let gossip_score_update = Interval::new_interval(Duration::from_millis(
+            beacon_chain.spec.milliseconds_per_slot * T::EthSpec::slots_per_epoch(),
+        ));
+
         // A delay used to initialise code after the network has started
         // This is currently used to obtain the listening addresses from the libp2p service.
         let initial_delay = Delay::new(Instant::now() + Duration::from_secs(1));
@@ -99,6 +119,17 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             network_log.clone(),
         )?;
 
+        // NAT port mapping thread
+        let port_mapping = if config.nat_port_mapping {
+            Some(nat::spawn_port_mapping(
+                NatConfig::from(config),
+                network_send.clone(),
+                network_log.clone(),
+            )?)
+        } else {
+            None
+        };
+
         // attestation service
         let attestation_service =
             AttestationService::new(beacon_chain.clone(), network_globals.clone(), &network_log);
@@ -114,6 +145,9 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             network_globals: network_globals.clone(),
             initial_delay,
             next_fork_update,
+            gossip_score_update,
+            port_mapping,
+            advertise_port_mapping: config.enr_address.is_none(),
             log: network_log,
             propagation_percentage,
         };
@@ -166,6 +200,11 @@ fn spawn_service<T: BeaconChainTypes>(
                         ),
                     }
 
+                    // the NAT thread removes our port mappings from the gateway in the background
+                    if let Some(port_mapping) = service.port_mapping.take() {
+                        port_mapping.shutdown();
+                    }
+
                     info!(log.clone(), "Network service shutdown");
                     return Ok(Async::Ready(()));
         }
@@ -179,9 +218,10 @@ fn spawn_service<T: BeaconChainTypes>(
                         trace!(log, "Sending RPC"; "rpc" => format!("{}", rpc_event));
                         service.libp2p.swarm.send_rpc(peer_id, rpc_event);
                     }
-                    NetworkMessage::Propagate {
+                    NetworkMessage::Validate {
                         propagation_source,
                         message_id,
+                        validation_result,
                     } => {
                         // TODO: Remove this for mainnet
                         // randomly prevents propagation
@@ -194,16 +234,21 @@ fn spawn_service<T: BeaconChainTypes>(
                                 should_send = false;
                             }
                         }
-                        if !should_send {
+                        trace!(log, "Reporting gossipsub message validation result";
+                        "propagation_peer" => format!("{:?}", propagation_source),
+                        "message_id" => message_id.to_string(),
+                        "validation_result" => format!("{:?}", validation_result),
+                        );
+                        if !should_send && validation_result == MessageAcceptance::Accept {
                             info!(log, "Random filter did not propagate message");
+                            // the peer is still scored and the message removed from our caches
+                            service.libp2p
+                                .swarm
+                                .report_message_validation_result_without_propagation(&propagation_source, message_id, validation_result);
                         } else {
-                            trace!(log, "Propagating gossipsub message";
-                            "propagation_peer" => format!("{:?}", propagation_source),
-                            "message_id" => message_id.to_string(),
-                            );
                             service.libp2p
                                 .swarm
-                                .propagate_message(&propagation_source, message_id);
+                                .report_message_validation_result(&propagation_source, message_id, validation_result);
                         }
                     }
                     NetworkMessage::Publish { messages } => {
@@ -237,6 +282,41 @@ fn spawn_service<T: BeaconChainTypes>(
                             std::time::Duration::from_secs(BAN_PEER_TIMEOUT),
                         );
                     }
+                    NetworkMessage::Dial { multiaddr } => {
+                        service.libp2p.dial(multiaddr);
+                    }
+                    NetworkMessage::DialEnr { enr } => {
+                        service.libp2p.dial_enr(enr);
+                    }
+                    NetworkMessage::BanPeer { peer_id } => {
+                        service.libp2p.ban_peer(peer_id);
+                    }
+                    NetworkMessage::UnbanPeer { peer_id } => {
+                        service.libp2p.unban_peer(peer_id);
+                    }
+                    NetworkMessage::BanIp { ip } => {
+                        service.libp2p.swarm.ban_ip(ip);
+                    }
+                    NetworkMessage::UnbanIp { ip } => {
+                        service.libp2p.swarm.unban_ip(&ip);
+                    }
+                    NetworkMessage::AddTrustedPeer { trusted_peer } => {
+                        service.libp2p.swarm.add_trusted_peer(trusted_peer);
+                    }
+                    NetworkMessage::PortMapped { mapping } => {
+                        // mappings are renewed periodically, only update the ENR on a change
+                        if service.network_globals.port_mapping().as_ref() != Some(&mapping) {
+                            // an explicit ENR address takes precedence over the mapping
+                            if service.advertise_port_mapping {
+                                service.libp2p.swarm.update_enr_external_address(
+                                    mapping.external_ip,
+                                    mapping.tcp_port,
+                                    mapping.udp_port,
+                                );
+                            }
+                            *service.network_globals.port_mapping.write() = Some(mapping);
+                        }
+                    }
                     NetworkMessage::Subscribe { subscriptions } =>
                     {
                        // the result is dropped as it used solely for ergonomics
@@ -339,6 +419,14 @@ fn spawn_service<T: BeaconChainTypes>(
                         }
                     }
                     BehaviourEvent::PeerSubscribed(_, _) => {}
+                    BehaviourEvent::DisconnectPeer(peer_id) => {
+                        service.libp2p.disconnect_peer(peer_id);
+                    }
+                    BehaviourEvent::BanPeer(peer_id) => {
+                        // this enforces all peer manager bans, including those for a banned IP
+                        // and for a low reputation
+                        peers_to_ban.push(peer_id);
+                    }
                 },
                 Ok(Async::Ready(None)) => unreachable!("Stream never ends"),
                 Ok(Async::NotReady) => break,
@@ -346,7 +434,8 @@ fn spawn_service<T: BeaconChainTypes>(
             }
         }
 
-        // ban and disconnect any peers that sent Goodbye requests
+        // ban and disconnect any peers that sent Goodbye requests or were refused by the peer
+        // manager
         while let Some(peer_id) = peers_to_ban.pop() {
             service.libp2p.disconnect_and_ban_peer(
                 peer_id.clone(),
@@ -354,6 +443,13 @@ fn spawn_service<T: BeaconChainTypes>(
             );
         }
 
+        // the number of active validators determines the expected rate of attestations
+        while let Ok(Async::Ready(Some(_))) = service.gossip_score_update.poll() {
+            if let Some(params) = gossip_score_params(&service.beacon_chain) {
+                service.libp2p.swarm.update_gossip_score_params(params);
+            }
+        }
+
         // if we have just forked, update inform the libp2p layer
         if let Some(mut update_fork_delay) =  service.next_fork_update.take() {
             if !update_fork_delay.is_elapsed() {
@@ -384,6 +480,18 @@ fn next_fork_delay<T: BeaconChainTypes>(
     })
 }
 
+/// Returns the gossipsub scoring parameters for the number of active validators at the head of the
+/// chain, or `None` if the head is unavailable.
+fn gossip_score_params<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+) -> Option<PeerScoreParams> {
+    let active_validators = beacon_chain.head_active_validator_count().ok()?;
+    Some(PeerScoreParams::new::<T::EthSpec>(
+        &beacon_chain.spec,
+        active_validators,
+    ))
+}
+
 /// Types of messages that the network service can receive.
 #[derive(Debug)]
 pub enum NetworkMessage<T: EthSpec> {
@@ -395,11 +503,29 @@ pub enum NetworkMessage<T: EthSpec> {
     RPC(PeerId, RPCEvent<T>),
     /// Publish a list of messages to the gossipsub protocol.
     Publish { messages: Vec<PubsubMessage<T>> },
-    /// Propagate a received gossipsub message.
-    Propagate {
+    /// Report the outcome of validating a received gossipsub message, which determines whether
+    /// it is propagated and how the peer that delivered it is scored.
+    Validate {
         propagation_source: PeerId,
         message_id: MessageId,
+        validation_result: MessageAcceptance,
     },
     /// Disconnect and bans a peer id.
     Disconnect { peer_id: PeerId },
+    /// Dial a libp2p multiaddr.
+    Dial { multiaddr: Multiaddr },
+    /// Add an ENR to the DHT and dial it.
+    DialEnr { enr: Enr },
+    /// Disconnect and ban a peer id until it is unbanned.
+    BanPeer { peer_id: PeerId },
+    /// Unban a peer id.
+    UnbanPeer { peer_id: PeerId },
+    /// Refuse connections from an IP address, disconnecting any peers which listen on it.
+    BanIp { ip: IpAddr },
+    /// Accept connections from an IP address which was banned.
+    UnbanIp { ip: IpAddr },
+    /// Keep a peer connected, exempting it from the peer limit and bans.
+    AddTrustedPeer { trusted_peer: TrustedPeer },
+    /// Advertise the external address of ports mapped on the NAT gateway.
+    PortMapped { mapping: PortMapping },
 }
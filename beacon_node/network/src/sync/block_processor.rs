@@ -6,15 +6,16 @@ use eth2_libp2p::PeerId;
 use slog::{debug, error, trace, warn};
 use std::sync::{Arc, Weak};
 use tokio::sync::mpsc;
-use types::SignedBeaconBlock;
+use types::{Hash256, SignedBeaconBlock};
 
 /// Id associated to a block processing request, either a batch or a single block.
 #[derive(Clone, Debug, PartialEq)]
 pub enum ProcessId {
     /// Processing Id of a range syncing batch.
     RangeBatchId(ChainId, BatchId),
-    /// Processing Id of the parent lookup of a block
-    ParentLookup(PeerId),
+    /// Processing Id of the parent lookup of a block, given by the peer that sent the last block
+    /// and the root of the block the lookup started from.
+    ParentLookup(PeerId, Hash256),
 }
 
 /// The result of a block processing request.
@@ -29,77 +30,90 @@ pub enum BatchProcessResult {
     Partial,
 }
 
-/// Spawns a thread handling the block processing of a request: range syncing or parent lookup.
-pub fn spawn_block_processor<T: BeaconChainTypes>(
+/// Processes the blocks of a request, range syncing or parent lookup, and informs the sync
+/// manager of the result. This is run by a beacon processor worker.
+pub fn process_chain_segment<T: BeaconChainTypes>(
     chain: Weak<BeaconChain<T>>,
     process_id: ProcessId,
     downloaded_blocks: Vec<SignedBeaconBlock<T::EthSpec>>,
     mut sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
-    log: slog::Logger,
+    log: &slog::Logger,
 ) {
-    std::thread::spawn(move || {
-        match process_id {
-            // this a request from the range sync
-            ProcessId::RangeBatchId(chain_id, batch_id) => {
-                debug!(log, "Processing batch"; "id" => *batch_id, "blocks" => downloaded_blocks.len());
-                let result = match process_blocks(chain, downloaded_blocks.iter(), &log) {
-                    (_, Ok(_)) => {
-                        debug!(log, "Batch processed"; "id" => *batch_id );
-                        BatchProcessResult::Success
-                    }
-                    (imported_blocks, Err(e)) if imported_blocks > 0 => {
-                        debug!(log, "Batch processing failed but imported some blocks";
-                            "id" => *batch_id, "error" => e, "imported_blocks"=> imported_blocks);
-                        BatchProcessResult::Partial
-                    }
-                    (_, Err(e)) => {
-                        debug!(log, "Batch processing failed"; "id" => *batch_id, "error" => e);
-                        BatchProcessResult::Failed
-                    }
-                };
-
-                let msg = SyncMessage::BatchProcessed {
-                    chain_id,
-                    batch_id,
-                    downloaded_blocks,
-                    result,
-                };
-                sync_send.try_send(msg).unwrap_or_else(|_| {
-                    debug!(
-                        log,
-                        "Block processor could not inform range sync result. Likely shutting down."
-                    );
-                });
-            }
-            // this a parent lookup request from the sync manager
-            ProcessId::ParentLookup(peer_id) => {
+    match process_id {
+        // this a request from the range sync
+        ProcessId::RangeBatchId(chain_id, batch_id) => {
+            debug!(log, "Processing batch"; "id" => *batch_id, "blocks" => downloaded_blocks.len());
+            let result = match process_blocks(chain, downloaded_blocks.iter(), log) {
+                (_, Ok(_)) => {
+                    debug!(log, "Batch processed"; "id" => *batch_id );
+                    BatchProcessResult::Success
+                }
+                (imported_blocks, Err(e)) if imported_blocks > 0 => {
+                    debug!(log, "Batch processing failed but imported some blocks";
+                        "id" => *batch_id, "error" => e, "imported_blocks"=> imported_blocks);
+                    BatchProcessResult::Partial
+                }
+                (_, Err(e)) => {
+                    debug!(log, "Batch processing failed"; "id" => *batch_id, "error" => e);
+                    BatchProcessResult::Failed
+                }
+            };
+
+            let msg = SyncMessage::BatchProcessed {
+                chain_id,
+                batch_id,
+                downloaded_blocks,
+                result,
+            };
+            sync_send.try_send(msg).unwrap_or_else(|_| {
                 debug!(
-                    log, "Processing parent lookup";
-                    "last_peer_id" => format!("{}", peer_id),
-                    "blocks" => downloaded_blocks.len()
+                    log,
+                    "Block processor could not inform range sync result. Likely shutting down."
                 );
-                // parent blocks are ordered from highest slot to lowest, so we need to process in
-                // reverse
-                match process_blocks(chain, downloaded_blocks.iter().rev(), &log) {
-                    (_, Err(e)) => {
-                        warn!(log, "Parent lookup failed"; "last_peer_id" => format!("{}", peer_id), "error" => e);
-                        sync_send
-                        .try_send(SyncMessage::ParentLookupFailed(peer_id))
-                        .unwrap_or_else(|_| {
-                            // on failure, inform to downvote the peer
-                            debug!(
-                                log,
-                                "Block processor could not inform parent lookup result. Likely shutting down."
-                            );
-                        });
-                    }
-                    (_, Ok(_)) => {
-                        debug!(log, "Parent lookup processed successfully");
-                    }
+            });
+        }
+        // this a parent lookup request from the sync manager
+        ProcessId::ParentLookup(peer_id, chain_head) => {
+            debug!(
+                log, "Processing parent lookup";
+                "last_peer_id" => format!("{}", peer_id),
+                "chain_head" => format!("{}", chain_head),
+                "blocks" => downloaded_blocks.len()
+            );
+            // parent blocks are ordered from highest slot to lowest, so we need to process in
+            // reverse
+            match process_blocks(chain.clone(), downloaded_blocks.iter().rev(), log) {
+                (_, Err(e)) => {
+                    warn!(log, "Parent lookup failed"; "last_peer_id" => format!("{}", peer_id), "error" => e)
                 }
+                (_, Ok(_)) => debug!(log, "Parent lookup processed successfully"),
             }
+
+            // Blocks which were already known are skipped by the chain segment processing, so the
+            // imported blocks are found by checking fork choice rather than counting them
+            let chain = chain.upgrade();
+            let (imported_blocks, failed_blocks) = downloaded_blocks
+                .iter()
+                .map(|block| block.canonical_root())
+                .partition(|block_root| {
+                    chain
+                        .as_ref()
+                        .map_or(false, |chain| chain.fork_choice.contains_block(block_root))
+                });
+
+            let msg = SyncMessage::ParentLookupProcessed {
+                peer_id,
+                imported_blocks,
+                failed_blocks,
+            };
+            sync_send.try_send(msg).unwrap_or_else(|_| {
+                debug!(
+                    log,
+                    "Block processor could not inform parent lookup result. Likely shutting down."
+                );
+            });
         }
-    });
+    }
 }
 
 /// Helper function to process blocks batches which only consumes the chain and blocks to process.
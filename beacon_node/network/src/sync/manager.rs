@@ -27,16 +27,24 @@
 //! fully sync'd peers. If `PARENT_FAIL_TOLERANCE` attempts at requesting the block fails, we
 //! drop the propagated block and downvote the peer that sent it to us.
 //!
-//! Block Lookup
+//! ## Block Lookup
 //!
 //! To keep the logic maintained to the syncing thread (and manage the request_ids), when a block needs to be searched for (i.e
 //! if an attestation references an unknown block) this manager can search for the block and
 //! subsequently search for parents if needed.
-
-use super::block_processor::{spawn_block_processor, BatchProcessResult, ProcessId};
+//!
+//! Only one lookup is made for each unknown block. Each peer that references the block is
+//! remembered and the block is requested from the next of these peers if a request fails, up to
+//! `SINGLE_BLOCK_FAIL_TOLERANCE` times. The attestations which referenced the block are kept by
+//! the manager and sent back to the beacon processor once the block has been imported, or
+//! dropped if the lookup fails. Attestations may also reference a block downloaded by a parent
+//! lookup, these are sent back once the parent lookup imports that block.
+
+use super::block_processor::{BatchProcessResult, ProcessId};
 use super::network_context::SyncNetworkContext;
 use super::peer_sync_info::{PeerSyncInfo, PeerSyncType};
 use super::range_sync::{BatchId, ChainId, RangeSync};
+use crate::beacon_processor::WorkEvent as BeaconWorkEvent;
 use crate::service::NetworkMessage;
 use beacon_chain::{BeaconChain, BeaconChainTypes, BlockProcessingOutcome};
 use eth2_libp2p::rpc::{methods::*, RequestId};
@@ -62,6 +70,10 @@ const PARENT_FAIL_TOLERANCE: usize = 5;
 /// canonical chain to its head once the peer connects. A chain should not appear where it's depth
 /// is further back than the most recent head slot.
 const PARENT_DEPTH_TOLERANCE: usize = SLOT_IMPORT_TOLERANCE * 2;
+/// How many peers we request a block from in a single block lookup before we give up.
+const SINGLE_BLOCK_FAIL_TOLERANCE: usize = 3;
+/// The maximum number of attestations that can be waiting for unknown blocks to be imported.
+const MAX_DEPENDENT_WORK: usize = 16_384;
 
 #[derive(Debug)]
 /// A message than can be sent to the sync manager thread.
@@ -86,9 +98,17 @@ pub enum SyncMessage<T: EthSpec> {
     /// A block with an unknown parent has been received.
     UnknownBlock(PeerId, Box<SignedBeaconBlock<T>>),
 
-    /// A peer has sent an object that references a block that is unknown. This triggers the
-    /// manager to attempt to find the block matching the unknown hash.
-    UnknownBlockHash(PeerId, Hash256),
+    /// A peer has sent an attestation that references a block that is unknown. This triggers the
+    /// manager to attempt to find the block matching the unknown hash. The `dependent_work` is sent
+    /// back to the beacon processor once the block has been imported.
+    UnknownBlockHash {
+        peer_id: PeerId,
+        block_hash: Hash256,
+        dependent_work: Box<BeaconWorkEvent<T>>,
+    },
+
+    /// A block has been imported into the beacon chain.
+    BlockImported(Hash256),
 
     /// A peer has disconnected.
     Disconnect(PeerId),
@@ -96,7 +116,7 @@ pub enum SyncMessage<T: EthSpec> {
     /// An RPC Error has occurred on a request.
     RPCError(PeerId, RequestId),
 
-    /// A batch has been processed by the block processor thread.
+    /// A batch has been processed by the beacon processor.
     BatchProcessed {
         chain_id: ChainId,
         batch_id: BatchId,
@@ -104,8 +124,14 @@ pub enum SyncMessage<T: EthSpec> {
         result: BatchProcessResult,
     },
 
-    /// A parent lookup has failed for a block given by this `peer_id`.
-    ParentLookupFailed(PeerId),
+    /// The blocks of a parent lookup, given by this `peer_id`, have been processed by the beacon
+    /// processor. The roots of the blocks that were imported are in `imported_blocks` and the
+    /// roots of the blocks that could not be imported are in `failed_blocks`.
+    ParentLookupProcessed {
+        peer_id: PeerId,
+        imported_blocks: Vec<Hash256>,
+        failed_blocks: Vec<Hash256>,
+    },
 }
 
 /// Maintains a sequential list of parents to lookup and the lookup's current state.
@@ -125,6 +151,54 @@ struct ParentRequests<T: EthSpec> {
     pending: Option<RequestId>,
 }
 
+impl<T: EthSpec> ParentRequests<T> {
+    /// The roots of the downloaded blocks and the root of the parent being searched for.
+    ///
+    /// These are the roots the lookup has added to the searched roots while it is waiting for a
+    /// parent, or once its newest block has been removed for processing.
+    fn searched_roots(&self) -> Vec<Hash256> {
+        let parent_root = self
+            .downloaded_blocks
+            .last()
+            .map(|block| block.parent_root());
+        self.downloaded_blocks
+            .iter()
+            .map(|block| block.canonical_root())
+            .chain(parent_root)
+            .collect()
+    }
+}
+
+/// The roots of the blocks being searched for, with the number of lookups searching for each
+/// root.
+///
+/// A root may be searched for by several lookups, e.g. two parent lookups with a common ancestor,
+/// so it is only no longer searched for once every lookup that added it has removed it.
+#[derive(Default)]
+struct SearchedRoots(FnvHashMap<Hash256, usize>);
+
+impl SearchedRoots {
+    /// Records that another lookup is searching for the block with `block_root`.
+    fn insert(&mut self, block_root: Hash256) {
+        *self.0.entry(block_root).or_insert(0) += 1;
+    }
+
+    /// Records that a lookup is no longer searching for the block with `block_root`.
+    fn remove(&mut self, block_root: &Hash256) {
+        if let Some(lookups) = self.0.get_mut(block_root) {
+            *lookups -= 1;
+            if *lookups == 0 {
+                self.0.remove(block_root);
+            }
+        }
+    }
+
+    /// Returns `true` if any lookup is searching for the block with `block_root`.
+    fn contains(&self, block_root: &Hash256) -> bool {
+        self.0.contains_key(block_root)
+    }
+}
+
 /// The primary object for handling and driving all the current syncing logic. It maintains the
 /// current state of the syncing process, the number of useful peers, downloaded blocks and
 /// controls the logic behind both the long-range (batch) sync and the on-going potential parent
@@ -154,28 +228,69 @@ pub struct SyncManager<T: BeaconChainTypes> {
     /// The flag allows us to determine if the peer returned data or sent us nothing.
     single_block_lookups: FnvHashMap<RequestId, SingleBlockRequest>,
 
+    /// The roots of the blocks being searched for by single block and parent lookups, including
+    /// the downloaded blocks of parent lookups which have not been imported yet.
+    searched_block_roots: SearchedRoots,
+
+    /// Attestations waiting for a block lookup to complete, by the root of the block they
+    /// reference.
+    dependent_work: FnvHashMap<Hash256, Vec<BeaconWorkEvent<T::EthSpec>>>,
+
     /// The logger for the import manager.
     log: Logger,
 
-    /// The sending part of input_channel
-    sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    /// A channel to the beacon processor, which processes parent lookups and the attestations
+    /// waiting for block lookups.
+    beacon_processor_send: mpsc::UnboundedSender<BeaconWorkEvent<T::EthSpec>>,
 }
 
 /// Object representing a single block lookup request.
 struct SingleBlockRequest {
     /// The hash of the requested block.
     pub hash: Hash256,
-    /// Whether a block was received from this request, or the peer returned an empty response.
+    /// The peer the block is currently requested from.
+    pub requested_peer: PeerId,
+    /// The other peers which referenced the block, which are asked for the block if the request
+    /// to `requested_peer` fails.
+    pub available_peers: Vec<PeerId>,
+    /// The number of peers that failed to return the block.
+    pub failed_attempts: usize,
+    /// Whether the block was received from this request, or the peer returned an empty response.
     pub block_returned: bool,
 }
 
 impl SingleBlockRequest {
-    pub fn new(hash: Hash256) -> Self {
+    pub fn new(hash: Hash256, peer_id: PeerId) -> Self {
         Self {
             hash,
+            requested_peer: peer_id,
+            available_peers: vec![],
+            failed_attempts: 0,
             block_returned: false,
         }
     }
+
+    /// Adds a peer which referenced the block as a source of the block.
+    pub fn add_peer(&mut self, peer_id: PeerId) {
+        if peer_id != self.requested_peer && !self.available_peers.contains(&peer_id) {
+            self.available_peers.push(peer_id);
+        }
+    }
+
+    /// Records that `requested_peer` failed to return the block and selects the next peer to
+    /// request it from. Returns `None` if the lookup has failed too many times or no other peer
+    /// referenced the block.
+    pub fn next_peer(&mut self) -> Option<PeerId> {
+        self.failed_attempts += 1;
+        if self.failed_attempts >= SINGLE_BLOCK_FAIL_TOLERANCE {
+            return None;
+        }
+
+        let peer_id = self.available_peers.pop()?;
+        self.requested_peer = peer_id.clone();
+        self.block_returned = false;
+        Some(peer_id)
+    }
 }
 
 /// Spawns a new `SyncManager` thread which has a weak reference to underlying beacon
@@ -186,6 +301,7 @@ pub fn spawn<T: BeaconChainTypes>(
     beacon_chain: Arc<BeaconChain<T>>,
     network_globals: Arc<NetworkGlobals<T::EthSpec>>,
     network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+    beacon_processor_send: mpsc::UnboundedSender<BeaconWorkEvent<T::EthSpec>>,
     log: slog::Logger,
 ) -> (
     mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
@@ -201,7 +317,7 @@ pub fn spawn<T: BeaconChainTypes>(
         range_sync: RangeSync::new(
             beacon_chain.clone(),
             network_globals.clone(),
-            sync_send.clone(),
+            beacon_processor_send.clone(),
             log.clone(),
         ),
         network: SyncNetworkContext::new(network_send, log.clone()),
@@ -210,8 +326,10 @@ pub fn spawn<T: BeaconChainTypes>(
         input_channel: sync_recv,
         parent_queue: SmallVec::new(),
         single_block_lookups: FnvHashMap::default(),
+        searched_block_roots: SearchedRoots::default(),
+        dependent_work: FnvHashMap::default(),
         log: log.clone(),
-        sync_send: sync_send.clone(),
+        beacon_processor_send,
     };
 
     // spawn the sync manager thread
@@ -310,14 +428,23 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                 // data was returned, not just a stream termination
 
                 // check if this is a single block lookup - i.e we were searching for a specific hash
-                let mut single_block_hash = None;
                 if let Some(block_request) = self.single_block_lookups.get_mut(&request_id) {
-                    // update the state of the lookup indicating a block was received from the peer
-                    block_request.block_returned = true;
-                    single_block_hash = Some(block_request.hash.clone());
-                }
-                if let Some(block_hash) = single_block_hash {
-                    self.single_block_lookup_response(peer_id, block, block_hash);
+                    let block_hash = block_request.hash;
+                    if block.canonical_root() == block_hash {
+                        // update the state of the lookup indicating the block was received from
+                        // the peer
+                        let first_response = !block_request.block_returned;
+                        block_request.block_returned = true;
+                        if first_response {
+                            // the search ends here, a parent lookup is started if required
+                            self.searched_block_roots.remove(&block_hash);
+                            self.single_block_lookup_response(peer_id, block);
+                        }
+                    } else {
+                        // the peer that sent this, sent us the wrong block
+                        warn!(self.log, "Peer sent incorrect block for single block lookup"; "peer_id" => format!("{}", peer_id));
+                        self.network.downvote_peer(peer_id);
+                    }
                     return;
                 }
 
@@ -350,6 +477,7 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                     if !single_block_request.block_returned {
                         warn!(self.log, "Peer didn't respond with a block it referenced"; "referenced_block_hash" => format!("{}", single_block_request.hash), "peer_id" =>  format!("{}", peer_id));
                         self.network.downvote_peer(peer_id);
+                        self.retry_single_block(single_block_request);
                     }
                     return;
                 }
@@ -377,29 +505,22 @@ impl<T: BeaconChainTypes> SyncManager<T> {
         }
     }
 
-    /// Processes the response obtained from a single block lookup search. If the block is
+    /// Processes the block obtained from a single block lookup search. If the block is
     /// processed or errors, the search ends. If the blocks parent is unknown, a block parent
     /// lookup search is started.
     fn single_block_lookup_response(
         &mut self,
         peer_id: PeerId,
         block: SignedBeaconBlock<T::EthSpec>,
-        expected_block_hash: Hash256,
     ) {
-        // verify the hash is correct and try and process the block
-        if expected_block_hash != block.canonical_root() {
-            // the peer that sent this, sent us the wrong block
-            warn!(self.log, "Peer sent incorrect block for single block lookup"; "peer_id" => format!("{}", peer_id));
-            self.network.downvote_peer(peer_id);
-            return;
-        }
+        let block_hash = block.canonical_root();
 
-        // we have the correct block, try and process it
         match BlockProcessingOutcome::shim(self.chain.process_block(block.clone())) {
             Ok(outcome) => {
                 match outcome {
                     BlockProcessingOutcome::Processed { block_root } => {
                         info!(self.log, "Processed block"; "block" => format!("{}", block_root));
+                        self.release_dependent_work(&block_root);
 
                         match self.chain.fork_choice() {
                             Ok(()) => trace!(
@@ -421,15 +542,18 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                     }
                     BlockProcessingOutcome::BlockIsAlreadyKnown => {
                         trace!(self.log, "Single block lookup already known");
+                        self.release_dependent_work(&block_hash);
                     }
                     _ => {
                         warn!(self.log, "Single block lookup failed"; "outcome" => format!("{:?}", outcome));
                         self.network.downvote_peer(peer_id);
+                        self.drop_dependent_work(&block_hash);
                     }
                 }
             }
             Err(e) => {
                 warn!(self.log, "Unexpected block processing error"; "error" => format!("{:?}", e));
+                self.drop_dependent_work(&block_hash);
             }
         }
     }
@@ -458,16 +582,9 @@ impl<T: BeaconChainTypes> SyncManager<T> {
         }
 
         // Make sure this block is not already being searched for
-        // NOTE: Potentially store a hashset of blocks for O(1) lookups
-        for parent_req in self.parent_queue.iter() {
-            if parent_req
-                .downloaded_blocks
-                .iter()
-                .any(|d_block| d_block == &block)
-            {
-                // we are already searching for this block, ignore it
-                return;
-            }
+        if self.searched_block_roots.contains(&block.canonical_root()) {
+            // we are already searching for this block, ignore it
+            return;
         }
 
         let parent_request = ParentRequests {
@@ -476,43 +593,216 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             last_submitted_peer: peer_id,
             pending: None,
         };
+        for block_root in parent_request.searched_roots() {
+            self.searched_block_roots.insert(block_root);
+        }
 
         self.request_parent(parent_request)
     }
 
     /// A request to search for a block hash has been received. This function begins a BlocksByRoot
-    /// request to find the requested block.
-    fn search_for_block(&mut self, peer_id: PeerId, block_hash: Hash256) {
+    /// request to find the requested block, unless the block is already being searched for.
+    ///
+    /// The `dependent_work` is sent to the beacon processor once the block has been imported.
+    fn search_for_block(
+        &mut self,
+        peer_id: PeerId,
+        block_hash: Hash256,
+        dependent_work: BeaconWorkEvent<T::EthSpec>,
+    ) {
         // If we are not synced, ignore this block
         if !self.network_globals.sync_state.read().is_synced() {
             return;
         }
 
-        // Do not re-request a block that is already being requested
-        if self
+        // The block may have been imported since the attestation was processed
+        if self.chain.fork_choice.contains_block(&block_hash) {
+            self.send_dependent_work(dependent_work);
+            return;
+        }
+
+        self.add_dependent_work(block_hash, dependent_work);
+
+        // Do not re-request a block that is already being requested, but remember the peer as
+        // another source of the block
+        if let Some(single_block_request) = self
             .single_block_lookups
-            .values()
+            .values_mut()
             .find(|single_block_request| single_block_request.hash == block_hash)
-            .is_some()
         {
+            single_block_request.add_peer(peer_id);
             return;
         }
 
+        // The block may be part of a parent lookup
+        if self.searched_block_roots.contains(&block_hash) {
+            return;
+        }
+
+        self.searched_block_roots.insert(block_hash);
+        self.request_single_block(SingleBlockRequest::new(block_hash, peer_id));
+    }
+
+    /// Sends a BlocksByRoot request for the block of `single_block_request` to its
+    /// `requested_peer`.
+    fn request_single_block(&mut self, single_block_request: SingleBlockRequest) {
         let request = BlocksByRootRequest {
-            block_roots: vec![block_hash],
+            block_roots: vec![single_block_request.hash],
         };
 
-        if let Ok(request_id) = self.network.blocks_by_root_request(peer_id, request) {
-            self.single_block_lookups
-                .insert(request_id, SingleBlockRequest::new(block_hash));
+        match self
+            .network
+            .blocks_by_root_request(single_block_request.requested_peer.clone(), request)
+        {
+            Ok(request_id) => {
+                self.single_block_lookups
+                    .insert(request_id, single_block_request);
+            }
+            Err(_) => self.retry_single_block(single_block_request),
+        }
+    }
+
+    /// The peer of a single block lookup failed to return the block. The block is requested from
+    /// the next peer that referenced it, unless the lookup has failed too many times.
+    fn retry_single_block(&mut self, mut single_block_request: SingleBlockRequest) {
+        if single_block_request.next_peer().is_some() {
+            return self.request_single_block(single_block_request);
+        }
+
+        debug!(self.log, "Single block lookup failed";
+            "block" => format!("{}", single_block_request.hash),
+            "failed_attempts" => single_block_request.failed_attempts,
+        );
+        self.search_failed(&single_block_request.hash);
+    }
+
+    /// A lookup has imported the block with `block_root`, the attestations waiting for it are
+    /// sent to the beacon processor.
+    fn search_succeeded(&mut self, block_root: &Hash256) {
+        self.searched_block_roots.remove(block_root);
+        self.release_dependent_work(block_root);
+    }
+
+    /// A lookup has failed to import the block with `block_root`. The attestations waiting for it
+    /// are dropped, unless another lookup is still searching for the block.
+    fn search_failed(&mut self, block_root: &Hash256) {
+        self.searched_block_roots.remove(block_root);
+        if !self.searched_block_roots.contains(block_root) {
+            self.drop_dependent_work(block_root);
+        }
+    }
+
+    /// A parent lookup has failed, every block it was searching for or has downloaded is no longer
+    /// searched for.
+    fn parent_lookup_failed(&mut self, parent_request: ParentRequests<T::EthSpec>) {
+        for block_root in parent_request.searched_roots() {
+            self.search_failed(&block_root);
+        }
+    }
+
+    /// The blocks of a parent lookup have been processed. The attestations waiting for the
+    /// imported blocks are re-processed, and the peer that sent the lookup's blocks is downvoted
+    /// if any of them could not be imported.
+    fn parent_lookup_processed(
+        &mut self,
+        peer_id: PeerId,
+        imported_blocks: Vec<Hash256>,
+        failed_blocks: Vec<Hash256>,
+    ) {
+        if !failed_blocks.is_empty() {
+            self.network.downvote_peer(peer_id);
+        }
+
+        for block_root in imported_blocks {
+            self.search_succeeded(&block_root);
+        }
+        for block_root in failed_blocks {
+            self.search_failed(&block_root);
+        }
+    }
+
+    /// Keeps `work` until the block with `block_hash` has been imported.
+    ///
+    /// If too many attestations are waiting, the attestations for blocks which are no longer being
+    /// searched for are removed first.
+    fn add_dependent_work(&mut self, block_hash: Hash256, work: BeaconWorkEvent<T::EthSpec>) {
+        if self.dependent_work_len() >= MAX_DEPENDENT_WORK {
+            self.prune_dependent_work();
+        }
+
+        if self.dependent_work_len() >= MAX_DEPENDENT_WORK {
+            debug!(self.log, "Too many attestations waiting for unknown blocks";
+                "block" => format!("{}", block_hash),
+            );
+            return;
+        }
+
+        self.dependent_work
+            .entry(block_hash)
+            .or_insert_with(Vec::new)
+            .push(work);
+    }
+
+    /// The number of attestations waiting for unknown blocks to be imported.
+    fn dependent_work_len(&self) -> usize {
+        self.dependent_work.values().map(Vec::len).sum()
+    }
+
+    /// Sends the attestations for blocks which have been imported to the beacon processor and
+    /// drops the attestations for blocks which are no longer being searched for.
+    fn prune_dependent_work(&mut self) {
+        let block_roots = self.dependent_work.keys().cloned().collect::<Vec<_>>();
+
+        for block_root in block_roots {
+            if self.chain.fork_choice.contains_block(&block_root) {
+                self.release_dependent_work(&block_root);
+            } else if !self.searched_block_roots.contains(&block_root) {
+                self.drop_dependent_work(&block_root);
+            }
+        }
+    }
+
+    /// Sends the attestations waiting for the block with `block_root` to the beacon processor.
+    fn release_dependent_work(&mut self, block_root: &Hash256) {
+        if let Some(dependent_work) = self.dependent_work.remove(block_root) {
+            debug!(self.log, "Re-processing attestations for imported block";
+                "block" => format!("{}", block_root),
+                "attestations" => dependent_work.len(),
+            );
+            for work in dependent_work {
+                self.send_dependent_work(work);
+            }
+        }
+    }
+
+    /// Drops the attestations waiting for the block with `block_root`.
+    fn drop_dependent_work(&mut self, block_root: &Hash256) {
+        if let Some(dependent_work) = self.dependent_work.remove(block_root) {
+            debug!(self.log, "Dropping attestations for unknown block";
+                "block" => format!("{}", block_root),
+                "attestations" => dependent_work.len(),
+            );
+        }
+    }
+
+    fn send_dependent_work(&mut self, work: BeaconWorkEvent<T::EthSpec>) {
+        if self.beacon_processor_send.try_send(work).is_err() {
+            warn!(
+                self.log,
+                "Failed to send attestation to the beacon processor"
+            );
         }
     }
 
     fn inject_error(&mut self, peer_id: PeerId, request_id: RequestId) {
         trace!(self.log, "Sync manager received a failed RPC");
-        // remove any single block lookups
-        if self.single_block_lookups.remove(&request_id).is_some() {
-            // this was a single block request lookup, look no further
+        // retry any single block lookups with the next peer
+        if let Some(single_block_request) = self.single_block_lookups.remove(&request_id) {
+            // this was a single block request lookup, look no further. If the block was already
+            // returned, the lookup has ended.
+            if !single_block_request.block_returned {
+                self.retry_single_block(single_block_request);
+            }
             return;
         }
 
@@ -638,6 +928,7 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             // If the last block in the queue has an unknown parent, we continue the parent
             // lookup-search.
 
+            let chain_head = parent_request.downloaded_blocks[0].canonical_root();
             let newest_block = parent_request
                 .downloaded_blocks
                 .pop()
@@ -646,19 +937,32 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                 Ok(BlockProcessingOutcome::ParentUnknown { .. }) => {
                     // need to keep looking for parents
                     // add the block back to the queue and continue the search
+                    self.searched_block_roots.insert(newest_block.parent_root());
                     parent_request.downloaded_blocks.push(newest_block);
                     self.request_parent(parent_request);
                     return;
                 }
                 Ok(BlockProcessingOutcome::Processed { .. })
                 | Ok(BlockProcessingOutcome::BlockIsAlreadyKnown { .. }) => {
-                    spawn_block_processor(
-                        Arc::downgrade(&self.chain),
-                        ProcessId::ParentLookup(parent_request.last_submitted_peer.clone()),
+                    // the remaining blocks are searched for until the beacon processor reports
+                    // the result of processing them
+                    self.search_succeeded(&newest_block.canonical_root());
+                    let process_id = ProcessId::ParentLookup(
+                        parent_request.last_submitted_peer.clone(),
+                        chain_head,
+                    );
+                    let work = BeaconWorkEvent::chain_segment(
+                        process_id,
                         parent_request.downloaded_blocks,
-                        self.sync_send.clone(),
-                        self.log.clone(),
                     );
+                    // the channel is unbounded, so this only fails once the beacon processor has
+                    // shut down
+                    if self.beacon_processor_send.try_send(work).is_err() {
+                        crit!(
+                            self.log,
+                            "Failed to send parent lookup to the beacon processor"
+                        );
+                    }
                 }
                 Ok(outcome) => {
                     // all else we consider the chain a failure and downvote the peer that sent
@@ -670,6 +974,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                     );
                     self.network
                         .downvote_peer(parent_request.last_submitted_peer.clone());
+                    // the newest block was the parent the remaining blocks were searching for
+                    self.parent_lookup_failed(parent_request);
                     return;
                 }
                 Err(e) => {
@@ -680,6 +986,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                     );
                     self.network
                         .downvote_peer(parent_request.last_submitted_peer.clone());
+                    // the newest block was the parent the remaining blocks were searching for
+                    self.parent_lookup_failed(parent_request);
                     return;
                 }
             }
@@ -701,11 +1009,13 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                 "reached maximum lookup-depth"
             };
 
+            let chain_head = parent_request.downloaded_blocks[0].canonical_root();
             debug!(self.log, "Parent import failed";
-            "block" => format!("{:?}",parent_request.downloaded_blocks[0].canonical_root()),
+            "block" => format!("{:?}", chain_head),
             "ancestors_found" => parent_request.downloaded_blocks.len(),
             "reason" => error
             );
+            self.parent_lookup_failed(parent_request);
             return; // drop the request
         }
 
@@ -728,6 +1038,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             // if the request was successful add the queue back into self
             parent_request.pending = Some(request_id);
             self.parent_queue.push(parent_request);
+        } else {
+            self.parent_lookup_failed(parent_request);
         }
     }
 }
@@ -766,8 +1078,15 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
                     SyncMessage::UnknownBlock(peer_id, block) => {
                         self.add_unknown_block(peer_id, *block);
                     }
-                    SyncMessage::UnknownBlockHash(peer_id, block_hash) => {
-                        self.search_for_block(peer_id, block_hash);
+                    SyncMessage::UnknownBlockHash {
+                        peer_id,
+                        block_hash,
+                        dependent_work,
+                    } => {
+                        self.search_for_block(peer_id, block_hash, *dependent_work);
+                    }
+                    SyncMessage::BlockImported(block_root) => {
+                        self.release_dependent_work(&block_root);
                     }
                     SyncMessage::Disconnect(peer_id) => {
                         self.peer_disconnect(&peer_id);
@@ -789,8 +1108,12 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
                             result,
                         );
                     }
-                    SyncMessage::ParentLookupFailed(peer_id) => {
-                        self.network.downvote_peer(peer_id);
+                    SyncMessage::ParentLookupProcessed {
+                        peer_id,
+                        imported_blocks,
+                        failed_blocks,
+                    } => {
+                        self.parent_lookup_processed(peer_id, imported_blocks, failed_blocks);
                     }
                 },
                 Ok(Async::NotReady) => break,
@@ -806,3 +1129,115 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
         Ok(Async::NotReady)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{BeaconBlock, MinimalEthSpec, Signature};
+
+    type E = MinimalEthSpec;
+
+    fn block(slot: u64, parent_root: Hash256) -> SignedBeaconBlock<E> {
+        let mut message = BeaconBlock::empty(&E::default_spec());
+        message.slot = Slot::new(slot);
+        message.parent_root = parent_root;
+        SignedBeaconBlock {
+            message,
+            signature: Signature::empty_signature(),
+        }
+    }
+
+    fn parent_request(block: SignedBeaconBlock<E>) -> ParentRequests<E> {
+        ParentRequests {
+            downloaded_blocks: vec![block],
+            failed_attempts: 0,
+            last_submitted_peer: PeerId::random(),
+            pending: None,
+        }
+    }
+
+    #[test]
+    fn single_block_request_deduplicates_peers() {
+        let requested_peer = PeerId::random();
+        let other_peer = PeerId::random();
+        let mut request = SingleBlockRequest::new(Hash256::zero(), requested_peer.clone());
+
+        request.add_peer(requested_peer.clone());
+        request.add_peer(other_peer.clone());
+        request.add_peer(other_peer.clone());
+
+        assert_eq!(request.requested_peer, requested_peer);
+        assert_eq!(request.available_peers, vec![other_peer]);
+    }
+
+    #[test]
+    fn single_block_request_retries_other_peers() {
+        let first_peer = PeerId::random();
+        let second_peer = PeerId::random();
+        let mut request = SingleBlockRequest::new(Hash256::zero(), first_peer.clone());
+        request.add_peer(second_peer.clone());
+        request.block_returned = true;
+
+        assert_eq!(request.next_peer(), Some(second_peer.clone()));
+        assert_eq!(request.requested_peer, second_peer);
+        assert_eq!(request.failed_attempts, 1);
+        assert!(!request.block_returned);
+
+        // no other peer referenced the block
+        assert_eq!(request.next_peer(), None);
+        assert_eq!(request.failed_attempts, 2);
+    }
+
+    #[test]
+    fn single_block_request_fails_after_tolerance() {
+        let mut request = SingleBlockRequest::new(Hash256::zero(), PeerId::random());
+        for _ in 0..SINGLE_BLOCK_FAIL_TOLERANCE {
+            request.add_peer(PeerId::random());
+        }
+
+        for _ in 1..SINGLE_BLOCK_FAIL_TOLERANCE {
+            assert!(request.next_peer().is_some());
+        }
+        assert_eq!(request.next_peer(), None);
+        assert_eq!(request.failed_attempts, SINGLE_BLOCK_FAIL_TOLERANCE);
+    }
+
+    #[test]
+    fn overlapping_parent_lookups_release_only_their_roots() {
+        // `grandparent` <- `parent` <- `child`, where `grandparent` is unknown
+        let grandparent = block(1, Hash256::zero());
+        let parent = block(2, grandparent.canonical_root());
+        let child = block(3, parent.canonical_root());
+        let mut searched = SearchedRoots::default();
+
+        // one lookup starts from `parent`, the other from `child` and searches for `parent`
+        let mut parent_lookup = parent_request(parent.clone());
+        let mut child_lookup = parent_request(child.clone());
+        for block_root in parent_lookup
+            .searched_roots()
+            .into_iter()
+            .chain(child_lookup.searched_roots())
+        {
+            searched.insert(block_root);
+        }
+
+        // the child lookup downloads `parent`, which fails to be processed
+        child_lookup.downloaded_blocks.push(parent.clone());
+        child_lookup.downloaded_blocks.pop();
+        for block_root in child_lookup.searched_roots() {
+            searched.remove(&block_root);
+        }
+        assert!(!searched.contains(&child.canonical_root()));
+        assert!(searched.contains(&parent.canonical_root()));
+        assert!(searched.contains(&grandparent.canonical_root()));
+
+        // the parent lookup downloads and imports `grandparent`, then its remaining blocks
+        parent_lookup.downloaded_blocks.push(grandparent.clone());
+        let newest_block = parent_lookup.downloaded_blocks.pop().unwrap();
+        searched.remove(&newest_block.canonical_root());
+        for block in &parent_lookup.downloaded_blocks {
+            searched.remove(&block.canonical_root());
+        }
+        assert!(searched.0.is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 //! Syncing for lighthouse.
 //!
 //! Stores the various syncing methods for the beacon chain.
-mod block_processor;
+pub mod block_processor;
 pub mod manager;
 mod network_context;
 mod peer_sync_info;
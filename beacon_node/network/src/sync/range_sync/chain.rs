@@ -1,7 +1,7 @@
 use super::batch::{Batch, BatchId, PendingBatches};
-use crate::sync::block_processor::{spawn_block_processor, BatchProcessResult, ProcessId};
+use crate::beacon_processor::WorkEvent as BeaconWorkEvent;
+use crate::sync::block_processor::{BatchProcessResult, ProcessId};
 use crate::sync::network_context::SyncNetworkContext;
-use crate::sync::SyncMessage;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::rpc::RequestId;
 use eth2_libp2p::PeerId;
@@ -84,9 +84,9 @@ pub struct SyncingChain<T: BeaconChainTypes> {
     /// The current processing batch, if any.
     current_processing_batch: Option<Batch<T::EthSpec>>,
 
-    /// A send channel to the sync manager. This is given to the batch processor thread to report
-    /// back once batch processing has completed.
-    sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    /// A send channel to the beacon processor, which processes batches and reports back to the
+    /// sync manager once batch processing has completed.
+    beacon_processor_send: mpsc::UnboundedSender<BeaconWorkEvent<T::EthSpec>>,
 
     /// A reference to the underlying beacon chain.
     chain: Arc<BeaconChain<T>>,
@@ -110,7 +110,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         target_head_slot: Slot,
         target_head_root: Hash256,
         peer_id: PeerId,
-        sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+        beacon_processor_send: mpsc::UnboundedSender<BeaconWorkEvent<T::EthSpec>>,
         chain: Arc<BeaconChain<T>>,
         log: slog::Logger,
     ) -> Self {
@@ -130,7 +130,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             to_be_processed_id: BatchId(1),
             state: ChainSyncingState::Stopped,
             current_processing_batch: None,
-            sync_send,
+            beacon_processor_send,
             chain,
             log,
         }
@@ -257,13 +257,11 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         let downloaded_blocks = std::mem::replace(&mut batch.downloaded_blocks, Vec::new());
         let process_id = ProcessId::RangeBatchId(self.id.clone(), batch.id.clone());
         self.current_processing_batch = Some(batch);
-        spawn_block_processor(
-            Arc::downgrade(&self.chain.clone()),
-            process_id,
-            downloaded_blocks,
-            self.sync_send.clone(),
-            self.log.clone(),
-        );
+        let work = BeaconWorkEvent::chain_segment(process_id, downloaded_blocks);
+        // the channel is unbounded, so this only fails once the beacon processor has shut down
+        if self.beacon_processor_send.try_send(work).is_err() {
+            crit!(self.log, "Failed to send batch to the beacon processor"; "chain_id" => self.id);
+        }
     }
 
     /// The block processor has completed processing a batch. This function handles the result
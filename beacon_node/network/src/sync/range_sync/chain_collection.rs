@@ -4,7 +4,7 @@
 //! with this struct to to simplify the logic of the other layers of sync.
 
 use super::chain::{ChainSyncingState, SyncingChain};
-use crate::sync::manager::SyncMessage;
+use crate::beacon_processor::WorkEvent as BeaconWorkEvent;
 use crate::sync::network_context::SyncNetworkContext;
 use crate::sync::PeerSyncInfo;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
@@ -302,7 +302,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
         target_head: Hash256,
         target_slot: Slot,
         peer_id: PeerId,
-        sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+        beacon_processor_send: mpsc::UnboundedSender<BeaconWorkEvent<T::EthSpec>>,
     ) {
         let chain_id = rand::random();
         self.finalized_chains.push(SyncingChain::new(
@@ -311,7 +311,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
             target_slot,
             target_head,
             peer_id,
-            sync_send,
+            beacon_processor_send,
             self.beacon_chain.clone(),
             self.log.clone(),
         ));
@@ -326,7 +326,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
         target_head: Hash256,
         target_slot: Slot,
         peer_id: PeerId,
-        sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+        beacon_processor_send: mpsc::UnboundedSender<BeaconWorkEvent<T::EthSpec>>,
     ) {
         // remove the peer from any other head chains
 
@@ -342,7 +342,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
             target_slot,
             target_head,
             peer_id,
-            sync_send,
+            beacon_processor_send,
             self.beacon_chain.clone(),
             self.log.clone(),
         );
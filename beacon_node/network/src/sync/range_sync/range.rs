@@ -43,8 +43,8 @@ use super::chain::{ChainId, ProcessingResult};
 use super::chain_collection::{ChainCollection, RangeSyncState};
 use super::sync_type::RangeSyncType;
 use super::BatchId;
+use crate::beacon_processor::WorkEvent as BeaconWorkEvent;
 use crate::sync::block_processor::BatchProcessResult;
-use crate::sync::manager::SyncMessage;
 use crate::sync::network_context::SyncNetworkContext;
 use crate::sync::PeerSyncInfo;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
@@ -69,9 +69,9 @@ pub struct RangeSync<T: BeaconChainTypes> {
     /// finalized chain(s) complete, these peer's get STATUS'ed to update their head slot before
     /// the head chains are formed and downloaded.
     awaiting_head_peers: HashSet<PeerId>,
-    /// The sync manager channel, allowing the batch processor thread to callback the sync task
-    /// once complete.
-    sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    /// A channel to the beacon processor, which processes downloaded batches and reports the
+    /// result back to the sync manager.
+    beacon_processor_send: mpsc::UnboundedSender<BeaconWorkEvent<T::EthSpec>>,
     /// The syncing logger.
     log: slog::Logger,
 }
@@ -80,14 +80,14 @@ impl<T: BeaconChainTypes> RangeSync<T> {
     pub fn new(
         beacon_chain: Arc<BeaconChain<T>>,
         network_globals: Arc<NetworkGlobals<T::EthSpec>>,
-        sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+        beacon_processor_send: mpsc::UnboundedSender<BeaconWorkEvent<T::EthSpec>>,
         log: slog::Logger,
     ) -> Self {
         RangeSync {
             beacon_chain: beacon_chain.clone(),
             chains: ChainCollection::new(beacon_chain, network_globals, log.clone()),
             awaiting_head_peers: HashSet::new(),
-            sync_send,
+            beacon_processor_send,
             log,
         }
     }
@@ -181,7 +181,7 @@ impl<T: BeaconChainTypes> RangeSync<T> {
                         remote_info.finalized_root,
                         remote_finalized_slot,
                         peer_id,
-                        self.sync_send.clone(),
+                        self.beacon_processor_send.clone(),
                     );
                     self.chains.update_finalized(network);
                     // update the global sync state
@@ -228,7 +228,7 @@ impl<T: BeaconChainTypes> RangeSync<T> {
                         remote_info.head_root,
                         remote_info.head_slot,
                         peer_id,
-                        self.sync_send.clone(),
+                        self.beacon_processor_send.clone(),
                     );
                 }
                 self.chains.update_finalized(network);
@@ -0,0 +1,106 @@
+//! Peer management endpoints which are only served to localhost, when enabled with `--http-admin`.
+//!
+//! Each endpoint takes a JSON string and forwards the request to the network service, the
+//! response does not indicate whether the action succeeded.
+
+use crate::helpers::check_content_type_for_json;
+use crate::response_builder::ResponseBuilder;
+use crate::{ApiError, BoxFut, NetworkChannel};
+use eth2_libp2p::{Enr, PeerId, TrustedPeer};
+use futures::{Future, Stream};
+use hyper::{Body, Request};
+use network::NetworkMessage;
+use std::net::IpAddr;
+use types::EthSpec;
+
+/// HTTP handler to dial a base64-encoded ENR (prefixed with `enr:`) or a libp2p multiaddr.
+pub fn post_dial<T: EthSpec>(req: Request<Body>, network_chan: NetworkChannel<T>) -> BoxFut {
+    post_network_message(req, network_chan, |peer| {
+        if peer.starts_with("enr:") {
+            let enr = peer
+                .parse::<Enr>()
+                .map_err(|_| ApiError::BadRequest(format!("Invalid ENR: {}", peer)))?;
+            Ok(NetworkMessage::DialEnr { enr })
+        } else {
+            let multiaddr = peer
+                .parse()
+                .map_err(|_| ApiError::BadRequest(format!("Invalid Multiaddr: {}", peer)))?;
+            Ok(NetworkMessage::Dial { multiaddr })
+        }
+    })
+}
+
+/// HTTP handler to disconnect a peer id. The peer is briefly banned, so that it does not
+/// immediately reconnect.
+pub fn post_disconnect<T: EthSpec>(req: Request<Body>, network_chan: NetworkChannel<T>) -> BoxFut {
+    post_network_message(req, network_chan, |peer| {
+        parse_peer_id(&peer).map(|peer_id| NetworkMessage::Disconnect { peer_id })
+    })
+}
+
+/// HTTP handler to ban a peer id or an IP address until it is unbanned.
+pub fn post_ban<T: EthSpec>(req: Request<Body>, network_chan: NetworkChannel<T>) -> BoxFut {
+    post_network_message(req, network_chan, |peer| match peer.parse::<IpAddr>() {
+        Ok(ip) => Ok(NetworkMessage::BanIp { ip }),
+        Err(_) => parse_peer_id(&peer).map(|peer_id| NetworkMessage::BanPeer { peer_id }),
+    })
+}
+
+/// HTTP handler to unban a peer id or an IP address.
+pub fn post_unban<T: EthSpec>(req: Request<Body>, network_chan: NetworkChannel<T>) -> BoxFut {
+    post_network_message(req, network_chan, |peer| match peer.parse::<IpAddr>() {
+        Ok(ip) => Ok(NetworkMessage::UnbanIp { ip }),
+        Err(_) => parse_peer_id(&peer).map(|peer_id| NetworkMessage::UnbanPeer { peer_id }),
+    })
+}
+
+/// HTTP handler to add a trusted peer, given as an ENR or a multiaddr which ends in
+/// `/p2p/<peer-id>`.
+pub fn post_trusted_peer<T: EthSpec>(
+    req: Request<Body>,
+    network_chan: NetworkChannel<T>,
+) -> BoxFut {
+    post_network_message(req, network_chan, |peer| {
+        peer.parse::<TrustedPeer>()
+            .map(|trusted_peer| NetworkMessage::AddTrustedPeer { trusted_peer })
+            .map_err(ApiError::BadRequest)
+    })
+}
+
+/// Parses the JSON string in the body of `req` into a `NetworkMessage` with `to_message`, and
+/// sends it to the network service.
+fn post_network_message<T: EthSpec, F>(
+    req: Request<Body>,
+    mut network_chan: NetworkChannel<T>,
+    to_message: F,
+) -> BoxFut
+where
+    F: FnOnce(String) -> Result<NetworkMessage<T>, ApiError> + Send + 'static,
+{
+    try_future!(check_content_type_for_json(&req));
+    let response_builder = ResponseBuilder::new(&req);
+
+    let body = req.into_body();
+    Box::new(
+        body.concat2()
+            .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+            .and_then(|chunks| {
+                serde_json::from_slice::<String>(&chunks).map_err(|e| {
+                    ApiError::BadRequest(format!("Unable to parse JSON into a string: {:?}", e))
+                })
+            })
+            .and_then(to_message)
+            .and_then(move |message| {
+                network_chan.try_send(message).map_err(|e| {
+                    ApiError::ServerError(format!("Unable to send message to the network: {:?}", e))
+                })
+            })
+            .and_then(|_| response_builder?.body_no_ssz(&())),
+    )
+}
+
+fn parse_peer_id(peer_id: &str) -> Result<PeerId, ApiError> {
+    peer_id
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid peer id: {}", peer_id)))
+}
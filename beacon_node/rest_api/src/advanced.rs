@@ -1,9 +1,19 @@
 use crate::response_builder::ResponseBuilder;
-use crate::ApiResult;
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use crate::{ApiError, ApiResult, UrlQuery};
+use beacon_chain::{BeaconChain, BeaconChainTypes, BlockProductionReport, StateSkipConfig};
 use hyper::{Body, Request};
 use operation_pool::PersistedOperationPool;
+use serde::Serialize;
 use std::sync::Arc;
+use types::{BeaconBlock, EthSpec, Signature};
+
+/// A block produced by the beacon node, alongside a report on its contents.
+#[derive(Serialize)]
+#[serde(bound = "T: EthSpec")]
+pub struct BlockAndReport<T: EthSpec> {
+    pub block: BeaconBlock<T>,
+    pub report: BlockProductionReport,
+}
 
 /// Returns the `proto_array` fork choice struct, encoded as JSON.
 ///
@@ -26,3 +36,61 @@ pub fn get_operation_pool<T: BeaconChainTypes>(
         &beacon_chain.op_pool,
     ))
 }
+
+/// Produces (but does not publish) a block at the given slot and returns it alongside a report on
+/// the operations that were packed into it and the reward it would earn its proposer.
+///
+/// The `randao_reveal` is optional, since an invalid reveal does not affect the contents of the
+/// block. Useful for comparing the quality of block production across nodes.
+pub fn get_block_production<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot = query.slot()?;
+    let randao_reveal = if query.first_of_opt(&["randao_reveal"]).is_some() {
+        query.randao_reveal()?
+    } else {
+        Signature::empty_signature()
+    };
+
+    let (block, _state, report) = beacon_chain
+        .produce_block_with_report(randao_reveal, slot)
+        .map_err(|e| {
+            ApiError::ServerError(format!(
+                "Beacon node is not able to produce a block: {:?}",
+                e
+            ))
+        })?;
+
+    ResponseBuilder::new(&req)?.body_no_ssz(&BlockAndReport { block, report })
+}
+
+/// Returns a description of the eth1 data voting at the given slot (or the current slot, if none
+/// is supplied): the candidate eth1 blocks, the votes already in the state, the vote this node
+/// would cast and the progress of the deposit cache.
+///
+/// Returns `null` if the node is not using eth1 blocks to vote (e.g., it uses the dummy backend).
+pub fn get_eth1_voting<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot = if query.first_of_opt(&["slot"]).is_some() {
+        query.slot()?
+    } else {
+        beacon_chain.slot()?
+    };
+
+    let eth1_chain = beacon_chain
+        .eth1_chain
+        .as_ref()
+        .ok_or_else(|| ApiError::ServerError("Beacon node has no eth1 chain".to_string()))?;
+
+    let state = beacon_chain.state_at_slot(slot, StateSkipConfig::WithoutStateRoots)?;
+
+    ResponseBuilder::new(&req)?
+        .body_no_ssz(&eth1_chain.eth1_voting_info(&state, &beacon_chain.spec))
+}
@@ -38,6 +38,8 @@ pub struct Config {
     pub listen_address: Ipv4Addr,
     /// The port the REST API HTTP server will listen on.
     pub port: u16,
+    /// Enable the `/lighthouse/admin` endpoints. These are only served to requests from localhost.
+    pub admin_enabled: bool,
 }
 
 impl Default for Config {
@@ -46,6 +48,7 @@ impl Default for Config {
             enabled: false,
             listen_address: Ipv4Addr::new(127, 0, 0, 1),
             port: 5052,
+            admin_enabled: false,
         }
     }
 }
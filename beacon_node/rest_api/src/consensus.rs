@@ -4,10 +4,15 @@ use crate::{ApiError, ApiResult, BoxFut, UrlQuery};
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use futures::{Future, Stream};
 use hyper::{Body, Request};
-use rest_types::{IndividualVotesRequest, IndividualVotesResponse};
+use rest_types::{
+    IndividualRewards, IndividualRewardsRequest, IndividualRewardsResponse, IndividualVotesRequest,
+    IndividualVotesResponse,
+};
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
-use state_processing::per_epoch_processing::{TotalBalances, ValidatorStatuses};
+use state_processing::per_epoch_processing::{
+    get_attestation_deltas, get_proposer_deltas, TotalBalances, ValidatorStatuses,
+};
 use std::sync::Arc;
 use types::EthSpec;
 
@@ -140,3 +145,92 @@ pub fn post_individual_votes<T: BeaconChainTypes>(
 
     Box::new(future)
 }
+
+/// HTTP handler to return the rewards and penalties applied to the given validators for their
+/// attestations during some epoch.
+///
+/// The rewards are computed by replaying the epoch transition in which they were applied.
+pub fn post_individual_rewards<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> BoxFut {
+    let response_builder = ResponseBuilder::new(&req);
+
+    let future = req
+        .into_body()
+        .concat2()
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+        .and_then(|chunks| {
+            serde_json::from_slice::<IndividualRewardsRequest>(&chunks).map_err(|e| {
+                ApiError::BadRequest(format!(
+                    "Unable to parse JSON into IndividualRewardsRequest: {:?}",
+                    e
+                ))
+            })
+        })
+        .and_then(move |body| {
+            let epoch = body.epoch;
+
+            // Rewards for attestations made in `epoch` are applied during the transition from
+            // `epoch + 1` to `epoch + 2`. This is the last slot prior to that transition.
+            let target_slot = (epoch + 2).start_slot(T::EthSpec::slots_per_epoch()) - 1;
+
+            let (_root, mut state) = state_at_slot(&beacon_chain, target_slot)?;
+            let spec = &beacon_chain.spec;
+
+            state.build_all_committee_caches(spec)?;
+
+            let mut validator_statuses = ValidatorStatuses::new(&state, spec)?;
+            validator_statuses.process_attestations(&state, spec)?;
+
+            let attestation_deltas = get_attestation_deltas(&state, &validator_statuses, spec)
+                .map_err(|e| {
+                    ApiError::ServerError(format!("Unable to compute attestation deltas: {:?}", e))
+                })?;
+            let proposer_deltas =
+                get_proposer_deltas(&state, &validator_statuses, spec).map_err(|e| {
+                    ApiError::ServerError(format!("Unable to compute proposer deltas: {:?}", e))
+                })?;
+
+            let rewards = body
+                .validator_indices
+                .into_iter()
+                .map(|validator_index| {
+                    let i = validator_index as usize;
+                    let rewards = match (attestation_deltas.get(i), proposer_deltas.get(i)) {
+                        (Some(attestation_delta), Some(proposer_delta)) => {
+                            Some(IndividualRewards::from_deltas(
+                                attestation_delta.clone(),
+                                proposer_delta.clone(),
+                            ))
+                        }
+                        _ => None,
+                    };
+
+                    IndividualRewardsResponse {
+                        epoch,
+                        validator_index,
+                        rewards,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            Ok(rewards)
+        })
+        .and_then(|rewards| response_builder?.body_no_ssz(&rewards));
+
+    Box::new(future)
+}
+
+/// HTTP handler to return the rewards earned by the proposer of the block with the given root.
+pub fn get_block_rewards<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+    let block_root = parse_root(&query.only_one("block_root")?)?;
+
+    let block_reward = beacon_chain.compute_block_reward_at_root(block_root)?;
+
+    ResponseBuilder::new(&req)?.body_no_ssz(&block_reward)
+}
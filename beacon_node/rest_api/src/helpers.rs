@@ -7,6 +7,7 @@ use http::header;
 use hyper::{Body, Request};
 use network::NetworkMessage;
 use ssz::Decode;
+use std::net::IpAddr;
 use store::{iter::AncestorIter, Store};
 use types::{
     Attestation, BeaconState, ChainSpec, CommitteeIndex, Epoch, EthSpec, Hash256, RelativeEpoch,
@@ -300,6 +301,15 @@ pub fn publish_aggregate_attestations_to_network<T: BeaconChainTypes + 'static>(
     Ok(())
 }
 
+/// Returns `true` if `ip` is a loopback address, including an IPv4 loopback address which is
+/// mapped to IPv6 (as seen by a dual-stack listener).
+pub fn is_loopback(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback(),
+        IpAddr::V6(ip) => ip.is_loopback() || ip.to_ipv4().map_or(false, |ip| ip.is_loopback()),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -328,4 +338,19 @@ mod test {
         assert_eq!(parse_slot("10000000"), Ok(Slot::new(10_000_000)));
         assert!(parse_slot("cats").is_err());
     }
+
+    #[test]
+    fn is_loopback_works() {
+        let is_loopback = |ip: &str| is_loopback(&ip.parse().expect("should parse ip"));
+
+        assert!(is_loopback("127.0.0.1"));
+        assert!(is_loopback("127.0.0.2"));
+        assert!(is_loopback("::1"));
+        assert!(is_loopback("::ffff:127.0.0.1"));
+        assert!(!is_loopback("0.0.0.0"));
+        assert!(!is_loopback("192.168.0.1"));
+        assert!(!is_loopback("::"));
+        assert!(!is_loopback("::ffff:192.168.0.1"));
+        assert!(!is_loopback("2001:db8::1"));
+    }
 }
@@ -4,6 +4,7 @@ mod macros;
 extern crate lazy_static;
 extern crate network as client_network;
 
+mod admin;
 mod advanced;
 mod beacon;
 pub mod config;
@@ -64,9 +65,12 @@ pub fn start_server<T: BeaconChainTypes>(
 ) -> Result<(oneshot::Sender<()>, SocketAddr), hyper::Error> {
     let inner_log = log.clone();
     let eth2_config = Arc::new(eth2_config);
+    let admin_enabled = config.admin_enabled;
 
     // Define the function that will build the request handler.
-    let make_service = make_service_fn(move |_socket: &AddrStream| {
+    let make_service = make_service_fn(move |socket: &AddrStream| {
+        // The admin endpoints are only served to localhost.
+        let admin_enabled = admin_enabled && helpers::is_loopback(&socket.remote_addr().ip());
         let beacon_chain = beacon_chain.clone();
         let log = inner_log.clone();
         let eth2_config = eth2_config.clone();
@@ -85,6 +89,7 @@ pub fn start_server<T: BeaconChainTypes>(
                 log.clone(),
                 db_path.clone(),
                 freezer_db_path.clone(),
+                admin_enabled,
             )
         })
     });
@@ -2,6 +2,7 @@
 
 use crate::response_builder::ResponseBuilder;
 use crate::ApiResult;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::{NetworkGlobals, PeerInfo};
 use hyper::{Body, Request};
 use serde::Serialize;
@@ -47,6 +48,24 @@ pub fn connected_peers<T: EthSpec>(
     ResponseBuilder::new(&req)?.body_no_ssz(&peers)
 }
 
+/// Returns the external address of the ports mapped on the NAT gateway, or `null` if no ports are
+/// mapped.
+pub fn port_mapping<T: EthSpec>(
+    req: Request<Body>,
+    network_globals: Arc<NetworkGlobals<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.body_no_ssz(&network_globals.port_mapping())
+}
+
+/// Returns the per-epoch performance of the validators tracked by the validator monitor.
+pub fn validator_monitor<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?
+        .body_no_ssz(&beacon_chain.validator_monitor.get_monitored_validators())
+}
+
 /// Information returned by `peers` and `connected_peers`.
 #[derive(Clone, Debug, Serialize)]
 #[serde(bound = "T: EthSpec")]
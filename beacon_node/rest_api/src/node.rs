@@ -1,10 +1,12 @@
 use crate::response_builder::ResponseBuilder;
-use crate::ApiResult;
+use crate::{ApiError, ApiResult};
+use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::{types::SyncState, NetworkGlobals};
 use hyper::{Body, Request};
 use rest_types::{SyncingResponse, SyncingStatus};
 use std::sync::Arc;
-use types::{EthSpec, Slot};
+use store::Store;
+use types::{EthSpec, Hash256, Slot};
 use version;
 
 /// Read the version string from the current Lighthouse build.
@@ -12,6 +14,63 @@ pub fn get_version(req: Request<Body>) -> ApiResult {
     ResponseBuilder::new(&req)?.body_no_ssz(&version::version())
 }
 
+/// Read the health of the eth1 endpoints and the deposit contract.
+///
+/// Returns `null` if the node is not connected to an eth1 node.
+pub fn get_eth1_status<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let status = beacon_chain
+        .eth1_chain
+        .as_ref()
+        .and_then(|eth1_chain| eth1_chain.eth1_status());
+
+    ResponseBuilder::new(&req)?.body_no_ssz(&status)
+}
+
+/// Read a snapshot of the deposit tree containing the deposits of the latest finalized state.
+///
+/// The snapshot can be used to bootstrap the deposit cache of another node.
+pub fn get_eth1_deposit_snapshot<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let finalized_root = beacon_chain.head_info()?.finalized_checkpoint.root;
+    let finalized_root = if finalized_root == Hash256::zero() {
+        beacon_chain.genesis_block_root
+    } else {
+        finalized_root
+    };
+
+    let finalized_block = beacon_chain
+        .store
+        .get_block(&finalized_root)?
+        .ok_or_else(|| {
+            ApiError::ServerError(format!("Finalized block {:?} is missing", finalized_root))
+        })?;
+    let finalized_state = beacon_chain
+        .store
+        .get_state(
+            &finalized_block.message.state_root,
+            Some(finalized_block.message.slot),
+        )?
+        .ok_or_else(|| {
+            ApiError::ServerError(format!(
+                "Finalized state {:?} is missing",
+                finalized_block.message.state_root
+            ))
+        })?;
+
+    let snapshot = beacon_chain
+        .eth1_chain
+        .as_ref()
+        .and_then(|eth1_chain| eth1_chain.deposit_snapshot(finalized_state.eth1_deposit_index))
+        .ok_or_else(|| ApiError::NotFound("No deposit snapshot is available".to_string()))?;
+
+    ResponseBuilder::new(&req)?.body_no_ssz(&snapshot)
+}
+
 pub fn syncing<T: EthSpec>(
     req: Request<Body>,
     network: Arc<NetworkGlobals<T>>,
@@ -1,6 +1,6 @@
 use crate::{
-    advanced, beacon, consensus, error::ApiError, helpers, lighthouse, metrics, network, node,
-    spec, validator, BoxFut, NetworkChannel,
+    admin, advanced, beacon, consensus, error::ApiError, helpers, lighthouse, metrics, network,
+    node, spec, validator, BoxFut, NetworkChannel,
 };
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_config::Eth2Config;
@@ -32,6 +32,7 @@ pub fn route<T: BeaconChainTypes>(
     local_log: slog::Logger,
     db_path: PathBuf,
     freezer_db_path: PathBuf,
+    admin_enabled: bool,
 ) -> impl Future<Item = Response<Body>, Error = Error> {
     metrics::inc_counter(&metrics::REQUEST_COUNT);
     let timer = metrics::start_timer(&metrics::REQUEST_RESPONSE_TIME);
@@ -42,8 +43,21 @@ pub fn route<T: BeaconChainTypes>(
     let log = local_log.clone();
     let request_result: Box<dyn Future<Item = Response<_>, Error = _> + Send> =
         match (req.method(), path.as_ref()) {
+            // Peer management is only permitted to localhost, when enabled
+            (_, path) if path.starts_with("/lighthouse/admin/") && !admin_enabled => {
+                Box::new(futures::future::err(ApiError::Forbidden(
+                    "The admin endpoints are disabled or not served to this address.".to_owned(),
+                )))
+            }
+
             // Methods for Client
             (&Method::GET, "/node/version") => into_boxfut(node::get_version(req)),
+            (&Method::GET, "/node/eth1") => {
+                into_boxfut(node::get_eth1_status::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/node/eth1/deposit_snapshot") => {
+                into_boxfut(node::get_eth1_deposit_snapshot::<T>(req, beacon_chain))
+            }
             (&Method::GET, "/node/syncing") => {
                 // inform the current slot, or set to 0
                 let current_slot = beacon_chain
@@ -183,6 +197,12 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::POST, "/consensus/individual_votes") => {
                 consensus::post_individual_votes::<T>(req, beacon_chain)
             }
+            (&Method::POST, "/consensus/individual_rewards") => {
+                consensus::post_individual_rewards::<T>(req, beacon_chain)
+            }
+            (&Method::GET, "/consensus/block_rewards") => {
+                into_boxfut(consensus::get_block_rewards::<T>(req, beacon_chain))
+            }
 
             // Methods for bootstrap and checking configuration
             (&Method::GET, "/spec") => into_boxfut(spec::get_spec::<T>(req, beacon_chain)),
@@ -203,6 +223,12 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::GET, "/advanced/operation_pool") => {
                 into_boxfut(advanced::get_operation_pool::<T>(req, beacon_chain))
             }
+            (&Method::GET, "/advanced/block_production") => {
+                into_boxfut(advanced::get_block_production::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/advanced/eth1_voting") => {
+                into_boxfut(advanced::get_eth1_voting::<T>(req, beacon_chain))
+            }
             (&Method::GET, "/metrics") => into_boxfut(metrics::get_prometheus::<T>(
                 req,
                 beacon_chain,
@@ -220,6 +246,27 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::GET, "/lighthouse/connected_peers") => into_boxfut(
                 lighthouse::connected_peers::<T::EthSpec>(req, network_globals),
             ),
+            (&Method::GET, "/lighthouse/port_mapping") => {
+                into_boxfut(lighthouse::port_mapping::<T::EthSpec>(req, network_globals))
+            }
+            (&Method::GET, "/lighthouse/validator_monitor") => {
+                into_boxfut(lighthouse::validator_monitor::<T>(req, beacon_chain))
+            }
+            (&Method::POST, "/lighthouse/admin/peers/dial") => {
+                admin::post_dial::<T::EthSpec>(req, network_channel)
+            }
+            (&Method::POST, "/lighthouse/admin/peers/disconnect") => {
+                admin::post_disconnect::<T::EthSpec>(req, network_channel)
+            }
+            (&Method::POST, "/lighthouse/admin/peers/ban") => {
+                admin::post_ban::<T::EthSpec>(req, network_channel)
+            }
+            (&Method::POST, "/lighthouse/admin/peers/unban") => {
+                admin::post_unban::<T::EthSpec>(req, network_channel)
+            }
+            (&Method::POST, "/lighthouse/admin/peers/trusted") => {
+                admin::post_trusted_peer::<T::EthSpec>(req, network_channel)
+            }
             _ => Box::new(futures::future::err(ApiError::NotFound(
                 "Request path and/or method not found.".to_owned(),
             ))),
@@ -1,3 +1,4 @@
+use crate::advanced::BlockAndReport;
 use crate::helpers::{
     check_content_type_for_json, publish_aggregate_attestations_to_network,
     publish_beacon_block_to_network, publish_raw_attestations_to_network,
@@ -6,7 +7,7 @@ use crate::response_builder::ResponseBuilder;
 use crate::{ApiError, ApiResult, BoxFut, NetworkChannel, UrlQuery};
 use beacon_chain::{
     AttestationProcessingOutcome, AttestationType, BeaconChain, BeaconChainTypes, BlockError,
-    StateSkipConfig,
+    BlockProductionError, StateSkipConfig,
 };
 use bls::PublicKeyBytes;
 use futures::{Future, Stream};
@@ -266,6 +267,9 @@ fn return_validator_duties<T: BeaconChainTypes>(
 }
 
 /// HTTP Handler to produce a new BeaconBlock from the current state, ready to be signed by a validator.
+///
+/// If the `report=true` query parameter is supplied, the block is returned alongside a
+/// `BlockProductionReport` as JSON.
 pub fn get_new_beacon_block<T: BeaconChainTypes>(
     req: Request<Body>,
     beacon_chain: Arc<BeaconChain<T>>,
@@ -275,23 +279,39 @@ pub fn get_new_beacon_block<T: BeaconChainTypes>(
 
     let slot = query.slot()?;
     let randao_reveal = query.randao_reveal()?;
+    let include_report = query
+        .first_of_opt(&["report"])
+        .map_or(false, |(_key, value)| value == "true");
+
+    let block_production_error = |e: BlockProductionError| {
+        error!(
+            log,
+            "Error whilst producing block";
+            "error" => format!("{:?}", e)
+        );
+
+        ApiError::ServerError(format!(
+            "Beacon node is not able to produce a block: {:?}",
+            e
+        ))
+    };
+
+    if include_report {
+        let (new_block, _state, report) = beacon_chain
+            .produce_block_with_report(randao_reveal, slot)
+            .map_err(block_production_error)?;
+
+        ResponseBuilder::new(&req)?.body_no_ssz(&BlockAndReport {
+            block: new_block,
+            report,
+        })
+    } else {
+        let (new_block, _state) = beacon_chain
+            .produce_block(randao_reveal, slot)
+            .map_err(block_production_error)?;
 
-    let (new_block, _state) = beacon_chain
-        .produce_block(randao_reveal, slot)
-        .map_err(|e| {
-            error!(
-                log,
-                "Error whilst producing block";
-                "error" => format!("{:?}", e)
-            );
-
-            ApiError::ServerError(format!(
-                "Beacon node is not able to produce a block: {:?}",
-                e
-            ))
-        })?;
-
-    ResponseBuilder::new(&req)?.body(&new_block)
+        ResponseBuilder::new(&req)?.body(&new_block)
+    }
 }
 
 /// HTTP Handler to publish a SignedBeaconBlock, which has been signed by a validator.
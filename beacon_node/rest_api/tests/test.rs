@@ -1,15 +1,19 @@
 #![cfg(test)]
 
 use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use eth2_libp2p::PeerId;
+use http::StatusCode;
 use node_test_rig::{
     environment::{Environment, EnvironmentBuilder},
     testing_client_config, ClientConfig, ClientGenesis, LocalBeaconNode,
 };
 use remote_beacon_node::{
-    Committee, HeadBeaconBlock, PersistedOperationPool, PublishStatus, ValidatorResponse,
+    Committee, Error, HeadBeaconBlock, PersistedOperationPool, PublishStatus, RemoteBeaconNode,
+    ValidatorResponse,
 };
 use rest_types::ValidatorDutyBytes;
 use std::convert::TryInto;
+use std::net::{Ipv4Addr, UdpSocket};
 use std::sync::Arc;
 use types::{
     test_utils::{
@@ -1122,3 +1126,130 @@ fn attester_slashing() {
     assert_eq!(attester_slashings.len(), 1);
     assert_eq!(attester_slashing, attester_slashings[0]);
 }
+
+/// Returns the status code of a request which did not succeed.
+fn error_status<T: std::fmt::Debug>(result: Result<T, Error>) -> StatusCode {
+    match result {
+        Err(Error::DidNotSucceed { status, .. }) => status,
+        other => panic!("request should not succeed, got {:?}", other),
+    }
+}
+
+#[test]
+fn admin_peers() {
+    let mut env = build_env();
+
+    let mut config = testing_client_config();
+    config.rest_api.admin_enabled = true;
+    let node = build_node(&mut env, config);
+    let admin = node
+        .remote_node()
+        .expect("should produce remote node")
+        .http
+        .admin();
+
+    let peer_id = PeerId::random().to_base58();
+    let multiaddr = "/ip4/127.0.0.1/tcp/9000".to_string();
+
+    env.runtime()
+        .block_on(admin.dial(multiaddr.clone()))
+        .expect("should dial multiaddr");
+    env.runtime()
+        .block_on(admin.disconnect(peer_id.clone()))
+        .expect("should disconnect peer id");
+    env.runtime()
+        .block_on(admin.ban(peer_id.clone()))
+        .expect("should ban peer id");
+    env.runtime()
+        .block_on(admin.unban(peer_id.clone()))
+        .expect("should unban peer id");
+    env.runtime()
+        .block_on(admin.ban("192.168.0.1".to_string()))
+        .expect("should ban ip");
+    env.runtime()
+        .block_on(admin.unban("192.168.0.1".to_string()))
+        .expect("should unban ip");
+    env.runtime()
+        .block_on(admin.add_trusted_peer(format!("{}/p2p/{}", multiaddr, peer_id)))
+        .expect("should add trusted peer");
+
+    assert_eq!(
+        error_status(env.runtime().block_on(admin.dial("enr:cats".to_string()))),
+        StatusCode::BAD_REQUEST,
+        "should reject an invalid enr"
+    );
+    assert_eq!(
+        error_status(env.runtime().block_on(admin.dial("cats".to_string()))),
+        StatusCode::BAD_REQUEST,
+        "should reject an invalid multiaddr"
+    );
+    assert_eq!(
+        error_status(env.runtime().block_on(admin.ban("cats".to_string()))),
+        StatusCode::BAD_REQUEST,
+        "should reject an invalid peer id"
+    );
+    assert_eq!(
+        error_status(env.runtime().block_on(admin.add_trusted_peer(multiaddr))),
+        StatusCode::BAD_REQUEST,
+        "should reject a trusted peer without a peer id"
+    );
+}
+
+#[test]
+fn admin_disabled() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let admin = node
+        .remote_node()
+        .expect("should produce remote node")
+        .http
+        .admin();
+
+    assert_eq!(
+        error_status(env.runtime().block_on(admin.ban("192.168.0.1".to_string()))),
+        StatusCode::FORBIDDEN,
+        "should not serve admin endpoints unless enabled"
+    );
+}
+
+#[test]
+fn admin_not_loopback() {
+    // The address of this host on the interface with a default route, if any. Connecting a UDP
+    // socket sends no packets.
+    let local_ip = UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("192.0.2.1:9")?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .ok()
+        .filter(|ip| !ip.is_loopback() && !ip.is_unspecified());
+    let local_ip = match local_ip {
+        Some(ip) => ip,
+        // there is no address other than loopback to connect from
+        None => return,
+    };
+
+    let mut env = build_env();
+
+    let mut config = testing_client_config();
+    config.rest_api.admin_enabled = true;
+    config.rest_api.listen_address = Ipv4Addr::UNSPECIFIED;
+    let node = build_node(&mut env, config);
+    let port = node
+        .client
+        .http_listen_addr()
+        .expect("should have http server")
+        .port();
+    let admin = RemoteBeaconNode::<E>::new(format!("http://{}:{}", local_ip, port))
+        .expect("should produce remote node")
+        .http
+        .admin();
+
+    assert_eq!(
+        error_status(env.runtime().block_on(admin.ban("192.168.0.1".to_string()))),
+        StatusCode::FORBIDDEN,
+        "should not serve admin endpoints to other addresses"
+    );
+}
@@ -41,8 +41,15 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("listen-address")
                 .long("listen-address")
                 .value_name("ADDRESS")
-                .help("The address lighthouse will listen for UDP and TCP connections.")
+                .help("The address lighthouse will listen for UDP and TCP connections. To listen \
+                       on both IPv4 and IPv6, set this flag twice with an IPv4 and an IPv6 \
+                       address. libp2p then also accepts TCP connections on the IPv6 address at \
+                       --port6. Known limitation: discovery only listens on the IPv4 address, so \
+                       a dual-stack node advertises no udp6 port and cannot be discovered over \
+                       IPv6.")
                 .default_value("0.0.0.0")
+                .multiple(true)
+                .number_of_values(1)
                 .takes_value(true)
         )
         .arg(
@@ -53,6 +60,16 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("9000")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("port6")
+                .long("port6")
+                .value_name("PORT")
+                .help("The TCP port to listen on at the IPv6 --listen-address when listening on \
+                       both IPv4 and IPv6. This must differ from --port, since an IPv6 socket also \
+                       accepts IPv4 connections on most platforms.")
+                .default_value("9090")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("discovery-port")
                 .long("discovery-port")
@@ -90,14 +107,25 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     The --port flag is used if this is not set.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("enr-tcp6-port")
+                .long("enr-tcp6-port")
+                .value_name("PORT")
+                .help("The TCP port of the local ENR for the IPv6 --listen-address. Set this only if you are sure other nodes can connect to your local node on this port.\
+                    The --port6 flag is used if this is not set.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("enr-address")
                 .long("enr-address")
                 .value_name("ADDRESS")
                 .help("The IP address to broadcast to other peers on how to reach this node. \
                 Set this only if you are sure other nodes can connect to your local node on this address. \
+                Set this flag twice to broadcast both an IPv4 and an IPv6 address. \
                 Discovery will automatically find your external address,if possible.
            ")
+                .multiple(true)
+                .number_of_values(1)
                 .takes_value(true),
         )
         .arg(
@@ -105,7 +133,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .short("e")
                 .long("enr-match")
                 .help("Sets the local ENR IP address and port to match those set for lighthouse. \
-                Specifically, the IP address will be the value of --listen-address and the UDP port will be --discovery-port.")
+                Specifically, the IP addresses will be the values of --listen-address and the UDP port will be --discovery-port.")
         )
         .arg(
             Arg::with_name("disable-enr-auto-update")
@@ -123,6 +151,33 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        without an ENR.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("trusted-peers")
+                .long("trusted-peers")
+                .allow_hyphen_values(true)
+                .value_name("PEER-LIST")
+                .help("One or more comma-delimited ENRs or multiaddrs ending in /p2p/<peer-id> of \
+                       peers to keep connected. Trusted peers are redialed when they disconnect, \
+                       are never banned and do not count towards --maxpeers.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("nat-port-mapping")
+                .long("nat-port-mapping")
+                .help("Map the libp2p TCP port and the discovery UDP port on the NAT gateway with \
+                       UPnP or NAT-PMP, and advertise the external address in the ENR unless \
+                       --enr-address is set.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("nat-pmp-gateway")
+                .long("nat-pmp-gateway")
+                .value_name("IP-ADDRESS")
+                .help("The IPv4 address of the NAT-PMP gateway used by --nat-port-mapping. \
+                       Defaults to the default gateway.")
+                .requires("nat-port-mapping")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("p2p-priv-key")
                 .long("p2p-priv-key")
@@ -164,6 +219,14 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("5052")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("http-admin")
+                .long("http-admin")
+                .help("Enable the /lighthouse/admin endpoints of the HTTP API server, which dial, \
+                       ban and trust peers. These are only served to requests from localhost and \
+                       require the --http-address to be a loopback address.")
+                .takes_value(false),
+        )
         /* Websocket related arguments */
         .arg(
             Arg::with_name("ws")
@@ -208,11 +271,32 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name("eth1-endpoint")
                 .long("eth1-endpoint")
-                .value_name("HTTP-ENDPOINT")
-                .help("Specifies the server for a web3 connection to the Eth1 chain. Also enables the --eth1 flag.")
+                .value_name("ENDPOINT")
+                .help("Specifies the server for a web3 connection to the Eth1 chain. Also enables the --eth1 flag. \
+                       May be an HTTP (http://), websocket (ws://) or IPC (ipc://, or the path of an existing socket) endpoint. Websocket and IPC \
+                       endpoints are subscribed to for new blocks and deposits.")
                 .takes_value(true)
                 .default_value("http://127.0.0.1:8545")
         )
+        .arg(
+            Arg::with_name("eth1-fallback-endpoints")
+                .long("eth1-fallback-endpoints")
+                .value_name("HTTP-ENDPOINTS")
+                .help("A comma-separated list of servers to use for the Eth1 chain when the \
+                       --eth1-endpoint is unhealthy, in order of priority.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("eth1-deposit-snapshot")
+                .long("eth1-deposit-snapshot")
+                .value_name("PATH_OR_URL")
+                .help("Initialises the deposit cache from a JSON deposit tree snapshot, rather than \
+                       downloading every deposit since the deposit contract was deployed. May be a \
+                       file path or the URL of another node's /node/eth1/deposit_snapshot endpoint. \
+                       Only used when there is no existing eth1 cache. The snapshot is verified \
+                       against the deposit contract once the cache is synced.")
+                .takes_value(true)
+        )
         .arg(
             Arg::with_name("slots-per-restore-point")
                 .long("slots-per-restore-point")
@@ -235,6 +319,26 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specifies how many states the database should cache in memory [default: 5]")
                 .takes_value(true)
         )
+        /*
+         * Validator monitoring.
+         */
+        .arg(
+            Arg::with_name("validator-monitor-pubkeys")
+                .long("validator-monitor-pubkeys")
+                .value_name("PUBKEYS")
+                .help("A comma-separated list of 0x-prefixed validator public keys. The \
+                       performance of these validators will be reported in the logs, metrics \
+                       and HTTP API.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("validator-monitor-indices")
+                .long("validator-monitor-indices")
+                .value_name("INDICES")
+                .help("A comma-separated list of validator indices. The performance of these \
+                       validators will be reported in the logs, metrics and HTTP API.")
+                .takes_value(true)
+        )
         /*
          * Purge.
          */
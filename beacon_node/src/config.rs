@@ -1,17 +1,18 @@
 use beacon_chain::builder::PUBKEY_CACHE_FILENAME;
 use clap::ArgMatches;
 use client::{config::DEFAULT_DATADIR, ClientConfig, ClientGenesis};
-use eth2_libp2p::{Enr, Multiaddr};
+use eth1::{DepositTreeSnapshot, Transport};
+use eth2_libp2p::{Enr, Multiaddr, TrustedPeer};
 use eth2_testnet_config::Eth2TestnetConfig;
 use slog::{crit, info, Logger};
 use ssz::Encode;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
-use std::net::{IpAddr, Ipv4Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::net::{TcpListener, UdpSocket};
 use std::path::PathBuf;
-use types::EthSpec;
+use types::{EthSpec, PublicKeyBytes};
 
 pub const CLIENT_CONFIG_FILENAME: &str = "beacon-node.toml";
 pub const BEACON_NODE_DIR: &str = "beacon";
@@ -93,11 +94,29 @@ pub fn get_config<E: EthSpec>(
         client_config.network.network_dir = client_config.data_dir.join(NETWORK_DIR);
     };
 
-    if let Some(listen_address_str) = cli_args.value_of("listen-address") {
-        let listen_address = listen_address_str
-            .parse()
-            .map_err(|_| format!("Invalid listen address: {:?}", listen_address_str))?;
-        client_config.network.listen_address = listen_address;
+    if let Some(listen_address_strs) = cli_args.values_of("listen-address") {
+        let listen_addresses = listen_address_strs
+            .map(|listen_address_str| {
+                listen_address_str
+                    .parse::<IpAddr>()
+                    .map_err(|_| format!("Invalid listen address: {:?}", listen_address_str))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match listen_addresses.as_slice() {
+            [listen_address] => client_config.network.listen_address = *listen_address,
+            // discovery listens on the IPv4 address of a dual-stack node
+            [IpAddr::V4(ipv4), IpAddr::V6(ipv6)] | [IpAddr::V6(ipv6), IpAddr::V4(ipv4)] => {
+                client_config.network.listen_address = IpAddr::V4(*ipv4);
+                client_config.network.listen_address_v6 = Some(*ipv6);
+            }
+            _ => {
+                return Err(
+                    "Expected a single --listen-address, or one IPv4 and one IPv6 address."
+                        .to_string(),
+                )
+            }
+        }
     }
 
     if let Some(max_peers_str) = cli_args.value_of("maxpeers") {
@@ -114,6 +133,12 @@ pub fn get_config<E: EthSpec>(
         client_config.network.discovery_port = port;
     }
 
+    if let Some(port_str) = cli_args.value_of("port6") {
+        client_config.network.libp2p_port_v6 = port_str
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid port6: {}", port_str))?;
+    }
+
     if let Some(port_str) = cli_args.value_of("discovery-port") {
         let port = port_str
             .parse::<u16>()
@@ -139,12 +164,25 @@ pub fn get_config<E: EthSpec>(
             .collect::<Result<Vec<Multiaddr>, _>>()?;
     }
 
-    if let Some(enr_address_str) = cli_args.value_of("enr-address") {
-        client_config.network.enr_address = Some(
-            enr_address_str
-                .parse()
-                .map_err(|_| format!("Invalid discovery address: {:?}", enr_address_str))?,
-        )
+    if let Some(trusted_peers_str) = cli_args.value_of("trusted-peers") {
+        client_config.network.trusted_peers = trusted_peers_str
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<TrustedPeer>, _>>()?;
+    }
+
+    if let Some(enr_address_strs) = cli_args.values_of("enr-address") {
+        for enr_address_str in enr_address_strs {
+            match enr_address_str.parse::<IpAddr>() {
+                Ok(IpAddr::V4(enr_address)) => {
+                    client_config.network.enr_address = Some(enr_address.into())
+                }
+                Ok(IpAddr::V6(enr_address)) => {
+                    client_config.network.enr_address_v6 = Some(enr_address)
+                }
+                Err(_) => return Err(format!("Invalid discovery address: {:?}", enr_address_str)),
+            }
+        }
     }
 
     if let Some(enr_udp_port_str) = cli_args.value_of("enr-udp-port") {
@@ -155,6 +193,14 @@ pub fn get_config<E: EthSpec>(
         );
     }
 
+    if let Some(enr_tcp6_port_str) = cli_args.value_of("enr-tcp6-port") {
+        client_config.network.enr_tcp6_port = Some(
+            enr_tcp6_port_str
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid ENR TCP6 port: {}", enr_tcp6_port_str))?,
+        );
+    }
+
     if let Some(enr_tcp_port_str) = cli_args.value_of("enr-tcp-port") {
         client_config.network.enr_tcp_port = Some(
             enr_tcp_port_str
@@ -164,18 +210,34 @@ pub fn get_config<E: EthSpec>(
     }
 
     if cli_args.is_present("enr-match") {
-        // set the enr address to localhost if the address is 0.0.0.0
-        if client_config.network.listen_address
-            == "0.0.0.0".parse::<IpAddr>().expect("valid ip addr")
-        {
-            client_config.network.enr_address =
-                Some("127.0.0.1".parse::<IpAddr>().expect("valid ip addr"));
-        } else {
-            client_config.network.enr_address = Some(client_config.network.listen_address);
+        // set the enr addresses to localhost if the addresses are unspecified
+        match client_config.network.listen_address {
+            IpAddr::V4(listen_address) if listen_address.is_unspecified() => {
+                client_config.network.enr_address = Some(Ipv4Addr::LOCALHOST.into())
+            }
+            IpAddr::V6(listen_address) => {
+                client_config.network.enr_address_v6 = Some(ipv6_or_localhost(listen_address))
+            }
+            listen_address => client_config.network.enr_address = Some(listen_address),
+        }
+        if let Some(listen_address_v6) = client_config.network.listen_address_v6 {
+            client_config.network.enr_address_v6 = Some(ipv6_or_localhost(listen_address_v6));
         }
         client_config.network.enr_udp_port = Some(client_config.network.discovery_port);
     }
 
+    if cli_args.is_present("nat-port-mapping") {
+        client_config.network.nat_port_mapping = true;
+    }
+
+    if let Some(nat_pmp_gateway_str) = cli_args.value_of("nat-pmp-gateway") {
+        client_config.network.nat_pmp_gateway = Some(
+            nat_pmp_gateway_str
+                .parse()
+                .map_err(|_| format!("Invalid NAT-PMP gateway: {}", nat_pmp_gateway_str))?,
+        );
+    }
+
     if cli_args.is_present("disable_enr_auto_update") {
         client_config.network.discv5_config.enr_update = false;
     }
@@ -218,6 +280,13 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "http-port is not a valid u16.")?;
     }
 
+    if cli_args.is_present("http-admin") {
+        if !client_config.rest_api.listen_address.is_loopback() {
+            return Err("http-admin requires http-address to be a loopback address.".into());
+        }
+        client_config.rest_api.admin_enabled = true;
+    }
+
     /*
      * Websocket server
      */
@@ -258,10 +327,26 @@ pub fn get_config<E: EthSpec>(
 
     // Defines the URL to reach the eth1 node.
     if let Some(val) = cli_args.value_of("eth1-endpoint") {
+        Transport::from_endpoint(val)?;
         client_config.sync_eth1_chain = true;
         client_config.eth1.endpoint = val.to_string();
     }
 
+    // Defines the URLs to use when the eth1 node is unhealthy.
+    if let Some(val) = cli_args.value_of("eth1-fallback-endpoints") {
+        client_config.eth1.fallback_endpoints = val
+            .split(',')
+            .map(str::trim)
+            .filter(|endpoint| !endpoint.is_empty())
+            .map(|endpoint| Transport::from_endpoint(endpoint).map(|_| endpoint.to_string()))
+            .collect::<Result<_, _>>()?;
+    }
+
+    // Defines a snapshot of the deposit tree to initialise the deposit cache with.
+    if let Some(val) = cli_args.value_of("eth1-deposit-snapshot") {
+        client_config.eth1_deposit_snapshot = Some(load_deposit_snapshot(val)?);
+    }
+
     if let Some(freezer_dir) = cli_args.value_of("freezer-dir") {
         client_config.freezer_db_path = Some(PathBuf::from(freezer_dir));
     }
@@ -289,6 +374,24 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "block-cache-size is not a valid integer".to_string())?;
     }
 
+    if let Some(pubkeys) = cli_args.value_of("validator-monitor-pubkeys") {
+        client_config.validator_monitor_pubkeys = pubkeys
+            .split(',')
+            .map(parse_pubkey_bytes)
+            .collect::<Result<_, _>>()?;
+    }
+
+    if let Some(indices) = cli_args.value_of("validator-monitor-indices") {
+        client_config.validator_monitor_indices = indices
+            .split(',')
+            .map(|index| {
+                index
+                    .parse()
+                    .map_err(|_| format!("Invalid validator index: {}", index))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
     if spec_constants != client_config.spec_constants {
         crit!(log, "Specification constants do not match.";
               "client_config" => client_config.spec_constants.to_string(),
@@ -314,6 +417,10 @@ pub fn get_config<E: EthSpec>(
             unused_port("tcp").map_err(|e| format!("Failed to get port for libp2p: {}", e))?;
         client_config.network.discovery_port =
             unused_port("udp").map_err(|e| format!("Failed to get port for discovery: {}", e))?;
+        if client_config.network.listen_address_v6.is_some() {
+            client_config.network.libp2p_port_v6 =
+                unused_port("tcp").map_err(|e| format!("Failed to get port for libp2p: {}", e))?;
+        }
         client_config.rest_api.port = 0;
         client_config.websocket_server.port = 0;
     }
@@ -331,6 +438,12 @@ pub fn get_config<E: EthSpec>(
     client_config.eth1.lowest_cached_block_number =
         client_config.eth1.deposit_contract_deploy_block;
 
+    // Only use eth1 nodes on the chain that hosts the deposit contract, if it is known.
+    if let Some(yaml_config) = &eth2_testnet_config.yaml_config {
+        client_config.eth1.chain_id = yaml_config.deposit_chain_id;
+        client_config.eth1.network_id = yaml_config.deposit_network_id;
+    }
+
     if let Some(mut boot_nodes) = eth2_testnet_config.boot_enr {
         client_config.network.boot_nodes.append(&mut boot_nodes)
     }
@@ -396,6 +509,48 @@ pub fn get_eth2_testnet_config<E: EthSpec>(
     })
 }
 
+/// Parses a 0x-prefixed, hex-encoded validator public key.
+fn parse_pubkey_bytes(string: &str) -> Result<PublicKeyBytes, String> {
+    if !string.starts_with("0x") {
+        return Err(format!("Public key must have a 0x prefix: {}", string));
+    }
+
+    let bytes = hex::decode(&string[2..])
+        .map_err(|e| format!("Invalid hex in public key {}: {:?}", string, e))?;
+    PublicKeyBytes::from_bytes(&bytes)
+        .map_err(|e| format!("Invalid public key {}: {:?}", string, e))
+}
+
+/// Loads a JSON `DepositTreeSnapshot` from `source`, which is either a `http(s)://` URL or a
+/// file path.
+fn load_deposit_snapshot(source: &str) -> Result<DepositTreeSnapshot, String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        reqwest::get(source)
+            .and_then(|response| response.error_for_status())
+            .and_then(|mut response| response.json())
+            .map_err(|e| {
+                format!(
+                    "Unable to download deposit snapshot from {}: {:?}",
+                    source, e
+                )
+            })
+    } else {
+        let file = File::open(source)
+            .map_err(|e| format!("Unable to open deposit snapshot {}: {:?}", source, e))?;
+        serde_json::from_reader(file)
+            .map_err(|e| format!("Unable to parse deposit snapshot {}: {:?}", source, e))
+    }
+}
+
+/// Returns `ip`, or the IPv6 localhost address if `ip` is unspecified.
+fn ipv6_or_localhost(ip: Ipv6Addr) -> Ipv6Addr {
+    if ip.is_unspecified() {
+        Ipv6Addr::LOCALHOST
+    } else {
+        ip
+    }
+}
+
 /// A bit of hack to find an unused port.
 ///
 /// Does not guarantee that the given port is unused after the function exists, just that it was
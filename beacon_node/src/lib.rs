@@ -98,9 +98,14 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
                         log,
                         "Block production enabled";
                         "endpoint" => &client_config.eth1.endpoint,
+                        "fallback_endpoints" =>
+                            format!("{:?}", client_config.eth1.fallback_endpoints),
                         "method" => "json rpc via http"
                     );
-                    builder.caching_eth1_backend(client_config.eth1.clone())?
+                    builder.caching_eth1_backend(
+                        client_config.eth1.clone(),
+                        client_config.eth1_deposit_snapshot.clone(),
+                    )?
                 } else if client_config.dummy_eth1_backend {
                     warn!(
                         log,
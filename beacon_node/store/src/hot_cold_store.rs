@@ -334,10 +334,50 @@ impl<E: EthSpec> HotColdDB<E> {
     ) -> Result<Self, Error> {
         Self::verify_slots_per_restore_point(config.slots_per_restore_point)?;
 
+        Self::from_dbs(
+            LevelDB::open(hot_path)?,
+            LevelDB::open(cold_path)?,
+            config,
+            spec,
+            log,
+        )
+    }
+
+    /// Open an existing database, returning an error if either the hot or cold DB does not exist.
+    ///
+    /// The databases are opened with write access, since LevelDB has no read-only mode, and
+    /// LevelDB itself may write to them (e.g., to recover its log or compact). Suitable for
+    /// inspecting the database of a beacon node that is not running (e.g., with `lcli`), but the
+    /// database should be copied first if it must not be modified at all.
+    pub fn open_existing(
+        hot_path: &Path,
+        cold_path: &Path,
+        config: StoreConfig,
+        spec: ChainSpec,
+        log: Logger,
+    ) -> Result<Self, Error> {
+        Self::verify_slots_per_restore_point(config.slots_per_restore_point)?;
+
+        Self::from_dbs(
+            LevelDB::open_existing(hot_path)?,
+            LevelDB::open_existing(cold_path)?,
+            config,
+            spec,
+            log,
+        )
+    }
+
+    fn from_dbs(
+        hot_db: LevelDB<E>,
+        cold_db: LevelDB<E>,
+        config: StoreConfig,
+        spec: ChainSpec,
+        log: Logger,
+    ) -> Result<Self, Error> {
         let db = HotColdDB {
             split: RwLock::new(Split::default()),
-            cold_db: LevelDB::open(cold_path)?,
-            hot_db: LevelDB::open(hot_path)?,
+            cold_db,
+            hot_db,
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
             config,
             spec,
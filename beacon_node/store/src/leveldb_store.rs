@@ -19,9 +19,20 @@ pub struct LevelDB<E: EthSpec> {
 impl<E: EthSpec> LevelDB<E> {
     /// Open a database at `path`, creating a new database if one does not already exist.
     pub fn open(path: &Path) -> Result<Self, Error> {
+        Self::open_with_options(path, true)
+    }
+
+    /// Open an existing database at `path`, returning an error if one does not exist.
+    ///
+    /// The database is opened with write access, like `Self::open`.
+    pub fn open_existing(path: &Path) -> Result<Self, Error> {
+        Self::open_with_options(path, false)
+    }
+
+    fn open_with_options(path: &Path, create_if_missing: bool) -> Result<Self, Error> {
         let mut options = Options::new();
 
-        options.create_if_missing = true;
+        options.create_if_missing = create_if_missing;
 
         let db = Database::open(path, options)?;
 
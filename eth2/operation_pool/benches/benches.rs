@@ -0,0 +1,278 @@
+//! Compares the attestation packing objectives of the `OperationPool`.
+//!
+//! For each pool, the total reward of the attestations chosen by each `PackingObjective` is
+//! printed, then the time taken to pack the attestations is benchmarked.
+//!
+//! By default a synthetic pool is generated. Recorded pools can be benchmarked by setting the
+//! `OP_POOL_BENCH_DIR` environment variable to a directory containing pairs of files named
+//! `<name>.pool.ssz` and `<name>.state.ssz`. The former is the SSZ-encoded
+//! `PersistedOperationPool` (e.g., from `/advanced/operation_pool`) and the latter is the
+//! SSZ-encoded `BeaconState` (e.g., from `/beacon/state`) of the parent of the block to be
+//! produced. Both must be for `MainnetEthSpec`.
+use criterion::Criterion;
+use criterion::{black_box, criterion_group, criterion_main, Benchmark};
+use operation_pool::{OperationPool, PackingObjective, PersistedOperationPool};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use ssz::Decode;
+use state_processing::common::{get_attesting_indices, get_base_reward};
+use state_processing::per_slot_processing;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::sync::Arc;
+use types::test_utils::{
+    AttestationTestTask, TestingAttestationBuilder, TestingBeaconStateBuilder,
+};
+use types::*;
+
+type E = MainnetEthSpec;
+
+pub const RECORDED_POOLS_DIR_ENV: &str = "OP_POOL_BENCH_DIR";
+pub const SYNTHETIC_VALIDATOR_COUNT: usize = 16_384;
+
+const POOL_SUFFIX: &str = ".pool.ssz";
+
+const OBJECTIVES: &[(PackingObjective, &str)] = &[
+    (PackingObjective::ProposerReward, "proposer_reward"),
+    (
+        PackingObjective::ProposerAndInclusionDelayReward,
+        "proposer_and_inclusion_delay_reward",
+    ),
+];
+
+fn all_benches(c: &mut Criterion) {
+    let spec = E::default_spec();
+
+    let pools = match env::var(RECORDED_POOLS_DIR_ENV) {
+        Ok(dir) => load_recorded_pools(&dir, &spec),
+        Err(_) => vec![synthetic_pool(SYNTHETIC_VALIDATOR_COUNT, &spec)],
+    };
+
+    for (name, op_pool, state) in pools {
+        bench_pool(c, &name, op_pool, state, &spec);
+    }
+}
+
+/// Load the recorded pools from `dir`, advancing each state to the slot of the block to be
+/// produced.
+fn load_recorded_pools(
+    dir: &str,
+    spec: &ChainSpec,
+) -> Vec<(String, OperationPool<E>, BeaconState<E>)> {
+    let mut entries = fs::read_dir(dir)
+        .expect("should read recorded pools dir")
+        .map(|entry| entry.expect("should read dir entry").path())
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    entries
+        .into_iter()
+        .filter_map(|path| {
+            let file_name = path.file_name()?.to_str()?;
+            if !file_name.ends_with(POOL_SUFFIX) {
+                return None;
+            }
+            let name = file_name[..file_name.len() - POOL_SUFFIX.len()].to_string();
+
+            let state_bytes = fs::read(path.with_file_name(format!("{}.state.ssz", name)))
+                .expect("should read recorded state");
+            let mut state =
+                BeaconState::from_ssz_bytes(&state_bytes).expect("should decode recorded state");
+            per_slot_processing(&mut state, None, spec).expect("should advance state");
+            state.build_all_caches(spec).expect("should build caches");
+
+            let pool_bytes = fs::read(&path).expect("should read recorded pool");
+            let op_pool = PersistedOperationPool::<E>::from_ssz_bytes(&pool_bytes)
+                .expect("should decode recorded pool")
+                .into_operation_pool(&state, spec);
+
+            Some((name, op_pool, state))
+        })
+        .collect()
+}
+
+/// Generate a pool of overlapping aggregates from every slot of the last epoch, for validators
+/// with a range of effective balances.
+fn synthetic_pool(
+    validator_count: usize,
+    spec: &ChainSpec,
+) -> (String, OperationPool<E>, BeaconState<E>) {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut state_builder =
+        TestingBeaconStateBuilder::from_default_keypairs_file_if_exists(validator_count, spec);
+    let slot = spec.genesis_slot + 1000 * E::slots_per_epoch() + E::slots_per_epoch() / 2;
+    state_builder.teleport_to_slot(slot);
+    state_builder
+        .build_caches(spec)
+        .expect("should build caches");
+    let (mut state, _keypairs) = state_builder.build();
+
+    for validator in state.validators.iter_mut() {
+        validator.effective_balance = rng.gen_range(16, 33) * spec.effective_balance_increment;
+    }
+
+    let op_pool = OperationPool::new();
+
+    for attestation_slot in (slot.as_u64() - E::slots_per_epoch()..slot.as_u64()).map(Slot::new) {
+        let committees = state
+            .get_beacon_committees_at_slot(attestation_slot)
+            .expect("should get committees")
+            .into_iter()
+            .map(BeaconCommittee::into_owned)
+            .collect::<Vec<_>>();
+
+        for committee in committees {
+            // Each aggregator sees a random subset of the committee.
+            for _ in 0..spec.target_aggregators_per_committee {
+                let mut attestation = TestingAttestationBuilder::<E>::new(
+                    AttestationTestTask::Valid,
+                    &state,
+                    &committee.committee,
+                    attestation_slot,
+                    committee.index,
+                    spec,
+                )
+                .build();
+
+                for i in 0..committee.committee.len() {
+                    if rng.gen_bool(0.5) {
+                        attestation
+                            .aggregation_bits
+                            .set(i, true)
+                            .expect("should set bit");
+                    }
+                }
+
+                op_pool
+                    .insert_attestation(
+                        attestation,
+                        &state.fork,
+                        state.genesis_validators_root,
+                        spec,
+                    )
+                    .expect("should insert attestation");
+            }
+        }
+    }
+
+    (
+        format!("synthetic_{}_validators", validator_count),
+        op_pool,
+        state,
+    )
+}
+
+/// Print the reward of the attestations chosen by each objective, then benchmark them.
+fn bench_pool(
+    c: &mut Criterion,
+    name: &str,
+    op_pool: OperationPool<E>,
+    state: BeaconState<E>,
+    spec: &ChainSpec,
+) {
+    println!(
+        "{}: {} attestations in pool at slot {}",
+        name,
+        op_pool.num_attestations(),
+        state.slot
+    );
+
+    for &(objective, objective_name) in OBJECTIVES {
+        let (attestations, _) = op_pool
+            .pack_attestations(&state, |_| true, objective, spec)
+            .expect("should pack attestations");
+        let (proposer_reward, inclusion_delay_reward) =
+            included_rewards(&state, &attestations, spec);
+
+        println!(
+            "{}/{}: {} attestations, proposer reward {} Gwei, inclusion delay reward {} Gwei, \
+             total {} Gwei",
+            name,
+            objective_name,
+            attestations.len(),
+            proposer_reward,
+            inclusion_delay_reward,
+            proposer_reward + inclusion_delay_reward
+        );
+    }
+
+    let op_pool = Arc::new(op_pool);
+    let state = Arc::new(state);
+
+    for &(objective, objective_name) in OBJECTIVES {
+        let op_pool = op_pool.clone();
+        let state = state.clone();
+        let spec = spec.clone();
+
+        c.bench(
+            name,
+            Benchmark::new(objective_name, move |b| {
+                b.iter(|| {
+                    black_box(
+                        op_pool
+                            .pack_attestations(&state, |_| true, objective, &spec)
+                            .expect("should pack attestations"),
+                    )
+                })
+            })
+            .sample_size(10),
+        );
+    }
+}
+
+/// Returns the total proposer reward and the total attester inclusion delay reward paid for the
+/// validators first included by `attestations`.
+fn included_rewards(
+    state: &BeaconState<E>,
+    attestations: &[Attestation<E>],
+    spec: &ChainSpec,
+) -> (u64, u64) {
+    let attesting_indices = |data: &AttestationData, bits: &BitList<_>| {
+        let committee = state
+            .get_beacon_committee(data.slot, data.index)
+            .expect("should get committee");
+        get_attesting_indices::<E>(committee.committee, bits).expect("should get indices")
+    };
+
+    let mut seen = HashSet::new();
+    for a in state
+        .previous_epoch_attestations
+        .iter()
+        .chain(state.current_epoch_attestations.iter())
+    {
+        for index in attesting_indices(&a.data, &a.aggregation_bits) {
+            seen.insert((a.data.target.epoch, index));
+        }
+    }
+
+    let total_active_balance = state
+        .get_total_balance(
+            state
+                .get_cached_active_validator_indices(RelativeEpoch::Current)
+                .expect("should get active indices"),
+            spec,
+        )
+        .expect("should get total balance");
+
+    let mut proposer_reward = 0;
+    let mut inclusion_delay_reward = 0;
+    for a in attestations {
+        let distance = state.slot.as_u64() - a.data.slot.as_u64();
+
+        for index in attesting_indices(&a.data, &a.aggregation_bits) {
+            if seen.insert((a.data.target.epoch, index)) {
+                let base_reward = get_base_reward(state, index, total_active_balance, spec)
+                    .expect("should get base reward");
+                let reward = base_reward / spec.proposer_reward_quotient;
+                proposer_reward += reward;
+                inclusion_delay_reward += (base_reward - reward) / distance;
+            }
+        }
+    }
+
+    (proposer_reward, inclusion_delay_reward)
+}
+
+criterion_group!(benches, all_benches);
+criterion_main!(benches);
@@ -0,0 +1,130 @@
+use types::{Attestation, EthSpec};
+
+/// Returns `true` if `attestation` is signed by a single validator.
+///
+/// Such attestations are kept individually in the pool, rather than merged into aggregates, so
+/// that they can be used to extend any of the aggregates for the same data.
+pub fn is_unaggregated<T: EthSpec>(attestation: &Attestation<T>) -> bool {
+    attestation.aggregation_bits.num_set_bits() == 1
+}
+
+/// Returns `true` if all the signers of `attestation` are also signers of `other`.
+pub fn is_subsumed_by<T: EthSpec>(attestation: &Attestation<T>, other: &Attestation<T>) -> bool {
+    attestation
+        .aggregation_bits
+        .difference(&other.aggregation_bits)
+        .is_zero()
+}
+
+/// Insert `attestation` into `attestations`, all of which must share the same `AttestationData`.
+///
+/// Unaggregated attestations are stored as-is (excluding duplicates). Aggregates are merged into
+/// all existing aggregates whose signers they are disjoint from, and any aggregates whose signers
+/// are a subset of another aggregate's are removed.
+pub fn insert_attestation<T: EthSpec>(
+    attestations: &mut Vec<Attestation<T>>,
+    attestation: Attestation<T>,
+) {
+    if is_unaggregated(&attestation) {
+        if !attestations.contains(&attestation) {
+            attestations.push(attestation);
+        }
+        return;
+    }
+
+    if attestations
+        .iter()
+        .filter(|a| !is_unaggregated(a))
+        .any(|existing| is_subsumed_by(&attestation, existing))
+    {
+        return;
+    }
+
+    let mut aggregated = false;
+    for existing in attestations.iter_mut().filter(|a| !is_unaggregated(a)) {
+        if existing.signers_disjoint_from(&attestation) {
+            existing.aggregate(&attestation);
+            aggregated = true;
+        }
+    }
+
+    if !aggregated {
+        attestations.push(attestation);
+    }
+
+    remove_subsumed_aggregates(attestations);
+}
+
+/// Remove all the aggregates from `attestations` that are subsumed by another aggregate.
+///
+/// Unaggregated attestations are never removed.
+fn remove_subsumed_aggregates<T: EthSpec>(attestations: &mut Vec<Attestation<T>>) {
+    let mut keep = vec![true; attestations.len()];
+
+    for i in 0..attestations.len() {
+        if is_unaggregated(&attestations[i]) {
+            continue;
+        }
+
+        // Only consider aggregates that are being kept, so that of two equal aggregates the
+        // latter is retained.
+        keep[i] = !(0..attestations.len()).any(|j| {
+            i != j
+                && keep[j]
+                && !is_unaggregated(&attestations[j])
+                && is_subsumed_by(&attestations[i], &attestations[j])
+        });
+    }
+
+    let mut keep = keep.into_iter();
+    attestations.retain(|_| keep.next().unwrap_or(true));
+}
+
+/// Greedily build aggregates from `attestations`, all of which must share the same
+/// `AttestationData`, such that between them they cover as many signers as possible.
+///
+/// Each candidate is seeded with the largest aggregate that is not already covered by a previous
+/// candidate, then extended with every other aggregate and unaggregated attestation that it is
+/// disjoint from. The returned aggregates are not subsumed by one another.
+pub fn covering_aggregates<T: EthSpec>(attestations: &[Attestation<T>]) -> Vec<Attestation<T>> {
+    let (unaggregated, mut aggregates): (Vec<_>, Vec<_>) =
+        attestations.iter().partition(|a| is_unaggregated(a));
+
+    aggregates.sort_by_key(|a| std::cmp::Reverse(a.aggregation_bits.num_set_bits()));
+
+    let extend = |candidate: &mut Attestation<T>| {
+        for other in aggregates.iter().chain(unaggregated.iter()) {
+            if candidate.signers_disjoint_from(other) {
+                candidate.aggregate(other);
+            }
+        }
+    };
+
+    let mut candidates: Vec<Attestation<T>> = vec![];
+
+    for seed in &aggregates {
+        if candidates
+            .iter()
+            .any(|candidate| is_subsumed_by(seed, candidate))
+        {
+            continue;
+        }
+
+        let mut candidate = (*seed).clone();
+        extend(&mut candidate);
+        candidates.push(candidate);
+    }
+
+    // Without any aggregates, build a single aggregate from the unaggregated attestations.
+    if aggregates.is_empty() {
+        if let Some(first) = unaggregated.first() {
+            let mut candidate = (*first).clone();
+            extend(&mut candidate);
+            candidates.push(candidate);
+        }
+    }
+
+    remove_subsumed_aggregates(&mut candidates);
+
+    candidates
+}
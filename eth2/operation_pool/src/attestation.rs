@@ -3,11 +3,34 @@ use state_processing::common::{get_attesting_indices, get_base_reward};
 use std::collections::HashMap;
 use types::{Attestation, BeaconState, BitList, ChainSpec, EthSpec};
 
+/// The objective used to value the fresh attesters of an attestation, when packing attestations
+/// into a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackingObjective {
+    /// Value each fresh attester at the reward paid to the proposer for including them.
+    ProposerReward,
+    /// Value each fresh attester at the reward paid to the proposer for including them, plus the
+    /// inclusion delay reward that the attester would forfeit if their attestation were left for
+    /// the next block.
+    ///
+    /// This prioritises attestations that are about to become too old for inclusion, and those
+    /// whose inclusion delay reward decays the most with each slot.
+    ProposerAndInclusionDelayReward,
+}
+
+impl Default for PackingObjective {
+    fn default() -> Self {
+        PackingObjective::ProposerAndInclusionDelayReward
+    }
+}
+
 pub struct AttMaxCover<'a, T: EthSpec> {
     /// Underlying attestation.
     att: &'a Attestation<T>,
-    /// Mapping of validator indices and their rewards.
+    /// Mapping of validator indices and their value according to the `PackingObjective`.
     fresh_validators_rewards: HashMap<u64, u64>,
+    /// Mapping of validator indices and the reward paid to the proposer for including them.
+    proposer_rewards: HashMap<u64, u64>,
 }
 
 impl<'a, T: EthSpec> AttMaxCover<'a, T> {
@@ -15,6 +38,7 @@ impl<'a, T: EthSpec> AttMaxCover<'a, T> {
         att: &'a Attestation<T>,
         state: &BeaconState<T>,
         total_active_balance: u64,
+        objective: PackingObjective,
         spec: &ChainSpec,
     ) -> Option<Self> {
         let fresh_validators = earliest_attestation_validators(att, state);
@@ -22,22 +46,96 @@ impl<'a, T: EthSpec> AttMaxCover<'a, T> {
             .get_beacon_committee(att.data.slot, att.data.index)
             .ok()?;
         let indices = get_attesting_indices::<T>(committee.committee, &fresh_validators).ok()?;
-        let fresh_validators_rewards: HashMap<u64, u64> = indices
+        let base_rewards: HashMap<u64, u64> = indices
             .iter()
             .map(|i| *i as u64)
             .flat_map(|validator_index| {
-                let reward =
+                let base_reward =
                     get_base_reward(state, validator_index as usize, total_active_balance, spec)
-                        .ok()?
-                        / spec.proposer_reward_quotient;
-                Some((validator_index, reward))
+                        .ok()?;
+                Some((validator_index, base_reward))
             })
             .collect();
+        let inclusion = InclusionTiming::new(att, state);
         Some(Self {
             att,
-            fresh_validators_rewards,
+            fresh_validators_rewards: base_rewards
+                .iter()
+                .map(|(&i, &base_reward)| {
+                    (i, attester_value(base_reward, inclusion, objective, spec))
+                })
+                .collect(),
+            proposer_rewards: base_rewards
+                .into_iter()
+                .map(|(i, base_reward)| (i, base_reward / spec.proposer_reward_quotient))
+                .collect(),
         })
     }
+
+    /// The underlying attestation.
+    pub fn attestation(&self) -> &'a Attestation<T> {
+        self.att
+    }
+
+    /// The reward paid to the proposer for including the validators in the current covering set.
+    pub fn proposer_reward(&self) -> u64 {
+        self.fresh_validators_rewards
+            .keys()
+            .filter_map(|i| self.proposer_rewards.get(i))
+            .sum()
+    }
+}
+
+/// Describes the timing of the inclusion of an attestation in a block at the state's slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InclusionTiming {
+    /// The number of slots between the attestation and the block.
+    pub distance: u64,
+    /// Whether the attestation will be too old for inclusion in the block at the next slot.
+    pub expiring: bool,
+}
+
+impl InclusionTiming {
+    pub fn new<T: EthSpec>(att: &Attestation<T>, state: &BeaconState<T>) -> Self {
+        let next_slot = state.slot + 1;
+        let expiring = att.data.slot + T::slots_per_epoch() < next_slot
+            || (att.data.target.epoch == state.previous_epoch()
+                && next_slot.epoch(T::slots_per_epoch()) > state.current_epoch());
+
+        Self {
+            distance: std::cmp::max(
+                state.slot.as_u64().saturating_sub(att.data.slot.as_u64()),
+                1,
+            ),
+            expiring,
+        }
+    }
+}
+
+/// The value of including the attestation of a fresh validator with the given `base_reward`,
+/// according to the `objective`.
+pub fn attester_value(
+    base_reward: u64,
+    inclusion: InclusionTiming,
+    objective: PackingObjective,
+    spec: &ChainSpec,
+) -> u64 {
+    let proposer_reward = base_reward / spec.proposer_reward_quotient;
+
+    match objective {
+        PackingObjective::ProposerReward => proposer_reward,
+        PackingObjective::ProposerAndInclusionDelayReward => {
+            let max_attester_reward = base_reward - proposer_reward;
+            let reward_now = max_attester_reward / inclusion.distance;
+            let reward_later = if inclusion.expiring {
+                0
+            } else {
+                max_attester_reward / (inclusion.distance + 1)
+            };
+
+            proposer_reward + reward_now - reward_later
+        }
+    }
 }
 
 impl<'a, T: EthSpec> MaxCover for AttMaxCover<'a, T> {
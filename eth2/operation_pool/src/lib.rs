@@ -1,13 +1,20 @@
+mod aggregation;
 mod attestation;
 mod attestation_id;
 mod max_cover;
+mod packing_report;
 mod persistence;
 
+pub use packing_report::{
+    AttestationPackingReport, DropReason, DroppedAttestation, PackedAttestation,
+};
 pub use persistence::PersistedOperationPool;
 
+pub use attestation::PackingObjective;
+
 use attestation::AttMaxCover;
 use attestation_id::AttestationId;
-use max_cover::maximum_cover;
+use max_cover::{maximum_cover_with_outcomes, MaxCoverOutcome};
 use parking_lot::RwLock;
 use state_processing::per_block_processing::errors::{
     AttestationValidationError, AttesterSlashingValidationError, ExitValidationError,
@@ -18,11 +25,12 @@ use state_processing::per_block_processing::{
     verify_attester_slashing, verify_exit, verify_exit_time_independent_only,
     verify_proposer_slashing, VerifySignatures,
 };
-use std::collections::{hash_map, HashMap, HashSet};
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use types::{
-    typenum::Unsigned, Attestation, AttesterSlashing, BeaconState, BeaconStateError, ChainSpec,
-    EthSpec, Fork, Hash256, ProposerSlashing, RelativeEpoch, SignedVoluntaryExit, Validator,
+    typenum::Unsigned, Attestation, AttesterSlashing, BeaconState, BeaconStateError, BitList,
+    ChainSpec, EthSpec, Fork, Hash256, ProposerSlashing, RelativeEpoch, SignedVoluntaryExit,
+    Validator,
 };
 
 #[derive(Default, Debug)]
@@ -38,6 +46,25 @@ pub struct OperationPool<T: EthSpec + Default> {
     _phantom: PhantomData<T>,
 }
 
+/// Statistics about the attestations in an `OperationPool`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AttestationStats {
+    /// The number of distinct `AttestationData` in the pool.
+    pub num_attestation_data: usize,
+    /// The number of aggregate attestations (signed by two or more validators).
+    pub num_aggregates: usize,
+    /// The number of unaggregated attestations (signed by a single validator).
+    pub num_unaggregated: usize,
+    /// The number of distinct signers of each `AttestationData`, summed across all data.
+    pub num_attesters: usize,
+    /// The number of signers of the largest stored aggregate for each `AttestationData`, summed
+    /// across all data.
+    ///
+    /// The shortfall from `num_attesters` is the coverage that must be recovered by aggregating
+    /// at block production time.
+    pub num_attesters_in_best_aggregates: usize,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum OpPoolError {
     GetAttestationsTotalBalanceError(BeaconStateError),
@@ -66,27 +93,7 @@ impl<T: EthSpec> OperationPool<T> {
         // Take a write lock on the attestations map.
         let mut attestations = self.attestations.write();
 
-        let existing_attestations = match attestations.entry(id) {
-            hash_map::Entry::Vacant(entry) => {
-                entry.insert(vec![attestation]);
-                return Ok(());
-            }
-            hash_map::Entry::Occupied(entry) => entry.into_mut(),
-        };
-
-        let mut aggregated = false;
-        for existing_attestation in existing_attestations.iter_mut() {
-            if existing_attestation.signers_disjoint_from(&attestation) {
-                existing_attestation.aggregate(&attestation);
-                aggregated = true;
-            } else if *existing_attestation == attestation {
-                aggregated = true;
-            }
-        }
-
-        if !aggregated {
-            existing_attestations.push(attestation);
-        }
+        aggregation::insert_attestation(attestations.entry(id).or_default(), attestation);
 
         Ok(())
     }
@@ -96,6 +103,42 @@ impl<T: EthSpec> OperationPool<T> {
         self.attestations.read().values().map(Vec::len).sum()
     }
 
+    /// Statistics describing the number of attestations in the pool and the validators they cover.
+    pub fn attestation_stats(&self) -> AttestationStats {
+        let attestations = self.attestations.read();
+
+        let mut stats = AttestationStats {
+            num_attestation_data: attestations.len(),
+            ..AttestationStats::default()
+        };
+
+        for attestations in attestations.values() {
+            let mut attesters = None::<BitList<T::MaxValidatorsPerCommittee>>;
+            let mut best_aggregate = 0;
+
+            for attestation in attestations {
+                let bits = &attestation.aggregation_bits;
+
+                if aggregation::is_unaggregated(attestation) {
+                    stats.num_unaggregated += 1;
+                } else {
+                    stats.num_aggregates += 1;
+                    best_aggregate = std::cmp::max(best_aggregate, bits.num_set_bits());
+                }
+
+                attesters = Some(match attesters {
+                    Some(attesters) => attesters.union(bits),
+                    None => bits.clone(),
+                });
+            }
+
+            stats.num_attesters += attesters.map_or(0, |bits| bits.num_set_bits());
+            stats.num_attesters_in_best_aggregates += best_aggregate;
+        }
+
+        stats
+    }
+
     /// Get a list of attestations for inclusion in a block.
     ///
     /// The `validity_filter` is a closure that provides extra filtering of the attestations
@@ -108,6 +151,35 @@ impl<T: EthSpec> OperationPool<T> {
         validity_filter: impl FnMut(&&Attestation<T>) -> bool,
         spec: &ChainSpec,
     ) -> Result<Vec<Attestation<T>>, OpPoolError> {
+        self.get_attestations_with_report(state, validity_filter, spec)
+            .map(|(attestations, _)| attestations)
+    }
+
+    /// As per `get_attestations`, but also return a report describing the reward offered by each
+    /// of the chosen attestations, and the reason each of the other candidates was dropped.
+    ///
+    /// The candidates are the aggregates built from the attestations in the pool for each
+    /// `AttestationData` (see `aggregation::covering_aggregates`), rather than the attestations
+    /// themselves. Attestations from other forks are not considered candidates and are omitted
+    /// from the report.
+    pub fn get_attestations_with_report(
+        &self,
+        state: &BeaconState<T>,
+        validity_filter: impl FnMut(&&Attestation<T>) -> bool,
+        spec: &ChainSpec,
+    ) -> Result<(Vec<Attestation<T>>, AttestationPackingReport), OpPoolError> {
+        self.pack_attestations(state, validity_filter, PackingObjective::default(), spec)
+    }
+
+    /// As per `get_attestations_with_report`, but value the attestations according to the given
+    /// `objective`.
+    pub fn pack_attestations(
+        &self,
+        state: &BeaconState<T>,
+        mut validity_filter: impl FnMut(&&Attestation<T>) -> bool,
+        objective: PackingObjective,
+        spec: &ChainSpec,
+    ) -> Result<(Vec<Attestation<T>>, AttestationPackingReport), OpPoolError> {
         // Attestations for the current fork, which may be from the current or previous epoch.
         let prev_epoch = state.previous_epoch();
         let current_epoch = state.current_epoch();
@@ -130,30 +202,79 @@ impl<T: EthSpec> OperationPool<T> {
         let total_active_balance = state
             .get_total_balance(&active_indices, spec)
             .map_err(OpPoolError::GetAttestationsTotalBalanceError)?;
-        let valid_attestations = reader
+
+        // Build the best aggregates from the stored aggregates and unaggregated attestations.
+        let candidates = reader
             .iter()
             .filter(|(key, _)| {
                 key.domain_bytes_match(&prev_domain_bytes)
                     || key.domain_bytes_match(&curr_domain_bytes)
             })
-            .flat_map(|(_, attestations)| attestations)
-            // That are valid...
-            .filter(|attestation| {
-                verify_attestation_for_block_inclusion(
-                    state,
-                    attestation,
-                    VerifySignatures::False,
-                    spec,
-                )
-                .is_ok()
-            })
-            .filter(validity_filter)
-            .flat_map(|att| AttMaxCover::new(att, state, total_active_balance, spec));
+            .flat_map(|(_, attestations)| aggregation::covering_aggregates(attestations))
+            .collect::<Vec<_>>();
+        drop(reader);
+
+        let mut report = AttestationPackingReport::default();
+        let mut valid_attestations = vec![];
+
+        for attestation in &candidates {
+            let drop_reason = if let Err(e) = verify_attestation_for_block_inclusion(
+                state,
+                attestation,
+                VerifySignatures::False,
+                spec,
+            ) {
+                DropReason::Invalid(format!("{:?}", e))
+            } else if !validity_filter(&attestation) {
+                DropReason::RejectedByFilter
+            } else if let Some(cover) =
+                AttMaxCover::new(attestation, state, total_active_balance, objective, spec)
+            {
+                valid_attestations.push(cover);
+                continue;
+            } else {
+                DropReason::UnknownCommittee
+            };
+
+            report
+                .dropped
+                .push(DroppedAttestation::new(attestation, drop_reason));
+        }
+
+        let mut selected = vec![];
+        for (cover, outcome) in
+            maximum_cover_with_outcomes(valid_attestations, T::MaxAttestations::to_usize())
+        {
+            let attestation = cover.attestation();
+            let drop_reason = match outcome {
+                MaxCoverOutcome::Selected { position, .. } => {
+                    selected.push((position, attestation, cover.proposer_reward()));
+                    continue;
+                }
+                MaxCoverOutcome::ZeroScore => DropReason::NoFreshValidators,
+                MaxCoverOutcome::Covered => DropReason::AlreadyCovered,
+                MaxCoverOutcome::LimitReached { .. } => DropReason::BlockFull {
+                    marginal_reward: cover.proposer_reward(),
+                },
+            };
+
+            report
+                .dropped
+                .push(DroppedAttestation::new(attestation, drop_reason));
+        }
+
+        selected.sort_by_key(|(position, _, _)| *position);
+        report.included = selected
+            .iter()
+            .map(|(_, attestation, reward)| PackedAttestation::new(attestation, *reward))
+            .collect();
+
+        let attestations = selected
+            .into_iter()
+            .map(|(_, attestation, _)| attestation.clone())
+            .collect();
 
-        Ok(maximum_cover(
-            valid_attestations,
-            T::MaxAttestations::to_usize(),
-        ))
+        Ok((attestations, report))
     }
 
     /// Remove attestations which are too old to be included in a block.
@@ -418,7 +539,7 @@ impl<T: EthSpec + Default> PartialEq for OperationPool<T> {
 // TODO: more tests
 #[cfg(all(test, not(debug_assertions)))]
 mod release_tests {
-    use super::attestation::earliest_attestation_validators;
+    use super::attestation::{attester_value, earliest_attestation_validators, InclusionTiming};
     use super::*;
     use state_processing::common::{get_attesting_indices, get_base_reward};
     use std::collections::BTreeSet;
@@ -662,8 +783,8 @@ mod release_tests {
         assert_eq!(op_pool.num_attestations(), committees.len());
     }
 
-    /// Adding lots of attestations that only intersect pairwise should lead to two aggregate
-    /// attestations.
+    /// Adding lots of attestations that only intersect pairwise should lead to a single aggregate
+    /// attestation.
     #[test]
     fn attestation_pairwise_overlapping() {
         let (ref mut state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(1);
@@ -699,10 +820,138 @@ mod release_tests {
             }
         }
 
-        // The attestations should get aggregated into two attestations that comprise all
-        // validators.
+        // Every second attestation is disjoint from the first aggregate and is merged into it,
+        // which subsumes the attestation in between. This leaves a single aggregate comprising
+        // all validators.
         assert_eq!(op_pool.attestations.read().len(), committees.len());
-        assert_eq!(op_pool.num_attestations(), 2 * committees.len());
+        assert_eq!(op_pool.num_attestations(), committees.len());
+        for attestations in op_pool.attestations.read().values() {
+            assert_eq!(
+                attestations[0].aggregation_bits.num_set_bits(),
+                spec.target_committee_size
+            );
+        }
+    }
+
+    /// Aggregates that are subsets of another aggregate should be removed from the pool.
+    #[test]
+    fn attestation_subsumed_aggregates() {
+        let (ref mut state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(1);
+
+        let op_pool = OperationPool::new();
+
+        let slot = state.slot - 1;
+        let committees = state
+            .get_beacon_committees_at_slot(slot)
+            .unwrap()
+            .into_iter()
+            .map(BeaconCommittee::into_owned)
+            .collect::<Vec<_>>();
+
+        for bc in &committees {
+            let insert = |range: std::ops::Range<usize>| {
+                let att = signed_attestation(
+                    &bc.committee,
+                    bc.index,
+                    keypairs,
+                    range,
+                    slot,
+                    state,
+                    spec,
+                    None,
+                );
+                op_pool
+                    .insert_attestation(att, &state.fork, state.genesis_validators_root, spec)
+                    .unwrap();
+            };
+
+            insert(0..4);
+            // A subset of an existing aggregate should not be stored.
+            insert(1..3);
+            assert_eq!(op_pool.num_attestations(), 1);
+            // An overlapping aggregate should be stored alongside the existing one.
+            insert(2..6);
+            assert_eq!(op_pool.num_attestations(), 2);
+            // A superset of the existing aggregates should replace them.
+            insert(0..8);
+            assert_eq!(op_pool.num_attestations(), 1);
+        }
+    }
+
+    /// Unaggregated attestations should be stored individually and used to extend the aggregates
+    /// at block production time.
+    #[test]
+    fn attestation_unaggregated_extend_aggregates() {
+        let (ref mut state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(1);
+
+        let op_pool = OperationPool::new();
+
+        let slot = state.slot - 1;
+        let committees = state
+            .get_beacon_committees_at_slot(slot)
+            .unwrap()
+            .into_iter()
+            .map(BeaconCommittee::into_owned)
+            .collect::<Vec<_>>();
+
+        let committee_size = spec.target_committee_size as usize;
+        let half = committee_size / 2;
+        let quarter = committee_size / 4;
+
+        for bc in &committees {
+            assert_eq!(bc.committee.len(), committee_size);
+
+            let insert = |range: std::ops::Range<usize>| {
+                let att = signed_attestation(
+                    &bc.committee,
+                    bc.index,
+                    keypairs,
+                    range,
+                    slot,
+                    state,
+                    spec,
+                    None,
+                );
+                op_pool
+                    .insert_attestation(att, &state.fork, state.genesis_validators_root, spec)
+                    .unwrap();
+            };
+
+            // Two overlapping aggregates.
+            insert(0..half);
+            insert(quarter..half + quarter);
+            // Unaggregated attestations for the second half of the committee, and a duplicate of a
+            // validator in the first aggregate.
+            for i in half..committee_size {
+                insert(i..i + 1);
+            }
+            insert(0..1);
+            insert(0..1);
+        }
+
+        let unaggregated_per_committee = committee_size - half + 1;
+        assert_eq!(
+            op_pool.attestation_stats(),
+            AttestationStats {
+                num_attestation_data: committees.len(),
+                num_aggregates: 2 * committees.len(),
+                num_unaggregated: unaggregated_per_committee * committees.len(),
+                num_attesters: committee_size * committees.len(),
+                num_attesters_in_best_aggregates: half * committees.len(),
+            }
+        );
+
+        state.slot += spec.min_attestation_inclusion_delay;
+        let best_attestations = op_pool
+            .get_attestations(state, |_| true, spec)
+            .expect("should have best attestations");
+
+        // The first aggregate should be extended by the unaggregated attestations to cover the
+        // whole committee, subsuming the second aggregate.
+        assert_eq!(best_attestations.len(), committees.len());
+        for att in &best_attestations {
+            assert_eq!(att.aggregation_bits.num_set_bits(), committee_size);
+        }
     }
 
     /// Create a bunch of attestations signed by a small number of validators, and another
@@ -715,8 +964,9 @@ mod release_tests {
         let small_step_size = 2;
         let big_step_size = 4;
 
+        // Use enough committees that there are more than `max_attestations` large attestations.
         let (ref mut state, ref keypairs, ref spec) =
-            attestation_test_state::<MainnetEthSpec>(big_step_size);
+            attestation_test_state::<MainnetEthSpec>(big_step_size + 1);
 
         let op_pool = OperationPool::new();
 
@@ -757,14 +1007,11 @@ mod release_tests {
             insert_attestations(committee, big_step_size);
         }
 
-        let num_small = target_committee_size / small_step_size;
         let num_big = target_committee_size / big_step_size;
 
+        // Each small attestation is subsumed by a large one, and is removed from the pool.
         assert_eq!(op_pool.attestations.read().len(), committees.len());
-        assert_eq!(
-            op_pool.num_attestations(),
-            (num_small + num_big) * committees.len()
-        );
+        assert_eq!(op_pool.num_attestations(), num_big * committees.len());
         assert!(op_pool.num_attestations() > max_attestations);
 
         state.slot += spec.min_attestation_inclusion_delay;
@@ -779,13 +1026,133 @@ mod release_tests {
         }
     }
 
+    /// Check that the packing report accounts for every candidate attestation, and that the
+    /// reported rewards are consistent with the order of selection.
+    #[test]
+    fn attestation_packing_report() {
+        let small_step_size = 2;
+        let big_step_size = 4;
+
+        // Use an extra committee, since the attestations for committee 0 will be filtered out.
+        let (ref mut state, ref keypairs, ref spec) =
+            attestation_test_state::<MainnetEthSpec>(big_step_size + 1);
+
+        let op_pool = OperationPool::new();
+
+        let slot = state.slot - 1;
+        let committees = state
+            .get_beacon_committees_at_slot(slot)
+            .unwrap()
+            .into_iter()
+            .map(BeaconCommittee::into_owned)
+            .collect::<Vec<_>>();
+
+        let max_attestations = <MainnetEthSpec as EthSpec>::MaxAttestations::to_usize();
+        let target_committee_size = spec.target_committee_size as usize;
+
+        for committee in &committees {
+            for &step_size in &[small_step_size, big_step_size] {
+                for i in (0..target_committee_size).step_by(step_size) {
+                    let att = signed_attestation(
+                        &committee.committee,
+                        committee.index,
+                        keypairs,
+                        i..i + step_size,
+                        slot,
+                        state,
+                        spec,
+                        if i == 0 { None } else { Some(0) },
+                    );
+                    op_pool
+                        .insert_attestation(att, &state.fork, state.genesis_validators_root, spec)
+                        .unwrap();
+                }
+            }
+        }
+
+        state.slot += spec.min_attestation_inclusion_delay;
+        let (best_attestations, report) = op_pool
+            .get_attestations_with_report(state, |att| att.data.index != 0, spec)
+            .expect("should have best attestations");
+
+        assert_eq!(best_attestations.len(), max_attestations);
+        assert_eq!(report.included.len(), max_attestations);
+        assert_eq!(
+            report.included.len() + report.dropped.len(),
+            op_pool.num_attestations()
+        );
+
+        for (att, packed) in best_attestations.iter().zip(report.included.iter()) {
+            assert_eq!(packed.slot, att.data.slot);
+            assert_eq!(packed.index, att.data.index);
+            assert_eq!(packed.num_signers, att.aggregation_bits.num_set_bits());
+            assert!(packed.marginal_reward > 0);
+        }
+
+        // Rewards should be in decreasing order of selection.
+        for pair in report.included.windows(2) {
+            assert!(pair[0].marginal_reward >= pair[1].marginal_reward);
+        }
+
+        // Every attestation for committee 0 should be rejected by the filter, and every other
+        // dropped attestation should be either redundant or excluded by the size of the block.
+        for dropped in &report.dropped {
+            match dropped.reason {
+                DropReason::RejectedByFilter => assert_eq!(dropped.index, 0),
+                DropReason::AlreadyCovered => assert_ne!(dropped.index, 0),
+                DropReason::BlockFull { marginal_reward } => {
+                    assert_ne!(dropped.index, 0);
+                    assert!(marginal_reward <= report.included.last().unwrap().marginal_reward);
+                }
+                ref other => panic!("unexpected drop reason: {:?}", other),
+            }
+        }
+        assert!(report
+            .dropped
+            .iter()
+            .any(|dropped| dropped.reason == DropReason::RejectedByFilter));
+    }
+
+    #[test]
+    fn attester_value_prioritises_decaying_rewards() {
+        let spec = &MainnetEthSpec::default_spec();
+        let base_reward = 1_000_000;
+        let value = |distance, expiring, objective| {
+            attester_value(
+                base_reward,
+                InclusionTiming { distance, expiring },
+                objective,
+                spec,
+            )
+        };
+        let proposer_reward = base_reward / spec.proposer_reward_quotient;
+
+        // The proposer reward does not depend on the inclusion distance.
+        for &(distance, expiring) in &[(1, false), (4, false), (32, true)] {
+            assert_eq!(
+                value(distance, expiring, PackingObjective::ProposerReward),
+                proposer_reward
+            );
+        }
+
+        let objective = PackingObjective::ProposerAndInclusionDelayReward;
+        // The inclusion delay reward decays fastest for recent attestations.
+        assert!(value(1, false, objective) > value(2, false, objective));
+        assert!(value(2, false, objective) > value(8, false, objective));
+        // Expiring attestations forfeit the entire inclusion delay reward if left out.
+        assert!(value(32, true, objective) > value(8, false, objective));
+        assert!(value(32, true, objective) > value(32, false, objective));
+        assert!(value(32, false, objective) > proposer_reward);
+    }
+
     #[test]
     fn attestation_rewards() {
         let small_step_size = 2;
         let big_step_size = 4;
 
+        // Use enough committees that there are more than `max_attestations` large attestations.
         let (ref mut state, ref keypairs, ref spec) =
-            attestation_test_state::<MainnetEthSpec>(big_step_size);
+            attestation_test_state::<MainnetEthSpec>(big_step_size + 1);
 
         let op_pool = OperationPool::new();
 
@@ -832,14 +1199,11 @@ mod release_tests {
             insert_attestations(committee, big_step_size);
         }
 
-        let num_small = target_committee_size / small_step_size;
         let num_big = target_committee_size / big_step_size;
 
+        // Each small attestation is subsumed by a large one, and is removed from the pool.
         assert_eq!(op_pool.attestations.read().len(), committees.len());
-        assert_eq!(
-            op_pool.num_attestations(),
-            (num_small + num_big) * committees.len()
-        );
+        assert_eq!(op_pool.num_attestations(), num_big * committees.len());
         assert!(op_pool.num_attestations() > max_attestations);
 
         state.slot += spec.min_attestation_inclusion_delay;
@@ -874,12 +1238,18 @@ mod release_tests {
 
             let fresh_indices = &att_indices - &seen_indices;
 
+            let inclusion = InclusionTiming::new(att, state);
             let rewards = fresh_indices
                 .iter()
                 .map(|validator_index| {
-                    get_base_reward(state, *validator_index as usize, total_active_balance, spec)
-                        .unwrap()
-                        / spec.proposer_reward_quotient
+                    let base_reward = get_base_reward(
+                        state,
+                        *validator_index as usize,
+                        total_active_balance,
+                        spec,
+                    )
+                    .unwrap();
+                    attester_value(base_reward, inclusion, PackingObjective::default(), spec)
                 })
                 .sum();
 
@@ -22,65 +22,90 @@ pub trait MaxCover {
     fn score(&self) -> usize;
 }
 
+/// The outcome of computing a maximum cover, for a single item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaxCoverOutcome {
+    /// The item was the `position`th item selected for the solution, at which time it covered
+    /// elements worth `score`.
+    Selected { position: usize, score: usize },
+    /// The item's covering set was empty to begin with.
+    ZeroScore,
+    /// The item's covering set was entirely covered by items selected before it.
+    Covered,
+    /// The solution reached its limit whilst the item still had a non-zero `score`.
+    LimitReached { score: usize },
+}
+
 /// Helper struct to track which items of the input are still available for inclusion.
 /// Saves removing elements from the work vector.
 struct MaxCoverItem<T> {
     item: T,
-    available: bool,
+    outcome: Option<MaxCoverOutcome>,
 }
 
-impl<T> MaxCoverItem<T> {
+impl<T: MaxCover> MaxCoverItem<T> {
     fn new(item: T) -> Self {
-        MaxCoverItem {
-            item,
-            available: true,
-        }
+        let outcome = if item.score() == 0 {
+            Some(MaxCoverOutcome::ZeroScore)
+        } else {
+            None
+        };
+        MaxCoverItem { item, outcome }
+    }
+
+    fn available(&self) -> bool {
+        self.outcome.is_none() && self.item.score() != 0
     }
 }
 
-/// Compute an approximate maximum cover using a greedy algorithm.
+/// Compute an approximate maximum cover using a greedy algorithm, returning each of the given
+/// items (in their original order) alongside its outcome.
 ///
 /// * Time complexity: `O(limit * items_iter.len())`
 /// * Space complexity: `O(item_iter.len())`
-pub fn maximum_cover<I, T>(items_iter: I, limit: usize) -> Vec<T::Object>
+pub fn maximum_cover_with_outcomes<I, T>(items_iter: I, limit: usize) -> Vec<(T, MaxCoverOutcome)>
 where
     I: IntoIterator<Item = T>,
     T: MaxCover,
 {
     // Construct an initial vec of all items, marked available.
-    let mut all_items: Vec<_> = items_iter
-        .into_iter()
-        .map(MaxCoverItem::new)
-        .filter(|x| x.item.score() != 0)
-        .collect();
-
-    let mut result = vec![];
+    let mut all_items: Vec<_> = items_iter.into_iter().map(MaxCoverItem::new).collect();
 
-    for _ in 0..limit {
+    for position in 0..limit {
         // Select the item with the maximum score.
         let (best_item, best_cover) = match all_items
             .iter_mut()
-            .filter(|x| x.available && x.item.score() != 0)
+            .filter(|x| x.available())
             .max_by_key(|x| x.item.score())
         {
             Some(x) => {
-                x.available = false;
+                x.outcome = Some(MaxCoverOutcome::Selected {
+                    position,
+                    score: x.item.score(),
+                });
                 (x.item.object(), x.item.covering_set().clone())
             }
-            None => return result,
+            None => break,
         };
 
         // Update the covering sets of the other items, for the inclusion of the selected item.
         // Items covered by the selected item can't be re-covered.
         all_items
             .iter_mut()
-            .filter(|x| x.available && x.item.score() != 0)
+            .filter(|x| x.available())
             .for_each(|x| x.item.update_covering_set(&best_item, &best_cover));
-
-        result.push(best_item);
     }
 
-    result
+    all_items
+        .into_iter()
+        .map(|x| {
+            let outcome = x.outcome.unwrap_or_else(|| match x.item.score() {
+                0 => MaxCoverOutcome::Covered,
+                score => MaxCoverOutcome::LimitReached { score },
+            });
+            (x.item, outcome)
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -89,6 +114,27 @@ mod test {
     use std::iter::FromIterator;
     use std::{collections::HashSet, hash::Hash};
 
+    /// Compute an approximate maximum cover, returning the selected objects in order of selection.
+    fn maximum_cover<I, T>(items_iter: I, limit: usize) -> Vec<T::Object>
+    where
+        I: IntoIterator<Item = T>,
+        T: MaxCover,
+    {
+        let mut selected = maximum_cover_with_outcomes(items_iter, limit)
+            .into_iter()
+            .filter_map(|(item, outcome)| match outcome {
+                MaxCoverOutcome::Selected { position, .. } => Some((position, item)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        selected.sort_by_key(|(position, _)| *position);
+
+        selected
+            .into_iter()
+            .map(|(_, item)| item.object())
+            .collect()
+    }
+
     impl<T> MaxCover for HashSet<T>
     where
         T: Clone + Eq + Hash,
@@ -186,4 +232,26 @@ mod test {
         assert_eq!(quality(&cover), 19);
         assert_eq!(cover.len(), 5);
     }
+
+    #[test]
+    fn outcomes() {
+        let sets = example_system();
+        let outcomes = maximum_cover_with_outcomes(sets, 1)
+            .into_iter()
+            .map(|(_, outcome)| outcome)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                MaxCoverOutcome::LimitReached { score: 1 },
+                MaxCoverOutcome::Covered,
+                MaxCoverOutcome::Selected {
+                    position: 0,
+                    score: 4
+                },
+                MaxCoverOutcome::Covered,
+                MaxCoverOutcome::Covered,
+            ]
+        );
+    }
 }
@@ -0,0 +1,87 @@
+use serde_derive::Serialize;
+use types::{Attestation, CommitteeIndex, Epoch, EthSpec, Hash256, Slot};
+
+/// Describes the outcome of packing the attestations from the pool into a block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AttestationPackingReport {
+    /// The attestations chosen for inclusion, in the order they were selected.
+    pub included: Vec<PackedAttestation>,
+    /// The candidate attestations which were not chosen for inclusion.
+    pub dropped: Vec<DroppedAttestation>,
+}
+
+impl AttestationPackingReport {
+    /// The sum of the marginal rewards of all included attestations.
+    pub fn total_reward(&self) -> u64 {
+        self.included.iter().map(|att| att.marginal_reward).sum()
+    }
+}
+
+/// An attestation that was included in a block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PackedAttestation {
+    pub slot: Slot,
+    pub index: CommitteeIndex,
+    pub beacon_block_root: Hash256,
+    pub target_epoch: Epoch,
+    /// The number of validators that signed the attestation.
+    pub num_signers: usize,
+    /// The proposer reward for the validators that had not been covered by the state or any
+    /// previously selected attestation at the time of selection.
+    pub marginal_reward: u64,
+}
+
+/// An attestation from the pool that was not included in a block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DroppedAttestation {
+    pub slot: Slot,
+    pub index: CommitteeIndex,
+    pub beacon_block_root: Hash256,
+    pub target_epoch: Epoch,
+    /// The number of validators that signed the attestation.
+    pub num_signers: usize,
+    pub reason: DropReason,
+}
+
+/// The reason for which an attestation was not included in a block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum DropReason {
+    /// The attestation is not valid for inclusion in a block at the state's slot.
+    Invalid(String),
+    /// The attestation was rejected by the caller-supplied validity filter.
+    RejectedByFilter,
+    /// The committee for the attestation could not be determined.
+    UnknownCommittee,
+    /// All signers of the attestation were already included in the state.
+    NoFreshValidators,
+    /// All fresh signers of the attestation were covered by other included attestations.
+    AlreadyCovered,
+    /// The block was full whilst the attestation still offered a non-zero reward.
+    BlockFull { marginal_reward: u64 },
+}
+
+impl PackedAttestation {
+    pub fn new<T: EthSpec>(att: &Attestation<T>, marginal_reward: u64) -> Self {
+        Self {
+            slot: att.data.slot,
+            index: att.data.index,
+            beacon_block_root: att.data.beacon_block_root,
+            target_epoch: att.data.target.epoch,
+            num_signers: att.aggregation_bits.num_set_bits(),
+            marginal_reward,
+        }
+    }
+}
+
+impl DroppedAttestation {
+    pub fn new<T: EthSpec>(att: &Attestation<T>, reason: DropReason) -> Self {
+        Self {
+            slot: att.data.slot,
+            index: att.data.index,
+            beacon_block_root: att.data.beacon_block_root,
+            target_epoch: att.data.target.epoch,
+            num_signers: att.aggregation_bits.num_set_bits(),
+            reason,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Applies arbitrary blocks to a fixed pre-state, without verifying signatures.
+#![no_main]
+#[macro_use]
+extern crate lazy_static;
+
+use libfuzzer_sys::fuzz_target;
+use state_processing::{per_block_processing, per_slot_processing, BlockSignatureStrategy};
+use types::{
+    test_utils::TestingBeaconStateBuilder, BeaconState, ChainSpec, EthSpec, MinimalEthSpec,
+    SignedBeaconBlock,
+};
+
+type E = MinimalEthSpec;
+
+const VALIDATOR_COUNT: usize = 64;
+
+lazy_static! {
+    static ref SPEC: ChainSpec = E::default_spec();
+    /// The state to which each block is applied, advanced to the slot after genesis.
+    static ref PRE_STATE: BeaconState<E> = {
+        let builder =
+            TestingBeaconStateBuilder::from_deterministic_keypairs(VALIDATOR_COUNT, &SPEC);
+        let (mut state, _keypairs) = builder.build();
+        per_slot_processing(&mut state, None, &SPEC).expect("should advance slot");
+        state.build_all_caches(&SPEC).expect("should build caches");
+        state
+    };
+}
+
+fuzz_target!(|block: SignedBeaconBlock<E>| {
+    let mut block = block;
+    let mut state = PRE_STATE.clone();
+
+    // Blocks which fail these checks are uninteresting, so set them correctly in order that the
+    // fuzzer spends its time on the block body.
+    block.message.slot = state.slot;
+    block.message.parent_root = state.latest_block_header.canonical_root();
+    block.message.proposer_index = state
+        .get_beacon_proposer_index(state.slot, &SPEC)
+        .expect("should get proposer index") as u64;
+
+    let _ = per_block_processing(
+        &mut state,
+        &block,
+        None,
+        BlockSignatureStrategy::NoVerification,
+        &SPEC,
+    );
+});
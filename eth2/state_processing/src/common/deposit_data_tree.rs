@@ -21,6 +21,30 @@ impl DepositDataTree {
         }
     }
 
+    /// Create a new Merkle tree of the first `mix_in_length` deposits, represented only by the
+    /// `finalized` hashes of the full subtrees that contain them (see `get_finalized_hashes`).
+    ///
+    /// Further deposits may be pushed to the tree and proven, however proofs cannot be generated
+    /// for the first `mix_in_length` deposits.
+    pub fn from_finalized_snapshot(
+        finalized: &[Hash256],
+        mix_in_length: usize,
+        depth: usize,
+    ) -> Result<Self, MerkleTreeError> {
+        Ok(Self {
+            tree: MerkleTree::from_finalized_snapshot(finalized, mix_in_length, depth)?,
+            mix_in_length,
+            depth,
+        })
+    }
+
+    /// Returns the hashes of the full subtrees which contain all of the deposits in this tree,
+    /// from left to right.
+    pub fn get_finalized_hashes(&self) -> Result<Vec<Hash256>, MerkleTreeError> {
+        self.tree
+            .get_finalized_hashes(self.mix_in_length, self.depth)
+    }
+
     /// Returns 32 bytes representing the "mix in length" for the merkle root of this tree.
     fn length_bytes(&self) -> Vec<u8> {
         int_to_bytes32(self.mix_in_length as u64)
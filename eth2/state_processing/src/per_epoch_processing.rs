@@ -10,7 +10,10 @@ pub mod registry_updates;
 pub mod tests;
 pub mod validator_statuses;
 
-pub use apply_rewards::process_rewards_and_penalties;
+pub use apply_rewards::{
+    get_attestation_deltas, get_proposer_deltas, process_rewards_and_penalties, AttestationDelta,
+    Delta,
+};
 pub use process_slashings::process_slashings;
 pub use registry_updates::process_registry_updates;
 pub use validator_statuses::{TotalBalances, ValidatorStatus, ValidatorStatuses};
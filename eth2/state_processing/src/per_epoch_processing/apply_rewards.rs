@@ -6,10 +6,10 @@ use safe_arith::SafeArith;
 use types::*;
 
 /// Use to track the changes to a validators balance.
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, PartialEq)]
 pub struct Delta {
-    rewards: u64,
-    penalties: u64,
+    pub rewards: u64,
+    pub penalties: u64,
 }
 
 impl Delta {
@@ -32,6 +32,41 @@ impl Delta {
     }
 }
 
+/// The changes to a validator's balance caused by its attestation in the previous epoch, split
+/// into the components defined in the spec.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct AttestationDelta {
+    pub source_delta: Delta,
+    pub target_delta: Delta,
+    pub head_delta: Delta,
+    pub inclusion_delay_delta: Delta,
+    pub inactivity_penalty_delta: Delta,
+}
+
+impl AttestationDelta {
+    /// Flatten into a single delta.
+    pub fn flatten(self) -> Result<Delta, Error> {
+        let AttestationDelta {
+            source_delta,
+            target_delta,
+            head_delta,
+            inclusion_delay_delta,
+            inactivity_penalty_delta,
+        } = self;
+        let mut result = Delta::default();
+        for delta in vec![
+            source_delta,
+            target_delta,
+            head_delta,
+            inclusion_delay_delta,
+            inactivity_penalty_delta,
+        ] {
+            result.combine(delta)?;
+        }
+        Ok(result)
+    }
+}
+
 /// Apply attester and proposer rewards.
 ///
 /// Spec v0.11.1
@@ -51,11 +86,13 @@ pub fn process_rewards_and_penalties<T: EthSpec>(
         return Err(Error::ValidatorStatusesInconsistent);
     }
 
-    let mut deltas = vec![Delta::default(); state.balances.len()];
+    let attestation_deltas = get_attestation_deltas(state, &validator_statuses, spec)?;
 
-    get_attestation_deltas(&mut deltas, state, &validator_statuses, spec)?;
+    let mut deltas = get_proposer_deltas(state, validator_statuses, spec)?;
 
-    get_proposer_deltas(&mut deltas, state, validator_statuses, spec)?;
+    for (delta, attestation_delta) in deltas.iter_mut().zip(attestation_deltas) {
+        delta.combine(attestation_delta.flatten()?)?;
+    }
 
     // Apply the deltas, erroring on overflow above but not on overflow below (saturating at 0
     // instead).
@@ -69,13 +106,16 @@ pub fn process_rewards_and_penalties<T: EthSpec>(
 
 /// For each attesting validator, reward the proposer who was first to include their attestation.
 ///
+/// Returns a delta for each validator in `validator_statuses`.
+///
 /// Spec v0.11.1
-fn get_proposer_deltas<T: EthSpec>(
-    deltas: &mut Vec<Delta>,
+pub fn get_proposer_deltas<T: EthSpec>(
     state: &BeaconState<T>,
-    validator_statuses: &mut ValidatorStatuses,
+    validator_statuses: &ValidatorStatuses,
     spec: &ChainSpec,
-) -> Result<(), Error> {
+) -> Result<Vec<Delta>, Error> {
+    let mut deltas = vec![Delta::default(); validator_statuses.statuses.len()];
+
     for (index, validator) in validator_statuses.statuses.iter().enumerate() {
         if validator.is_previous_epoch_attester && !validator.is_slashed {
             let inclusion = validator
@@ -98,40 +138,42 @@ fn get_proposer_deltas<T: EthSpec>(
         }
     }
 
-    Ok(())
+    Ok(deltas)
 }
 
-/// Apply rewards for participation in attestations during the previous epoch.
+/// Compute the rewards for participation in attestations during the previous epoch.
+///
+/// Returns a delta for each validator in `validator_statuses`, sans proposer rewards.
 ///
 /// Spec v0.11.1
-fn get_attestation_deltas<T: EthSpec>(
-    deltas: &mut Vec<Delta>,
+pub fn get_attestation_deltas<T: EthSpec>(
     state: &BeaconState<T>,
     validator_statuses: &ValidatorStatuses,
     spec: &ChainSpec,
-) -> Result<(), Error> {
+) -> Result<Vec<AttestationDelta>, Error> {
     let finality_delay = (state.previous_epoch() - state.finalized_checkpoint.epoch).as_u64();
 
-    for (index, validator) in validator_statuses.statuses.iter().enumerate() {
-        let base_reward = get_base_reward(
-            state,
-            index,
-            validator_statuses.total_balances.current_epoch(),
-            spec,
-        )?;
-
-        let delta = get_attestation_delta::<T>(
-            &validator,
-            &validator_statuses.total_balances,
-            base_reward,
-            finality_delay,
-            spec,
-        )?;
-
-        deltas[index].combine(delta)?;
-    }
+    validator_statuses
+        .statuses
+        .iter()
+        .enumerate()
+        .map(|(index, validator)| {
+            let base_reward = get_base_reward(
+                state,
+                index,
+                validator_statuses.total_balances.current_epoch(),
+                spec,
+            )?;
 
-    Ok(())
+            get_attestation_delta::<T>(
+                &validator,
+                &validator_statuses.total_balances,
+                base_reward,
+                finality_delay,
+                spec,
+            )
+        })
+        .collect()
 }
 
 /// Determine the delta for a single validator, sans proposer rewards.
@@ -143,8 +185,8 @@ fn get_attestation_delta<T: EthSpec>(
     base_reward: u64,
     finality_delay: u64,
     spec: &ChainSpec,
-) -> Result<Delta, Error> {
-    let mut delta = Delta::default();
+) -> Result<AttestationDelta, Error> {
+    let mut delta = AttestationDelta::default();
 
     // Is this validator eligible to be rewarded or penalized?
     // Spec: validator index in `eligible_validator_indices`
@@ -177,7 +219,7 @@ fn get_attestation_delta<T: EthSpec>(
     // Spec:
     // - validator index in `get_unslashed_attesting_indices(state, matching_source_attestations)`
     if validator.is_previous_epoch_attester && !validator.is_slashed {
-        delta.reward(
+        delta.source_delta.reward(
             base_reward
                 .safe_mul(total_attesting_balance_ebi)?
                 .safe_div(total_balance_ebi)?,
@@ -188,45 +230,49 @@ fn get_attestation_delta<T: EthSpec>(
         let inclusion = validator
             .inclusion_info
             .expect("It is a logic error for an attester not to have an inclusion delay.");
-        delta.reward(max_attester_reward.safe_div(inclusion.delay)?)?;
+        delta
+            .inclusion_delay_delta
+            .reward(max_attester_reward.safe_div(inclusion.delay)?)?;
     } else {
-        delta.penalize(base_reward)?;
+        delta.source_delta.penalize(base_reward)?;
     }
 
     // Expected FFG target.
     // Spec:
     // - validator index in `get_unslashed_attesting_indices(state, matching_target_attestations)`
     if validator.is_previous_epoch_target_attester && !validator.is_slashed {
-        delta.reward(
+        delta.target_delta.reward(
             base_reward
                 .safe_mul(matching_target_balance_ebi)?
                 .safe_div(total_balance_ebi)?,
         )?;
     } else {
-        delta.penalize(base_reward)?;
+        delta.target_delta.penalize(base_reward)?;
     }
 
     // Expected head.
     // Spec:
     // - validator index in `get_unslashed_attesting_indices(state, matching_head_attestations)`
     if validator.is_previous_epoch_head_attester && !validator.is_slashed {
-        delta.reward(
+        delta.head_delta.reward(
             base_reward
                 .safe_mul(matching_head_balance_ebi)?
                 .safe_div(total_balance_ebi)?,
         )?;
     } else {
-        delta.penalize(base_reward)?;
+        delta.head_delta.penalize(base_reward)?;
     }
 
     // Inactivity penalty
     if finality_delay > spec.min_epochs_to_inactivity_penalty {
         // All eligible validators are penalized
-        delta.penalize(spec.base_rewards_per_epoch.safe_mul(base_reward)?)?;
+        delta
+            .inactivity_penalty_delta
+            .penalize(spec.base_rewards_per_epoch.safe_mul(base_reward)?)?;
 
         // Additionally, all validators whose FFG target didn't match are penalized extra
         if !validator.is_previous_epoch_target_attester {
-            delta.penalize(
+            delta.inactivity_penalty_delta.penalize(
                 validator
                     .current_epoch_effective_balance
                     .safe_mul(finality_delay)?
@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use ssz::{Decode, Encode};
+use types::{BeaconState, MainnetEthSpec};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(decoded) = BeaconState::<MainnetEthSpec>::from_ssz_bytes(data) {
+        assert_eq!(decoded.as_ssz_bytes(), data);
+    }
+});
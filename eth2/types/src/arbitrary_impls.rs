@@ -0,0 +1,192 @@
+//! This module contains implementations of `arbitrary::Arbitrary` for the types which contain
+//! `Hash256` values.
+//!
+//! `ethereum-types` does not implement `Arbitrary` for its hashes, so these types cannot derive it.
+//! Their fields should be kept in sync with the struct definitions.
+use crate::{
+    AttestationData, BeaconBlock, BeaconBlockHeader, BeaconState, Checkpoint, Deposit, DepositData,
+    DepositMessage, Eth1Data, EthSpec, FixedVector, ForkData, Hash256, HistoricalBatch,
+    SigningRoot, Unsigned, Validator, VariableList,
+};
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+fn hash256(u: &mut Unstructured<'_>) -> Result<Hash256> {
+    <[u8; 32]>::arbitrary(u).map(Hash256::from)
+}
+
+fn hash256_vector<N: Unsigned>(u: &mut Unstructured<'_>) -> Result<FixedVector<Hash256, N>> {
+    let hashes = (0..N::to_usize())
+        .map(|_| hash256(u))
+        .collect::<Result<Vec<_>>>()?;
+    FixedVector::new(hashes).map_err(|_| Error::IncorrectFormat)
+}
+
+fn hash256_list<N: Unsigned>(u: &mut Unstructured<'_>) -> Result<VariableList<Hash256, N>> {
+    let len = u.arbitrary_len::<[u8; 32]>()?;
+    if len > N::to_usize() {
+        return Err(Error::IncorrectFormat);
+    }
+
+    let hashes = (0..len).map(|_| hash256(u)).collect::<Result<Vec<_>>>()?;
+    VariableList::new(hashes).map_err(|_| Error::IncorrectFormat)
+}
+
+impl Arbitrary for Checkpoint {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            epoch: Arbitrary::arbitrary(u)?,
+            root: hash256(u)?,
+        })
+    }
+}
+
+impl Arbitrary for AttestationData {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            slot: Arbitrary::arbitrary(u)?,
+            index: Arbitrary::arbitrary(u)?,
+            beacon_block_root: hash256(u)?,
+            source: Arbitrary::arbitrary(u)?,
+            target: Arbitrary::arbitrary(u)?,
+        })
+    }
+}
+
+impl<T: EthSpec + Arbitrary> Arbitrary for BeaconBlock<T> {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            slot: Arbitrary::arbitrary(u)?,
+            proposer_index: Arbitrary::arbitrary(u)?,
+            parent_root: hash256(u)?,
+            state_root: hash256(u)?,
+            body: Arbitrary::arbitrary(u)?,
+        })
+    }
+}
+
+impl Arbitrary for BeaconBlockHeader {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            slot: Arbitrary::arbitrary(u)?,
+            proposer_index: Arbitrary::arbitrary(u)?,
+            parent_root: hash256(u)?,
+            state_root: hash256(u)?,
+            body_root: hash256(u)?,
+        })
+    }
+}
+
+impl Arbitrary for Deposit {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            proof: hash256_vector(u)?,
+            data: Arbitrary::arbitrary(u)?,
+        })
+    }
+}
+
+impl Arbitrary for DepositData {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            pubkey: Arbitrary::arbitrary(u)?,
+            withdrawal_credentials: hash256(u)?,
+            amount: Arbitrary::arbitrary(u)?,
+            signature: Arbitrary::arbitrary(u)?,
+        })
+    }
+}
+
+impl Arbitrary for DepositMessage {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            pubkey: Arbitrary::arbitrary(u)?,
+            withdrawal_credentials: hash256(u)?,
+            amount: Arbitrary::arbitrary(u)?,
+        })
+    }
+}
+
+impl Arbitrary for Eth1Data {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            deposit_root: hash256(u)?,
+            deposit_count: Arbitrary::arbitrary(u)?,
+            block_hash: hash256(u)?,
+        })
+    }
+}
+
+impl Arbitrary for Validator {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            pubkey: Arbitrary::arbitrary(u)?,
+            withdrawal_credentials: hash256(u)?,
+            effective_balance: Arbitrary::arbitrary(u)?,
+            slashed: Arbitrary::arbitrary(u)?,
+            activation_eligibility_epoch: Arbitrary::arbitrary(u)?,
+            activation_epoch: Arbitrary::arbitrary(u)?,
+            exit_epoch: Arbitrary::arbitrary(u)?,
+            withdrawable_epoch: Arbitrary::arbitrary(u)?,
+        })
+    }
+}
+
+impl<T: EthSpec> Arbitrary for HistoricalBatch<T> {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            block_roots: hash256_vector(u)?,
+            state_roots: hash256_vector(u)?,
+        })
+    }
+}
+
+impl Arbitrary for ForkData {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            current_version: Arbitrary::arbitrary(u)?,
+            genesis_validators_root: hash256(u)?,
+        })
+    }
+}
+
+impl Arbitrary for SigningRoot {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            object_root: hash256(u)?,
+            domain: hash256(u)?,
+        })
+    }
+}
+
+/// The caches are not part of the consensus data, so they are always empty.
+impl<T: EthSpec + Arbitrary> Arbitrary for BeaconState<T> {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            genesis_time: Arbitrary::arbitrary(u)?,
+            genesis_validators_root: hash256(u)?,
+            slot: Arbitrary::arbitrary(u)?,
+            fork: Arbitrary::arbitrary(u)?,
+            latest_block_header: Arbitrary::arbitrary(u)?,
+            block_roots: hash256_vector(u)?,
+            state_roots: hash256_vector(u)?,
+            historical_roots: hash256_list(u)?,
+            eth1_data: Arbitrary::arbitrary(u)?,
+            eth1_data_votes: Arbitrary::arbitrary(u)?,
+            eth1_deposit_index: Arbitrary::arbitrary(u)?,
+            validators: Arbitrary::arbitrary(u)?,
+            balances: Arbitrary::arbitrary(u)?,
+            randao_mixes: hash256_vector(u)?,
+            slashings: Arbitrary::arbitrary(u)?,
+            previous_epoch_attestations: Arbitrary::arbitrary(u)?,
+            current_epoch_attestations: Arbitrary::arbitrary(u)?,
+            justification_bits: Arbitrary::arbitrary(u)?,
+            previous_justified_checkpoint: Arbitrary::arbitrary(u)?,
+            current_justified_checkpoint: Arbitrary::arbitrary(u)?,
+            finalized_checkpoint: Arbitrary::arbitrary(u)?,
+            committee_caches: Default::default(),
+            pubkey_cache: Default::default(),
+            exit_cache: Default::default(),
+            tree_hash_cache: None,
+        })
+    }
+}
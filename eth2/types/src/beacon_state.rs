@@ -21,12 +21,14 @@ use tree_hash_derive::TreeHash;
 
 pub use self::committee_cache::CommitteeCache;
 pub use clone_config::CloneConfig;
+pub use diff::{BeaconStateDiff, ElementDiff, FieldDiff, FieldRoots, ValidatorDiff};
 pub use eth_spec::*;
 pub use tree_hash_cache::BeaconTreeHashCache;
 
 #[macro_use]
 mod committee_cache;
 mod clone_config;
+mod diff;
 mod exit_cache;
 mod pubkey_cache;
 mod tests;
@@ -161,10 +163,12 @@ where
     pub block_roots: FixedVector<Hash256, T::SlotsPerHistoricalRoot>,
     #[compare_fields(as_slice)]
     pub state_roots: FixedVector<Hash256, T::SlotsPerHistoricalRoot>,
+    #[compare_fields(as_slice)]
     pub historical_roots: VariableList<Hash256, T::HistoricalRootsLimit>,
 
     // Ethereum 1.0 chain data
     pub eth1_data: Eth1Data,
+    #[compare_fields(as_slice)]
     pub eth1_data_votes: VariableList<Eth1Data, T::SlotsPerEth1VotingPeriod>,
     pub eth1_deposit_index: u64,
 
@@ -175,13 +179,17 @@ where
     pub balances: VariableList<u64, T::ValidatorRegistryLimit>,
 
     // Randomness
+    #[compare_fields(as_slice)]
     pub randao_mixes: FixedVector<Hash256, T::EpochsPerHistoricalVector>,
 
     // Slashings
+    #[compare_fields(as_slice)]
     pub slashings: FixedVector<u64, T::EpochsPerSlashingsVector>,
 
     // Attestations
+    #[compare_fields(as_slice)]
     pub previous_epoch_attestations: VariableList<PendingAttestation<T>, T::MaxPendingAttestations>,
+    #[compare_fields(as_slice)]
     pub current_epoch_attestations: VariableList<PendingAttestation<T>, T::MaxPendingAttestations>,
 
     // Finality
@@ -197,24 +205,28 @@ where
     #[ssz(skip_deserializing)]
     #[tree_hash(skip_hashing)]
     #[test_random(default)]
+    #[compare_fields(skip)]
     pub committee_caches: [CommitteeCache; CACHED_EPOCHS],
     #[serde(skip_serializing, skip_deserializing)]
     #[ssz(skip_serializing)]
     #[ssz(skip_deserializing)]
     #[tree_hash(skip_hashing)]
     #[test_random(default)]
+    #[compare_fields(skip)]
     pub pubkey_cache: PubkeyCache,
     #[serde(skip_serializing, skip_deserializing)]
     #[ssz(skip_serializing)]
     #[ssz(skip_deserializing)]
     #[tree_hash(skip_hashing)]
     #[test_random(default)]
+    #[compare_fields(skip)]
     pub exit_cache: ExitCache,
     #[serde(skip_serializing, skip_deserializing)]
     #[ssz(skip_serializing)]
     #[ssz(skip_deserializing)]
     #[tree_hash(skip_hashing)]
     #[test_random(default)]
+    #[compare_fields(skip)]
     pub tree_hash_cache: Option<BeaconTreeHashCache>,
 }
 
@@ -260,6 +260,14 @@ impl CommitteeCache {
     }
 }
 
+/// The cache is not part of the consensus data, so an arbitrary cache is an empty one.
+#[cfg(feature = "arbitrary-fuzz")]
+impl arbitrary::Arbitrary for CommitteeCache {
+    fn arbitrary(_u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        Ok(Self::default())
+    }
+}
+
 /// Returns a list of all `validators` indices where the validator is active at the given
 /// `epoch`.
 ///
@@ -0,0 +1,381 @@
+//! Field-level differences between two `BeaconState`s.
+//!
+//! Intended for finding the divergent field(s) when two implementations disagree on a state root.
+use super::{BeaconState, EthSpec, Hash256, Validator};
+use compare_fields::{CompareFields, Comparison, FieldComparison};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::Debug;
+use tree_hash::TreeHash;
+
+/// The differences between two `BeaconState`s, `a` and `b`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BeaconStateDiff {
+    /// The tree hash root of each top-level field of both states, in the order of the fields in
+    /// the state.
+    pub field_roots: Vec<FieldRoots>,
+    /// The top-level fields which differ.
+    ///
+    /// Differences in the `validators` and `balances` lists are only described in `validators`.
+    pub fields: Vec<FieldDiff>,
+    /// The validators whose record or balance differs, in order of validator index.
+    pub validators: Vec<ValidatorDiff>,
+}
+
+/// The tree hash roots of a top-level field in both states.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FieldRoots {
+    pub field: String,
+    pub equal: bool,
+    pub a: Hash256,
+    pub b: Hash256,
+}
+
+/// A field which differs between the two states.
+///
+/// The values are `Debug` representations, except for lists, where only the differing elements
+/// are described.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+    /// The elements which differ, if the field is a list.
+    pub elements: Vec<ElementDiff>,
+}
+
+/// An element of a list which differs between the two states.
+///
+/// The values are `None` if the element is not present in the list of that state.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ElementDiff {
+    pub index: usize,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+/// A validator whose record or balance differs between the two states.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ValidatorDiff {
+    pub index: usize,
+    /// The fields of the validator record which differ. If the validator is only present in one
+    /// state, contains a single `validator` field with the whole record.
+    pub fields: Vec<FieldDiff>,
+    pub balance_a: Option<u64>,
+    pub balance_b: Option<u64>,
+}
+
+impl BeaconStateDiff {
+    /// Returns the differences between `a` and `b`.
+    ///
+    /// Computes the tree hash root of every field of both states without using their tree hash
+    /// caches, so this is not fast.
+    pub fn new<T: EthSpec>(a: &BeaconState<T>, b: &BeaconState<T>) -> Self {
+        let field_roots = field_roots(a)
+            .into_iter()
+            .zip(field_roots(b))
+            .map(|((field, root_a), (_, root_b))| FieldRoots {
+                field: field.to_string(),
+                equal: root_a == root_b,
+                a: root_a,
+                b: root_b,
+            })
+            .collect();
+
+        let fields = a
+            .compare_fields(b)
+            .into_iter()
+            .filter(Comparison::not_equal)
+            .map(|comparison| match comparison {
+                Comparison::Child(field) => FieldDiff::from(field),
+                Comparison::Parent {
+                    field_name,
+                    children,
+                    ..
+                } => {
+                    let elements = if field_name == "validators" || field_name == "balances" {
+                        vec![]
+                    } else {
+                        children
+                            .into_iter()
+                            .enumerate()
+                            .filter(|(_, child)| child.not_equal())
+                            .map(|(index, child)| ElementDiff {
+                                index,
+                                a: unwrap_option_debug(child.a),
+                                b: unwrap_option_debug(child.b),
+                            })
+                            .collect()
+                    };
+
+                    FieldDiff {
+                        field: field_name,
+                        a: None,
+                        b: None,
+                        elements,
+                    }
+                }
+            })
+            .collect();
+
+        let validators = (0..std::cmp::max(a.validators.len(), b.validators.len()))
+            .filter_map(|index| {
+                let fields = validator_fields(a.validators.get(index), b.validators.get(index));
+                let balance_a = a.balances.get(index).copied();
+                let balance_b = b.balances.get(index).copied();
+
+                if fields.is_empty() && balance_a == balance_b {
+                    None
+                } else {
+                    Some(ValidatorDiff {
+                        index,
+                        fields,
+                        balance_a,
+                        balance_b,
+                    })
+                }
+            })
+            .collect();
+
+        Self {
+            field_roots,
+            fields,
+            validators,
+        }
+    }
+
+    /// Returns `true` if the states do not differ.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.validators.is_empty()
+    }
+
+    /// Returns the names of the top-level fields whose tree hash roots differ.
+    pub fn differing_roots(&self) -> impl Iterator<Item = &str> {
+        self.field_roots
+            .iter()
+            .filter(|roots| !roots.equal)
+            .map(|roots| roots.field.as_str())
+    }
+}
+
+impl From<FieldComparison> for FieldDiff {
+    fn from(comparison: FieldComparison) -> Self {
+        Self {
+            field: comparison.field_name,
+            a: Some(comparison.a),
+            b: Some(comparison.b),
+            elements: vec![],
+        }
+    }
+}
+
+/// Returns the differing fields of two validator records, either of which may be absent.
+fn validator_fields(a: Option<&Validator>, b: Option<&Validator>) -> Vec<FieldDiff> {
+    match (a, b) {
+        (Some(a), Some(b)) => a
+            .compare_fields(b)
+            .into_iter()
+            .filter(Comparison::not_equal)
+            .filter_map(|comparison| match comparison {
+                Comparison::Child(field) => Some(FieldDiff::from(field)),
+                Comparison::Parent { .. } => None,
+            })
+            .collect(),
+        (None, None) => vec![],
+        (a, b) => vec![FieldDiff {
+            field: "validator".to_string(),
+            a: a.map(debug),
+            b: b.map(debug),
+            elements: vec![],
+        }],
+    }
+}
+
+/// Returns the tree hash root of each top-level field of `state`, in order.
+fn field_roots<T: EthSpec>(state: &BeaconState<T>) -> Vec<(&'static str, Hash256)> {
+    vec![
+        ("genesis_time", state.genesis_time.tree_hash_root()),
+        (
+            "genesis_validators_root",
+            state.genesis_validators_root.tree_hash_root(),
+        ),
+        ("slot", state.slot.tree_hash_root()),
+        ("fork", state.fork.tree_hash_root()),
+        (
+            "latest_block_header",
+            state.latest_block_header.tree_hash_root(),
+        ),
+        ("block_roots", state.block_roots.tree_hash_root()),
+        ("state_roots", state.state_roots.tree_hash_root()),
+        ("historical_roots", state.historical_roots.tree_hash_root()),
+        ("eth1_data", state.eth1_data.tree_hash_root()),
+        ("eth1_data_votes", state.eth1_data_votes.tree_hash_root()),
+        (
+            "eth1_deposit_index",
+            state.eth1_deposit_index.tree_hash_root(),
+        ),
+        ("validators", state.validators.tree_hash_root()),
+        ("balances", state.balances.tree_hash_root()),
+        ("randao_mixes", state.randao_mixes.tree_hash_root()),
+        ("slashings", state.slashings.tree_hash_root()),
+        (
+            "previous_epoch_attestations",
+            state.previous_epoch_attestations.tree_hash_root(),
+        ),
+        (
+            "current_epoch_attestations",
+            state.current_epoch_attestations.tree_hash_root(),
+        ),
+        (
+            "justification_bits",
+            state.justification_bits.tree_hash_root(),
+        ),
+        (
+            "previous_justified_checkpoint",
+            state.previous_justified_checkpoint.tree_hash_root(),
+        ),
+        (
+            "current_justified_checkpoint",
+            state.current_justified_checkpoint.tree_hash_root(),
+        ),
+        (
+            "finalized_checkpoint",
+            state.finalized_checkpoint.tree_hash_root(),
+        ),
+    ]
+}
+
+fn debug<T: Debug>(item: &T) -> String {
+    format!("{:?}", item)
+}
+
+/// `compare_fields` describes list elements as `Some(element)` or `None`, this returns the
+/// `element` or `None`.
+fn unwrap_option_debug(string: String) -> Option<String> {
+    if string == "None" {
+        None
+    } else if string.starts_with("Some(") && string.ends_with(')') {
+        Some(string["Some(".len()..string.len().saturating_sub(1)].to_string())
+    } else {
+        Some(string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Epoch, Eth1Data, MinimalEthSpec, Slot};
+
+    type E = MinimalEthSpec;
+
+    fn state() -> BeaconState<E> {
+        let mut state = BeaconState::new(0, Eth1Data::default(), &E::default_spec());
+        for i in 0..8 {
+            state.validators.push(Validator::default()).unwrap();
+            state.balances.push(32_000_000_000 + i).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn equal_states() {
+        let state = state();
+        let diff = BeaconStateDiff::new(&state, &state.clone());
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.differing_roots().count(), 0);
+        assert_eq!(
+            diff.field_roots.len(),
+            21,
+            "should have a root for each field"
+        );
+    }
+
+    #[test]
+    fn caches_are_not_compared() {
+        let spec = E::default_spec();
+        let mut a = state();
+        for validator in a.validators.iter_mut() {
+            validator.activation_epoch = Epoch::new(0);
+        }
+        let mut b = a.clone();
+        b.build_all_caches(&spec).expect("should build caches");
+        b.update_tree_hash_cache()
+            .expect("should update tree hash cache");
+
+        let diff = BeaconStateDiff::new(&a, &b);
+
+        assert!(diff.is_empty(), "should not compare caches: {:?}", diff);
+        assert_eq!(diff.differing_roots().count(), 0);
+    }
+
+    #[test]
+    fn differing_fields() {
+        let a = state();
+        let mut b = a.clone();
+        b.slot = Slot::new(3);
+        b.block_roots[2] = Hash256::repeat_byte(1);
+        b.balances[4] += 1;
+        b.validators[6].slashed = true;
+
+        let diff = BeaconStateDiff::new(&a, &b);
+
+        assert_eq!(
+            diff.differing_roots().collect::<Vec<_>>(),
+            vec!["slot", "block_roots", "validators", "balances"]
+        );
+
+        assert_eq!(
+            diff.fields
+                .iter()
+                .map(|field| field.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["slot", "block_roots", "validators", "balances"]
+        );
+        assert_eq!(diff.fields[0].b, Some("Slot(3)".to_string()));
+        assert_eq!(
+            diff.fields[1].elements,
+            vec![ElementDiff {
+                index: 2,
+                a: Some(debug(&a.block_roots[2])),
+                b: Some(debug(&Hash256::repeat_byte(1))),
+            }]
+        );
+
+        assert_eq!(
+            diff.validators,
+            vec![
+                ValidatorDiff {
+                    index: 4,
+                    fields: vec![],
+                    balance_a: Some(a.balances[4]),
+                    balance_b: Some(a.balances[4] + 1),
+                },
+                ValidatorDiff {
+                    index: 6,
+                    fields: vec![FieldDiff {
+                        field: "slashed".to_string(),
+                        a: Some("false".to_string()),
+                        b: Some("true".to_string()),
+                        elements: vec![],
+                    }],
+                    balance_a: Some(a.balances[6]),
+                    balance_b: Some(a.balances[6]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_validator() {
+        let a = state();
+        let mut b = a.clone();
+        b.validators.push(a.validators[0].clone()).unwrap();
+
+        let diff = BeaconStateDiff::new(&a, &b);
+
+        assert_eq!(diff.validators.len(), 1);
+        assert_eq!(diff.validators[0].index, 8);
+        assert_eq!(diff.validators[0].fields[0].a, None);
+        assert_eq!(diff.validators[0].balance_b, None);
+    }
+}
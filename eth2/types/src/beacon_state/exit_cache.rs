@@ -70,3 +70,11 @@ impl ExitCache {
         Ok(self.exits_per_epoch.get(&epoch).cloned().unwrap_or(0))
     }
 }
+
+/// The cache is not part of the consensus data, so an arbitrary cache is an empty one.
+#[cfg(feature = "arbitrary-fuzz")]
+impl arbitrary::Arbitrary for ExitCache {
+    fn arbitrary(_u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        Ok(Self::default())
+    }
+}
@@ -39,3 +39,11 @@ impl PubkeyCache {
         self.map.get(pubkey).copied()
     }
 }
+
+/// The cache is not part of the consensus data, so an arbitrary cache is an empty one.
+#[cfg(feature = "arbitrary-fuzz")]
+impl arbitrary::Arbitrary for PubkeyCache {
+    fn arbitrary(_u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        Ok(Self::default())
+    }
+}
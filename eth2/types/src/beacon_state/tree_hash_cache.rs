@@ -166,6 +166,14 @@ impl BeaconTreeHashCache {
     }
 }
 
+/// The cache is not part of the consensus data, so an arbitrary cache is an empty one.
+#[cfg(feature = "arbitrary-fuzz")]
+impl arbitrary::Arbitrary for BeaconTreeHashCache {
+    fn arbitrary(_u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        Ok(Self::default())
+    }
+}
+
 /// A specialized cache for computing the tree hash root of `state.validators`.
 #[derive(Debug, PartialEq, Clone, Default, Encode, Decode)]
 struct ValidatorsListTreeHashCache {
@@ -550,6 +550,14 @@ pub struct YamlConfig {
     random_subnets_per_validator: u64,
     epochs_per_random_subnet_subscription: u64,
     seconds_per_eth1_block: u64,
+
+    // Deposit contract
+    /// The `eth_chainId` of the eth1 chain hosting the deposit contract, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit_chain_id: Option<u64>,
+    /// The `net_version` of the eth1 chain hosting the deposit contract, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit_network_id: Option<u64>,
 }
 
 impl Default for YamlConfig {
@@ -630,6 +638,10 @@ impl YamlConfig {
             random_subnets_per_validator: spec.random_subnets_per_validator,
             epochs_per_random_subnet_subscription: spec.epochs_per_random_subnet_subscription,
             seconds_per_eth1_block: spec.seconds_per_eth1_block,
+
+            // Deposit contract
+            deposit_chain_id: None,
+            deposit_network_id: None,
         }
     }
 
@@ -761,6 +773,20 @@ mod yaml_tests {
         assert_eq!(from, yamlconfig);
     }
 
+    #[test]
+    fn deposit_chain_and_network_ids() {
+        let yamlconfig: YamlConfig =
+            serde_yaml::from_str("DEPOSIT_CHAIN_ID: 5\nDEPOSIT_NETWORK_ID: 5")
+                .expect("should deserialize ids");
+        assert_eq!(yamlconfig.deposit_chain_id, Some(5));
+        assert_eq!(yamlconfig.deposit_network_id, Some(5));
+
+        let yamlconfig: YamlConfig =
+            serde_yaml::from_str("SECONDS_PER_ETH1_BLOCK: 14").expect("should deserialize");
+        assert_eq!(yamlconfig.deposit_chain_id, None);
+        assert_eq!(yamlconfig.deposit_network_id, None);
+    }
+
     #[test]
     fn apply_to_spec() {
         let mut spec = ChainSpec::minimal();
@@ -14,6 +14,7 @@ use tree_hash_derive::TreeHash;
 #[derive(
     Debug, Clone, PartialEq, Default, Serialize, Deserialize, Encode, Decode, TreeHash, TestRandom,
 )]
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
 pub struct EnrForkId {
     #[serde(
         serialize_with = "fork_to_hex_str",
@@ -132,6 +132,7 @@ macro_rules! params_from_eth_spec {
 ///
 /// Spec v0.11.1
 #[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
 pub struct MainnetEthSpec;
 
 impl EthSpec for MainnetEthSpec {
@@ -165,6 +166,7 @@ pub type FoundationBeaconState = BeaconState<MainnetEthSpec>;
 ///
 /// Spec v0.11.1
 #[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
 pub struct MinimalEthSpec;
 
 impl EthSpec for MinimalEthSpec {
@@ -199,6 +201,7 @@ pub type MinimalBeaconState = BeaconState<MinimalEthSpec>;
 
 /// Interop testnet spec
 #[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
 pub struct InteropEthSpec;
 
 impl EthSpec for InteropEthSpec {
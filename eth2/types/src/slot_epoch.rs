@@ -22,10 +22,12 @@ use std::iter::Iterator;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, Sub, SubAssign};
 
 #[derive(Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
 #[serde(transparent)]
 pub struct Slot(u64);
 
 #[derive(Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
 pub struct Epoch(u64);
 
 impl_common!(Slot);
@@ -155,6 +155,17 @@ impl_ssz!(
 
 impl_tree_hash!(AggregateSignature, BLS_AGG_SIG_BYTE_SIZE);
 
+#[cfg(feature = "arbitrary")]
+impl arbitrary::Arbitrary for AggregateSignature {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let mut aggregate = AggregateSignature::new();
+        for _ in 0..u.int_in_range(0..=4)? {
+            aggregate.add(&arbitrary::Arbitrary::arbitrary(u)?);
+        }
+        Ok(aggregate)
+    }
+}
+
 impl Serialize for AggregateSignature {
     /// Serde serialization is compliant the Ethereum YAML test format.
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
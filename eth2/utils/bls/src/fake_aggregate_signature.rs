@@ -111,6 +111,8 @@ impl_ssz!(
 
 impl_tree_hash!(FakeAggregateSignature, BLS_AGG_SIG_BYTE_SIZE);
 
+impl_arbitrary!(FakeAggregateSignature, BLS_AGG_SIG_BYTE_SIZE);
+
 impl Serialize for FakeAggregateSignature {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -118,6 +118,8 @@ impl_ssz!(FakePublicKey, BLS_PUBLIC_KEY_BYTE_SIZE, "FakePublicKey");
 
 impl_tree_hash!(FakePublicKey, BLS_PUBLIC_KEY_BYTE_SIZE);
 
+impl_arbitrary!(FakePublicKey, BLS_PUBLIC_KEY_BYTE_SIZE);
+
 impl Serialize for FakePublicKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -93,6 +93,8 @@ impl_ssz!(FakeSignature, BLS_SIG_BYTE_SIZE, "FakeSignature");
 
 impl_tree_hash!(FakeSignature, BLS_SIG_BYTE_SIZE);
 
+impl_arbitrary!(FakeSignature, BLS_SIG_BYTE_SIZE);
+
 impl Serialize for FakeSignature {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -75,6 +75,23 @@ macro_rules! impl_tree_hash {
     };
 }
 
+/// Implements `arbitrary::Arbitrary` by decoding arbitrary bytes.
+///
+/// Only suitable for types which accept any bytes of the correct length (e.g., the fake and
+/// `*Bytes` types), since inputs which fail to decode are rejected.
+macro_rules! impl_arbitrary {
+    ($type: ty, $byte_size: expr) => {
+        #[cfg(feature = "arbitrary")]
+        impl arbitrary::Arbitrary for $type {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+                let mut bytes = [0; $byte_size];
+                u.fill_buffer(&mut bytes)?;
+                <$type>::from_bytes(&bytes).map_err(|_| arbitrary::Error::IncorrectFormat)
+            }
+        }
+    };
+}
+
 macro_rules! bytes_struct {
     ($name: ident, $type: ty, $byte_size: expr, $small_name: expr,
      $type_str: expr, $byte_size_str: expr) => {
@@ -252,5 +269,7 @@ macro_rules! bytes_struct {
                 Ok(signature)
             }
         }
+
+        impl_arbitrary!($name, $byte_size);
     };
 }
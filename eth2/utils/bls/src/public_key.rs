@@ -106,6 +106,14 @@ impl_ssz!(PublicKey, BLS_PUBLIC_KEY_BYTE_SIZE, "PublicKey");
 
 impl_tree_hash!(PublicKey, BLS_PUBLIC_KEY_BYTE_SIZE);
 
+#[cfg(feature = "arbitrary")]
+impl arbitrary::Arbitrary for PublicKey {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let secret_key: SecretKey = arbitrary::Arbitrary::arbitrary(u)?;
+        Ok(PublicKey::from_secret_key(&secret_key))
+    }
+}
+
 impl Serialize for PublicKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
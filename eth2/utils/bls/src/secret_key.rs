@@ -60,6 +60,23 @@ impl Serialize for SecretKey {
     }
 }
 
+/// Generates a valid secret key, rather than decoding arbitrary bytes (almost all of which are
+/// not below the curve order).
+#[cfg(feature = "arbitrary")]
+impl arbitrary::Arbitrary for SecretKey {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        // Secret keys are 32-byte big-endian integers, left-padded to 48 bytes. Clearing the top
+        // two bits keeps the key below the curve order and setting the lowest bit keeps it
+        // non-zero.
+        let mut bytes = [0; BLS_SECRET_KEY_BYTE_SIZE];
+        let offset = BLS_SECRET_KEY_BYTE_SIZE - 32;
+        u.fill_buffer(&mut bytes[offset..])?;
+        bytes[offset] &= 0x3f;
+        bytes[BLS_SECRET_KEY_BYTE_SIZE - 1] |= 1;
+        SecretKey::from_bytes(&bytes).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 impl<'de> Deserialize<'de> for SecretKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
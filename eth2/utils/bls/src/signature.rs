@@ -88,6 +88,15 @@ impl_ssz!(Signature, BLS_SIG_BYTE_SIZE, "Signature");
 
 impl_tree_hash!(Signature, BLS_SIG_BYTE_SIZE);
 
+#[cfg(feature = "arbitrary")]
+impl arbitrary::Arbitrary for Signature {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let message: [u8; 32] = arbitrary::Arbitrary::arbitrary(u)?;
+        let secret_key: SecretKey = arbitrary::Arbitrary::arbitrary(u)?;
+        Ok(Signature::new(&message, &secret_key))
+    }
+}
+
 impl Serialize for Signature {
     /// Serde serialization is compliant the Ethereum YAML test format.
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -3,7 +3,8 @@
 //! Returns comparisons as data, without making assumptions about the desired equality (e.g.,
 //! does not `panic!` on inequality).
 //!
-//! Note: `compare_fields_derive` requires `PartialEq` and `Debug` implementations.
+//! Note: `compare_fields_derive` requires `PartialEq` and `Debug` implementations. Fields with
+//! the `#[compare_fields(skip)]` attribute are not compared.
 //!
 //! ## Example
 //!
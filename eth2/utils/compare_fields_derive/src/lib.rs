@@ -12,6 +12,12 @@ fn is_slice(field: &syn::Field) -> bool {
     })
 }
 
+fn is_skipped(field: &syn::Field) -> bool {
+    field.attrs.iter().any(|attr| {
+        attr.path.is_ident("compare_fields") && attr.tts.to_string().replace(" ", "") == "(skip)"
+    })
+}
+
 #[proc_macro_derive(CompareFields, attributes(compare_fields))]
 pub fn compare_fields_derive(input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input as DeriveInput);
@@ -27,6 +33,10 @@ pub fn compare_fields_derive(input: TokenStream) -> TokenStream {
     let mut quotes = vec![];
 
     for field in struct_data.fields.iter() {
+        if is_skipped(field) {
+            continue;
+        }
+
         let ident_a = match &field.ident {
             Some(ref ident) => ident,
             _ => panic!("compare_fields_derive only supports named struct fields."),
@@ -26,6 +26,11 @@ pub const HARDCODED_DEPOSIT_CONTRACT: &[u8] = include_bytes!("../testnet5/deposi
 pub const HARDCODED_GENESIS_STATE: &[u8] = include_bytes!("../testnet5/genesis.ssz");
 pub const HARDCODED_BOOT_ENR: &[u8] = include_bytes!("../testnet5/boot_enr.yaml");
 
+/// The `eth_chainId` and `net_version` of Goerli, which hosts the deposit contract of the
+/// hard-coded testnet.
+pub const HARDCODED_DEPOSIT_CHAIN_ID: u64 = 5;
+pub const HARDCODED_DEPOSIT_NETWORK_ID: u64 = 5;
+
 /// Specifies an Eth2 testnet.
 ///
 /// See the crate-level documentation for more details.
@@ -57,10 +62,7 @@ impl<E: EthSpec> Eth2TestnetConfig<E> {
                 BeaconState::from_ssz_bytes(HARDCODED_GENESIS_STATE)
                     .map_err(|e| format!("Unable to parse genesis state: {:?}", e))?,
             ),
-            yaml_config: Some(
-                serde_yaml::from_reader(HARDCODED_YAML_CONFIG)
-                    .map_err(|e| format!("Unable to parse genesis state: {:?}", e))?,
-            ),
+            yaml_config: Some(hard_coded_yaml_config()?),
         })
     }
 
@@ -196,6 +198,22 @@ impl<E: EthSpec> Eth2TestnetConfig<E> {
     }
 }
 
+/// Parses the `YamlConfig` of the hard-coded testnet.
+///
+/// The published testnet config does not specify the eth1 chain hosting the deposit contract, so
+/// the Goerli ids are used unless the config sets them.
+fn hard_coded_yaml_config() -> Result<YamlConfig, String> {
+    let mut yaml_config: YamlConfig = serde_yaml::from_reader(HARDCODED_YAML_CONFIG)
+        .map_err(|e| format!("Unable to parse yaml config: {:?}", e))?;
+    yaml_config.deposit_chain_id = yaml_config
+        .deposit_chain_id
+        .or(Some(HARDCODED_DEPOSIT_CHAIN_ID));
+    yaml_config.deposit_network_id = yaml_config
+        .deposit_network_id
+        .or(Some(HARDCODED_DEPOSIT_NETWORK_ID));
+    Ok(yaml_config)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,6 +234,20 @@ mod tests {
     }
     */
 
+    #[test]
+    fn hard_coded_yaml_config_has_deposit_chain() {
+        let yaml_config = hard_coded_yaml_config().expect("should decode hard_coded yaml config");
+
+        assert_eq!(
+            yaml_config.deposit_chain_id,
+            Some(HARDCODED_DEPOSIT_CHAIN_ID)
+        );
+        assert_eq!(
+            yaml_config.deposit_network_id,
+            Some(HARDCODED_DEPOSIT_NETWORK_ID)
+        );
+    }
+
     #[test]
     fn round_trip() {
         let spec = &E::default_spec();
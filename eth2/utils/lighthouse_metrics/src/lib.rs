@@ -8,6 +8,8 @@
 //! - `IncCounter`: used to represent an ideally ever-growing, never-shrinking integer (e.g.,
 //! number of block processing requests).
 //! - `IntGauge`: used to represent an varying integer (e.g., number of attestations per block).
+//! - `IntCounterVec` and `IntGaugeVec`: a family of counters or gauges, distinguished by a set of
+//! labels (e.g., a gauge per validator).
 //!
 //! ## Important
 //!
@@ -56,7 +58,10 @@
 
 use prometheus::{HistogramOpts, HistogramTimer, Opts};
 
-pub use prometheus::{Encoder, Histogram, IntCounter, IntGauge, Result, TextEncoder};
+pub use prometheus::{
+    Encoder, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Result,
+    TextEncoder,
+};
 
 /// Collect all the metrics for reporting.
 pub fn gather() -> Vec<prometheus::proto::MetricFamily> {
@@ -90,6 +95,45 @@ pub fn try_create_histogram(name: &str, help: &str) -> Result<Histogram> {
     Ok(histogram)
 }
 
+/// Attempts to create an `IntCounterVec`, returning `Err` if the registry does not accept the
+/// counter (potentially due to naming conflict).
+pub fn try_create_int_counter_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntCounterVec> {
+    let opts = Opts::new(name, help);
+    let counter_vec = IntCounterVec::new(opts, label_names)?;
+    prometheus::register(Box::new(counter_vec.clone()))?;
+    Ok(counter_vec)
+}
+
+/// Attempts to create an `IntGaugeVec`, returning `Err` if the registry does not accept the gauge
+/// (potentially due to naming conflict).
+pub fn try_create_int_gauge_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntGaugeVec> {
+    let opts = Opts::new(name, help);
+    let gauge_vec = IntGaugeVec::new(opts, label_names)?;
+    prometheus::register(Box::new(gauge_vec.clone()))?;
+    Ok(gauge_vec)
+}
+
+/// Attempts to create a `HistogramVec`, returning `Err` if the registry does not accept the
+/// histogram (potentially due to naming conflict).
+pub fn try_create_histogram_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<HistogramVec> {
+    let opts = HistogramOpts::new(name, help);
+    let histogram_vec = HistogramVec::new(opts, label_names)?;
+    prometheus::register(Box::new(histogram_vec.clone()))?;
+    Ok(histogram_vec)
+}
+
 /// Starts a timer for the given `Histogram`, stopping when it gets dropped or given to `stop_timer(..)`.
 pub fn start_timer(histogram: &Result<Histogram>) -> Option<HistogramTimer> {
     if let Ok(histogram) = histogram {
@@ -124,9 +168,36 @@ pub fn set_gauge(gauge: &Result<IntGauge>, value: i64) {
     }
 }
 
+/// Increments the counter with the given `label_values` in an `IntCounterVec`.
+pub fn inc_counter_vec(counter_vec: &Result<IntCounterVec>, label_values: &[&str]) {
+    if let Ok(counter_vec) = counter_vec {
+        if let Ok(counter) = counter_vec.get_metric_with_label_values(label_values) {
+            counter.inc();
+        }
+    }
+}
+
+/// Sets the gauge with the given `label_values` in an `IntGaugeVec`.
+pub fn set_gauge_vec(gauge_vec: &Result<IntGaugeVec>, label_values: &[&str], value: i64) {
+    if let Ok(gauge_vec) = gauge_vec {
+        if let Ok(gauge) = gauge_vec.get_metric_with_label_values(label_values) {
+            gauge.set(value);
+        }
+    }
+}
+
 /// Sets the value of a `Histogram` manually.
 pub fn observe(histogram: &Result<Histogram>, value: f64) {
     if let Ok(histogram) = histogram {
         histogram.observe(value);
     }
 }
+
+/// Sets the value of the `Histogram` with the given `label_values` in a `HistogramVec` manually.
+pub fn observe_vec(histogram_vec: &Result<HistogramVec>, label_values: &[&str], value: f64) {
+    if let Ok(histogram_vec) = histogram_vec {
+        if let Ok(histogram) = histogram_vec.get_metric_with_label_values(label_values) {
+            histogram.observe(value);
+        }
+    }
+}
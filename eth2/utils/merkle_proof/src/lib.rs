@@ -27,6 +27,10 @@ pub enum MerkleTree {
     ///
     /// It represents a Merkle tree of 2^depth zero leaves.
     Zero(usize),
+    /// Full subtree whose leaves have been discarded, leaving only its hash.
+    ///
+    /// Proofs cannot be generated for leaves within a finalized subtree.
+    Finalized(H256),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -41,6 +45,8 @@ pub enum MerkleTreeError {
     DepthTooSmall,
     // Overflow occurred
     ArithError,
+    // Trying to generate a proof or snapshot within a finalized subtree
+    FinalizedNodeReached,
 }
 
 impl MerkleTree {
@@ -78,6 +84,107 @@ impl MerkleTree {
         }
     }
 
+    /// Create a new Merkle tree of the given `depth` whose first `leaf_count` leaves are
+    /// represented only by the `finalized` hashes of the full subtrees which contain them, from
+    /// left to right (see `get_finalized_hashes`).
+    ///
+    /// Leaves may be pushed to the resulting tree, however proofs may only be generated for leaves
+    /// at indices of `leaf_count` or greater.
+    pub fn from_finalized_snapshot(
+        finalized: &[H256],
+        leaf_count: usize,
+        depth: usize,
+    ) -> Result<Self, MerkleTreeError> {
+        use MerkleTree::*;
+
+        if finalized.is_empty() {
+            return if leaf_count == 0 {
+                Ok(Zero(depth))
+            } else {
+                Err(MerkleTreeError::Invalid)
+            };
+        }
+
+        if depth > MAX_TREE_DEPTH {
+            return Err(MerkleTreeError::Invalid);
+        }
+
+        if leaf_count == 2usize.pow(depth as u32) {
+            return if finalized.len() == 1 {
+                Ok(Finalized(finalized[0]))
+            } else {
+                Err(MerkleTreeError::Invalid)
+            };
+        }
+
+        if depth == 0 || leaf_count > 2usize.pow(depth as u32) {
+            return Err(MerkleTreeError::Invalid);
+        }
+
+        let subtree_capacity = 2usize.pow(depth as u32 - 1);
+        let (left_subtree, right_subtree) = if leaf_count <= subtree_capacity {
+            (
+                MerkleTree::from_finalized_snapshot(finalized, leaf_count, depth - 1)?,
+                Zero(depth - 1),
+            )
+        } else {
+            (
+                Finalized(finalized[0]),
+                MerkleTree::from_finalized_snapshot(
+                    &finalized[1..],
+                    leaf_count - subtree_capacity,
+                    depth - 1,
+                )?,
+            )
+        };
+        let hash = H256::from_slice(&hash32_concat(
+            left_subtree.hash().as_bytes(),
+            right_subtree.hash().as_bytes(),
+        ));
+
+        Ok(Node(hash, Box::new(left_subtree), Box::new(right_subtree)))
+    }
+
+    /// Returns the hashes of the full subtrees which contain the first `leaf_count` leaves of
+    /// this tree of the given `depth`, from left to right.
+    ///
+    /// These hashes are sufficient to recreate the tree with `from_finalized_snapshot`.
+    pub fn get_finalized_hashes(
+        &self,
+        leaf_count: usize,
+        depth: usize,
+    ) -> Result<Vec<H256>, MerkleTreeError> {
+        let mut hashes = vec![];
+        let mut current_node = self;
+        let mut current_depth = depth;
+        let mut remaining = leaf_count;
+
+        while remaining > 0 {
+            if remaining == 2usize.pow(current_depth as u32) {
+                hashes.push(current_node.hash());
+                break;
+            }
+
+            let (left, right) = match current_node {
+                MerkleTree::Node(_, left, right) => (left, right),
+                MerkleTree::Finalized(_) => return Err(MerkleTreeError::FinalizedNodeReached),
+                _ => return Err(MerkleTreeError::Invalid),
+            };
+
+            let subtree_capacity = 2usize.pow(current_depth as u32 - 1);
+            if remaining <= subtree_capacity {
+                current_node = left;
+            } else {
+                hashes.push(left.hash());
+                current_node = right;
+                remaining -= subtree_capacity;
+            }
+            current_depth -= 1;
+        }
+
+        Ok(hashes)
+    }
+
     /// Push an element in the MerkleTree.
     /// MerkleTree and depth must be correct, as the algorithm expects valid data.
     pub fn push_leaf(&mut self, elem: H256, depth: usize) -> Result<(), MerkleTreeError> {
@@ -90,6 +197,7 @@ impl MerkleTree {
 
         match self {
             Leaf(_) => return Err(MerkleTreeError::LeafReached),
+            Finalized(_) => return Err(MerkleTreeError::MerkleTreeFull),
             Zero(_) => {
                 mem::replace(self, MerkleTree::create(&[elem], depth));
             }
@@ -98,7 +206,19 @@ impl MerkleTree {
                 let right: &mut MerkleTree = &mut *right;
                 match (&*left, &*right) {
                     // Tree is full
-                    (Leaf(_), Leaf(_)) => return Err(MerkleTreeError::MerkleTreeFull),
+                    (Leaf(_), Leaf(_)) | (Finalized(_), Leaf(_)) => {
+                        return Err(MerkleTreeError::MerkleTreeFull)
+                    }
+                    // Finalized on left branch and zero on right branch, insert on right side
+                    (Finalized(_), Zero(_)) => {
+                        mem::replace(right, MerkleTree::create(&[elem], depth - 1));
+                    }
+                    // Left branch is finalized (and therefore full) so insert in right node
+                    (Finalized(_), Node(_, _, _)) | (Finalized(_), Finalized(_)) => {
+                        if let Err(e) = right.push_leaf(elem, depth - 1) {
+                            return Err(e);
+                        }
+                    }
                     // There is a right node so insert in right node
                     (Node(_, _, _), Node(_, _, _)) => {
                         if let Err(e) = right.push_leaf(elem, depth - 1) {
@@ -143,13 +263,14 @@ impl MerkleTree {
             MerkleTree::Leaf(h) => h,
             MerkleTree::Node(h, _, _) => h,
             MerkleTree::Zero(depth) => H256::from_slice(&ZERO_HASHES[depth]),
+            MerkleTree::Finalized(h) => h,
         }
     }
 
     /// Get a reference to the left and right subtrees if they exist.
     pub fn left_and_right_branches(&self) -> Option<(&Self, &Self)> {
         match *self {
-            MerkleTree::Leaf(_) | MerkleTree::Zero(0) => None,
+            MerkleTree::Leaf(_) | MerkleTree::Zero(0) | MerkleTree::Finalized(_) => None,
             MerkleTree::Node(_, ref l, ref r) => Some((l, r)),
             MerkleTree::Zero(depth) => Some((&ZERO_NODES[depth - 1], &ZERO_NODES[depth - 1])),
         }
@@ -167,14 +288,21 @@ impl MerkleTree {
     ///
     /// The Merkle proof is in "bottom-up" order, starting with a leaf node
     /// and moving up the tree. Its length will be exactly equal to `depth`.
+    ///
+    /// ## Panics
+    ///
+    /// If `index` is within a finalized subtree.
     pub fn generate_proof(&self, index: usize, depth: usize) -> (H256, Vec<H256>) {
         let mut proof = vec![];
         let mut current_node = self;
         let mut current_depth = depth;
         while current_depth > 0 {
             let ith_bit = (index >> (current_depth - 1)) & 0x01;
-            // Note: unwrap is safe because leaves are only ever constructed at depth == 0.
-            let (left, right) = current_node.left_and_right_branches().unwrap();
+            // Note: unwrap is safe because leaves are only ever constructed at depth == 0 and
+            // finalized subtrees are not traversed (by the contract of this function).
+            let (left, right) = current_node
+                .left_and_right_branches()
+                .expect("index should not be within a finalized subtree");
 
             // Go right, include the left branch in the proof.
             if ith_bit == 1 {
@@ -287,6 +415,68 @@ mod tests {
         TestResult::from_bool(proofs_ok)
     }
 
+    #[quickcheck]
+    fn quickcheck_finalized_snapshot_and_push(
+        int_leaves: Vec<u64>,
+        finalized_count: usize,
+        depth: usize,
+    ) -> TestResult {
+        if depth == 0
+            || depth > MAX_TREE_DEPTH
+            || int_leaves.len() > 2usize.pow(depth as u32)
+            || finalized_count > int_leaves.len()
+        {
+            return TestResult::discard();
+        }
+
+        let leaves: Vec<_> = int_leaves.into_iter().map(H256::from_low_u64_be).collect();
+        let full_tree = MerkleTree::create(&leaves, depth);
+
+        let finalized = full_tree
+            .get_finalized_hashes(finalized_count, depth)
+            .expect("should get finalized hashes");
+        if finalized.len() != finalized_count.count_ones() as usize {
+            return TestResult::failed();
+        }
+
+        let mut snapshot_tree =
+            MerkleTree::from_finalized_snapshot(&finalized, finalized_count, depth)
+                .expect("should create tree from snapshot");
+        if snapshot_tree.hash() != MerkleTree::create(&leaves[..finalized_count], depth).hash() {
+            return TestResult::failed();
+        }
+
+        let proofs_ok = (finalized_count..leaves.len()).all(|i| {
+            assert_eq!(snapshot_tree.push_leaf(leaves[i], depth), Ok(()));
+            let (stored_leaf, branch) = snapshot_tree.generate_proof(i, depth);
+            stored_leaf == leaves[i]
+                && verify_merkle_proof(leaves[i], &branch, depth, i, snapshot_tree.hash())
+        });
+
+        TestResult::from_bool(proofs_ok && snapshot_tree.hash() == full_tree.hash())
+    }
+
+    #[test]
+    fn finalized_snapshot_invalid() {
+        let leaf = H256::from([0xAA; 32]);
+
+        assert_eq!(
+            MerkleTree::from_finalized_snapshot(&[], 1, 2),
+            Err(MerkleTreeError::Invalid),
+            "missing finalized hashes"
+        );
+        assert_eq!(
+            MerkleTree::from_finalized_snapshot(&[leaf, leaf], 2, 2),
+            Err(MerkleTreeError::Invalid),
+            "too many finalized hashes"
+        );
+        assert_eq!(
+            MerkleTree::from_finalized_snapshot(&[leaf], 5, 2),
+            Err(MerkleTreeError::Invalid),
+            "leaf count exceeds capacity"
+        );
+    }
+
     #[test]
     fn sparse_zero_correct() {
         let depth = 2;
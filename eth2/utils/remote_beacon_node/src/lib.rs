@@ -115,6 +115,10 @@ impl<E: EthSpec> HttpClient<E> {
         Consensus(self.clone())
     }
 
+    pub fn admin(&self) -> Admin<E> {
+        Admin(self.clone())
+    }
+
     fn url(&self, path: &str) -> Result<Url, Error> {
         self.url.join(path).map_err(|e| e.into())
     }
@@ -694,6 +698,55 @@ impl<E: EthSpec> Advanced<E> {
     }
 }
 
+/// Provides the functions on the `/lighthouse/admin` endpoint of the node.
+#[derive(Clone)]
+pub struct Admin<E>(HttpClient<E>);
+
+impl<E: EthSpec> Admin<E> {
+    fn url(&self, path: &str) -> Result<Url, Error> {
+        self.0
+            .url("lighthouse/admin/peers/")
+            .and_then(move |url| url.join(path).map_err(Error::from))
+            .map_err(Into::into)
+    }
+
+    /// Dials an ENR (prefixed with `enr:`) or a multiaddr.
+    pub fn dial(&self, peer: String) -> impl Future<Item = (), Error = Error> {
+        self.post_peer("dial", peer)
+    }
+
+    /// Disconnects a peer id.
+    pub fn disconnect(&self, peer_id: String) -> impl Future<Item = (), Error = Error> {
+        self.post_peer("disconnect", peer_id)
+    }
+
+    /// Bans a peer id or an IP address.
+    pub fn ban(&self, peer: String) -> impl Future<Item = (), Error = Error> {
+        self.post_peer("ban", peer)
+    }
+
+    /// Unbans a peer id or an IP address.
+    pub fn unban(&self, peer: String) -> impl Future<Item = (), Error = Error> {
+        self.post_peer("unban", peer)
+    }
+
+    /// Adds a trusted peer, given as an ENR or a multiaddr which ends in `/p2p/<peer-id>`.
+    pub fn add_trusted_peer(&self, peer: String) -> impl Future<Item = (), Error = Error> {
+        self.post_peer("trusted", peer)
+    }
+
+    fn post_peer(&self, path: &str, peer: String) -> impl Future<Item = (), Error = Error> {
+        let client = self.0.clone();
+
+        self.url(path).into_future().and_then(move |url| {
+            client
+                .json_post::<_>(url, peer)
+                .and_then(|response| error_for_status(response).map_err(Error::from))
+                .map(|_| ())
+        })
+    }
+}
+
 /// Provides the functions on the `/consensus` endpoint of the node.
 #[derive(Clone)]
 pub struct Consensus<E>(HttpClient<E>);
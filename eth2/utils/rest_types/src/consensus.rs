@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
-use state_processing::per_epoch_processing::ValidatorStatus;
+use state_processing::per_epoch_processing::{AttestationDelta, Delta, ValidatorStatus};
 use types::{Epoch, PublicKeyBytes};
 
 #[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
@@ -64,3 +64,69 @@ pub struct IndividualVotesResponse {
     /// Voting statistics for the validator, if they voted in the given epoch.
     pub vote: Option<IndividualVote>,
 }
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct IndividualRewardsRequest {
+    /// The epoch in which the attestations being rewarded were made.
+    ///
+    /// Rewards are applied during the transition out of the _following_ epoch, therefore
+    /// `epoch + 1` must have ended.
+    pub epoch: Epoch,
+    pub validator_indices: Vec<u64>,
+}
+
+/// The change in a validator's balance for a single reward component.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct RewardDelta {
+    pub rewards_gwei: u64,
+    pub penalties_gwei: u64,
+}
+
+impl From<Delta> for RewardDelta {
+    fn from(delta: Delta) -> Self {
+        RewardDelta {
+            rewards_gwei: delta.rewards,
+            penalties_gwei: delta.penalties,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct IndividualRewards {
+    /// The reward or penalty for the validator's source vote.
+    pub source: RewardDelta,
+    /// The reward or penalty for the validator's target vote.
+    pub target: RewardDelta,
+    /// The reward or penalty for the validator's head vote.
+    pub head: RewardDelta,
+    /// The reward for the speed with which the validator's attestation was included.
+    pub inclusion_delay: RewardDelta,
+    /// The reward earned by the validator for including the attestations of others in the blocks
+    /// it proposed.
+    pub proposer: RewardDelta,
+    /// The penalty applied whilst the chain is failing to finalize.
+    pub inactivity_penalty: RewardDelta,
+}
+
+impl IndividualRewards {
+    pub fn from_deltas(attestation_delta: AttestationDelta, proposer_delta: Delta) -> Self {
+        Self {
+            source: attestation_delta.source_delta.into(),
+            target: attestation_delta.target_delta.into(),
+            head: attestation_delta.head_delta.into(),
+            inclusion_delay: attestation_delta.inclusion_delay_delta.into(),
+            proposer: proposer_delta.into(),
+            inactivity_penalty: attestation_delta.inactivity_penalty_delta.into(),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct IndividualRewardsResponse {
+    /// The epoch in which the rewarded attestations were made.
+    pub epoch: Epoch,
+    /// The index of the validator in state.validators.
+    pub validator_index: u64,
+    /// The rewards and penalties for the validator, if it is known to the state.
+    pub rewards: Option<IndividualRewards>,
+}
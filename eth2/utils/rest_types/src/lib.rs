@@ -16,6 +16,9 @@ pub use validator::{
     ValidatorDutiesRequest, ValidatorDuty, ValidatorDutyBytes, ValidatorSubscription,
 };
 
-pub use consensus::{IndividualVote, IndividualVotesRequest, IndividualVotesResponse};
+pub use consensus::{
+    IndividualRewards, IndividualRewardsRequest, IndividualRewardsResponse, IndividualVote,
+    IndividualVotesRequest, IndividualVotesResponse, RewardDelta,
+};
 
 pub use node::{SyncingResponse, SyncingStatus};
@@ -37,6 +37,11 @@ impl ManualSlotClock {
             self.genesis_duration + self.slot_duration * slots_since_genesis;
     }
 
+    /// Sets the current time, as a duration since the UNIX epoch.
+    pub fn set_current_time(&self, time: Duration) {
+        *self.current_time.write() = time;
+    }
+
     pub fn advance_slot(&self) {
         self.set_slot(self.now().unwrap().as_u64() + 1)
     }
@@ -0,0 +1,15 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use ssz::{Decode, Encode};
+use ssz_types::{typenum::U2048, BitList};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(bitlist) = BitList::<U2048>::from_bytes(data.to_vec()) {
+        assert_eq!(bitlist.clone().into_bytes(), data);
+        assert!(bitlist.len() <= BitList::<U2048>::max_len());
+    }
+
+    if let Ok(bitlist) = BitList::<U2048>::from_ssz_bytes(data) {
+        assert_eq!(bitlist.as_ssz_bytes(), data);
+    }
+});
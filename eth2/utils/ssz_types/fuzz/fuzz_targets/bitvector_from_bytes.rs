@@ -0,0 +1,15 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use ssz::{Decode, Encode};
+use ssz_types::{typenum::U513, BitVector};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(bitvector) = BitVector::<U513>::from_bytes(data.to_vec()) {
+        assert_eq!(bitvector.clone().into_bytes(), data);
+        assert_eq!(bitvector.len(), 513);
+    }
+
+    if let Ok(bitvector) = BitVector::<U513>::from_ssz_bytes(data) {
+        assert_eq!(bitvector.as_ssz_bytes(), data);
+    }
+});
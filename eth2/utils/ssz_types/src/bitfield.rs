@@ -617,6 +617,39 @@ impl<N: Unsigned + Clone> tree_hash::TreeHash for Bitfield<Fixed<N>> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<N: 'static + Unsigned + Clone> arbitrary::Arbitrary for Bitfield<Variable<N>> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let bit_len = u.int_in_range(0..=N::to_usize())?;
+        arbitrary_bitfield(u, bit_len)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<N: 'static + Unsigned + Clone> arbitrary::Arbitrary for Bitfield<Fixed<N>> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        arbitrary_bitfield(u, Self::capacity())
+    }
+}
+
+/// Returns a bitfield of `bit_len` bits, with bits taken from `u`.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_bitfield<T: BitfieldBehaviour>(
+    u: &mut arbitrary::Unstructured<'_>,
+    bit_len: usize,
+) -> arbitrary::Result<Bitfield<T>> {
+    let mut bytes = vec![0; bytes_for_bit_len(bit_len)];
+    u.fill_buffer(&mut bytes)?;
+
+    // Clear the bits higher than `bit_len`, which are not permitted to be set.
+    let (mask, _) = u8::max_value().overflowing_shr(8 - (bit_len as u32 % 8));
+    if let Some(last) = bytes.last_mut() {
+        *last &= if bit_len == 0 { 0 } else { mask };
+    }
+
+    Bitfield::from_raw_bytes(bytes, bit_len).map_err(|_| arbitrary::Error::IncorrectFormat)
+}
+
 #[cfg(test)]
 mod bitvector {
     use super::*;
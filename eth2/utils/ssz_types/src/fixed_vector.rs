@@ -266,6 +266,17 @@ where
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<T: arbitrary::Arbitrary, N: 'static + Unsigned> arbitrary::Arbitrary for FixedVector<T, N> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let vec = (0..N::to_usize())
+            .map(|_| T::arbitrary(u))
+            .collect::<arbitrary::Result<Vec<T>>>()?;
+
+        Self::new(vec).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
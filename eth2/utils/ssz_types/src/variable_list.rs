@@ -256,6 +256,22 @@ where
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<T: arbitrary::Arbitrary, N: 'static + Unsigned> arbitrary::Arbitrary for VariableList<T, N> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        let len = u.arbitrary_len::<T>()?;
+        if len > N::to_usize() {
+            return Err(arbitrary::Error::IncorrectFormat);
+        }
+
+        let vec = (0..len)
+            .map(|_| T::arbitrary(u))
+            .collect::<arbitrary::Result<Vec<T>>>()?;
+
+        Self::new(vec).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
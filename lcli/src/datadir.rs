@@ -0,0 +1,90 @@
+//! Helpers for commands which read the database of a (stopped) beacon node.
+use clap::ArgMatches;
+use clap_utils::{parse_optional, parse_path_with_default_in_home_dir};
+use environment::Environment;
+use eth2_testnet_config::Eth2TestnetConfig;
+use std::path::PathBuf;
+use store::{config::DEFAULT_SLOTS_PER_RESTORE_POINT, DiskStore, StoreConfig};
+use types::{ChainSpec, EthSpec, Hash256};
+
+/// The directory within the `--datadir` used by the beacon node.
+const BEACON_NODE_DIR: &str = "beacon";
+/// The default names of the hot and cold databases within the beacon node directory.
+const HOT_DB_DIR: &str = "chain_db";
+const FREEZER_DB_DIR: &str = "freezer_db";
+
+/// Opens the existing database of the beacon node in `--datadir`, using the `--freezer-dir` and
+/// `--slots-per-restore-point` flags, if present.
+pub fn open_store<T: EthSpec>(
+    env: &mut Environment<T>,
+    matches: &ArgMatches,
+    spec: ChainSpec,
+) -> Result<DiskStore<T>, String> {
+    let datadir =
+        parse_path_with_default_in_home_dir(matches, "datadir", PathBuf::from(".lighthouse"))?;
+    let hot_path = datadir.join(BEACON_NODE_DIR).join(HOT_DB_DIR);
+    let cold_path = parse_optional::<PathBuf>(matches, "freezer-dir")?
+        .unwrap_or_else(|| datadir.join(BEACON_NODE_DIR).join(FREEZER_DB_DIR));
+
+    let slots_per_restore_point = parse_optional(matches, "slots-per-restore-point")?
+        .unwrap_or_else(|| {
+            std::cmp::min(
+                T::slots_per_historical_root() as u64,
+                DEFAULT_SLOTS_PER_RESTORE_POINT,
+            )
+        });
+    let store_config = StoreConfig {
+        slots_per_restore_point,
+        ..StoreConfig::default()
+    };
+
+    info!("Opening database at {:?} and {:?}", hot_path, cold_path);
+
+    DiskStore::open_existing(
+        &hot_path,
+        &cold_path,
+        store_config,
+        spec,
+        env.core_context().log.clone(),
+    )
+    .map_err(|e| format!("Unable to open database: {:?}", e))
+}
+
+/// Returns the spec of the `--testnet-dir` if one was supplied, otherwise the default spec.
+pub fn load_spec<T: EthSpec>(
+    env: &mut Environment<T>,
+    matches: &ArgMatches,
+) -> Result<ChainSpec, String> {
+    let spec = env.core_context().eth2_config.spec;
+
+    match parse_optional::<PathBuf>(matches, "testnet-dir")? {
+        Some(testnet_dir) => {
+            let testnet_config: Eth2TestnetConfig<T> = Eth2TestnetConfig::load(testnet_dir)?;
+            match testnet_config.yaml_config {
+                Some(yaml_config) => yaml_config.apply_to_chain_spec::<T>(&spec).ok_or_else(|| {
+                    format!(
+                        "The loaded config is not compatible with the {} spec",
+                        &env.core_context().eth2_config.spec_constants
+                    )
+                }),
+                None => Ok(spec),
+            }
+        }
+        None => Ok(spec),
+    }
+}
+
+/// Parses a 0x-prefixed, hex-encoded root.
+pub fn parse_root(string: &str) -> Result<Hash256, String> {
+    if !string.starts_with("0x") {
+        return Err(format!("Root must have a 0x prefix: {}", string));
+    }
+
+    let bytes = hex::decode(&string[2..])
+        .map_err(|e| format!("Invalid hex in root {}: {:?}", string, e))?;
+    if bytes.len() != 32 {
+        return Err(format!("Root must be 32 bytes: {}", string));
+    }
+
+    Ok(Hash256::from_slice(&bytes))
+}
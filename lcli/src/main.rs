@@ -3,6 +3,7 @@ extern crate log;
 
 mod change_genesis_time;
 mod check_deposit_data;
+mod datadir;
 mod deploy_deposit_contract;
 mod eth1_genesis;
 mod generate_bootnode_enr;
@@ -10,6 +11,8 @@ mod interop_genesis;
 mod new_testnet;
 mod parse_hex;
 mod refund_deposit_contract;
+mod replay_blocks;
+mod state_diff;
 mod transition_blocks;
 
 use clap::{App, Arg, ArgMatches, SubCommand};
@@ -103,6 +106,149 @@ fn main() {
                         .help("Path to output a SSZ file."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("replay-blocks")
+                .about(
+                    "Replays the blocks of a range of slots from the database of a beacon node, \
+                    reporting the time taken by each stage of the state transition. The beacon \
+                    node must not be running. Nothing is written to the database by lcli, but it \
+                    is opened with write access so LevelDB may modify it (e.g., by compacting). \
+                    Use a copy of the database if it must remain untouched.",
+                )
+                .arg(
+                    Arg::with_name("datadir")
+                        .long("datadir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help("The data directory of the beacon node. Defaults to ~/.lighthouse"),
+                )
+                .arg(
+                    Arg::with_name("freezer-dir")
+                        .long("freezer-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help("The freezer database directory, if not the default within --datadir."),
+                )
+                .arg(
+                    Arg::with_name("slots-per-restore-point")
+                        .long("slots-per-restore-point")
+                        .value_name("SLOT_COUNT")
+                        .takes_value(true)
+                        .help("The --slots-per-restore-point used by the beacon node, if not the default."),
+                )
+                .arg(
+                    Arg::with_name("start-slot")
+                        .long("start-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The slot of the state to begin from. The blocks after this slot are replayed."),
+                )
+                .arg(
+                    Arg::with_name("end-slot")
+                        .long("end-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The last slot to process."),
+                )
+                .arg(
+                    Arg::with_name("head-block-root")
+                        .long("head-block-root")
+                        .value_name("BLOCK_ROOT")
+                        .takes_value(true)
+                        .help("A 0x-prefixed block root. The blocks of the chain ending in this block \
+                            are replayed. Defaults to the head of the beacon node."),
+                )
+                .arg(
+                    Arg::with_name("verify-signatures")
+                        .long("verify-signatures")
+                        .help("Verify the signatures of each block."),
+                )
+                .arg(
+                    Arg::with_name("dump-dir")
+                        .long("dump-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help("A directory in which to write states and diffs. If a block's state root \
+                            does not match, its post-state is always written here."),
+                )
+                .arg(
+                    Arg::with_name("dump-slots")
+                        .long("dump-slots")
+                        .value_name("SLOTS")
+                        .takes_value(true)
+                        .requires("dump-dir")
+                        .help("A comma-separated list of slots at which to write the state, or \"all\"."),
+                )
+                .arg(
+                    Arg::with_name("dump-format")
+                        .long("dump-format")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(&["ssz", "json"])
+                        .default_value("ssz")
+                        .help("The encoding of the written states."),
+                )
+                .arg(
+                    Arg::with_name("dump-diffs")
+                        .long("dump-diffs")
+                        .requires("dump-slots")
+                        .help("Also write the fields which changed from the previous slot, as JSON."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("state-diff")
+                .about(
+                    "Writes the field-level differences between two states as JSON, including \
+                    the validators whose records or balances differ and the tree hash root of \
+                    each top-level field.",
+                )
+                .arg(
+                    Arg::with_name("state-a")
+                        .value_name("STATE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to a SSZ file of a state, or a 0x-prefixed state root to load \
+                            from the database in --datadir."),
+                )
+                .arg(
+                    Arg::with_name("state-b")
+                        .value_name("STATE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to a SSZ file of a state, or a 0x-prefixed state root to load \
+                            from the database in --datadir."),
+                )
+                .arg(
+                    Arg::with_name("datadir")
+                        .long("datadir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help("The data directory of the beacon node. Defaults to ~/.lighthouse"),
+                )
+                .arg(
+                    Arg::with_name("freezer-dir")
+                        .long("freezer-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help("The freezer database directory, if not the default within --datadir."),
+                )
+                .arg(
+                    Arg::with_name("slots-per-restore-point")
+                        .long("slots-per-restore-point")
+                        .value_name("SLOT_COUNT")
+                        .takes_value(true)
+                        .help("The --slots-per-restore-point used by the beacon node, if not the default."),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("The file to write the diff to. Defaults to stdout."),
+                )
+        )
         .subcommand(
             SubCommand::with_name("pretty-hex")
                 .about("Parses SSZ encoded as ASCII 0x-prefixed hex")
@@ -480,6 +626,10 @@ fn run<T: EthSpec>(env_builder: EnvironmentBuilder<T>, matches: &ArgMatches) ->
         }
         ("transition-blocks", Some(matches)) => run_transition_blocks::<T>(matches)
             .map_err(|e| format!("Failed to transition blocks: {}", e)),
+        ("replay-blocks", Some(matches)) => replay_blocks::run::<T>(env, matches)
+            .map_err(|e| format!("Failed to run replay-blocks command: {}", e)),
+        ("state-diff", Some(matches)) => state_diff::run::<T>(env, matches)
+            .map_err(|e| format!("Failed to run state-diff command: {}", e)),
         ("pretty-hex", Some(matches)) => {
             run_parse_hex::<T>(matches).map_err(|e| format!("Failed to pretty print hex: {}", e))
         }
@@ -0,0 +1,339 @@
+use crate::datadir::{load_spec, open_store, parse_root};
+use beacon_chain::{PersistedBeaconChain, BEACON_CHAIN_DB_KEY};
+use clap::ArgMatches;
+use clap_utils::{parse_optional, parse_required};
+use environment::Environment;
+use serde::Serialize;
+use ssz::Encode;
+use state_processing::{per_block_processing, per_slot_processing, BlockSignatureStrategy};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use store::{iter::ParentRootBlockIterator, DiskStore, Store};
+use types::{BeaconState, BeaconStateDiff, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// The encoding used when writing states and diffs to the dump directory.
+#[derive(Clone, Copy)]
+enum DumpFormat {
+    Ssz,
+    Json,
+}
+
+impl FromStr for DumpFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "ssz" => Ok(DumpFormat::Ssz),
+            "json" => Ok(DumpFormat::Json),
+            other => Err(format!("Unknown dump format: {}", other)),
+        }
+    }
+}
+
+/// The slots at which the state should be written to the dump directory.
+enum DumpSlots {
+    None,
+    All,
+    Some(HashSet<Slot>),
+}
+
+impl DumpSlots {
+    fn contains(&self, slot: Slot) -> bool {
+        match self {
+            DumpSlots::None => false,
+            DumpSlots::All => true,
+            DumpSlots::Some(slots) => slots.contains(&slot),
+        }
+    }
+}
+
+/// Where and how states should be dumped during the replay.
+struct Dumper {
+    dir: PathBuf,
+    format: DumpFormat,
+    slots: DumpSlots,
+    diffs: bool,
+}
+
+impl Dumper {
+    /// Writes `state` to the dump directory, along with its differences to `pre_state` (if
+    /// supplied).
+    fn dump<T: EthSpec>(
+        &self,
+        state: &BeaconState<T>,
+        pre_state: Option<&BeaconState<T>>,
+    ) -> Result<(), String> {
+        let path = match self.format {
+            DumpFormat::Ssz => {
+                let path = self.dir.join(format!("state_{}.ssz", state.slot));
+                write_file(&path, &state.as_ssz_bytes())?;
+                path
+            }
+            DumpFormat::Json => {
+                let path = self.dir.join(format!("state_{}.json", state.slot));
+                write_file(&path, &to_json_bytes(state)?)?;
+                path
+            }
+        };
+        info!("Wrote state at slot {} to {:?}", state.slot, path);
+
+        if let Some(pre_state) = pre_state {
+            let path = self.dir.join(format!("diff_{}.json", state.slot));
+            write_file(
+                &path,
+                &to_json_bytes(&BeaconStateDiff::new(pre_state, state))?,
+            )?;
+            info!(
+                "Wrote diff between slots {} and {} to {:?}",
+                pre_state.slot, state.slot, path
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The time spent processing the slots up to and including a block.
+#[derive(Default)]
+struct Timings {
+    slot_processing: Duration,
+    epoch_processing: Duration,
+    block_processing: Duration,
+    tree_hash: Duration,
+}
+
+impl Timings {
+    fn add(&mut self, other: &Timings) {
+        self.slot_processing += other.slot_processing;
+        self.epoch_processing += other.epoch_processing;
+        self.block_processing += other.block_processing;
+        self.tree_hash += other.tree_hash;
+    }
+}
+
+pub fn run<T: EthSpec>(mut env: Environment<T>, matches: &ArgMatches) -> Result<(), String> {
+    let start_slot = Slot::new(parse_required(matches, "start-slot")?);
+    let end_slot = Slot::new(parse_required(matches, "end-slot")?);
+    if end_slot <= start_slot {
+        return Err("end-slot must be greater than start-slot".into());
+    }
+
+    let signature_strategy = if matches.is_present("verify-signatures") {
+        BlockSignatureStrategy::VerifyBulk
+    } else {
+        BlockSignatureStrategy::NoVerification
+    };
+
+    let dumper = parse_optional::<PathBuf>(matches, "dump-dir")?
+        .map(|dir| {
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("Unable to create dump dir {:?}: {:?}", dir, e))?;
+
+            Ok::<_, String>(Dumper {
+                dir,
+                format: parse_required(matches, "dump-format")?,
+                slots: parse_dump_slots(matches.value_of("dump-slots"))?,
+                diffs: matches.is_present("dump-diffs"),
+            })
+        })
+        .transpose()?;
+
+    let spec = load_spec::<T>(&mut env, matches)?;
+
+    let store = open_store(&mut env, matches, spec.clone())?;
+
+    let head_block_root = match parse_optional::<String>(matches, "head-block-root")? {
+        Some(root) => parse_root(&root)?,
+        None => {
+            store
+                .get::<PersistedBeaconChain>(&Hash256::from_slice(&BEACON_CHAIN_DB_KEY))
+                .map_err(|e| format!("Unable to read persisted beacon chain: {:?}", e))?
+                .ok_or_else(|| "The database does not contain a head block".to_string())?
+                .canonical_head_block_root
+        }
+    };
+
+    let (anchor_block, blocks) = load_blocks(&store, head_block_root, start_slot, end_slot)?;
+
+    info!(
+        "Replaying {} blocks between slots {} and {} on the chain of block {:?}",
+        blocks.len(),
+        start_slot,
+        end_slot,
+        head_block_root
+    );
+
+    let mut state = store
+        .get_state(&anchor_block.state_root(), Some(anchor_block.slot()))
+        .map_err(|e| format!("Unable to load state: {:?}", e))?
+        .ok_or_else(|| {
+            format!(
+                "The database does not contain the state of the block at slot {}",
+                anchor_block.slot()
+            )
+        })?;
+    let mut state_root = Some(anchor_block.state_root());
+
+    state
+        .build_all_caches(&spec)
+        .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+
+    while state.slot < start_slot {
+        per_slot_processing(&mut state, state_root.take(), &spec)
+            .map_err(|e| format!("Unable to advance to start slot: {:?}", e))?;
+    }
+
+    if let Some(dumper) = dumper.as_ref().filter(|d| d.slots.contains(state.slot)) {
+        dumper.dump(&state, None)?;
+    }
+
+    let mut blocks = blocks.into_iter().peekable();
+    let mut timings = Timings::default();
+    let mut total_timings = Timings::default();
+
+    while state.slot < end_slot {
+        let next_slot = state.slot + 1;
+        let pre_state = dumper
+            .as_ref()
+            .filter(|dumper| dumper.diffs && dumper.slots.contains(next_slot))
+            .map(|_| state.clone());
+
+        let is_epoch_transition =
+            state.slot > spec.genesis_slot && (state.slot + 1) % T::slots_per_epoch() == 0;
+
+        let timer = Instant::now();
+        per_slot_processing(&mut state, state_root.take(), &spec)
+            .map_err(|e| format!("Slot processing failed at slot {}: {:?}", next_slot, e))?;
+        if is_epoch_transition {
+            timings.epoch_processing += timer.elapsed();
+        } else {
+            timings.slot_processing += timer.elapsed();
+        }
+
+        // Build any caches invalidated by an epoch transition outside of the timed regions, so
+        // they are not attributed to slot or epoch processing.
+        state
+            .build_all_caches(&spec)
+            .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+
+        let block = match blocks.peek() {
+            Some(block) if block.slot() == state.slot => blocks.next(),
+            _ => None,
+        };
+
+        if let Some(block) = block {
+            let timer = Instant::now();
+            per_block_processing(&mut state, &block, None, signature_strategy, &spec)
+                .map_err(|e| format!("Block processing failed at slot {}: {:?}", state.slot, e))?;
+            timings.block_processing = timer.elapsed();
+
+            let timer = Instant::now();
+            let root = state
+                .update_tree_hash_cache()
+                .map_err(|e| format!("Unable to compute state root: {:?}", e))?;
+            timings.tree_hash = timer.elapsed();
+
+            info!(
+                "Slot {}: slot processing {:?}, epoch processing {:?}, block processing {:?}, \
+                 tree hash {:?}",
+                state.slot,
+                timings.slot_processing,
+                timings.epoch_processing,
+                timings.block_processing,
+                timings.tree_hash
+            );
+
+            if root != block.state_root() {
+                if let Some(dumper) = &dumper {
+                    dumper.dump(&state, pre_state.as_ref())?;
+                }
+                return Err(format!(
+                    "State root mismatch at slot {}. Block: {:?}, computed: {:?}",
+                    state.slot,
+                    block.state_root(),
+                    root
+                ));
+            }
+
+            state_root = Some(root);
+            total_timings.add(&timings);
+            timings = Timings::default();
+        }
+
+        if let Some(dumper) = dumper.as_ref().filter(|d| d.slots.contains(state.slot)) {
+            dumper.dump(&state, pre_state.as_ref())?;
+        }
+    }
+
+    total_timings.add(&timings);
+
+    info!(
+        "Replay complete. Total slot processing {:?}, epoch processing {:?}, block processing \
+         {:?}, tree hash {:?}",
+        total_timings.slot_processing,
+        total_timings.epoch_processing,
+        total_timings.block_processing,
+        total_timings.tree_hash
+    );
+
+    Ok(())
+}
+
+/// Walks back from `head_block_root`, returning the latest block at or prior to `start_slot` and
+/// the blocks after `start_slot` up to and including `end_slot`, in ascending slot order.
+fn load_blocks<T: EthSpec>(
+    store: &DiskStore<T>,
+    head_block_root: Hash256,
+    start_slot: Slot,
+    end_slot: Slot,
+) -> Result<(SignedBeaconBlock<T>, Vec<SignedBeaconBlock<T>>), String> {
+    let mut blocks = vec![];
+
+    for (_, block) in ParentRootBlockIterator::new(store, head_block_root) {
+        if block.slot() > end_slot {
+            continue;
+        } else if block.slot() > start_slot {
+            blocks.push(block);
+        } else {
+            blocks.reverse();
+            return Ok((block, blocks));
+        }
+    }
+
+    Err(format!(
+        "Unable to find a block at or prior to slot {} on the chain of block {:?}",
+        start_slot, head_block_root
+    ))
+}
+
+/// Parses a comma-separated list of slots, or `all`.
+fn parse_dump_slots(value: Option<&str>) -> Result<DumpSlots, String> {
+    match value {
+        None => Ok(DumpSlots::None),
+        Some("all") => Ok(DumpSlots::All),
+        Some(list) => list
+            .split(',')
+            .map(|slot| {
+                slot.trim()
+                    .parse::<u64>()
+                    .map(Slot::new)
+                    .map_err(|e| format!("Invalid slot {}: {}", slot, e))
+            })
+            .collect::<Result<_, _>>()
+            .map(DumpSlots::Some),
+    }
+}
+
+fn to_json_bytes<T: Serialize>(item: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(item).map_err(|e| format!("Unable to encode JSON: {:?}", e))
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    File::create(path)
+        .and_then(|mut file| file.write_all(bytes))
+        .map_err(|e| format!("Unable to write to {:?}: {:?}", path, e))
+}
@@ -0,0 +1,81 @@
+use crate::datadir::{load_spec, open_store, parse_root};
+use clap::ArgMatches;
+use clap_utils::parse_optional;
+use environment::Environment;
+use ssz::Decode;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use store::{DiskStore, Store};
+use types::{BeaconState, BeaconStateDiff, EthSpec};
+
+pub fn run<T: EthSpec>(mut env: Environment<T>, matches: &ArgMatches) -> Result<(), String> {
+    let a = matches
+        .value_of("state-a")
+        .ok_or_else(|| "No state-a supplied".to_string())?;
+    let b = matches
+        .value_of("state-b")
+        .ok_or_else(|| "No state-b supplied".to_string())?;
+    let output_path = parse_optional::<PathBuf>(matches, "output")?;
+
+    // Only open the database if a state is to be read from it, since the beacon node may hold a
+    // lock on it.
+    let store = if a.starts_with("0x") || b.starts_with("0x") {
+        let spec = load_spec(&mut env, matches)?;
+        Some(open_store(&mut env, matches, spec)?)
+    } else {
+        None
+    };
+
+    let state_a = load_state::<T>(a, store.as_ref())?;
+    let state_b = load_state::<T>(b, store.as_ref())?;
+
+    let diff = BeaconStateDiff::new(&state_a, &state_b);
+
+    info!(
+        "States at slots {} and {} differ in {} fields and {} validators",
+        state_a.slot,
+        state_b.slot,
+        diff.fields.len(),
+        diff.validators.len()
+    );
+    for field in diff.differing_roots() {
+        info!("Tree hash root of {} differs", field);
+    }
+
+    let json = serde_json::to_vec_pretty(&diff)
+        .map_err(|e| format!("Unable to encode diff as JSON: {:?}", e))?;
+
+    match output_path {
+        Some(path) => File::create(&path)
+            .and_then(|mut file| file.write_all(&json))
+            .map_err(|e| format!("Unable to write to {:?}: {:?}", path, e))?,
+        None => io::stdout()
+            .write_all(&json)
+            .map_err(|e| format!("Unable to write to stdout: {:?}", e))?,
+    }
+
+    Ok(())
+}
+
+/// Loads a state from the database if `arg` is a 0x-prefixed state root, otherwise from the SSZ
+/// file at the path `arg`.
+fn load_state<T: EthSpec>(
+    arg: &str,
+    store: Option<&DiskStore<T>>,
+) -> Result<BeaconState<T>, String> {
+    if arg.starts_with("0x") {
+        let state_root = parse_root(arg)?;
+        let store = store.ok_or_else(|| "No database supplied".to_string())?;
+
+        store
+            .get_state(&state_root, None)
+            .map_err(|e| format!("Unable to load state {:?}: {:?}", state_root, e))?
+            .ok_or_else(|| format!("The database does not contain state {:?}", state_root))
+    } else {
+        let bytes = fs::read(arg).map_err(|e| format!("Unable to read {}: {:?}", arg, e))?;
+
+        BeaconState::from_ssz_bytes(&bytes)
+            .map_err(|e| format!("Unable to decode state from {}: {:?}", arg, e))
+    }
+}
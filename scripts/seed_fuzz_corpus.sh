@@ -0,0 +1,44 @@
+#!/usr/bin/env bash
+#
+# Seeds the corpora of the `cargo fuzz` targets with the SSZ test vectors from the Ethereum
+# Foundation, so that fuzzing starts from valid inputs.
+#
+# The test vectors must already have been downloaded with `make make-ef-tests`.
+
+set -e
+
+ROOT=$(cd "$(dirname "$0")/.." && pwd)
+VECTORS="$ROOT/tests/ef_tests/eth2.0-spec-tests/tests"
+
+if [ ! -d "$VECTORS" ]; then
+	echo "Test vectors not found in $VECTORS, run \`make make-ef-tests\` first."
+	exit 1
+fi
+
+# Copies each `serialized.ssz` file matching the given glob into the corpus of a fuzz target.
+#
+# Usage: seed <fuzz crate directory> <fuzz target> <glob, relative to $VECTORS>
+seed() {
+	local corpus="$ROOT/$1/fuzz/corpus/$2"
+	local count=0
+
+	mkdir -p "$corpus"
+	for file in $VECTORS/$3/serialized.ssz; do
+		[ -f "$file" ] || continue
+		cp "$file" "$corpus/$(sha1sum "$file" | cut -d ' ' -f 1)"
+		count=$((count + 1))
+	done
+
+	echo "Seeded $2 with $count inputs"
+}
+
+STATIC="mainnet/phase0/ssz_static"
+GENERIC="general/phase0/ssz_generic"
+
+seed eth2/types ssz_decode_signed_beacon_block "$STATIC/SignedBeaconBlock/*/case_*"
+seed eth2/types ssz_decode_attestation "$STATIC/Attestation/*/case_*"
+seed eth2/types ssz_decode_beacon_state "$STATIC/BeaconState/*/case_*"
+
+# The targets use a `BitList` with a maximum length of 2048 and a `BitVector` of length 513.
+seed eth2/utils/ssz_types bitlist_from_bytes "$GENERIC/bitlist/valid/*"
+seed eth2/utils/ssz_types bitvector_from_bytes "$GENERIC/bitvector/valid/bitvec_513_*"
@@ -0,0 +1,396 @@
+use super::*;
+use crate::bls_setting::BlsSetting;
+use crate::decode::{ssz_decode_file, yaml_decode_file};
+use beacon_chain::{
+    slot_clock::{SlotClock, TestingSlotClock},
+    test_utils::{BeaconChainHarness, HarnessType},
+    BeaconChain,
+};
+use serde_derive::{Deserialize, Serialize};
+use state_processing::per_block_processing::{is_valid_indexed_attestation, VerifySignatures};
+use state_processing::{common::get_indexed_attestation, per_slot_processing};
+use std::time::Duration;
+use types::{
+    Attestation, BeaconBlock, BeaconState, Checkpoint, EthSpec, Hash256, RelativeEpoch,
+    SignedBeaconBlock, Slot,
+};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Metadata {
+    pub description: Option<String>,
+    pub bls_setting: Option<BlsSetting>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Head {
+    pub slot: Slot,
+    pub root: Hash256,
+}
+
+/// The values which should be checked against fork choice, if present.
+///
+/// Unknown checks are rejected so that they are not silently ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Checks {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<Head>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justified_checkpoint: Option<Checkpoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justified_checkpoint_root: Option<Hash256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finalized_checkpoint: Option<Checkpoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_justified_checkpoint: Option<Checkpoint>,
+}
+
+/// A step of a fork choice test.
+///
+/// Blocks and attestations are referred to by file name in `steps.yaml`, `B` and `A` are the types
+/// they are loaded as.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Step<B, A> {
+    Tick {
+        tick: u64,
+    },
+    Block {
+        block: B,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        valid: Option<bool>,
+    },
+    Attestation {
+        attestation: A,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        valid: Option<bool>,
+    },
+    Checks {
+        checks: Box<Checks>,
+    },
+}
+
+#[derive(Debug)]
+pub struct ForkChoiceTest<E: EthSpec> {
+    pub description: String,
+    pub metadata: Metadata,
+    pub anchor_state: BeaconState<E>,
+    pub anchor_block: BeaconBlock<E>,
+    pub steps: Vec<Step<SignedBeaconBlock<E>, Attestation<E>>>,
+}
+
+impl<E: EthSpec> LoadCase for ForkChoiceTest<E> {
+    fn load_from_dir(path: &Path) -> Result<Self, Error> {
+        let description = path
+            .file_name()
+            .expect("path must have a file name")
+            .to_string_lossy()
+            .into_owned();
+        let meta_file = path.join("meta.yaml");
+        let metadata = if meta_file.is_file() {
+            yaml_decode_file(&meta_file)?
+        } else {
+            Metadata::default()
+        };
+        let anchor_state = ssz_decode_file(&path.join("anchor_state.ssz"))?;
+        let anchor_block = ssz_decode_file(&path.join("anchor_block.ssz"))?;
+
+        let steps: Vec<Step<String, String>> = yaml_decode_file(&path.join("steps.yaml"))?;
+        let steps = steps
+            .into_iter()
+            .map(|step| match step {
+                Step::Tick { tick } => Ok(Step::Tick { tick }),
+                Step::Block { block, valid } => {
+                    ssz_decode_file(&path.join(format!("{}.ssz", block)))
+                        .map(|block| Step::Block { block, valid })
+                }
+                Step::Attestation { attestation, valid } => {
+                    ssz_decode_file(&path.join(format!("{}.ssz", attestation)))
+                        .map(|attestation| Step::Attestation { attestation, valid })
+                }
+                Step::Checks { checks } => Ok(Step::Checks { checks }),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            description,
+            metadata,
+            anchor_state,
+            anchor_block,
+            steps,
+        })
+    }
+}
+
+impl<E: EthSpec> Case for ForkChoiceTest<E> {
+    fn description(&self) -> String {
+        self.metadata
+            .description
+            .clone()
+            .unwrap_or_else(|| self.description.clone())
+    }
+
+    fn result(&self, _case_index: usize) -> Result<(), Error> {
+        self.metadata.bls_setting.unwrap_or_default().check()?;
+
+        let tester = Tester::new(self.anchor_state.clone());
+
+        let anchor_block_root = self.anchor_block.canonical_root();
+        check_equal(
+            "anchor block root",
+            tester.chain().genesis_block_root,
+            anchor_block_root,
+        )?;
+
+        for step in &self.steps {
+            match step {
+                Step::Tick { tick } => tester.set_tick(*tick)?,
+                Step::Block { block, valid } => {
+                    tester.process_block(block.clone(), valid.unwrap_or(true))?
+                }
+                Step::Attestation { attestation, valid } => {
+                    tester.process_attestation(attestation.clone(), valid.unwrap_or(true))?
+                }
+                Step::Checks { checks } => tester.check(checks)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Drives a `BeaconChain` through the steps of a test.
+struct Tester<E: EthSpec> {
+    harness: BeaconChainHarness<HarnessType<E>>,
+}
+
+impl<E: EthSpec> Tester<E> {
+    fn new(anchor_state: BeaconState<E>) -> Self {
+        let genesis_time = anchor_state.genesis_time;
+        let mut harness =
+            BeaconChainHarness::from_genesis_state(E::default(), vec![], anchor_state);
+
+        // Ticks are times rather than slots, so the slot clock must use the real slot duration
+        // rather than the harness default.
+        harness.chain.slot_clock = TestingSlotClock::new(
+            harness.spec.genesis_slot,
+            Duration::from_secs(genesis_time),
+            Duration::from_millis(harness.spec.milliseconds_per_slot),
+        );
+
+        Self { harness }
+    }
+
+    fn chain(&self) -> &BeaconChain<HarnessType<E>> {
+        &self.harness.chain
+    }
+
+    /// Returns the current slot, as per the slot clock.
+    fn current_slot(&self) -> Result<Slot, Error> {
+        self.chain()
+            .slot()
+            .map_err(|e| Error::InternalError(format!("Unable to read slot: {:?}", e)))
+    }
+
+    /// Sets the slot clock to `tick`, which is in seconds since the UNIX epoch.
+    fn set_tick(&self, tick: u64) -> Result<(), Error> {
+        let time = Duration::from_secs(tick);
+        let slot_clock = &self.chain().slot_clock;
+
+        if slot_clock.now_duration().map_or(false, |now| time < now) {
+            return Err(Error::FailedToParseTest(format!(
+                "Tick {} is prior to the current time",
+                tick
+            )));
+        }
+
+        slot_clock.set_current_time(time);
+        self.find_head()
+    }
+
+    fn process_block(&self, block: SignedBeaconBlock<E>, valid: bool) -> Result<(), Error> {
+        let slot = block.slot();
+        let result = self.chain().process_block(block);
+
+        match (result, valid) {
+            (Ok(_), true) | (Err(_), false) => Ok(()),
+            (Ok(block_root), false) => Err(Error::DidntFail(format!(
+                "Block {:?} at slot {} should be invalid",
+                block_root, slot
+            ))),
+            (Err(e), true) => Err(Error::NotEqual(format!(
+                "Block at slot {} should be valid, but failed with {:?}",
+                slot, e
+            ))),
+        }
+    }
+
+    fn process_attestation(&self, attestation: Attestation<E>, valid: bool) -> Result<(), Error> {
+        let result = self.apply_attestation(&attestation);
+
+        match (result, valid) {
+            (Ok(()), true) | (Err(_), false) => Ok(()),
+            (Ok(()), false) => Err(Error::DidntFail(format!(
+                "Attestation at slot {} should be invalid",
+                attestation.data.slot
+            ))),
+            (Err(e), true) => Err(Error::NotEqual(format!(
+                "Attestation at slot {} should be valid, but was rejected: {}",
+                attestation.data.slot, e
+            ))),
+        }
+    }
+
+    /// Validates `attestation` as per the spec's `on_attestation` and applies it to fork choice.
+    ///
+    /// Unlike `BeaconChain::process_attestation`, this does not apply the gossip propagation
+    /// checks (e.g., the attestation may be from any slot of the current or previous epoch).
+    fn apply_attestation(&self, attestation: &Attestation<E>) -> Result<(), String> {
+        let spec = &self.harness.spec;
+        let fork_choice = &self.chain().fork_choice;
+        let data = &attestation.data;
+        let target = &data.target;
+
+        let current_slot = self.current_slot().map_err(|e| format!("{:?}", e))?;
+        let current_epoch = current_slot.epoch(E::slots_per_epoch());
+        let previous_epoch = current_epoch.saturating_sub(1u64);
+
+        if target.epoch != current_epoch && target.epoch != previous_epoch {
+            return Err(format!(
+                "Target epoch {} is not the current or previous epoch",
+                target.epoch
+            ));
+        }
+        if target.epoch != data.slot.epoch(E::slots_per_epoch()) {
+            return Err("Target epoch does not match the slot".to_string());
+        }
+        if current_slot <= data.slot {
+            return Err("Attestations can only affect fork choice of later slots".to_string());
+        }
+
+        let (target_block_slot, target_state_root) = fork_choice
+            .block_slot_and_state_root(&target.root)
+            .ok_or_else(|| format!("Unknown target root {:?}", target.root))?;
+        let (block_slot, _) = fork_choice
+            .block_slot_and_state_root(&data.beacon_block_root)
+            .ok_or_else(|| format!("Unknown block root {:?}", data.beacon_block_root))?;
+        if block_slot > data.slot {
+            return Err("Attestation is for a block later than itself".to_string());
+        }
+
+        // The state of the target block, advanced to the start of the target epoch.
+        let mut target_state = self
+            .chain()
+            .get_state(&target_state_root, Some(target_block_slot))
+            .map_err(|e| format!("Unable to load target state: {:?}", e))?
+            .ok_or_else(|| "Missing target state".to_string())?;
+        let mut state_root = Some(target_state_root);
+        while target_state.slot < target.epoch.start_slot(E::slots_per_epoch()) {
+            per_slot_processing(&mut target_state, state_root.take(), spec)
+                .map_err(|e| format!("Unable to advance target state: {:?}", e))?;
+        }
+        target_state
+            .build_committee_cache(RelativeEpoch::Current, spec)
+            .map_err(|e| format!("Unable to build committee cache: {:?}", e))?;
+
+        let committee = target_state
+            .get_beacon_committee(data.slot, data.index)
+            .map_err(|e| format!("Unable to get committee: {:?}", e))?;
+        let indexed_attestation = get_indexed_attestation(committee.committee, attestation)
+            .map_err(|e| format!("Invalid aggregation bits: {:?}", e))?;
+        is_valid_indexed_attestation(
+            &target_state,
+            &indexed_attestation,
+            VerifySignatures::True,
+            spec,
+        )
+        .map_err(|e| format!("Invalid indexed attestation: {:?}", e))?;
+
+        fork_choice
+            .process_indexed_attestation(&indexed_attestation)
+            .map_err(|e| format!("Fork choice failed to process attestation: {:?}", e))
+    }
+
+    /// Runs fork choice, which updates the head and possibly the justified checkpoint.
+    fn find_head(&self) -> Result<(), Error> {
+        self.chain()
+            .fork_choice()
+            .map_err(|e| Error::InternalError(format!("Fork choice failed: {:?}", e)))
+    }
+
+    fn check(&self, checks: &Checks) -> Result<(), Error> {
+        self.find_head()?;
+
+        let fork_choice = &self.chain().fork_choice;
+
+        if let Some(time) = checks.time {
+            check_equal(
+                "time",
+                self.chain().slot_clock.now_duration(),
+                Some(Duration::from_secs(time)),
+            )?;
+        }
+
+        if let Some(head) = checks.head {
+            let head_info = self
+                .chain()
+                .head_info()
+                .map_err(|e| Error::InternalError(format!("Unable to read head: {:?}", e)))?;
+            check_equal(
+                "head",
+                Head {
+                    slot: head_info.slot,
+                    root: head_info.block_root,
+                },
+                head,
+            )?;
+        }
+
+        if let Some(checkpoint) = &checks.justified_checkpoint {
+            check_equal(
+                "justified_checkpoint",
+                &fork_choice.justified_checkpoint(),
+                checkpoint,
+            )?;
+        }
+
+        if let Some(root) = checks.justified_checkpoint_root {
+            check_equal(
+                "justified_checkpoint_root",
+                fork_choice.justified_checkpoint().root,
+                root,
+            )?;
+        }
+
+        if let Some(checkpoint) = &checks.finalized_checkpoint {
+            check_equal(
+                "finalized_checkpoint",
+                &fork_choice.finalized_checkpoint(),
+                checkpoint,
+            )?;
+        }
+
+        if let Some(checkpoint) = &checks.best_justified_checkpoint {
+            check_equal(
+                "best_justified_checkpoint",
+                &fork_choice.best_justified_checkpoint(),
+                checkpoint,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn check_equal<T: Debug + PartialEq>(check: &str, ours: T, expected: T) -> Result<(), Error> {
+    if ours == expected {
+        Ok(())
+    } else {
+        Err(Error::NotEqual(format!(
+            "{} check failed: Got {:?} | Expected {:?}",
+            check, ours, expected
+        )))
+    }
+}
@@ -5,7 +5,7 @@ use cached_tree_hash::CachedTreeHash;
 use std::fmt::Debug;
 use std::fs;
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use types::EthSpec;
 
 pub trait Handler {
@@ -24,9 +24,19 @@ pub trait Handler {
     fn handler_name() -> String;
 
     fn run() {
-        let handler_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        let tests_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .join("eth2.0-spec-tests")
-            .join("tests")
+            .join("tests");
+
+        Self::run_in(&tests_path)
+    }
+
+    /// Runs the cases of this handler which are beneath `tests_path`, a directory with the same
+    /// layout as the `tests` directory of the EF test vectors.
+    ///
+    /// Useful for running test vectors which were not produced by the EF.
+    fn run_in(tests_path: &Path) {
+        let handler_path = tests_path
             .join(Self::config_name())
             .join(Self::fork_name())
             .join(Self::runner_name())
@@ -261,6 +271,30 @@ impl<E: EthSpec + TypeName, O: Operation<E>> Handler for OperationsHandler<E, O>
     }
 }
 
+pub struct ForkChoiceHandler<E, H>(PhantomData<(E, H)>);
+
+impl<E: EthSpec + TypeName, H: TypeName> Handler for ForkChoiceHandler<E, H> {
+    type Case = cases::ForkChoiceTest<E>;
+
+    fn config_name() -> &'static str {
+        E::name()
+    }
+
+    fn runner_name() -> &'static str {
+        "fork_choice"
+    }
+
+    fn handler_name() -> String {
+        H::name().into()
+    }
+}
+
+// Supported fork choice handlers
+pub struct GetHead;
+type_name!(GetHead, "get_head");
+pub struct OnBlock;
+type_name!(OnBlock, "on_block");
+
 pub struct SszGenericHandler<H>(PhantomData<H>);
 
 impl<H: TypeName> Handler for SszGenericHandler<H> {
@@ -2,6 +2,7 @@ use types::EthSpec;
 
 pub use case_result::CaseResult;
 pub use cases::Case;
+pub use cases::{Checks, Head, Step};
 pub use cases::{
     FinalUpdates, JustificationAndFinalization, RegistryUpdates, RewardsAndPenalties, Slashings,
 };
@@ -1,7 +1,13 @@
 #![cfg(feature = "ef_tests")]
 
+use beacon_chain::test_utils::{
+    generate_deterministic_keypairs, AttestationStrategy, BeaconChainHarness, BlockStrategy,
+};
 use ef_tests::*;
-use std::path::PathBuf;
+use ssz::Encode;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
 use types::*;
 
 // Check that the config from the Eth2.0 spec tests matches our minimal/mainnet config.
@@ -218,6 +224,134 @@ fn epoch_processing_final_updates() {
     EpochProcessingHandler::<MainnetEthSpec, FinalUpdates>::run();
 }
 
+/// Writes a `fork_choice` test case to `tests_path`, in the layout of the EF test vectors.
+///
+/// The v0.11 test vectors do not include `fork_choice` tests, so the case is generated by building
+/// a chain with a `BeaconChainHarness`: the blocks and attestations it imported become the steps
+/// of the case, and the state of its fork choice afterwards becomes the checks.
+fn write_fork_choice_case<E: EthSpec + TypeName>(tests_path: &Path) {
+    const VALIDATOR_COUNT: usize = 16;
+
+    let harness = BeaconChainHarness::new(
+        E::default(),
+        generate_deterministic_keypairs(VALIDATOR_COUNT),
+    );
+    let seconds_per_slot = harness.spec.milliseconds_per_slot / 1000;
+
+    // Build enough epochs for the chain to justify and finalize.
+    harness.advance_slot();
+    harness.extend_chain(
+        E::slots_per_epoch() as usize * 4,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+    harness.chain.fork_choice().expect("should find head");
+
+    let case_path = tests_path
+        .join(E::name())
+        .join(ForkChoiceHandler::<E, GetHead>::fork_name())
+        .join(ForkChoiceHandler::<E, GetHead>::runner_name())
+        .join(ForkChoiceHandler::<E, GetHead>::handler_name())
+        .join("lighthouse")
+        .join("extend_chain");
+    fs::create_dir_all(&case_path).expect("should create case dir");
+    let write = |name: &str, bytes: &[u8]| {
+        fs::write(case_path.join(name), bytes).expect("should write case file")
+    };
+
+    let chain_dump = harness.chain.chain_dump().expect("should dump chain");
+    let genesis = &chain_dump[0];
+    let genesis_time = genesis.beacon_state.genesis_time;
+    let tick = |slot: Slot| Step::Tick {
+        tick: genesis_time + slot.as_u64() * seconds_per_slot,
+    };
+
+    write("anchor_state.ssz", &genesis.beacon_state.as_ssz_bytes());
+    write(
+        "anchor_block.ssz",
+        &genesis.beacon_block.message.as_ssz_bytes(),
+    );
+
+    let mut steps: Vec<Step<String, String>> = vec![];
+    for (i, snapshot) in chain_dump.iter().enumerate().skip(1) {
+        let slot = snapshot.beacon_block.slot();
+
+        steps.push(tick(slot));
+        let block = format!("block_{}", i);
+        write(
+            &format!("{}.ssz", block),
+            &snapshot.beacon_block.as_ssz_bytes(),
+        );
+        steps.push(Step::Block { block, valid: None });
+
+        // The attestations the harness produced for the block, which may only be applied to fork
+        // choice from the next slot.
+        steps.push(tick(slot + 1));
+        let attestations = harness.get_free_attestations(
+            &AttestationStrategy::AllValidators,
+            &snapshot.beacon_state,
+            snapshot.beacon_block_root,
+            slot,
+        );
+        for (j, attestation) in attestations.iter().enumerate() {
+            let attestation_name = format!("attestation_{}_{}", i, j);
+            write(
+                &format!("{}.ssz", attestation_name),
+                &attestation.as_ssz_bytes(),
+            );
+            steps.push(Step::Attestation {
+                attestation: attestation_name,
+                valid: None,
+            });
+        }
+    }
+
+    // A block with an unknown parent must be rejected.
+    let mut orphan = chain_dump
+        .last()
+        .expect("chain should not be empty")
+        .beacon_block
+        .clone();
+    orphan.message.parent_root = Hash256::repeat_byte(42);
+    write("block_orphan.ssz", &orphan.as_ssz_bytes());
+    steps.push(Step::Block {
+        block: "block_orphan".to_string(),
+        valid: Some(false),
+    });
+
+    let head = harness.chain.head_info().expect("should get head");
+    let fork_choice = &harness.chain.fork_choice;
+    steps.push(Step::Checks {
+        checks: Box::new(Checks {
+            time: Some(genesis_time + harness.get_chain_slot().as_u64() * seconds_per_slot),
+            head: Some(Head {
+                slot: head.slot,
+                root: head.block_root,
+            }),
+            justified_checkpoint: Some(fork_choice.justified_checkpoint()),
+            finalized_checkpoint: Some(fork_choice.finalized_checkpoint()),
+            best_justified_checkpoint: Some(fork_choice.best_justified_checkpoint()),
+            ..Checks::default()
+        }),
+    });
+
+    write(
+        "steps.yaml",
+        serde_yaml::to_string(&steps)
+            .expect("should encode steps")
+            .as_bytes(),
+    );
+}
+
+#[test]
+fn fork_choice_get_head() {
+    let tests_dir = tempdir().expect("should create temp dir");
+
+    write_fork_choice_case::<MinimalEthSpec>(tests_dir.path());
+    ForkChoiceHandler::<MinimalEthSpec, GetHead>::run_in(tests_dir.path());
+}
+
 #[test]
 fn genesis_initialization() {
     GenesisInitializationHandler::<MinimalEthSpec>::run();
@@ -69,6 +69,7 @@ pub fn testing_client_config() -> ClientConfig {
     // Setting ports to `0` means that the OS will choose some available port.
     client_config.network.libp2p_port = 0;
     client_config.network.discovery_port = 0;
+    client_config.network.libp2p_port_v6 = 0;
     client_config.rest_api.enabled = true;
     client_config.rest_api.port = 0;
     client_config.websocket_server.enabled = true;
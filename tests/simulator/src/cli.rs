@@ -41,6 +41,12 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                         .long("end_after_checks")
                         .takes_value(false)
                         .help("End after checks (default true)"))
+                    .arg(Arg::with_name("ip_family")
+                        .long("ip_family")
+                        .takes_value(true)
+                        .default_value("ipv4")
+                        .possible_values(&["ipv4", "ipv6", "dual-stack"])
+                        .help("The IP families on which the beacon nodes listen. Dual-stack nodes are only discoverable over IPv4"))
         )
         .subcommand(
             SubCommand::with_name("no-eth1-sim")
@@ -69,6 +75,12 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                         .long("end_after_checks")
                         .takes_value(false)
                         .help("End after checks (default true)"))
+                    .arg(Arg::with_name("ip_family")
+                        .long("ip_family")
+                        .takes_value(true)
+                        .default_value("ipv4")
+                        .possible_values(&["ipv4", "ipv6", "dual-stack"])
+                        .help("The IP families on which the beacon nodes listen. Dual-stack nodes are only discoverable over IPv4"))
         )
         .subcommand(
             SubCommand::with_name("syncing-sim")
@@ -103,6 +115,14 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                         .default_value("all")
                         .possible_values(&["one-node", "two-nodes", "mixed", "all"])
                         .help("Sync verification strategy to run."),
+                )
+                .arg(
+                    Arg::with_name("ip_family")
+                        .long("ip_family")
+                        .takes_value(true)
+                        .default_value("ipv4")
+                        .possible_values(&["ipv4", "ipv6", "dual-stack"])
+                        .help("The IP families on which the beacon nodes listen. Dual-stack nodes are only discoverable over IPv4."),
                 ),
         )
 }
@@ -4,11 +4,59 @@ use node_test_rig::{
     RemoteBeaconNode, ValidatorConfig,
 };
 use parking_lot::RwLock;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::ops::Deref;
+use std::str::FromStr;
 use std::sync::Arc;
 use types::{Epoch, EthSpec};
 
 const BOOTNODE_PORT: u16 = 42424;
+/// The libp2p port of the boot node on its IPv6 address, when listening on both IPv4 and IPv6.
+const BOOTNODE_PORT_V6: u16 = 42425;
+
+/// The IP families on which the beacon nodes of a simulation listen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IpFamily {
+    Ipv4,
+    Ipv6,
+    DualStack,
+}
+
+impl FromStr for IpFamily {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ipv4" => Ok(IpFamily::Ipv4),
+            "ipv6" => Ok(IpFamily::Ipv6),
+            "dual-stack" => Ok(IpFamily::DualStack),
+            other => Err(format!("Unknown IP family: {}", other)),
+        }
+    }
+}
+
+impl IpFamily {
+    /// Configures a beacon node to listen on, and advertise, the localhost addresses of this
+    /// family.
+    pub fn configure(self, beacon_config: &mut ClientConfig) {
+        let network = &mut beacon_config.network;
+        match self {
+            IpFamily::Ipv4 => {
+                network.enr_address = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+            }
+            IpFamily::Ipv6 => {
+                network.listen_address = IpAddr::V6(Ipv6Addr::LOCALHOST);
+                network.enr_address_v6 = Some(Ipv6Addr::LOCALHOST);
+            }
+            IpFamily::DualStack => {
+                network.listen_address = IpAddr::V4(Ipv4Addr::LOCALHOST);
+                network.listen_address_v6 = Some(Ipv6Addr::LOCALHOST);
+                network.enr_address = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+                network.enr_address_v6 = Some(Ipv6Addr::LOCALHOST);
+            }
+        }
+    }
+}
 
 /// Helper struct to reduce `Arc` usage.
 pub struct Inner<E: EthSpec> {
@@ -50,6 +98,8 @@ impl<E: EthSpec> LocalNetwork<E> {
         beacon_config.network.libp2p_port = BOOTNODE_PORT;
         beacon_config.network.enr_udp_port = Some(BOOTNODE_PORT);
         beacon_config.network.enr_tcp_port = Some(BOOTNODE_PORT);
+        beacon_config.network.libp2p_port_v6 = BOOTNODE_PORT_V6;
+        beacon_config.network.enr_tcp6_port = Some(BOOTNODE_PORT_V6);
         LocalBeaconNode::production(context.service_context("boot_node".into()), beacon_config).map(
             |beacon_node| Self {
                 inner: Arc::new(Inner {
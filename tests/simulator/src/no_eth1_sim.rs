@@ -1,10 +1,10 @@
+use crate::local_network::IpFamily;
 use crate::{checks, LocalNetwork};
 use clap::ArgMatches;
 use futures::{future, stream, Future, Stream};
 use node_test_rig::{
     environment::EnvironmentBuilder, testing_client_config, ClientGenesis, ValidatorConfig,
 };
-use std::net::{IpAddr, Ipv4Addr};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub fn run_no_eth1_sim(matches: &ArgMatches) -> Result<(), String> {
@@ -13,6 +13,7 @@ pub fn run_no_eth1_sim(matches: &ArgMatches) -> Result<(), String> {
         .expect("missing validators_per_node default");
     let speed_up_factor =
         value_t!(matches, "speed_up_factor", u64).expect("missing speed_up_factor default");
+    let ip_family = value_t!(matches, "ip_family", IpFamily).expect("missing ip_family default");
     let mut end_after_checks = true;
     if matches.is_present("end_after_checks") {
         end_after_checks = false;
@@ -22,6 +23,7 @@ pub fn run_no_eth1_sim(matches: &ArgMatches) -> Result<(), String> {
     println!(" nodes:{}", node_count);
     println!(" validators_per_node:{}", validators_per_node);
     println!(" end_after_checks:{}", end_after_checks);
+    println!(" ip_family:{:?}", ip_family);
 
     let log_level = "debug";
     let log_format = None;
@@ -61,7 +63,7 @@ pub fn run_no_eth1_sim(matches: &ArgMatches) -> Result<(), String> {
     beacon_config.dummy_eth1_backend = true;
     beacon_config.sync_eth1_chain = true;
 
-    beacon_config.network.enr_address = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+    ip_family.configure(&mut beacon_config);
 
     let future = LocalNetwork::new(context, beacon_config.clone())
         /*
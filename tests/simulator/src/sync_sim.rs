@@ -1,12 +1,11 @@
 use crate::checks::{epoch_delay, verify_all_finalized_at};
-use crate::local_network::LocalNetwork;
+use crate::local_network::{IpFamily, LocalNetwork};
 use clap::ArgMatches;
 use futures::{future, stream, Future, IntoFuture, Stream};
 use node_test_rig::ClientConfig;
 use node_test_rig::{
     environment::EnvironmentBuilder, testing_client_config, ClientGenesis, ValidatorConfig,
 };
-use std::net::{IpAddr, Ipv4Addr};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::timer::Interval;
 use types::{Epoch, EthSpec};
@@ -16,12 +15,14 @@ pub fn run_syncing_sim(matches: &ArgMatches) -> Result<(), String> {
     let sync_timeout = value_t!(matches, "sync_timeout", u64).unwrap();
     let speed_up_factor = value_t!(matches, "speedup", u64).unwrap();
     let strategy = value_t!(matches, "strategy", String).unwrap();
+    let ip_family = value_t!(matches, "ip_family", IpFamily).unwrap();
 
     println!("Syncing Simulator:");
     println!(" initial_delay:{}", initial_delay);
     println!(" sync timeout: {}", sync_timeout);
     println!(" speed up factor:{}", speed_up_factor);
     println!(" strategy:{}", strategy);
+    println!(" ip_family:{:?}", ip_family);
 
     let log_level = "debug";
     let log_format = None;
@@ -31,6 +32,7 @@ pub fn run_syncing_sim(matches: &ArgMatches) -> Result<(), String> {
         initial_delay,
         sync_timeout,
         strategy,
+        ip_family,
         log_level,
         log_format,
     )
@@ -41,6 +43,7 @@ fn syncing_sim(
     initial_delay: u64,
     sync_timeout: u64,
     strategy: String,
+    ip_family: IpFamily,
     log_level: &str,
     log_format: Option<&str>,
 ) -> Result<(), String> {
@@ -76,7 +79,7 @@ fn syncing_sim(
     beacon_config.dummy_eth1_backend = true;
     beacon_config.sync_eth1_chain = true;
 
-    beacon_config.network.enr_address = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+    ip_family.configure(&mut beacon_config);
 
     let future = LocalNetwork::new(context, beacon_config.clone())
         /*
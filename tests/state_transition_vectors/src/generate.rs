@@ -0,0 +1,187 @@
+use beacon_chain::test_utils::{
+    generate_deterministic_keypairs, AttestationStrategy, BeaconChainHarness, BlockStrategy,
+    HarnessType,
+};
+use clap::ArgMatches;
+use clap_utils::parse_required;
+use ef_tests::TypeName;
+use rand::Rng;
+use serde_derive::Serialize;
+use ssz::Encode;
+use std::fs;
+use std::path::{Path, PathBuf};
+use types::{
+    test_utils::{SeedableRng, XorShiftRng},
+    BeaconState, EthSpec, Keypair, SignedBeaconBlock,
+};
+
+/// The name of the suite directory in which vectors are written.
+const SUITE_NAME: &str = "lighthouse_random";
+/// The maximum number of slots skipped between two blocks of a vector.
+const MAX_SKIP_SLOTS: u64 = 3;
+/// The probability that a validator attests to a block.
+const ATTESTATION_PROBABILITY: f64 = 0.8;
+
+/// The `meta.yaml` of a `sanity/blocks` vector.
+#[derive(Serialize)]
+struct Metadata {
+    blocks_count: usize,
+    bls_setting: u8,
+}
+
+/// A `sanity/blocks` test vector.
+struct TestVector<E: EthSpec> {
+    pre: BeaconState<E>,
+    blocks: Vec<SignedBeaconBlock<E>>,
+    post: BeaconState<E>,
+}
+
+pub fn run<E: EthSpec + TypeName>(matches: &ArgMatches) -> Result<(), String> {
+    let output_dir = parse_required::<PathBuf>(matches, "output-dir")?;
+    let count = parse_required::<usize>(matches, "count")?;
+    let num_blocks = parse_required::<usize>(matches, "blocks")?;
+    let validator_count = parse_required::<usize>(matches, "validator-count")?;
+    let seed = parse_required::<u64>(matches, "seed")?;
+
+    generate::<E>(&output_dir, count, num_blocks, validator_count, seed)
+}
+
+/// Writes `count` vectors of `num_blocks` blocks each to the `sanity/blocks` handler directory
+/// beneath `tests_dir`.
+pub fn generate<E: EthSpec + TypeName>(
+    tests_dir: &Path,
+    count: usize,
+    num_blocks: usize,
+    validator_count: usize,
+    seed: u64,
+) -> Result<(), String> {
+    let suite_dir = tests_dir
+        .join(E::name())
+        .join("phase0")
+        .join("sanity")
+        .join("blocks")
+        .join(SUITE_NAME);
+
+    let mut rng = XorShiftRng::seed_from_u64(seed);
+    let keypairs = generate_deterministic_keypairs(validator_count);
+
+    for i in 0..count {
+        let case_dir = suite_dir.join(format!("random_{}_{}", seed, i));
+        if case_dir.exists() {
+            return Err(format!("{:?} already exists", case_dir));
+        }
+
+        let vector = TestVector::<E>::random(&mut rng, keypairs.clone(), num_blocks);
+        vector.write(&case_dir)?;
+
+        println!(
+            "Wrote {} blocks from slot {} to {} in {:?}",
+            vector.blocks.len(),
+            vector.pre.slot,
+            vector.post.slot,
+            case_dir
+        );
+    }
+
+    Ok(())
+}
+
+impl<E: EthSpec> TestVector<E> {
+    /// Produces a valid chain of `num_blocks` blocks with a random number of skipped slots between
+    /// them and random participation in attestations.
+    fn random(rng: &mut XorShiftRng, keypairs: Vec<Keypair>, num_blocks: usize) -> Self {
+        let validator_count = keypairs.len();
+        let harness: BeaconChainHarness<HarnessType<E>> =
+            BeaconChainHarness::new(E::default(), keypairs);
+
+        // Build some history so that the pre-state is not the genesis state.
+        let warmup_blocks = rng.gen_range(1, 2 * E::slots_per_epoch() as usize + 1);
+        harness.advance_slot();
+        harness.extend_chain(
+            warmup_blocks,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        );
+
+        let pre = harness.get_head_state();
+        let mut state = pre.clone();
+        let mut blocks = Vec::with_capacity(num_blocks);
+
+        for _ in 0..num_blocks {
+            let slot = state.slot + 1 + rng.gen_range(0, MAX_SKIP_SLOTS + 1);
+            let attesters = (0..validator_count)
+                .filter(|_| rng.gen_bool(ATTESTATION_PROBABILITY))
+                .collect::<Vec<_>>();
+
+            let (block_root, post) =
+                harness.add_block(&state, BlockStrategy::OnCanonicalHead, slot, &attesters);
+            let block = harness
+                .chain
+                .get_block(&block_root.into())
+                .expect("should read block")
+                .expect("block should be in the store");
+
+            blocks.push(block);
+            state = post;
+        }
+
+        Self {
+            pre,
+            blocks,
+            post: state,
+        }
+    }
+
+    /// Writes the vector to `case_dir`, in the format of the EF `sanity/blocks` tests.
+    fn write(&self, case_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(case_dir)
+            .map_err(|e| format!("Unable to create {:?}: {:?}", case_dir, e))?;
+
+        let metadata = Metadata {
+            blocks_count: self.blocks.len(),
+            // The signatures are only valid if they were produced with real crypto.
+            bls_setting: if cfg!(feature = "fake_crypto") { 2 } else { 1 },
+        };
+        let yaml = serde_yaml::to_string(&metadata)
+            .map_err(|e| format!("Unable to encode metadata: {:?}", e))?;
+        write_file(&case_dir.join("meta.yaml"), yaml.as_bytes())?;
+
+        write_file(&case_dir.join("pre.ssz"), &self.pre.as_ssz_bytes())?;
+        for (i, block) in self.blocks.iter().enumerate() {
+            write_file(
+                &case_dir.join(format!("blocks_{}.ssz", i)),
+                &block.as_ssz_bytes(),
+            )?;
+        }
+        write_file(&case_dir.join("post.ssz"), &self.post.as_ssz_bytes())
+    }
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|e| format!("Unable to write {:?}: {:?}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ef_tests::{Handler, SanityBlocksHandler};
+    use tempfile::tempdir;
+    use types::MinimalEthSpec;
+
+    #[test]
+    fn generated_vectors_pass() {
+        let tests_dir = tempdir().expect("should create temp dir");
+
+        generate::<MinimalEthSpec>(tests_dir.path(), 2, 4, 16, 7).expect("should generate");
+
+        SanityBlocksHandler::<MinimalEthSpec>::run_in(tests_dir.path());
+    }
+
+    #[test]
+    fn does_not_overwrite_vectors() {
+        let tests_dir = tempdir().expect("should create temp dir");
+
+        generate::<MinimalEthSpec>(tests_dir.path(), 1, 1, 16, 7).expect("should generate");
+        assert!(generate::<MinimalEthSpec>(tests_dir.path(), 1, 1, 16, 7).is_err());
+    }
+}
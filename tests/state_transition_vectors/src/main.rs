@@ -0,0 +1,124 @@
+//! Generates test vectors from random, valid chains and runs test vectors through the Lighthouse
+//! state transition.
+//!
+//! Vectors use the layout of the EF `sanity/blocks` tests, so the vectors generated here may be run
+//! by other implementations and vectors generated by other implementations may be run here. A
+//! failing vector indicates a consensus divergence.
+mod generate;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use ef_tests::{Handler, SanityBlocksHandler, TypeName};
+use std::path::PathBuf;
+use std::process;
+use types::{EthSpec, MainnetEthSpec, MinimalEthSpec};
+
+fn main() {
+    let matches = App::new("state_transition_vectors")
+        .about("Generates and runs state transition test vectors in the EF test format.")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("spec")
+                .long("spec")
+                .value_name("STRING")
+                .takes_value(true)
+                .possible_values(&["minimal", "mainnet"])
+                .default_value("minimal")
+                .global(true)
+                .help("The spec constants of the test vectors."),
+        )
+        .subcommand(
+            SubCommand::with_name("generate")
+                .about(
+                    "Generates random, valid chains of blocks with the `BeaconChainHarness` and \
+                    writes them as `sanity/blocks` test vectors.",
+                )
+                .arg(
+                    Arg::with_name("output-dir")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help(
+                            "The directory in which to write the vectors, which has the layout \
+                            of the `tests` directory of the EF test vectors.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("count")
+                        .long("count")
+                        .value_name("INTEGER")
+                        .takes_value(true)
+                        .default_value("8")
+                        .help("The number of test vectors to generate."),
+                )
+                .arg(
+                    Arg::with_name("blocks")
+                        .long("blocks")
+                        .value_name("INTEGER")
+                        .takes_value(true)
+                        .default_value("16")
+                        .help("The number of blocks in each test vector."),
+                )
+                .arg(
+                    Arg::with_name("validator-count")
+                        .long("validator-count")
+                        .value_name("INTEGER")
+                        .takes_value(true)
+                        .default_value("64")
+                        .help("The number of validators in the genesis state."),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .value_name("INTEGER")
+                        .takes_value(true)
+                        .default_value("42")
+                        .help(
+                            "The seed of the random choices, the same seed gives the same vectors.",
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about(
+                    "Runs `sanity/blocks` test vectors through `per_slot_processing` and \
+                    `per_block_processing`.",
+                )
+                .arg(
+                    Arg::with_name("tests-dir")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help(
+                            "The directory of the vectors, which has the layout of the `tests` \
+                            directory of the EF test vectors.",
+                        ),
+                ),
+        )
+        .get_matches();
+
+    let result = match matches.value_of("spec") {
+        Some("minimal") => run::<MinimalEthSpec>(&matches),
+        Some("mainnet") => run::<MainnetEthSpec>(&matches),
+        spec => Err(format!("Unknown spec: {:?}", spec)),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Failed: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run<E: EthSpec + TypeName>(matches: &ArgMatches) -> Result<(), String> {
+    match matches.subcommand() {
+        ("generate", Some(matches)) => generate::run::<E>(matches),
+        ("run", Some(matches)) => {
+            let tests_dir = clap_utils::parse_required::<PathBuf>(matches, "tests-dir")?;
+
+            // Panics and prints the failing cases if any vector fails.
+            SanityBlocksHandler::<E>::run_in(&tests_dir);
+
+            Ok(())
+        }
+        (other, _) => Err(format!("Unknown subcommand: {}", other)),
+    }
+}